@@ -0,0 +1,14 @@
+-- Notification execution logs
+CREATE TABLE IF NOT EXISTS notification_logs (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL,
+    fired_at TEXT NOT NULL,
+    notification_type TEXT NOT NULL, -- 'due_date_based', 'recurring', etc.
+    level INTEGER NOT NULL DEFAULT 1,
+    success BOOLEAN NOT NULL DEFAULT 1,
+    error_message TEXT,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_logs_fired_at ON notification_logs(fired_at);
+CREATE INDEX IF NOT EXISTS idx_notification_logs_task_id ON notification_logs(task_id);
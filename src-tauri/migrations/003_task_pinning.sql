@@ -0,0 +1,2 @@
+-- Pinned tasks are always listed first within their status group
+ALTER TABLE tasks ADD COLUMN is_pinned BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- Last local date (YYYY-MM-DD) on which recurring notifications fire
+ALTER TABLE tasks ADD COLUMN notification_until TEXT;
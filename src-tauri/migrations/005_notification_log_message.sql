@@ -0,0 +1,2 @@
+-- Notification text (AI-generated or fallback) shown when the notification fired
+ALTER TABLE notification_logs ADD COLUMN message TEXT;
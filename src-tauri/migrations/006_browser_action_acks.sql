@@ -0,0 +1,6 @@
+-- Last time a notification's browser actions were opened for a task
+CREATE TABLE IF NOT EXISTS browser_action_acks (
+    task_id TEXT PRIMARY KEY,
+    acted_at TEXT NOT NULL,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- When the task last changed status (used to rank long-running in-progress tasks)
+ALTER TABLE tasks ADD COLUMN status_changed_at TEXT;
+
+UPDATE tasks SET status_changed_at = updated_at WHERE status_changed_at IS NULL;
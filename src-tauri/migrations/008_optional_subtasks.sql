@@ -0,0 +1,2 @@
+-- Optional (nice-to-have) subtasks don't block parent completion or drag down its progress
+ALTER TABLE tasks ADD COLUMN is_optional BOOLEAN NOT NULL DEFAULT 0;
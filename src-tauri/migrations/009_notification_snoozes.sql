@@ -0,0 +1,6 @@
+-- Tasks whose notifications are snoozed until the given time
+CREATE TABLE IF NOT EXISTS notification_snoozes (
+    task_id TEXT PRIMARY KEY,
+    snooze_until TEXT NOT NULL,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
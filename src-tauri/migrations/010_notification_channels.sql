@@ -0,0 +1,2 @@
+-- Per-task notification channels (JSON array such as ["toast","browser"]); NULL keeps the level-based behavior
+ALTER TABLE tasks ADD COLUMN notification_channels TEXT;
@@ -0,0 +1,2 @@
+-- Recurrence rule for repeating tasks ("daily", "weekly:1,3,5", "monthly:15", "monthly:2:2")
+ALTER TABLE tasks ADD COLUMN recurrence_rule TEXT;
@@ -0,0 +1,2 @@
+-- Last date (YYYY-MM-DD) on which a recurring task may get a new instance
+ALTER TABLE tasks ADD COLUMN recurrence_end TEXT;
@@ -0,0 +1,3 @@
+-- When the task was archived (NULL = not archived)
+ALTER TABLE tasks ADD COLUMN archived_at TEXT;
+CREATE INDEX IF NOT EXISTS idx_tasks_archived_at ON tasks(archived_at);
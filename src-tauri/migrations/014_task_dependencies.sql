@@ -0,0 +1,12 @@
+-- Dependencies between tasks ("blocks", "requires", "relates_to")
+CREATE TABLE IF NOT EXISTS task_dependencies (
+    from_task_id TEXT NOT NULL,
+    to_task_id TEXT NOT NULL,
+    dependency_type TEXT NOT NULL CHECK (dependency_type IN ('blocks', 'requires', 'relates_to')),
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (from_task_id, to_task_id),
+    FOREIGN KEY (from_task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (to_task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_dependencies_to_task_id ON task_dependencies(to_task_id);
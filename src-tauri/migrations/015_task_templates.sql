@@ -0,0 +1,7 @@
+-- Saved task structures (a task and its subtasks) that can be created again
+CREATE TABLE IF NOT EXISTS task_templates (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    structure TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
@@ -0,0 +1,3 @@
+-- When the task was moved to the trash (NULL = not deleted)
+ALTER TABLE tasks ADD COLUMN deleted_at TEXT;
+CREATE INDEX IF NOT EXISTS idx_tasks_deleted_at ON tasks(deleted_at);
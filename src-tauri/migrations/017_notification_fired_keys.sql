@@ -0,0 +1,10 @@
+-- Notifications already fired, keyed by the local date and time of the slot they fired for
+CREATE TABLE IF NOT EXISTS notification_fired_keys (
+    task_id TEXT NOT NULL,
+    notification_key TEXT NOT NULL,
+    fired_at TEXT NOT NULL,
+    PRIMARY KEY (task_id, notification_key),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_fired_keys_fired_at ON notification_fired_keys(fired_at);
@@ -0,0 +1,10 @@
+-- Task analyses keyed by a hash of the analyzed description and the model that produced them
+CREATE TABLE IF NOT EXISTS analysis_cache (
+    input_hash TEXT NOT NULL,
+    model TEXT NOT NULL,
+    analysis TEXT NOT NULL, -- JSON-encoded TaskAnalysis
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (input_hash, model)
+);
+
+CREATE INDEX IF NOT EXISTS idx_analysis_cache_created_at ON analysis_cache(created_at);
@@ -0,0 +1,10 @@
+-- Embedding of each task's description, used to find similar tasks
+CREATE TABLE IF NOT EXISTS task_embeddings (
+    task_id TEXT PRIMARY KEY,
+    model TEXT NOT NULL,
+    embedding BLOB NOT NULL, -- little-endian f32 values
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_embeddings_model ON task_embeddings(model);
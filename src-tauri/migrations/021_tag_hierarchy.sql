@@ -0,0 +1,3 @@
+-- Parent tag for nesting tags such as "仕事 > プロジェクトA" (NULL = top level)
+ALTER TABLE tags ADD COLUMN parent_id TEXT REFERENCES tags(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_tags_parent_id ON tags(parent_id);
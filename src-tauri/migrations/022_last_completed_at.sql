@@ -0,0 +1,4 @@
+-- When the task was last moved to done (kept after reopening; notification escalation counts fires after it)
+ALTER TABLE tasks ADD COLUMN last_completed_at TEXT;
+
+UPDATE tasks SET last_completed_at = completed_at WHERE completed_at IS NOT NULL;
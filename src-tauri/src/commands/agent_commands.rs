@@ -1,7 +1,10 @@
-use crate::services::{AgentService, PersonalityManager, ContextService};
+use crate::models::Task;
+use crate::services::{AgentService, PersonalityManager, TaskService};
 use crate::services::personality_manager::AIPersonality;
-use crate::services::agent_service::{AgentConfig, ModelPreference, ModelPerformanceTier};
-use tauri::State;
+use crate::services::llm_backend::BackendType;
+use crate::services::agent_service::{AgentConfig, AgentConversation, AgentError, ChatStreamResult, ConversationSummary, DueDateSuggestion, InboxAnalysisProgress, LlmUsageStats, ModelPreference, ModelPerformanceTier, SubtaskSuggestion, INBOX_ANALYSIS_PROGRESS_EVENT, MODEL_FALLBACK_EVENT};
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Emitter, State};
 use serde_json::Value;
 use std::sync::{Arc, RwLock};
 
@@ -23,6 +26,42 @@ pub async fn test_ollama_connection(
     Ok(result)
 }
 
+#[tauri::command]
+pub async fn get_ai_enabled(
+    agent: State<'_, AgentService>,
+) -> Result<bool, String> {
+    Ok(agent.is_ai_enabled().await)
+}
+
+#[tauri::command]
+pub async fn set_ai_enabled(
+    enabled: bool,
+    agent: State<'_, AgentService>,
+) -> Result<(), String> {
+    agent
+        .set_ai_enabled(enabled)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_temperature_bias(
+    agent: State<'_, AgentService>,
+) -> Result<f32, String> {
+    Ok(agent.get_temperature_bias().await)
+}
+
+#[tauri::command]
+pub async fn set_temperature_bias(
+    bias: f32,
+    agent: State<'_, AgentService>,
+) -> Result<(), String> {
+    agent
+        .set_temperature_bias(bias)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn list_ollama_models(
     agent: State<'_, AgentService>,
@@ -43,6 +82,16 @@ pub async fn list_ollama_models_detailed(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn get_ollama_raw_tags(
+    agent: State<'_, AgentService>,
+) -> Result<crate::services::ollama_client::RawTagsResponse, String> {
+    agent
+        .raw_ollama_tags()
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn get_current_model(
     agent: State<'_, AgentService>,
@@ -55,30 +104,21 @@ pub async fn set_current_model(
     model: String,
     agent: State<'_, AgentService>,
 ) -> Result<(), String> {
-    // 設定をデータベースに保存（実際のモデル変更は次回起動時に反映）
-    sqlx::query(
-        r#"
-        INSERT OR REPLACE INTO agent_config (key, value, updated_at) 
-        VALUES ('current_model', ?1, datetime('now'))
-        "#
-    )
-    .bind(&model)
-    .execute(&agent.db)
-    .await
-    .map_err(|e| e.to_string())?;
-    
-    Ok(())
+    // インストール済みのタグ付きモデル名に解決して保存し、すぐに切り替える
+    agent.set_model(model).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn analyze_task_with_ai(
     description: String,
+    model: Option<String>,
+    use_cache: Option<bool>,
     agent: State<'_, AgentService>,
 ) -> Result<Value, String> {
     log::info!("AI分析リクエスト開始: {}", description);
     
     let analysis = agent
-        .analyze_task(&description)
+        .analyze_task_with_model(&description, model.as_deref(), use_cache.unwrap_or(true))
         .await
         .map_err(|e| {
             log::error!("AI分析エラー: {}", e);
@@ -93,6 +133,90 @@ pub async fn analyze_task_with_ai(
         })
 }
 
+#[tauri::command]
+pub async fn analyze_inbox_batch(
+    app: AppHandle,
+    agent: State<'_, AgentService>,
+    task_service: State<'_, TaskService>,
+) -> Result<Vec<InboxAnalysisProgress>, String> {
+    agent
+        .analyze_inbox_batch(&task_service, |progress| {
+            if let Err(e) = app.emit(INBOX_ANALYSIS_PROGRESS_EVENT, progress) {
+                log::warn!("受信箱分析の進捗イベント送信に失敗: {}", e);
+            }
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_llm_usage_stats(
+    agent: State<'_, AgentService>,
+) -> Result<Vec<LlmUsageStats>, String> {
+    agent
+        .get_llm_usage_stats()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn generate_status_report(
+    agent: State<'_, AgentService>,
+) -> Result<String, String> {
+    agent
+        .generate_status_report()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn suggest_due_date(
+    task_id: String,
+    agent: State<'_, AgentService>,
+) -> Result<DueDateSuggestion, String> {
+    agent
+        .suggest_due_date(&task_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn summarize_task(
+    task_id: String,
+    agent: State<'_, AgentService>,
+) -> Result<String, String> {
+    agent
+        .summarize_task(&task_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn suggest_subtasks(
+    task_id: String,
+    agent: State<'_, AgentService>,
+) -> Result<Vec<SubtaskSuggestion>, String> {
+    agent
+        .suggest_subtasks(&task_id)
+        .await
+        .map_err(|e| {
+            log::error!("サブタスク提案エラー: {}", e);
+            format!("サブタスクの提案に失敗しました: {}", e)
+        })
+}
+
+#[tauri::command]
+pub async fn apply_task_analysis(
+    task_id: String,
+    subtasks: Vec<SubtaskSuggestion>,
+    task_service: State<'_, TaskService>,
+) -> Result<Vec<Task>, String> {
+    task_service
+        .apply_task_analysis(&task_id, subtasks)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn create_project_plan(
     description: String,
@@ -134,12 +258,79 @@ pub async fn parse_natural_language_task(
 pub async fn chat_with_agent(
     message: String,
     context: Option<String>,
+    model: Option<String>,
+    app: AppHandle,
+    agent: State<'_, AgentService>,
+    personality_manager: State<'_, Arc<RwLock<PersonalityManager>>>,
+) -> Result<String, String> {
+    let enhanced_prompt = build_chat_prompt(&agent, &personality_manager, &message, context).await?;
+    
+    // モデルを明示した場合はそのモデルだけを使う
+    if model.as_deref().is_some_and(|m| !m.trim().is_empty()) {
+        return agent
+            .chat_with_personality_using_model(&enhanced_prompt, true, model.as_deref())
+            .await
+            .map_err(|e| e.to_string());
+    }
+    
+    // 性格が適用されたプロンプトでチャット実行（現在のモデルが使えなければ別モデルにフォールバック）
+    let generation = agent
+        .chat_with_personality_fallback(&enhanced_prompt)
+        .await
+        .map_err(|e| e.to_string())?;
+    if generation.fell_back() {
+        if let Err(e) = app.emit(MODEL_FALLBACK_EVENT, &generation) {
+            log::warn!("モデルのフォールバック通知の送信に失敗: {}", e);
+        }
+    }
+    Ok(generation.content)
+}
+
+/// `chat_with_agent`のストリーミング版（応答を生成された順に`on_chunk`へ送る）
+#[tauri::command]
+pub async fn chat_with_agent_stream(
+    stream_id: String,
+    message: String,
+    context: Option<String>,
+    model: Option<String>,
+    on_chunk: Channel<String>,
     agent: State<'_, AgentService>,
-    context_service: State<'_, ContextService>,
     personality_manager: State<'_, Arc<RwLock<PersonalityManager>>>,
+) -> Result<ChatStreamResult, String> {
+    let enhanced_prompt = build_chat_prompt(&agent, &personality_manager, &message, context).await?;
+    
+    agent
+        .chat_with_personality_stream(&stream_id, &enhanced_prompt, true, model.as_deref(), |chunk| {
+            if let Err(e) = on_chunk.send(chunk.to_string()) {
+                log::warn!("チャットのストリーミング送信に失敗: {}", e);
+            }
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn cancel_chat_stream(
+    stream_id: String,
+    agent: State<'_, AgentService>,
+) -> Result<bool, String> {
+    Ok(agent.cancel_chat_stream(&stream_id))
+}
+
+/// 自動収集したコンテキストと現在の性格を適用したチャット用プロンプトを組み立てる
+async fn build_chat_prompt(
+    agent: &AgentService,
+    personality_manager: &RwLock<PersonalityManager>,
+    message: &str,
+    context: Option<String>,
 ) -> Result<String, String> {
-    // 自動的にコンテキストを収集
-    let auto_context = match context_service.collect_basic_context().await {
+    // AI無効時はコンテキスト収集も行わない
+    if !agent.is_ai_enabled().await {
+        return Err(AgentError::AiDisabled.to_string());
+    }
+    
+    // チャットに必要なコンテキストを自動的に収集
+    let auto_context = match agent.assemble_context("chat").await {
         Ok(context_data) => {
             let mut context_info = Vec::new();
             for data in context_data {
@@ -172,18 +363,63 @@ pub async fn chat_with_agent(
     };
     
     // 現在の性格でプロンプトを拡張
-    let enhanced_prompt = {
-        let manager = personality_manager.read().map_err(|e| e.to_string())?;
-        manager.enhance_prompt(&base_prompt)
-    };
-    
-    // 性格が適用されたプロンプトでチャット実行
+    let manager = personality_manager.read().map_err(|e| e.to_string())?;
+    Ok(manager.enhance_prompt(&base_prompt))
+}
+
+#[tauri::command]
+pub async fn export_task_bundle(
+    task_id: String,
+    conversation_id: Option<String>,
+    agent: State<'_, AgentService>,
+    task_service: State<'_, TaskService>,
+) -> Result<String, String> {
     agent
-        .chat_with_personality(&enhanced_prompt, true)
+        .export_task_bundle(&task_service, &task_id, conversation_id.as_deref())
         .await
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn prune_conversations(
+    keep_days: u32,
+    agent: State<'_, AgentService>,
+) -> Result<u64, String> {
+    agent
+        .prune_conversations(keep_days)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 保存済みの会話を更新日時の新しい順に取得（最初のメッセージの抜粋付き）
+#[tauri::command]
+pub async fn list_conversations(
+    limit: Option<i64>,
+    agent: State<'_, AgentService>,
+) -> Result<Vec<ConversationSummary>, String> {
+    agent
+        .list_conversations(limit.unwrap_or(50))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_conversation(
+    id: String,
+    agent: State<'_, AgentService>,
+) -> Result<Option<AgentConversation>, String> {
+    agent.get_conversation(&id).await.map_err(|e| e.to_string())
+}
+
+/// 会話を完全に削除する
+#[tauri::command]
+pub async fn delete_conversation(
+    id: String,
+    agent: State<'_, AgentService>,
+) -> Result<(), String> {
+    agent.delete_conversation(&id).await.map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn get_available_personalities(
     personality_manager: State<'_, Arc<RwLock<PersonalityManager>>>,
@@ -238,7 +474,56 @@ pub async fn set_ai_personality(
 pub fn get_agent_config(
     agent: State<'_, AgentService>,
 ) -> Result<AgentConfig, String> {
-    Ok(agent.get_config().clone())
+    Ok(agent.get_config())
+}
+
+/// AI関連の保存済み設定を削除し、実行中のモデル・接続先・タイムアウトも既定値に戻す
+///
+/// モデルの推奨設定は`clear_model_preferences`を指定しない限り残す。
+#[tauri::command]
+pub async fn reset_agent_config(
+    clear_model_preferences: Option<bool>,
+    agent: State<'_, AgentService>,
+) -> Result<(), String> {
+    agent
+        .reset_config(clear_model_preferences.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 接続先の種類（Ollama / OpenAI互換）を保存する（次回起動時に反映）
+#[tauri::command]
+pub async fn set_llm_backend_type(
+    backend_type: BackendType,
+    agent: State<'_, AgentService>,
+) -> Result<(), String> {
+    agent.save_backend_type(backend_type).await.map_err(|e| e.to_string())
+}
+
+/// OpenAI互換サーバー用のAPIキーを保存する（`None`で削除、次回起動時に反映）
+#[tauri::command]
+pub async fn set_llm_api_key(
+    api_key: Option<String>,
+    agent: State<'_, AgentService>,
+) -> Result<(), String> {
+    agent.set_api_key(api_key).await.map_err(|e| e.to_string())
+}
+
+/// 埋め込み（類似タスク検索）に使うモデルを保存する（次回起動時に反映）
+#[tauri::command]
+pub async fn set_embedding_model(
+    model: String,
+    agent: State<'_, AgentService>,
+) -> Result<(), String> {
+    agent.save_embedding_model(&model).await.map_err(|e| e.to_string())
+}
+
+/// APIキーが保存されているか（キー自体はフロントエンドに返さない）
+#[tauri::command]
+pub async fn has_llm_api_key(
+    agent: State<'_, AgentService>,
+) -> Result<bool, String> {
+    agent.has_api_key().await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -256,7 +541,7 @@ pub fn get_model_preference(
     model_name: String,
     agent: State<'_, AgentService>,
 ) -> Result<Option<ModelPreference>, String> {
-    Ok(agent.get_model_preference(&model_name).cloned())
+    Ok(agent.get_model_preference(&model_name))
 }
 
 #[tauri::command]
@@ -1,5 +1,5 @@
-use crate::models::browser_action::{BrowserAction, URLValidationResult};
-use crate::services::{BrowserActionService, URLValidator};
+use crate::models::browser_action::{BrowserAction, UnreachableBrowserAction, URLValidationResult};
+use crate::services::{BrowserActionService, TaskService, URLValidator};
 use tauri::State;
 use std::sync::Arc;
 
@@ -98,6 +98,29 @@ fn extract_domain(url: &str) -> Option<String> {
     }
 }
 
+#[tauri::command]
+pub async fn audit_browser_action_urls(
+    task_service: State<'_, TaskService>,
+    browser_action_service: State<'_, Arc<BrowserActionService>>
+) -> Result<Vec<UnreachableBrowserAction>, String> {
+    task_service
+        .audit_browser_action_urls(&browser_action_service)
+        .await
+        .map_err(|e| format!("Failed to audit browser action URLs: {}", e))
+}
+
+#[tauri::command]
+pub async fn clear_browser_actions(
+    task_ids: Vec<String>,
+    all: Option<bool>,
+    task_service: State<'_, TaskService>,
+) -> Result<u64, String> {
+    task_service
+        .clear_browser_actions(task_ids, all.unwrap_or(false))
+        .await
+        .map_err(|e| format!("Failed to clear browser actions: {}", e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,4 +163,4 @@ mod tests {
         assert!(!suggestions.is_empty());
         assert!(suggestions.contains(&"https://google".to_string()));
     }
-}
\ No newline at end of file
+}
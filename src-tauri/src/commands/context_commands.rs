@@ -6,7 +6,7 @@ use serde_json::Value;
 pub async fn get_temporal_context(
     context_service: State<'_, ContextService>,
 ) -> Result<Value, String> {
-    let temporal = context_service.get_temporal_context();
+    let temporal = context_service.get_temporal_context().await;
     serde_json::to_value(temporal).map_err(|e| format!("Serialization error: {}", e))
 }
 
@@ -19,6 +19,22 @@ pub async fn get_task_context(
     serde_json::to_value(task_context).map_err(|e| format!("Serialization error: {}", e))
 }
 
+#[tauri::command]
+pub async fn get_context_cache_enabled(
+    context_service: State<'_, ContextService>,
+) -> Result<bool, String> {
+    Ok(context_service.is_cache_enabled().await)
+}
+
+#[tauri::command]
+pub async fn set_context_cache_enabled(
+    context_service: State<'_, ContextService>,
+    enabled: bool,
+) -> Result<(), String> {
+    context_service.set_cache_enabled(enabled).await
+        .map_err(|e| format!("Failed to save context cache setting: {}", e))
+}
+
 #[tauri::command]
 pub async fn get_basic_context(
     context_service: State<'_, ContextService>,
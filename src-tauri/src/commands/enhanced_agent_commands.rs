@@ -1,5 +1,6 @@
+use std::collections::HashMap;
 use tauri::State;
-use crate::services::agent_service::{AgentService, TaskAnalysis};
+use crate::services::agent_service::{AgentService, TaskAnalysis, TemplateTestResult};
 use crate::services::prompt_manager::GeneratedPrompt;
 use crate::services::context_service::ContextData;
 
@@ -86,4 +87,60 @@ pub async fn get_motivation_prompt(
     agent_service.generate_context_aware_prompt("motivation_boost")
         .await
         .map_err(|e| e.to_string())
-}
\ No newline at end of file
+}
+
+#[tauri::command]
+pub async fn test_prompt_template(
+    template_id: String,
+    variables: HashMap<String, String>,
+    agent_service: State<'_, AgentService>,
+) -> Result<TemplateTestResult, String> {
+    agent_service.test_template(&template_id, &variables)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_context_scopes(
+    agent_service: State<'_, AgentService>,
+) -> Result<HashMap<String, Vec<String>>, String> {
+    Ok(agent_service.get_context_scopes().await)
+}
+
+#[tauri::command]
+pub async fn set_context_scope(
+    operation: String,
+    scope: Option<Vec<String>>,
+    agent_service: State<'_, AgentService>,
+) -> Result<(), String> {
+    agent_service.set_context_scope(&operation, scope)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_context_order(
+    agent_service: State<'_, AgentService>,
+) -> Result<Option<Vec<String>>, String> {
+    Ok(agent_service.get_context_order().await)
+}
+
+#[tauri::command]
+pub async fn set_context_order(
+    order: Option<Vec<String>>,
+    agent_service: State<'_, AgentService>,
+) -> Result<(), String> {
+    agent_service.set_context_order(order)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn preview_prompt(
+    operation: String,
+    agent_service: State<'_, AgentService>,
+) -> Result<String, String> {
+    agent_service.preview_prompt(&operation)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -6,6 +6,7 @@ pub mod browser_commands;
 pub mod context_commands;
 pub mod prompt_commands;
 pub mod enhanced_agent_commands;
+pub mod notification_commands;
 
 pub use task_commands::*;
 pub use tag_commands::*;
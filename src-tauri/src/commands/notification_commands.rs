@@ -0,0 +1,392 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::BTreeSet;
+use tauri::{AppHandle, State};
+use crate::commands::task_commands::present_notification;
+use crate::models::{FocusSession, MissedNotification, NotificationLog, NotificationSelfTestReport, ScheduledNotification, TaskNotification};
+use crate::services::NotificationService;
+use crate::services::business_days::BusinessDaySettings;
+use crate::services::daily_summary::DailySummarySettings;
+use crate::services::notification_escalation::NotificationEscalationSettings;
+use crate::services::notification_level::NotificationLevelRules;
+use crate::services::notification_presentation::{NotificationActions, NotificationPresentation, NotificationPresentationSettings};
+use crate::services::notification_profile::{NotificationProfile, NotificationProfiles};
+use crate::services::quiet_hours::QuietHours;
+
+#[tauri::command]
+pub async fn export_notification_logs_csv(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    service: State<'_, NotificationService>,
+) -> Result<String, String> {
+    service
+        .export_logs_csv(from, to)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn peek_notifications(
+    service: State<'_, NotificationService>,
+) -> Result<Vec<TaskNotification>, String> {
+    service
+        .peek_notifications(Utc::now())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn run_notification_self_test(
+    app: AppHandle,
+    service: State<'_, NotificationService>,
+) -> Result<NotificationSelfTestReport, String> {
+    // 本番と同じ経路でトーストを表示する
+    let actions = NotificationActions::resolve(None, NotificationPresentation::Toast, false);
+    service
+        .run_self_test(Utc::now(), || {
+            present_notification(&app, "TaskNag".to_string(), "通知セルフテスト".to_string(), 1, actions)
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn enumerate_notification_schedule(
+    days: Option<i64>,
+    service: State<'_, NotificationService>,
+) -> Result<Vec<ScheduledNotification>, String> {
+    service
+        .enumerate_schedule(days.unwrap_or(7))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_firing_heatmap(
+    days: Option<i64>,
+    service: State<'_, NotificationService>,
+) -> Result<[[u32; 24]; 7], String> {
+    service
+        .firing_heatmap(days.unwrap_or(30))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn snooze_notification(
+    task_id: String,
+    minutes: i64,
+    service: State<'_, NotificationService>,
+) -> Result<DateTime<Utc>, String> {
+    service
+        .snooze_notification(&task_id, minutes)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn clear_snooze(task_id: String, service: State<'_, NotificationService>) -> Result<(), String> {
+    service.clear_snooze(&task_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn snooze_until_next_business_day(
+    task_id: String,
+    service: State<'_, NotificationService>,
+) -> Result<DateTime<Utc>, String> {
+    service
+        .snooze_until_next_business_day(&task_id, Utc::now())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn start_focus_session(
+    task_id: String,
+    minutes: Option<i64>,
+    service: State<'_, NotificationService>,
+) -> Result<FocusSession, String> {
+    service
+        .start_focus_session(&task_id, minutes.unwrap_or(25), Utc::now())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn end_focus_session(
+    service: State<'_, NotificationService>,
+) -> Result<Option<FocusSession>, String> {
+    service.end_focus_session().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_focus_session(
+    service: State<'_, NotificationService>,
+) -> Result<Option<FocusSession>, String> {
+    service.get_focus_session().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_quiet_hours(service: State<'_, NotificationService>) -> Result<Option<QuietHours>, String> {
+    service.get_quiet_hours().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_quiet_hours(
+    quiet_hours: Option<QuietHours>,
+    service: State<'_, NotificationService>,
+) -> Result<Option<QuietHours>, String> {
+    service.set_quiet_hours(quiet_hours).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_business_day_settings(
+    service: State<'_, NotificationService>,
+) -> Result<BusinessDaySettings, String> {
+    service.get_business_day_settings().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_business_day_settings(
+    settings: BusinessDaySettings,
+    service: State<'_, NotificationService>,
+) -> Result<BusinessDaySettings, String> {
+    service.set_business_day_settings(settings).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_notification_history(
+    limit: Option<i64>,
+    service: State<'_, NotificationService>,
+) -> Result<Vec<NotificationLog>, String> {
+    service
+        .get_notification_logs(limit.unwrap_or(100))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_browser_actions_acted_at(
+    task_id: String,
+    service: State<'_, NotificationService>,
+) -> Result<Option<String>, String> {
+    service
+        .get_browser_actions_acted_at(&task_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_no_nag_days(
+    service: State<'_, NotificationService>,
+) -> Result<BTreeSet<NaiveDate>, String> {
+    service.list_no_nag_days().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn add_no_nag_day(
+    date: NaiveDate,
+    service: State<'_, NotificationService>,
+) -> Result<BTreeSet<NaiveDate>, String> {
+    service.add_no_nag_day(date).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_no_nag_day(
+    date: NaiveDate,
+    service: State<'_, NotificationService>,
+) -> Result<BTreeSet<NaiveDate>, String> {
+    service.remove_no_nag_day(date).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_inbox_aging_days(service: State<'_, NotificationService>) -> Result<i64, String> {
+    service.get_inbox_aging_days().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_inbox_aging_days(
+    days: i64,
+    service: State<'_, NotificationService>,
+) -> Result<(), String> {
+    service
+        .set_inbox_aging_days(days)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_configured_notification_times(
+    service: State<'_, NotificationService>,
+) -> Result<Vec<(String, i32)>, String> {
+    service.get_configured_times().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_missed_notifications(
+    service: State<'_, NotificationService>,
+) -> Result<Vec<MissedNotification>, String> {
+    Ok(service.get_missed_notifications())
+}
+
+#[tauri::command]
+pub async fn get_timezone(service: State<'_, NotificationService>) -> Result<Option<String>, String> {
+    service.get_timezone().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_timezone(timezone: Option<String>, service: State<'_, NotificationService>) -> Result<(), String> {
+    service.set_timezone(timezone).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_webhook_url(service: State<'_, NotificationService>) -> Result<Option<String>, String> {
+    service.get_webhook_url().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_webhook_url(url: Option<String>, service: State<'_, NotificationService>) -> Result<(), String> {
+    service.set_webhook_url(url).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_escalation_settings(
+    service: State<'_, NotificationService>,
+) -> Result<NotificationEscalationSettings, String> {
+    service.get_escalation_settings().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_escalation_settings(
+    settings: NotificationEscalationSettings,
+    service: State<'_, NotificationService>,
+) -> Result<NotificationEscalationSettings, String> {
+    service.set_escalation_settings(settings).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_daily_summary_settings(
+    service: State<'_, NotificationService>,
+) -> Result<DailySummarySettings, String> {
+    service.get_daily_summary_settings().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_daily_summary_settings(
+    settings: DailySummarySettings,
+    service: State<'_, NotificationService>,
+) -> Result<DailySummarySettings, String> {
+    service
+        .set_daily_summary_settings(settings)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_notification_level_rules(
+    service: State<'_, NotificationService>,
+) -> Result<NotificationLevelRules, String> {
+    service.get_level_rules().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_notification_level_rules(
+    rules: NotificationLevelRules,
+    service: State<'_, NotificationService>,
+) -> Result<NotificationLevelRules, String> {
+    service.set_level_rules(rules).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_notification_presentation(
+    service: State<'_, NotificationService>,
+) -> Result<NotificationPresentationSettings, String> {
+    service.get_presentation_settings().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_notification_presentation(
+    settings: NotificationPresentationSettings,
+    service: State<'_, NotificationService>,
+) -> Result<NotificationPresentationSettings, String> {
+    service.set_presentation_settings(settings).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_allow_command_actions(
+    service: State<'_, NotificationService>,
+) -> Result<bool, String> {
+    service.get_command_actions_allowed().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_allow_command_actions(
+    allowed: bool,
+    service: State<'_, NotificationService>,
+) -> Result<(), String> {
+    service.set_command_actions_allowed(allowed).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_browser_action_min_interval(
+    service: State<'_, NotificationService>,
+) -> Result<i64, String> {
+    service.get_browser_action_min_interval().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_browser_action_min_interval(
+    minutes: i64,
+    service: State<'_, NotificationService>,
+) -> Result<(), String> {
+    service.set_browser_action_min_interval(minutes).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_notification_profiles(
+    service: State<'_, NotificationService>,
+) -> Result<NotificationProfiles, String> {
+    service.get_notification_profiles().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_notification_profile(
+    profile: NotificationProfile,
+    service: State<'_, NotificationService>,
+) -> Result<NotificationProfile, String> {
+    service.save_notification_profile(profile).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_notification_profile(
+    name: String,
+    service: State<'_, NotificationService>,
+) -> Result<(), String> {
+    service.delete_notification_profile(&name).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn activate_notification_profile(
+    name: String,
+    service: State<'_, NotificationService>,
+) -> Result<NotificationProfile, String> {
+    service.activate_profile(&name).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn deactivate_notification_profile(
+    service: State<'_, NotificationService>,
+) -> Result<(), String> {
+    service.deactivate_profile().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_effective_notification_level(
+    task_id: String,
+    at: Option<DateTime<Utc>>,
+    service: State<'_, NotificationService>,
+) -> Result<i32, String> {
+    service
+        .effective_level(&task_id, at.unwrap_or_else(Utc::now))
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,5 +1,5 @@
 use tauri::State;
-use crate::models::{Tag, CreateTagRequest, UpdateTagRequest};
+use crate::models::{Task, Tag, TagTreeNode, CreateTagRequest, UpdateTagRequest};
 use crate::services::TaskService;
 
 #[tauri::command]
@@ -23,8 +23,27 @@ pub async fn update_tag(id: String, request: UpdateTagRequest, service: State<'_
 }
 
 #[tauri::command]
-pub async fn delete_tag(id: String, service: State<'_, TaskService>) -> Result<(), String> {
-    service.delete_tag(&id).await.map_err(|e| e.to_string())
+pub async fn delete_tag(id: String, cascade: Option<bool>, service: State<'_, TaskService>) -> Result<(), String> {
+    if cascade.unwrap_or(false) {
+        service.delete_tag_cascade(&id).await.map(|_| ()).map_err(|e| e.to_string())
+    } else {
+        service.delete_tag(&id).await.map_err(|e| e.to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn set_tag_parent(id: String, parent_id: Option<String>, service: State<'_, TaskService>) -> Result<Tag, String> {
+    service.set_tag_parent(&id, parent_id.as_deref()).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_child_tags(parent_id: String, service: State<'_, TaskService>) -> Result<Vec<Tag>, String> {
+    service.get_child_tags(&parent_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_tag_tree(service: State<'_, TaskService>) -> Result<Vec<TagTreeNode>, String> {
+    service.get_tag_tree().await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -40,4 +59,24 @@ pub async fn remove_tag_from_task(task_id: String, tag_id: String, service: Stat
 #[tauri::command]
 pub async fn get_tags_for_task(task_id: String, service: State<'_, TaskService>) -> Result<Vec<Tag>, String> {
     service.get_tags_for_task(&task_id).await.map_err(|e| e.to_string())
-}
\ No newline at end of file
+}
+
+#[tauri::command]
+pub async fn suggest_tags(query: String, limit: Option<usize>, service: State<'_, TaskService>) -> Result<Vec<Tag>, String> {
+    service.suggest_tags(&query, limit.unwrap_or(5)).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_tasks_by_tag(tag_id: String, service: State<'_, TaskService>) -> Result<Vec<Task>, String> {
+    service.get_tasks_by_tag(&tag_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_tasks_by_tag_recursive(tag_id: String, service: State<'_, TaskService>) -> Result<Vec<Task>, String> {
+    service.get_tasks_by_tag_recursive(&tag_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_tasks_by_tags(tag_ids: Vec<String>, match_all: Option<bool>, service: State<'_, TaskService>) -> Result<Vec<Task>, String> {
+    service.get_tasks_by_tags(&tag_ids, match_all.unwrap_or(true)).await.map_err(|e| e.to_string())
+}
@@ -1,17 +1,40 @@
-use crate::models::{CreateTaskRequest, Task, UpdateTaskRequest};
-use crate::services::TaskService;
+use crate::models::{CompleteTaskResult, CompletionCheck, CreateTaskRequest, CreateTaskResult, DueBucket, DueDateTimezoneAudit, MarkdownImportResult, NotificationChannel, NotificationPreset, Task, TaskDependencyRecord, TaskStatus, TaskTemplate, UpdateTaskRequest};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use crate::services::{AgentService, NotificationService, TaskService};
+use crate::services::task_limits::TaskFieldLimits;
+use crate::services::tray_behavior::{TrayClickBehavior, TrayClickState, TRAY_ID};
+use crate::services::close_behavior::{CloseBehavior, CloseBehaviorState};
+use crate::services::notification_presentation::{NotificationActions, NotificationPresentation, NotificationPresentationSettings};
+use crate::services::notification_service::notification_text;
+use crate::services::subtask_completion::SubtaskCompletionRules;
+use crate::services::urgency_score::{TaskUrgency, UrgencyWeights};
+use crate::services::task_similarity::SimilarTask;
 use tauri::{AppHandle, State, Emitter, Manager};
 use tauri_plugin_notification::NotificationExt;
 
 #[tauri::command]
 pub async fn create_task(
+    app: AppHandle,
     request: CreateTaskRequest,
     service: State<'_, TaskService>,
-) -> Result<Task, String> {
-    service
-        .create_task(request)
+) -> Result<CreateTaskResult, String> {
+    let result = service
+        .create_task_with_warnings(request)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    
+    // 類似タスク検索用の埋め込みは作成を待たせないよう裏で保存する（AI無効時・失敗時は保存しない）
+    let task = result.task.clone();
+    tauri::async_runtime::spawn(async move {
+        let agent = app.state::<AgentService>();
+        let tasks = app.state::<TaskService>();
+        if let Err(e) = agent.embed_task(&tasks, &task).await {
+            log::debug!("Failed to store embedding for task {}: {}", task.id, e);
+        }
+    });
+    
+    Ok(result)
 }
 
 #[tauri::command]
@@ -19,6 +42,18 @@ pub async fn get_tasks(service: State<'_, TaskService>) -> Result<Vec<Task>, Str
     service.get_tasks().await.map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn get_tasks_paginated(
+    limit: i64,
+    offset: i64,
+    service: State<'_, TaskService>,
+) -> Result<(Vec<Task>, i64), String> {
+    service
+        .get_tasks_paginated(limit, offset)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_task_by_id(id: String, service: State<'_, TaskService>) -> Result<Task, String> {
     service
@@ -27,6 +62,14 @@ pub async fn get_task_by_id(id: String, service: State<'_, TaskService>) -> Resu
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn search_tasks(query: String, service: State<'_, TaskService>) -> Result<Vec<Task>, String> {
+    service
+        .search_tasks(&query)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn update_task(
     id: String,
@@ -47,6 +90,62 @@ pub async fn delete_task(id: String, service: State<'_, TaskService>) -> Result<
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn bulk_move_tasks(
+    ids: Vec<String>,
+    new_status: String,
+    service: State<'_, TaskService>,
+) -> Result<Vec<Task>, String> {
+    service
+        .bulk_move_tasks(&ids, &new_status)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn duplicate_task(
+    id: String,
+    include_children: bool,
+    service: State<'_, TaskService>,
+) -> Result<Task, String> {
+    service
+        .duplicate_task(&id, include_children)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn restore_task(id: String, service: State<'_, TaskService>) -> Result<Task, String> {
+    service
+        .restore_task(&id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_deleted_tasks(service: State<'_, TaskService>) -> Result<Vec<Task>, String> {
+    service
+        .get_deleted_tasks()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn empty_trash(service: State<'_, TaskService>) -> Result<u64, String> {
+    service
+        .empty_trash()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_task_cascade(id: String, service: State<'_, TaskService>) -> Result<usize, String> {
+    service
+        .delete_task_cascade(&id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_tasks_by_status(
     status: String,
@@ -70,6 +169,110 @@ pub async fn move_task(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn archive_task(id: String, service: State<'_, TaskService>) -> Result<Task, String> {
+    service.archive_task(&id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn unarchive_task(id: String, service: State<'_, TaskService>) -> Result<Task, String> {
+    service.unarchive_task(&id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_archived_tasks(service: State<'_, TaskService>) -> Result<Vec<Task>, String> {
+    service.get_archived_tasks().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn add_task_dependency(
+    from_task_id: String,
+    to_task_id: String,
+    dependency_type: String,
+    service: State<'_, TaskService>,
+) -> Result<TaskDependencyRecord, String> {
+    service
+        .add_dependency(&from_task_id, &to_task_id, &dependency_type)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_task_dependency(
+    from_task_id: String,
+    to_task_id: String,
+    service: State<'_, TaskService>,
+) -> Result<(), String> {
+    service
+        .remove_dependency(&from_task_id, &to_task_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_task_dependencies(
+    task_id: String,
+    service: State<'_, TaskService>,
+) -> Result<Vec<TaskDependencyRecord>, String> {
+    service.get_dependencies(&task_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_topological_order(
+    root_id: Option<String>,
+    service: State<'_, TaskService>,
+) -> Result<Vec<Task>, String> {
+    service.get_topological_order(root_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn can_complete_task(task_id: String, service: State<'_, TaskService>) -> Result<CompletionCheck, String> {
+    service.can_complete(&task_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_task_as_template(
+    task_id: String,
+    name: String,
+    service: State<'_, TaskService>,
+) -> Result<TaskTemplate, String> {
+    service.save_as_template(&task_id, &name).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_task_templates(service: State<'_, TaskService>) -> Result<Vec<TaskTemplate>, String> {
+    service.get_task_templates().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_task_from_template(
+    template_id: String,
+    service: State<'_, TaskService>,
+) -> Result<Task, String> {
+    service.create_from_template(&template_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_task_template(template_id: String, service: State<'_, TaskService>) -> Result<(), String> {
+    service.delete_task_template(&template_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_overdue_tasks(service: State<'_, TaskService>) -> Result<Vec<Task>, String> {
+    service
+        .get_overdue_tasks()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_overdue_count(service: State<'_, TaskService>) -> Result<usize, String> {
+    service
+        .get_overdue_count()
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_incomplete_task_count(service: State<'_, TaskService>) -> Result<usize, String> {
     service
@@ -101,37 +304,146 @@ pub async fn update_tray_title(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_tray_click_behavior(state: State<'_, TrayClickState>) -> Result<TrayClickBehavior, String> {
+    Ok(state.behavior())
+}
+
+#[tauri::command]
+pub async fn set_tray_click_behavior(
+    app: AppHandle,
+    behavior: TrayClickBehavior,
+    state: State<'_, TrayClickState>,
+) -> Result<(), String> {
+    state.set_behavior(behavior).await.map_err(|e| e.to_string())?;
+    
+    // 「メニュー」の場合のみ左クリックでトレイメニューを開く
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        tray.set_show_menu_on_left_click(behavior == TrayClickBehavior::Menu)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_close_behavior(state: State<'_, CloseBehaviorState>) -> Result<CloseBehavior, String> {
+    Ok(state.behavior())
+}
+
+#[tauri::command]
+pub async fn set_close_behavior(
+    behavior: CloseBehavior,
+    state: State<'_, CloseBehaviorState>,
+) -> Result<(), String> {
+    state.set_behavior(behavior).await.map_err(|e| e.to_string())
+}
+
+/// 「閉じる」の確認結果を反映（`remember`なら次回から確認せずにその動作にする）
+#[tauri::command]
+pub async fn resolve_close_request(
+    app: AppHandle,
+    quit: bool,
+    remember: Option<bool>,
+    state: State<'_, CloseBehaviorState>,
+) -> Result<(), String> {
+    if remember.unwrap_or(false) {
+        let behavior = if quit { CloseBehavior::Quit } else { CloseBehavior::Minimize };
+        state.set_behavior(behavior).await.map_err(|e| e.to_string())?;
+    }
+    
+    if quit {
+        app.exit(0);
+    } else if let Some(window) = app.get_webview_window("main") {
+        let _ = window.hide();
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn check_notifications(
     app: AppHandle,
     service: State<'_, TaskService>,
+    notification_service: State<'_, NotificationService>,
+    agent: State<'_, AgentService>,
 ) -> Result<Vec<serde_json::Value>, String> {
-    let notifications = service.check_notifications().await.map_err(|e| e.to_string())?;
+    // 終了時刻を過ぎた集中セッションを終了し、進捗の更新を促す
+    match notification_service.take_finished_focus_session(chrono::Utc::now()).await {
+        Ok(Some(session)) => {
+            if let Err(e) = present_notification(
+                &app,
+                "⏱ 集中セッション終了".to_string(),
+                format!("{} の進捗を更新しましょう", session.task_title),
+                2,
+                NotificationActions::resolve(None, NotificationPresentation::ToastAndSound, false),
+            ) {
+                log::warn!("Failed to show focus session notification: {}", e);
+            }
+            let _ = app.emit("focus_session_finished", &session);
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to check focus session: {}", e),
+    }
+    
+    // 通知しない日はまとめ通知も含めて何も出さない
+    if notification_service.is_no_nag_day(chrono::Utc::now()).await.map_err(|e| e.to_string())? {
+        if let Err(e) = notification_service.record_scheduler_tick(chrono::Utc::now()).await {
+            log::warn!("Failed to record scheduler tick: {}", e);
+        }
+        return Ok(Vec::new());
+    }
+    
+    // スヌーズ・レベルのルール・受信箱リマインド・クワイエットタイム・プロファイル・集中セッション・
+    // 発火済みの判定はすべてNotificationService側で反映する
+    let notifications = notification_service
+        .check_notifications(chrono::Utc::now())
+        .await
+        .map_err(|e| e.to_string())?;
+    let presentation = notification_service.get_presentation_settings().await.unwrap_or_else(|e| {
+        log::warn!("Failed to load notification presentation settings: {}", e);
+        NotificationPresentationSettings::default()
+    });
+    let profile = notification_service.active_profile().await.unwrap_or_else(|e| {
+        log::warn!("Failed to load active notification profile: {}", e);
+        None
+    });
     let mut result = Vec::new();
     
     for notification in notifications {
         // 通知レベルに応じて通知を送信
-        let title = match notification.notification_type.as_str() {
-            "due_date_based" => {
-                let days_text = match notification.days_until_due.unwrap_or(0) {
-                    0 => "【期限当日】",
-                    1 => "【期限明日】",
-                    d if d <= 3 => "【期限間近】",
-                    _ => "【期限通知】",
-                };
-                format!("📅 {}", days_text)
-            },
-            "recurring" => "🔔 定期リマインド".to_string(),
-            _ => "📋 タスク通知".to_string()
-        };
+        let (title, body) = notification_text(&notification);
         
-        // Windows通知を送信
-        send_windows_notification(
-            app.clone(),
-            title,
-            notification.title.clone(),
-            notification.level as u32,
-        ).await?;
+        // タスクの通知経路（未設定ならレベルごとの設定）に従って通知
+        let task = service.get_task_by_id(&notification.task_id).await.ok();
+        let channels = task.as_ref().and_then(|task| task.notification_channels());
+        let actions = NotificationActions::resolve(
+            channels.as_deref(),
+            presentation.for_level(notification.level),
+            presentation.focuses_window(
+                &notification.notification_type,
+                notification.level,
+                profile.as_ref().map_or(Some(3), |p| p.focus_window_level),
+            ),
+        );
+        // 通知ログには実際に表示した本文を残す
+        let fired = notification_service
+            .fire_notification(&notification, &body, actions.run_browser_actions && task.is_some(), || {
+                present_notification(&app, title, body.clone(), notification.level as u32, actions)
+            })
+            .await;
+        // 1件の表示に失敗しても残りの通知は出す
+        if let Err(e) = fired {
+            log::warn!("Failed to show notification for task {}: {}", notification.task_id, e);
+            continue;
+        }
+        // Webhookの再試行で次の通知を待たせない
+        let webhook_app = app.clone();
+        let webhook_notification = notification.clone();
+        tauri::async_runtime::spawn(async move {
+            webhook_app
+                .state::<NotificationService>()
+                .notify_webhook(&webhook_notification)
+                .await;
+        });
         
         // 通知情報を記録
         result.push(serde_json::json!({
@@ -143,6 +455,22 @@ pub async fn check_notifications(
         }));
     }
     
+    // 一日のまとめ通知（1日1回）
+    match notification_service.check_daily_summary(chrono::Utc::now()).await {
+        Ok(Some(summary)) => {
+            let body = phrase_daily_summary(summary, &notification_service, &agent).await;
+            if let Err(e) = send_windows_notification(app.clone(), "🌙 今日のまとめ".to_string(), body, 1).await {
+                log::warn!("Failed to send daily summary: {}", e);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to check daily summary: {}", e),
+    }
+    
+    if let Err(e) = notification_service.record_scheduler_tick(chrono::Utc::now()).await {
+        log::warn!("Failed to record scheduler tick: {}", e);
+    }
+    
     Ok(result)
 }
 
@@ -214,33 +542,367 @@ pub async fn calculate_and_update_progress(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn export_tree_json(service: State<'_, TaskService>) -> Result<String, String> {
+    service.export_tree_json().await.map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_root_tasks(service: State<'_, TaskService>) -> Result<Vec<Task>, String> {
     service.get_root_tasks().await.map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn get_tasks_by_urgency(
+    limit: Option<usize>,
+    service: State<'_, TaskService>,
+) -> Result<Vec<TaskUrgency>, String> {
+    service
+        .get_tasks_by_urgency(limit.unwrap_or(20))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn find_inert_notifications(
+    task_service: State<'_, TaskService>,
+    notification_service: State<'_, NotificationService>,
+) -> Result<Vec<Task>, String> {
+    task_service
+        .find_inert_notifications(&notification_service)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_longest_in_progress(
+    limit: Option<usize>,
+    service: State<'_, TaskService>,
+) -> Result<Vec<Task>, String> {
+    service
+        .get_longest_in_progress(limit.unwrap_or(10))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn find_similar_tasks(
+    id: String,
+    top_k: Option<usize>,
+    service: State<'_, TaskService>,
+) -> Result<Vec<SimilarTask>, String> {
+    service
+        .find_similar_tasks(&id, top_k.unwrap_or(5))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn estimate_completion_eta(
+    parent_id: String,
+    service: State<'_, TaskService>,
+) -> Result<Option<NaiveDate>, String> {
+    service
+        .estimate_completion_eta(&parent_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_urgency_weights(service: State<'_, TaskService>) -> Result<UrgencyWeights, String> {
+    service.get_urgency_weights().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_urgency_weights(
+    weights: UrgencyWeights,
+    service: State<'_, TaskService>,
+) -> Result<UrgencyWeights, String> {
+    service
+        .set_urgency_weights(weights)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_default_task_status(service: State<'_, TaskService>) -> Result<Option<TaskStatus>, String> {
+    service.get_default_status().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_default_task_status(
+    status: Option<TaskStatus>,
+    service: State<'_, TaskService>,
+) -> Result<(), String> {
+    service
+        .set_default_status(status)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_task_field_limits(service: State<'_, TaskService>) -> Result<TaskFieldLimits, String> {
+    service.get_field_limits().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_task_field_limits(
+    limits: TaskFieldLimits,
+    service: State<'_, TaskService>,
+) -> Result<TaskFieldLimits, String> {
+    service
+        .set_field_limits(limits)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_tasks_by_due_bucket(
+    service: State<'_, TaskService>,
+) -> Result<BTreeMap<DueBucket, Vec<Task>>, String> {
+    service
+        .get_tasks_by_due_bucket()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn audit_due_date_timezones(
+    service: State<'_, TaskService>,
+) -> Result<Vec<DueDateTimezoneAudit>, String> {
+    service
+        .audit_due_date_timezones()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_tasks_completed_between(
+    from: NaiveDate,
+    to: NaiveDate,
+    service: State<'_, TaskService>,
+) -> Result<Vec<Task>, String> {
+    service
+        .get_tasks_completed_between(from, to)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn import_markdown(
+    markdown: String,
+    parent_id: Option<String>,
+    service: State<'_, TaskService>,
+) -> Result<MarkdownImportResult, String> {
+    service
+        .import_markdown(&markdown, parent_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn pin_task(
+    id: String,
+    service: State<'_, TaskService>,
+) -> Result<Task, String> {
+    service.pin_task(&id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn unpin_task(
+    id: String,
+    service: State<'_, TaskService>,
+) -> Result<Task, String> {
+    service.unpin_task(&id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_task_optional(
+    id: String,
+    optional: bool,
+    service: State<'_, TaskService>,
+) -> Result<Task, String> {
+    service.set_task_optional(&id, optional).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_notification_channels(
+    id: String,
+    channels: Option<Vec<NotificationChannel>>,
+    service: State<'_, TaskService>,
+) -> Result<Task, String> {
+    service.set_notification_channels(&id, channels).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_recurrence_rule(
+    id: String,
+    rule: Option<String>,
+    end: Option<NaiveDate>,
+    service: State<'_, TaskService>,
+) -> Result<Task, String> {
+    service.set_recurrence_rule(&id, rule, end).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn complete_task(id: String, service: State<'_, TaskService>) -> Result<CompleteTaskResult, String> {
+    service.complete_task(&id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_next_recurrence(
+    task_id: String,
+    service: State<'_, TaskService>,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, String> {
+    service.next_recurrence(&task_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_subtask_completion_rules(
+    service: State<'_, TaskService>,
+) -> Result<SubtaskCompletionRules, String> {
+    service.get_subtask_completion_rules().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_subtask_completion_rules(
+    rules: SubtaskCompletionRules,
+    service: State<'_, TaskService>,
+) -> Result<SubtaskCompletionRules, String> {
+    service
+        .set_subtask_completion_rules(rules)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_notification_presets(
+    service: State<'_, TaskService>,
+) -> Result<Vec<NotificationPreset>, String> {
+    service.get_notification_presets().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_notification_preset(
+    preset: NotificationPreset,
+    service: State<'_, TaskService>,
+) -> Result<NotificationPreset, String> {
+    service
+        .save_notification_preset(preset)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_notification_preset(
+    name: String,
+    service: State<'_, TaskService>,
+) -> Result<(), String> {
+    service
+        .delete_notification_preset(&name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn apply_notification_preset(
+    task_id: String,
+    preset_name: String,
+    service: State<'_, TaskService>,
+) -> Result<Task, String> {
+    service
+        .apply_notification_preset(&task_id, &preset_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_due_date_relative(
+    task_ids: Vec<String>,
+    days: i64,
+    business_days: Option<bool>,
+    service: State<'_, TaskService>,
+) -> Result<Vec<Task>, String> {
+    service
+        .set_due_date_relative(task_ids, days, business_days.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn copy_notification_settings(
+    from_id: String,
+    to_ids: Vec<String>,
+    service: State<'_, TaskService>,
+) -> Result<Vec<Task>, String> {
+    service
+        .copy_notification_settings(&from_id, to_ids)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// 設定に応じてまとめの文面をAIで整える（AI無効時・失敗時は元の文面を使う）
+async fn phrase_daily_summary(summary: String, notification_service: &NotificationService, agent: &AgentService) -> String {
+    let use_ai = notification_service
+        .get_daily_summary_settings()
+        .await
+        .map(|settings| settings.use_ai)
+        .unwrap_or(false);
+    if !use_ai {
+        return summary;
+    }
+    
+    agent
+        .phrase_notification(
+            &summary,
+            "次の一日のまとめを、ねぎらいの一言を添えて2文程度の短い通知文に言い換えてください。件数は変えないでください。",
+        )
+        .await
+}
+
 #[tauri::command]
 pub async fn send_windows_notification(
     app: AppHandle,
     title: String,
     body: String,
     level: u32,
+) -> Result<(), String> {
+    let presentation = NotificationPresentationSettings::default().for_level(level as i32);
+    present_notification(&app, title, body, level, NotificationActions::resolve(None, presentation, level >= 3))
+}
+
+/// 決めた操作に従って通知を出す（トースト・音・トレイ表示・アプリの前面表示）
+pub(crate) fn present_notification(
+    app: &AppHandle,
+    title: String,
+    body: String,
+    level: u32,
+    actions: NotificationActions,
 ) -> Result<(), String> {
     // Windows通知を送信
-    app.notification()
-        .builder()
-        .title(&title)
-        .body(&body)
-        .show()
-        .map_err(|e| e.to_string())?;
+    if actions.show_toast {
+        app.notification()
+            .builder()
+            .title(&title)
+            .body(&body)
+            .show()
+            .map_err(|e| e.to_string())?;
+    }
     
-    // レベル2以上で音を鳴らす
-    if level >= 2 {
+    if actions.play_sound {
         let _ = app.emit("play_notification_sound", serde_json::json!({ "level": level }));
     }
     
-    // レベル3でアプリを最大化
-    if level >= 3 {
+    // トーストを出さずにトレイアイコンの表示だけで知らせる
+    if actions.update_tray {
+        if let Some(tray) = app.tray_by_id(TRAY_ID) {
+            let _ = tray.set_tooltip(Some(format!("TaskNag - {}", body)));
+        }
+        let _ = app.emit("tray_notification", serde_json::json!({ "title": title, "body": body, "level": level }));
+    }
+    
+    // レベル3（またはプロファイルの指定レベル以上）、またはタスクの指定でアプリを前面に
+    if actions.focus_window {
         if let Some(window) = app.get_webview_window("main") {
             let _ = window.show();
             let _ = window.unminimize();
@@ -255,9 +917,13 @@ pub async fn send_windows_notification(
 pub async fn test_notification_immediate(
     app: AppHandle,
     service: State<'_, TaskService>,
+    notification_service: State<'_, NotificationService>,
 ) -> Result<Vec<serde_json::Value>, String> {
     // 現在の通知設定を持つタスクをすべて取得して即座に通知を送信
-    let _notifications = service.check_notifications().await.map_err(|e| e.to_string())?;
+    let _notifications = notification_service
+        .peek_notifications(chrono::Utc::now())
+        .await
+        .map_err(|e| e.to_string())?;
     let mut result = Vec::new();
     
     // 通知チェックロジックを無視して、設定のあるすべてのタスクを通知
@@ -8,21 +8,35 @@ pub mod tests;
 
 use database::Database;
 use services::{TaskService, AgentService, PersonalityManager, BrowserActionService, NotificationService, ContextService};
+use services::tray_behavior::{decide_tray_click_action, TrayClickAction, TrayClickBehavior, TrayClickState, TRAY_ID};
+use services::close_behavior::{decide_close_action, CloseAction, CloseBehaviorState, CLOSE_REQUESTED_EVENT};
 use tauri::{
-  AppHandle, Manager, WindowEvent, 
-  tray::{TrayIconBuilder, TrayIconEvent, MouseButton},
+  AppHandle, Emitter, Manager, WindowEvent, 
+  tray::{TrayIconBuilder, TrayIconEvent, MouseButton, MouseButtonState},
   menu::{Menu, MenuItem, MenuEvent}
 };
 
 fn handle_tray_event(app: &AppHandle, event: TrayIconEvent) {
   match event {
-    TrayIconEvent::Click { button, .. } => {
-      if button == MouseButton::Left {
-        if let Some(window) = app.get_webview_window("main") {
-          // シングルクリックでは表示のみ（非表示にはしない）
-          let _ = window.show();
-          let _ = window.set_focus();
-          let _ = window.unminimize();
+    TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } => {
+      if let Some(window) = app.get_webview_window("main") {
+        let behavior = app
+          .try_state::<TrayClickState>()
+          .map(|state| state.behavior())
+          .unwrap_or_default();
+        // 最小化中は見えていないものとして扱う
+        let visible = window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false);
+        
+        match decide_tray_click_action(behavior, visible) {
+          TrayClickAction::Show => {
+            let _ = window.show();
+            let _ = window.unminimize();
+            let _ = window.set_focus();
+          }
+          TrayClickAction::Hide => {
+            let _ = window.hide();
+          }
+          TrayClickAction::Nothing => {}
         }
       }
     }
@@ -64,9 +78,27 @@ pub fn run() {
   tauri::Builder::default()
     .on_window_event(|window, event| {
       if let WindowEvent::CloseRequested { api, .. } = event {
-        // ウィンドウを閉じる代わりに最小化
-        let _ = window.hide();
-        api.prevent_close();
+        let behavior = window
+          .app_handle()
+          .try_state::<CloseBehaviorState>()
+          .map(|state| state.behavior())
+          .unwrap_or_default();
+        
+        match decide_close_action(behavior) {
+          CloseAction::Hide => {
+            // ウィンドウを閉じる代わりに最小化
+            let _ = window.hide();
+            api.prevent_close();
+          }
+          CloseAction::Quit => {
+            window.app_handle().exit(0);
+          }
+          CloseAction::Ask => {
+            // フロントエンドで確認し、resolve_close_requestで結果を受け取る
+            api.prevent_close();
+            let _ = window.emit(CLOSE_REQUESTED_EVENT, ());
+          }
+        }
       }
     })
     .setup(|app| {
@@ -88,24 +120,34 @@ pub fn run() {
       let icon = app.default_window_icon().unwrap().clone();
       
       // Initialize database
-      tauri::async_runtime::block_on(async move {
+      let tray_click_behavior = tauri::async_runtime::block_on(async move {
         let db = Database::new(&handle)
           .await
           .expect("Failed to initialize database");
         
         // Initialize services
         let task_service = TaskService::new(db.clone());
-        let mut agent_service = AgentService::new(db.pool.clone());
+        let agent_service = AgentService::new(db.pool.clone());
         let context_service = ContextService::new(db.pool.clone());
         
         // Load saved configuration if exists
         agent_service.load_saved_config().await.ok();
+        agent_service.check_conversation_count().await;
         
         let mut personality_manager_instance = PersonalityManager::new_with_db(Some(db.pool.clone()));
         personality_manager_instance.load_saved_personality().await.ok();
         let personality_manager = std::sync::Arc::new(std::sync::RwLock::new(personality_manager_instance));
         let browser_action_service = std::sync::Arc::new(BrowserActionService::new());
         let notification_service = NotificationService::with_browser_action_service(db.clone(), browser_action_service.clone());
+        if let Err(e) = notification_service.detect_missed_notifications_on_startup(chrono::Utc::now()).await {
+          log::warn!("Failed to detect missed notifications: {}", e);
+        }
+        if let Err(e) = notification_service.load_command_actions_setting().await {
+          log::warn!("Failed to load command action setting: {}", e);
+        }
+        if let Err(e) = task_service.purge_expired_trash().await {
+          log::warn!("Failed to purge trash: {}", e);
+        }
         
         // Add services to app state
         handle.manage(task_service);
@@ -114,6 +156,12 @@ pub fn run() {
         handle.manage(personality_manager);
         handle.manage(browser_action_service);
         handle.manage(notification_service);
+        
+        let tray_click_state = TrayClickState::load(db.pool.clone()).await;
+        let tray_click_behavior = tray_click_state.behavior();
+        handle.manage(tray_click_state);
+        handle.manage(CloseBehaviorState::load(db.pool.clone()).await);
+        tray_click_behavior
       });
       
       // Create system tray menu
@@ -123,10 +171,11 @@ pub fn run() {
       let menu = Menu::with_items(app, &[&show_item, &hide_item, &quit_item])?;
       
       // Create system tray
-      let _tray = TrayIconBuilder::new()
+      let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .icon(icon)
         .title("TaskNag")
         .menu(&menu)
+        .show_menu_on_left_click(tray_click_behavior == TrayClickBehavior::Menu)
         .on_tray_icon_event(|tray, event| handle_tray_event(tray.app_handle(), event))
         .on_menu_event(handle_menu_event)
         .build(app)?;
@@ -136,13 +185,40 @@ pub fn run() {
     .invoke_handler(tauri::generate_handler![
       commands::task_commands::create_task,
       commands::task_commands::get_tasks,
+      commands::task_commands::get_tasks_paginated,
       commands::task_commands::get_task_by_id,
+      commands::task_commands::search_tasks,
       commands::task_commands::update_task,
       commands::task_commands::delete_task,
+      commands::task_commands::delete_task_cascade,
+      commands::task_commands::restore_task,
+      commands::task_commands::get_deleted_tasks,
+      commands::task_commands::empty_trash,
+      commands::task_commands::duplicate_task,
+      commands::task_commands::archive_task,
+      commands::task_commands::unarchive_task,
+      commands::task_commands::get_archived_tasks,
+      commands::task_commands::add_task_dependency,
+      commands::task_commands::remove_task_dependency,
+      commands::task_commands::get_task_dependencies,
+      commands::task_commands::get_topological_order,
+      commands::task_commands::can_complete_task,
+      commands::task_commands::save_task_as_template,
+      commands::task_commands::get_task_templates,
+      commands::task_commands::create_task_from_template,
+      commands::task_commands::delete_task_template,
       commands::task_commands::get_tasks_by_status,
       commands::task_commands::move_task,
+      commands::task_commands::bulk_move_tasks,
       commands::task_commands::get_incomplete_task_count,
+      commands::task_commands::get_overdue_tasks,
+      commands::task_commands::get_overdue_count,
       commands::task_commands::update_tray_title,
+      commands::task_commands::get_tray_click_behavior,
+      commands::task_commands::set_tray_click_behavior,
+      commands::task_commands::get_close_behavior,
+      commands::task_commands::set_close_behavior,
+      commands::task_commands::resolve_close_request,
       commands::task_commands::check_notifications,
       commands::task_commands::update_task_notification_settings,
       commands::task_commands::get_children,
@@ -150,6 +226,37 @@ pub fn run() {
       commands::task_commands::update_progress,
       commands::task_commands::calculate_and_update_progress,
       commands::task_commands::get_root_tasks,
+      commands::task_commands::export_tree_json,
+      commands::task_commands::get_tasks_by_urgency,
+      commands::task_commands::get_longest_in_progress,
+      commands::task_commands::find_similar_tasks,
+      commands::task_commands::find_inert_notifications,
+      commands::task_commands::estimate_completion_eta,
+      commands::task_commands::get_urgency_weights,
+      commands::task_commands::set_urgency_weights,
+      commands::task_commands::get_default_task_status,
+      commands::task_commands::set_default_task_status,
+      commands::task_commands::get_task_field_limits,
+      commands::task_commands::set_task_field_limits,
+      commands::task_commands::get_tasks_by_due_bucket,
+      commands::task_commands::get_tasks_completed_between,
+      commands::task_commands::audit_due_date_timezones,
+      commands::task_commands::import_markdown,
+      commands::task_commands::pin_task,
+      commands::task_commands::unpin_task,
+      commands::task_commands::set_task_optional,
+      commands::task_commands::set_notification_channels,
+      commands::task_commands::set_recurrence_rule,
+      commands::task_commands::complete_task,
+      commands::task_commands::get_next_recurrence,
+      commands::task_commands::get_subtask_completion_rules,
+      commands::task_commands::set_subtask_completion_rules,
+      commands::task_commands::get_notification_presets,
+      commands::task_commands::save_notification_preset,
+      commands::task_commands::delete_notification_preset,
+      commands::task_commands::apply_notification_preset,
+      commands::task_commands::copy_notification_settings,
+      commands::task_commands::set_due_date_relative,
       commands::task_commands::send_windows_notification,
       commands::task_commands::test_notification_immediate,
       commands::tag_commands::get_all_tags,
@@ -157,24 +264,55 @@ pub fn run() {
       commands::tag_commands::create_tag,
       commands::tag_commands::update_tag,
       commands::tag_commands::delete_tag,
+      commands::tag_commands::set_tag_parent,
+      commands::tag_commands::get_child_tags,
+      commands::tag_commands::get_tag_tree,
       commands::tag_commands::add_tag_to_task,
       commands::tag_commands::remove_tag_from_task,
       commands::tag_commands::get_tags_for_task,
+      commands::tag_commands::suggest_tags,
+      commands::tag_commands::get_tasks_by_tag,
+      commands::tag_commands::get_tasks_by_tag_recursive,
+      commands::tag_commands::get_tasks_by_tags,
       commands::log_commands::write_log,
       commands::log_commands::get_log_file_path,
       commands::log_commands::read_recent_logs,
       commands::agent_commands::test_ollama_connection,
+      commands::agent_commands::get_ai_enabled,
+      commands::agent_commands::set_ai_enabled,
+      commands::agent_commands::get_temperature_bias,
+      commands::agent_commands::set_temperature_bias,
       commands::agent_commands::list_ollama_models,
       commands::agent_commands::list_ollama_models_detailed,
+      commands::agent_commands::get_ollama_raw_tags,
       commands::agent_commands::get_agent_config,
+      commands::agent_commands::reset_agent_config,
+      commands::agent_commands::set_llm_backend_type,
+      commands::agent_commands::set_llm_api_key,
+      commands::agent_commands::has_llm_api_key,
+      commands::agent_commands::set_embedding_model,
       commands::agent_commands::get_model_preference,
       commands::agent_commands::get_model_preferences_for_available_models,
       commands::agent_commands::get_current_model,
       commands::agent_commands::set_current_model,
       commands::agent_commands::analyze_task_with_ai,
+      commands::agent_commands::analyze_inbox_batch,
+      commands::agent_commands::generate_status_report,
+      commands::agent_commands::suggest_due_date,
+      commands::agent_commands::suggest_subtasks,
+      commands::agent_commands::summarize_task,
+      commands::agent_commands::apply_task_analysis,
       commands::agent_commands::create_project_plan,
       commands::agent_commands::parse_natural_language_task,
       commands::agent_commands::chat_with_agent,
+      commands::agent_commands::get_llm_usage_stats,
+      commands::agent_commands::chat_with_agent_stream,
+      commands::agent_commands::cancel_chat_stream,
+      commands::agent_commands::prune_conversations,
+      commands::agent_commands::list_conversations,
+      commands::agent_commands::get_conversation,
+      commands::agent_commands::delete_conversation,
+      commands::agent_commands::export_task_bundle,
       commands::agent_commands::get_available_personalities,
       commands::agent_commands::set_ai_personality,
       commands::agent_commands::get_current_personality,
@@ -182,11 +320,15 @@ pub fn run() {
       commands::browser_commands::test_browser_action_command,
       commands::browser_commands::execute_browser_action_command,
       commands::browser_commands::execute_browser_actions_command,
+      commands::browser_commands::audit_browser_action_urls,
+      commands::browser_commands::clear_browser_actions,
       commands::browser_commands::test_url_command,
       commands::browser_commands::get_url_suggestions_command,
       commands::browser_commands::get_url_preview_command,
       commands::context_commands::get_temporal_context,
       commands::context_commands::get_task_context,
+      commands::context_commands::get_context_cache_enabled,
+      commands::context_commands::set_context_cache_enabled,
       commands::context_commands::get_basic_context,
       commands::context_commands::get_context_for_scope,
       commands::context_commands::get_context_as_prompt_variables,
@@ -206,6 +348,58 @@ pub fn run() {
       commands::enhanced_agent_commands::get_task_consultation_prompt,
       commands::enhanced_agent_commands::get_planning_prompt,
       commands::enhanced_agent_commands::get_motivation_prompt,
+      commands::enhanced_agent_commands::test_prompt_template,
+      commands::enhanced_agent_commands::get_context_scopes,
+      commands::enhanced_agent_commands::set_context_scope,
+      commands::enhanced_agent_commands::get_context_order,
+      commands::enhanced_agent_commands::set_context_order,
+      commands::enhanced_agent_commands::preview_prompt,
+      commands::notification_commands::export_notification_logs_csv,
+      commands::notification_commands::peek_notifications,
+      commands::notification_commands::run_notification_self_test,
+      commands::notification_commands::enumerate_notification_schedule,
+      commands::notification_commands::get_firing_heatmap,
+      commands::notification_commands::snooze_notification,
+      commands::notification_commands::clear_snooze,
+      commands::notification_commands::snooze_until_next_business_day,
+      commands::notification_commands::start_focus_session,
+      commands::notification_commands::end_focus_session,
+      commands::notification_commands::get_focus_session,
+      commands::notification_commands::get_quiet_hours,
+      commands::notification_commands::set_quiet_hours,
+      commands::notification_commands::get_business_day_settings,
+      commands::notification_commands::set_business_day_settings,
+      commands::notification_commands::get_notification_history,
+      commands::notification_commands::get_browser_actions_acted_at,
+      commands::notification_commands::list_no_nag_days,
+      commands::notification_commands::add_no_nag_day,
+      commands::notification_commands::remove_no_nag_day,
+      commands::notification_commands::get_inbox_aging_days,
+      commands::notification_commands::set_inbox_aging_days,
+      commands::notification_commands::get_configured_notification_times,
+      commands::notification_commands::get_missed_notifications,
+      commands::notification_commands::get_timezone,
+      commands::notification_commands::set_timezone,
+      commands::notification_commands::get_webhook_url,
+      commands::notification_commands::set_webhook_url,
+      commands::notification_commands::get_escalation_settings,
+      commands::notification_commands::set_escalation_settings,
+      commands::notification_commands::get_daily_summary_settings,
+      commands::notification_commands::set_daily_summary_settings,
+      commands::notification_commands::get_notification_level_rules,
+      commands::notification_commands::set_notification_level_rules,
+      commands::notification_commands::get_notification_presentation,
+      commands::notification_commands::set_notification_presentation,
+      commands::notification_commands::get_allow_command_actions,
+      commands::notification_commands::set_allow_command_actions,
+      commands::notification_commands::get_browser_action_min_interval,
+      commands::notification_commands::set_browser_action_min_interval,
+      commands::notification_commands::get_notification_profiles,
+      commands::notification_commands::save_notification_profile,
+      commands::notification_commands::delete_notification_profile,
+      commands::notification_commands::activate_notification_profile,
+      commands::notification_commands::deactivate_notification_profile,
+      commands::notification_commands::get_effective_notification_level,
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
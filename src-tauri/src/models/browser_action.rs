@@ -2,13 +2,31 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+/// What a browser action does when it runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BrowserActionKind {
+    /// Open `url` in the default browser
+    #[default]
+    Url,
+    /// Run `command` as a local command line (only when command actions are allowed)
+    Command,
+}
+
 /// Individual browser action configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BrowserAction {
     pub id: String,
     pub label: String,
+    #[serde(default)]
+    pub kind: BrowserActionKind,
+    /// URL to open (empty for `command` actions)
+    #[serde(default)]
     pub url: String,
+    /// Command line to run for `command` actions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
     pub enabled: bool,
     pub order: i32,
     pub created_at: DateTime<Utc>,
@@ -19,12 +37,32 @@ impl BrowserAction {
         Self {
             id: uuid::Uuid::new_v4().to_string(),
             label,
+            kind: BrowserActionKind::Url,
             url,
+            command: None,
             enabled: true,
             order,
             created_at: Utc::now(),
         }
     }
+
+    /// Action that runs `command` instead of opening a URL
+    pub fn command(label: String, command: String, order: i32) -> Self {
+        Self {
+            kind: BrowserActionKind::Command,
+            url: String::new(),
+            command: Some(command),
+            ..Self::new(label, String::new(), order)
+        }
+    }
+
+    /// What the action opens or runs, for logs and error messages
+    pub fn target(&self) -> &str {
+        match self.kind {
+            BrowserActionKind::Url => &self.url,
+            BrowserActionKind::Command => self.command.as_deref().unwrap_or_default(),
+        }
+    }
 }
 
 /// Browser action settings for a task
@@ -35,6 +73,9 @@ pub struct BrowserActionSettings {
     pub enabled: bool,
     #[serde(default)]
     pub actions: Vec<BrowserAction>,
+    /// Move the task from `todo` to `in_progress` once its actions have been opened
+    #[serde(default)]
+    pub auto_advance: bool,
 }
 
 impl BrowserActionSettings {
@@ -42,9 +83,15 @@ impl BrowserActionSettings {
         Self {
             enabled,
             actions: Vec::new(),
+            auto_advance: false,
         }
     }
 
+    /// Whether a task in `current_status` should be advanced after its actions ran
+    pub fn should_auto_advance(&self, current_status: &str) -> bool {
+        self.auto_advance && current_status == "todo"
+    }
+
     pub fn add_action(&mut self, action: BrowserAction) {
         // Ensure maximum 5 actions
         if self.actions.len() < 5 {
@@ -141,6 +188,18 @@ impl URLValidationResult {
     }
 }
 
+/// Browser action whose URL failed a health check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnreachableBrowserAction {
+    pub task_id: String,
+    pub task_title: String,
+    pub action_id: String,
+    pub label: String,
+    pub url: String,
+    pub error: Option<String>,
+}
+
 /// URL preview information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -199,6 +258,34 @@ mod tests {
         assert!(!action.id.is_empty());
     }
 
+    #[test]
+    fn test_command_action_keeps_command_out_of_url() {
+        let action = BrowserAction::command("Backup".to_string(), "backup.sh --full".to_string(), 1);
+        assert_eq!(action.kind, BrowserActionKind::Command);
+        assert_eq!(action.url, "");
+        assert_eq!(action.target(), "backup.sh --full");
+
+        let json = serde_json::to_value(&action).unwrap();
+        assert_eq!(json["kind"], "command");
+        assert_eq!(json["command"], "backup.sh --full");
+
+        // URL actions don't carry a command, and command actions may omit the url
+        let url = serde_json::to_value(BrowserAction::new("Docs".to_string(), "https://docs.rs".to_string(), 2)).unwrap();
+        assert!(url.get("command").is_none());
+        let parsed: BrowserAction = serde_json::from_value(serde_json::json!({
+            "id": "a1",
+            "label": "Backup",
+            "kind": "command",
+            "command": "backup.sh",
+            "enabled": true,
+            "order": 1,
+            "createdAt": "2025-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        assert_eq!(parsed.command.as_deref(), Some("backup.sh"));
+        assert_eq!(parsed.url, "");
+    }
+
     #[test]
     fn test_browser_action_settings() {
         let mut settings = BrowserActionSettings::new(true);
@@ -1,7 +1,11 @@
 pub mod task;
 pub mod tag;
 pub mod browser_action;
+pub mod notification_log;
+pub mod task_template;
 
-pub use task::{Task, TaskStatus, CreateTaskRequest, UpdateTaskRequest, TaskNotificationSettings, TaskNotification};
-pub use tag::{Tag, CreateTagRequest, UpdateTagRequest};
-pub use browser_action::{BrowserAction, BrowserActionSettings, BrowserActionError, URLValidationResult, URLPreviewInfo};
\ No newline at end of file
+pub use task::{Task, TaskStatus, DueBucket, CompleteTaskResult, CreateTaskRequest, CreateTaskResult, UpdateTaskRequest, TaskNotificationSettings, NotificationChannel, TaskNotification, MissedNotification, ScheduledNotification, MarkdownImportResult, NotificationPreset, TaskTreeNode, DueDateTimezoneAudit, TaskDependencyRecord, CompletionCheck};
+pub use tag::{Tag, TagTreeNode, CreateTagRequest, UpdateTagRequest};
+pub use browser_action::{BrowserAction, BrowserActionKind, BrowserActionSettings, BrowserActionError, URLValidationResult, URLPreviewInfo};
+pub use task_template::{TaskTemplate, TemplateTask};
+pub use notification_log::{FocusSession, NotificationLog, NotificationSelfTestReport, NotificationSelfTestStep};
\ No newline at end of file
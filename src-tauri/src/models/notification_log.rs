@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationLog {
+    pub id: String,
+    pub task_id: String,
+    pub fired_at: String,
+    pub notification_type: String,
+    pub level: i32,
+    pub success: bool,
+    pub error_message: Option<String>,
+    /// 通知に表示した文面（生成された場合）
+    pub message: Option<String>,
+}
+
+/// 通知セルフテストの各ステップの結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationSelfTestStep {
+    pub name: String,
+    pub success: bool,
+    pub detail: Option<String>,
+}
+
+/// 通知セルフテストの結果（一時タスクの作成〜検出〜発火〜後片付け）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationSelfTestReport {
+    pub steps: Vec<NotificationSelfTestStep>,
+    pub passed: bool,
+}
+
+/// タスクに集中するためのタイマー（ポモドーロ）
+///
+/// セッション中は対象タスク以外の通知を出さない。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusSession {
+    pub task_id: String,
+    pub task_title: String,
+    pub started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+impl FocusSession {
+    /// 設定キー（agent_configテーブル）
+    pub const SETTINGS_KEY: &'static str = "focus_session";
+
+    /// `now`の時点でセッション中か
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.started_at <= now && now < self.ends_at
+    }
+
+    /// セッション中にこのタスクの通知を出してよいか
+    pub fn allows(&self, task_id: &str, now: DateTime<Utc>) -> bool {
+        !self.is_active(now) || self.task_id == task_id
+    }
+}
@@ -10,6 +10,9 @@ pub struct Tag {
     pub color: String,
     pub created_at: String,
     pub updated_at: String,
+    /// 親タグのID（最上位のタグはNone）
+    #[serde(default)]
+    pub parent_id: Option<String>,
 }
 
 impl Tag {
@@ -21,10 +24,20 @@ impl Tag {
             color,
             created_at: now.clone(),
             updated_at: now,
+            parent_id: None,
         }
     }
 }
 
+/// 子タグを入れ子にしたタグ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagTreeNode {
+    #[serde(flatten)]
+    pub tag: Tag,
+    pub children: Vec<TagTreeNode>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateTagRequest {
@@ -42,6 +42,20 @@ impl std::str::FromStr for TaskStatus {
 // Priority enum REMOVED as per .kiro/specs/notification-system-redesign
 // Individual notification settings replace the priority system
 
+/// 通知をどの経路で出すか（タスクごとに複数指定できる）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationChannel {
+    /// トレイアイコンの表示だけを更新する
+    Tray,
+    /// トースト（レベルの設定に応じて音も）
+    Toast,
+    /// タスクのブラウザアクションを実行する
+    Browser,
+    /// アプリを前面に出す
+    Window,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskNotificationSettings {
@@ -50,6 +64,8 @@ pub struct TaskNotificationSettings {
     pub notification_time: Option<String>,   // HH:MM形式
     pub days_of_week: Option<Vec<i32>>,      // 0=日曜, 1=月曜...
     pub level: i32,                          // 1, 2, 3
+    #[serde(default)]
+    pub notification_until: Option<String>,  // YYYY-MM-DD形式、この日を過ぎたら繰り返し通知しない
 }
 
 impl Default for TaskNotificationSettings {
@@ -60,10 +76,63 @@ impl Default for TaskNotificationSettings {
             notification_time: None,
             days_of_week: None,
             level: 1,
+            notification_until: None,
         }
     }
 }
 
+impl TaskNotificationSettings {
+    /// 通知設定の妥当性を検証
+    pub fn validate(&self) -> Result<(), String> {
+        match self.notification_type.as_str() {
+            "none" | "due_date_based" | "recurring" => {}
+            other => return Err(format!("Invalid notification type: {}", other)),
+        }
+        
+        if !(1..=3).contains(&self.level) {
+            return Err("Notification level must be between 1 and 3".to_string());
+        }
+        
+        if let Some(time) = &self.notification_time {
+            if chrono::NaiveTime::parse_from_str(time, "%H:%M").is_err() {
+                return Err(format!("Invalid notification time (expected HH:MM): {}", time));
+            }
+        }
+        
+        if let Some(days) = &self.days_of_week {
+            if days.iter().any(|d| !(0..=6).contains(d)) {
+                return Err("Days of week must be between 0 (Sunday) and 6 (Saturday)".to_string());
+            }
+        }
+        
+        if let Some(until) = &self.notification_until {
+            if chrono::NaiveDate::parse_from_str(until, "%Y-%m-%d").is_err() {
+                return Err(format!("Invalid notification end date (expected YYYY-MM-DD): {}", until));
+            }
+        }
+        
+        if matches!(self.days_before, Some(d) if d < 0) {
+            return Err("Days before must not be negative".to_string());
+        }
+        
+        if self.notification_type == "recurring"
+            && (self.notification_time.is_none() || self.days_of_week.is_none())
+        {
+            return Err("Recurring notifications require a time and days of week".to_string());
+        }
+        
+        Ok(())
+    }
+}
+
+/// 名前付きの通知設定プリセット
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationPreset {
+    pub name: String,
+    pub settings: TaskNotificationSettings,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskNotification {
@@ -72,6 +141,126 @@ pub struct TaskNotification {
     pub level: i32,
     pub days_until_due: Option<i64>,
     pub notification_type: String,
+    /// 重複発火の判定に使うキー（発火枠のローカル日時、Noneなら判定しない）
+    #[serde(default)]
+    pub notification_key: Option<String>,
+}
+
+/// タスク作成結果（作成は成功したが確認を促したい点を`warnings`に含める）
+///
+/// JSONではタスクのフィールドに`warnings`を加えた形になる。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTaskResult {
+    #[serde(flatten)]
+    pub task: Task,
+    pub warnings: Vec<String>,
+}
+
+/// タスクの完了結果（繰り返しタスクなら作成した次回分を含む）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompleteTaskResult {
+    pub task: Task,
+    pub next_task: Option<Task>,
+}
+
+/// Markdownからのタスク取り込み結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkdownImportResult {
+    pub tasks: Vec<Task>,
+    pub warnings: Vec<String>,
+}
+
+/// アプリ停止中に発火予定だった通知
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissedNotification {
+    pub task_id: String,
+    pub title: String,
+    pub level: i32,
+    pub notification_type: String,
+    pub scheduled_at: DateTime<Utc>,
+}
+
+/// 今後発火する予定の通知（カレンダー表示用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledNotification {
+    pub task_id: String,
+    pub title: String,
+    pub fire_at: DateTime<Utc>,
+    pub level: i32,
+    pub notification_type: String,
+}
+
+/// 子タスクを入れ子にしたタスク（JSONバックアップ用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskTreeNode {
+    #[serde(flatten)]
+    pub task: Task,
+    pub children: Vec<TaskTreeNode>,
+}
+
+/// 保存されているタスク間の依存関係
+///
+/// 種別は`blocks`（fromが終わるまでtoに着手できない）、`requires`（fromはtoの完了を前提にする）、
+/// `relates_to`（関連のみ）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskDependencyRecord {
+    pub from_task_id: String,
+    pub to_task_id: String,
+    pub dependency_type: String,
+    pub created_at: String,
+}
+
+/// タスクを完了してよいかの確認結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionCheck {
+    /// 先に終わらせるべき未完了のタスクがない
+    pub can_complete: bool,
+    pub warnings: Vec<String>,
+}
+
+/// 保存されている期日のタイムゾーンの診断結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DueDateTimezoneAudit {
+    pub task_id: String,
+    pub title: String,
+    /// 保存されている期日の文字列
+    pub due_date: String,
+    /// 期日に含まれるUTCからのオフセット（秒、解析できなければNone）
+    pub offset_seconds: Option<i32>,
+    /// その時刻でのアプリのタイムゾーンのオフセット（秒）
+    pub app_offset_seconds: Option<i32>,
+    /// 期日のオフセットがアプリのタイムゾーンと一致するか（一致しなければ発火時刻がずれるおそれがある）
+    pub matches_app_timezone: bool,
+}
+
+/// 期日によるタスクの区分（アジェンダ表示用）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DueBucket {
+    Overdue,   // 期日が昨日以前
+    Today,     // 期日が今日
+    ThisWeek,  // 期日が明日〜6日後
+    Later,     // 期日が7日後以降
+    NoDate,    // 期日なし
+}
+
+impl DueBucket {
+    pub const ALL: [DueBucket; 5] = [
+        DueBucket::Overdue,
+        DueBucket::Today,
+        DueBucket::ThisWeek,
+        DueBucket::Later,
+        DueBucket::NoDate,
+    ];
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -94,8 +283,25 @@ pub struct Task {
     pub notification_time: Option<String>,       // HH:MM形式
     pub notification_days_of_week: Option<String>, // JSON配列 "[0,1,2]"
     pub notification_level: Option<i32>,         // 1, 2, 3
+    pub notification_until: Option<String>,      // YYYY-MM-DD形式
     // Browser actions for notifications
     pub browser_actions: Option<String>,         // JSON stored browser action settings
+    // 同じステータス内で常に上位に表示
+    pub is_pinned: bool,
+    // 最後にステータスが変わった日時
+    pub status_changed_at: Option<String>,
+    // 任意の子タスク（親の完了判定・進捗率の計算から外せる）
+    pub is_optional: bool,
+    // 通知の出し方（JSON配列 ["tray","toast"]、未設定ならレベルに応じた従来の動作）
+    pub notification_channels: Option<String>,
+    // 繰り返しタスクの規則（"daily", "weekly:1,3,5", "monthly:15", "monthly:2:2"）
+    pub recurrence_rule: Option<String>,
+    // 繰り返しタスクの次回分を作る最終日（YYYY-MM-DD形式、未設定なら無期限）
+    pub recurrence_end: Option<String>,
+    // アーカイブした日時（未設定なら通常の一覧・通知の対象）
+    pub archived_at: Option<String>,
+    // ゴミ箱に移した日時（未設定なら削除されていない）
+    pub deleted_at: Option<String>,
     // Tag system
     #[sqlx(skip)]
     pub tags: Option<Vec<Tag>>,
@@ -114,7 +320,7 @@ impl Task {
             due_date: None,
             completed_at: None,
             created_at: now.clone(),
-            updated_at: now,
+            updated_at: now.clone(),
             progress: Some(0),
             // Default notification settings (as per .kiro spec)
             notification_type: Some("none".to_string()),
@@ -122,12 +328,51 @@ impl Task {
             notification_time: None,
             notification_days_of_week: None,
             notification_level: Some(1),
+            notification_until: None,
             // Browser actions
             browser_actions: None,
+            is_pinned: false,
+            status_changed_at: Some(now),
+            is_optional: false,
+            notification_channels: None,
+            recurrence_rule: None,
+            recurrence_end: None,
+            archived_at: None,
+            deleted_at: None,
             // Tag system
             tags: None,
         }
     }
+    
+    /// タスクに保存されている通知設定
+    pub fn notification_settings(&self) -> TaskNotificationSettings {
+        let defaults = TaskNotificationSettings::default();
+        TaskNotificationSettings {
+            notification_type: self.notification_type.clone().unwrap_or(defaults.notification_type),
+            days_before: self.notification_days_before,
+            notification_time: self.notification_time.clone(),
+            days_of_week: self.notification_days_of_week
+                .as_deref()
+                .and_then(|days| serde_json::from_str(days).ok()),
+            level: self.notification_level.unwrap_or(defaults.level),
+            notification_until: self.notification_until.clone(),
+        }
+    }
+    
+    /// タスクの通知経路（未設定ならNone）
+    pub fn notification_channels(&self) -> Option<Vec<NotificationChannel>> {
+        self.notification_channels
+            .as_deref()
+            .and_then(|channels| serde_json::from_str(channels).ok())
+    }
+    
+    /// 繰り返し通知の終了日（ローカル日付）を過ぎているか
+    pub fn is_past_notification_until(&self, local_date: chrono::NaiveDate) -> bool {
+        self.notification_until
+            .as_deref()
+            .and_then(|until| chrono::NaiveDate::parse_from_str(until, "%Y-%m-%d").ok())
+            .is_some_and(|until| local_date > until)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -135,7 +380,9 @@ impl Task {
 pub struct CreateTaskRequest {
     pub title: String,
     pub description: Option<String>,
-    pub status: TaskStatus,
+    /// 省略時は設定のデフォルトステータス（未設定ならTODO）
+    #[serde(default)]
+    pub status: Option<TaskStatus>,
     // priority field removed as per .kiro/specs/notification-system-redesign
     pub parent_id: Option<String>,
     pub due_date: Option<DateTime<Utc>>,
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+/// テンプレートに保存するタスク（子タスクを含む構造）
+///
+/// 期日と通知の終了日は保存した日からの相対日数で持ち、展開した日を基準に計算し直す。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateTask {
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 期日（保存した日から何日後か）
+    #[serde(default)]
+    pub due_offset_days: Option<i64>,
+    /// 期日の時刻（HH:MM:SS形式のローカル時刻）
+    #[serde(default)]
+    pub due_time: Option<String>,
+    #[serde(default)]
+    pub notification_type: Option<String>,
+    #[serde(default)]
+    pub notification_days_before: Option<i32>,
+    #[serde(default)]
+    pub notification_time: Option<String>,
+    #[serde(default)]
+    pub notification_days_of_week: Option<String>,
+    #[serde(default)]
+    pub notification_level: Option<i32>,
+    /// 通知の終了日（保存した日から何日後か）
+    #[serde(default)]
+    pub notification_until_offset_days: Option<i64>,
+    #[serde(default)]
+    pub notification_channels: Option<String>,
+    #[serde(default)]
+    pub browser_actions: Option<String>,
+    #[serde(default)]
+    pub is_optional: bool,
+    #[serde(default)]
+    pub children: Vec<TemplateTask>,
+}
+
+/// 保存済みのタスクテンプレート
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskTemplate {
+    pub id: String,
+    pub name: String,
+    /// 展開するタスクの構造（ルートのタスク）
+    pub root: TemplateTask,
+    pub created_at: String,
+}
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use sqlx::{Pool, Sqlite};
+
+use crate::services::SettingsService;
+
+/// 通知判定などで使うアプリのタイムゾーン
+///
+/// agent_configテーブルの`timezone`にIANA名（例 "Asia/Tokyo"）が保存されていればそれを使い、
+/// 未設定・不正な値の場合はシステムローカルにフォールバックする。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AppTimezone {
+    #[default]
+    System,
+    Named(Tz),
+}
+
+impl AppTimezone {
+    /// 設定キー（agent_configテーブル）
+    pub const SETTINGS_KEY: &'static str = "timezone";
+
+    /// IANAタイムゾーン名を解析
+    pub fn parse(name: &str) -> Result<Self, String> {
+        name.trim()
+            .parse::<Tz>()
+            .map(AppTimezone::Named)
+            .map_err(|_| format!("Unknown timezone: {}", name))
+    }
+
+    /// 保存済みのタイムゾーンを読み込む
+    pub async fn load(pool: &Pool<Sqlite>) -> Self {
+        match SettingsService::get(pool, Self::SETTINGS_KEY).await {
+            Ok(Some(name)) if !name.trim().is_empty() => Self::parse(&name).unwrap_or_else(|e| {
+                log::warn!("{}. Falling back to system local time", e);
+                AppTimezone::System
+            }),
+            Ok(_) => AppTimezone::System,
+            Err(e) => {
+                log::warn!("Failed to load timezone setting: {}", e);
+                AppTimezone::System
+            }
+        }
+    }
+
+    /// 指定時刻のローカル日時
+    pub fn to_local(&self, instant: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            AppTimezone::System => instant.with_timezone(&Local).naive_local(),
+            AppTimezone::Named(tz) => instant.with_timezone(tz).naive_local(),
+        }
+    }
+
+    /// 指定時刻のローカル日付
+    pub fn local_date(&self, instant: DateTime<Utc>) -> NaiveDate {
+        self.to_local(instant).date()
+    }
+
+    /// 指定時刻のローカル曜日（0=日曜日）
+    pub fn weekday_from_sunday(&self, instant: DateTime<Utc>) -> u32 {
+        self.local_date(instant).weekday().num_days_from_sunday()
+    }
+
+    /// 指定時刻でのUTCからのオフセット（秒）
+    pub fn utc_offset_seconds(&self, instant: DateTime<Utc>) -> i32 {
+        (self.to_local(instant) - instant.naive_utc()).num_seconds() as i32
+    }
+
+    /// 指定時刻をその時点のオフセット付きのローカル日時で表す
+    pub fn to_fixed_offset(&self, instant: DateTime<Utc>) -> DateTime<FixedOffset> {
+        let offset = FixedOffset::east_opt(self.utc_offset_seconds(instant))
+            .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+        instant.with_timezone(&offset)
+    }
+
+    /// ローカル日時を実時刻に変換
+    ///
+    /// DSTで重複する時刻は早い方、DSTで存在しない時刻は1時間後ろにずらして解釈する。
+    pub fn from_local(&self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        let resolve = |local: NaiveDateTime| match self {
+            AppTimezone::System => to_utc(Local.from_local_datetime(&local)),
+            AppTimezone::Named(tz) => to_utc(tz.from_local_datetime(&local)),
+        };
+
+        resolve(local).or_else(|| resolve(local + Duration::hours(1)))
+    }
+
+    /// ローカル日付と"HH:MM"形式の時刻から実時刻を計算
+    pub fn at_local_time(&self, date: NaiveDate, time_str: &str) -> Option<DateTime<Utc>> {
+        let time = NaiveTime::parse_from_str(time_str, "%H:%M").ok()?;
+        self.from_local(date.and_time(time))
+    }
+}
+
+fn to_utc<T: TimeZone>(result: LocalResult<DateTime<T>>) -> Option<DateTime<Utc>> {
+    result.earliest().map(|dt| dt.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!(
+            AppTimezone::parse("Asia/Tokyo"),
+            Ok(AppTimezone::Named(chrono_tz::Asia::Tokyo))
+        );
+        assert!(AppTimezone::parse("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn test_local_time_across_dst() {
+        let tz = AppTimezone::parse("America/New_York").unwrap();
+
+        // 2025-03-09 にDST開始（EST→EDT）
+        let before = tz.at_local_time(NaiveDate::from_ymd_opt(2025, 3, 7).unwrap(), "09:00").unwrap();
+        let after = tz.at_local_time(NaiveDate::from_ymd_opt(2025, 3, 10).unwrap(), "09:00").unwrap();
+        assert_eq!(before, Utc.with_ymd_and_hms(2025, 3, 7, 14, 0, 0).unwrap());
+        assert_eq!(after, Utc.with_ymd_and_hms(2025, 3, 10, 13, 0, 0).unwrap());
+
+        // 存在しない時刻（02:30）は1時間後ろにずらす
+        let gap = tz.at_local_time(NaiveDate::from_ymd_opt(2025, 3, 9).unwrap(), "02:30").unwrap();
+        assert_eq!(gap, Utc.with_ymd_and_hms(2025, 3, 9, 7, 30, 0).unwrap());
+
+        // オフセットもDSTに従う
+        assert_eq!(tz.utc_offset_seconds(before), -5 * 3600);
+        assert_eq!(tz.utc_offset_seconds(after), -4 * 3600);
+    }
+}
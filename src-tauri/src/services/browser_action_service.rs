@@ -1,15 +1,25 @@
-use crate::models::browser_action::{BrowserAction, BrowserActionError};
+use crate::models::browser_action::{BrowserAction, BrowserActionError, BrowserActionKind, URLValidationResult};
 use crate::services::url_validator::URLValidator;
-use std::process::Command;
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::timeout;
 use std::pin::Pin;
 use std::future::Future;
 
+/// Timeout for URL health checks
+const URL_HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum number of URL health checks running at once
+pub const URL_HEALTH_CONCURRENCY: usize = 4;
+
 /// Trait for abstracting shell command execution (for testing)
 pub trait ShellExecutor: Send + Sync {
     fn open_url(&self, url: &str) -> Pin<Box<dyn Future<Output = Result<(), BrowserActionError>> + Send + '_>>;
+
+    /// Start a program directly (no shell) with the given arguments
+    fn spawn_command(&self, program: &str, args: &[String]) -> Pin<Box<dyn Future<Output = Result<(), BrowserActionError>> + Send + '_>>;
 }
 
 /// Real shell executor implementation
@@ -22,6 +32,13 @@ impl ShellExecutor for SystemShellExecutor {
             Self::open_url_impl(&url).await
         })
     }
+
+    fn spawn_command(&self, program: &str, args: &[String]) -> Pin<Box<dyn Future<Output = Result<(), BrowserActionError>> + Send + '_>> {
+        let result = Command::new(program).args(args).spawn();
+        Box::pin(async move {
+            Self::check_started(result, "command")
+        })
+    }
 }
 
 impl SystemShellExecutor {
@@ -40,13 +57,18 @@ impl SystemShellExecutor {
                 .spawn()
         };
 
+        Self::check_started(result, "browser command")
+    }
+
+    /// Ensure a spawned process started, without waiting for it to finish
+    fn check_started(result: std::io::Result<Child>, what: &str) -> Result<(), BrowserActionError> {
         match result {
             Ok(mut child) => {
                 // Don't wait for the browser to close, just ensure it started
                 match child.try_wait() {
                     Ok(Some(status)) if !status.success() => {
                         Err(BrowserActionError::CommandFailed(
-                            format!("Browser command failed with status: {}", status)
+                            format!("The {} failed with status: {}", what, status)
                         ))
                     }
                     Ok(Some(_)) => Ok(()), // Exited successfully
@@ -57,7 +79,7 @@ impl SystemShellExecutor {
                 }
             }
             Err(e) => Err(BrowserActionError::CommandFailed(
-                format!("Failed to execute browser command: {}", e)
+                format!("Failed to execute {}: {}", what, e)
             ))
         }
     }
@@ -68,6 +90,9 @@ pub struct BrowserActionService {
     shell: Arc<dyn ShellExecutor>,
     url_validator: URLValidator,
     timeout_duration: Duration,
+    http_client: reqwest::Client,
+    /// Whether `command` actions may run (off unless the user opts in)
+    command_actions_allowed: AtomicBool,
 }
 
 impl BrowserActionService {
@@ -76,6 +101,8 @@ impl BrowserActionService {
             shell: Arc::new(SystemShellExecutor),
             url_validator: URLValidator::new(),
             timeout_duration: Duration::from_secs(3),
+            http_client: Self::health_check_client(),
+            command_actions_allowed: AtomicBool::new(false),
         }
     }
 
@@ -85,6 +112,8 @@ impl BrowserActionService {
             shell,
             url_validator: URLValidator::new(),
             timeout_duration: Duration::from_secs(3),
+            http_client: Self::health_check_client(),
+            command_actions_allowed: AtomicBool::new(false),
         }
     }
 
@@ -104,28 +133,40 @@ impl BrowserActionService {
             }
 
             log::info!("Executing browser action {}/{}: {} -> {}", 
-                index + 1, actions.len(), action.label, action.url);
-
-            // Validate URL before opening
-            let validation_result = self.url_validator.validate(&action.url);
-            if !validation_result.is_valid {
-                let error_msg = validation_result.error
-                    .unwrap_or_else(|| "Unknown validation error".to_string());
-                log::warn!("Skipping invalid URL {}: {}", action.url, error_msg);
-                
-                // Continue with next action instead of failing completely
-                continue;
-            }
+                index + 1, actions.len(), action.label, action.target());
 
-            // Execute with timeout
-            match self.open_url_with_timeout(&action.url).await {
-                Ok(_) => {
-                    log::info!("Successfully opened URL: {}", action.url);
+            if action.kind == BrowserActionKind::Command {
+                if !self.command_actions_allowed() {
+                    log::warn!("Skipping command action {}: command actions are disabled", action.label);
+                    continue;
+                }
+                match self.run_command_action(action).await {
+                    Ok(_) => log::info!("Successfully started command: {}", action.target()),
+                    Err(e) => log::warn!("Failed to run command {}: {}. Continuing with remaining actions.", 
+                        action.target(), e),
                 }
-                Err(e) => {
-                    log::warn!("Failed to open URL {}: {}. Continuing with remaining actions.", 
-                        action.url, e);
-                    // Continue with next URL instead of failing completely
+            } else {
+                // Validate URL before opening
+                let validation_result = self.url_validator.validate(&action.url);
+                if !validation_result.is_valid {
+                    let error_msg = validation_result.error
+                        .unwrap_or_else(|| "Unknown validation error".to_string());
+                    log::warn!("Skipping invalid URL {}: {}", action.url, error_msg);
+                    
+                    // Continue with next action instead of failing completely
+                    continue;
+                }
+
+                // Execute with timeout
+                match self.open_url_with_timeout(&action.url).await {
+                    Ok(_) => {
+                        log::info!("Successfully opened URL: {}", action.url);
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to open URL {}: {}. Continuing with remaining actions.", 
+                            action.url, e);
+                        // Continue with next URL instead of failing completely
+                    }
                 }
             }
 
@@ -145,6 +186,13 @@ impl BrowserActionService {
             return Ok(());
         }
 
+        if action.kind == BrowserActionKind::Command {
+            if !self.command_actions_allowed() {
+                return Err(BrowserActionError::SecurityViolation("Command actions are disabled".to_string()));
+            }
+            return self.run_command_action(action).await;
+        }
+
         // Validate URL
         let validation_result = self.url_validator.validate(&action.url);
         if !validation_result.is_valid {
@@ -177,11 +225,64 @@ impl BrowserActionService {
         }
     }
 
+    /// Run the command line of a `command` action
+    async fn run_command_action(&self, action: &BrowserAction) -> Result<(), BrowserActionError> {
+        let command_line = action.command.as_deref().ok_or_else(|| {
+            BrowserActionError::CommandFailed(format!("Command action {} has no command", action.label))
+        })?;
+        self.run_command_with_timeout(command_line).await
+    }
+
+    /// Run a command line (parsed without a shell) with timeout protection
+    async fn run_command_with_timeout(&self, command_line: &str) -> Result<(), BrowserActionError> {
+        let mut args = parse_command_line(command_line)?;
+        let program = args.remove(0);
+        match timeout(self.timeout_duration, self.shell.spawn_command(&program, &args)).await {
+            Ok(result) => result,
+            Err(_) => Err(BrowserActionError::Timeout),
+        }
+    }
+
+    /// Whether `command` actions may run
+    pub fn command_actions_allowed(&self) -> bool {
+        self.command_actions_allowed.load(Ordering::SeqCst)
+    }
+
+    /// Allow or forbid `command` actions (the setting is persisted by `NotificationService`)
+    pub fn set_command_actions_allowed(&self, allowed: bool) {
+        self.command_actions_allowed.store(allowed, Ordering::SeqCst);
+    }
+
     /// Validate a URL using the internal validator
     pub fn validate_url(&self, url: &str) -> crate::models::browser_action::URLValidationResult {
         self.url_validator.validate(url)
     }
 
+    /// Check that a URL is still reachable with a lightweight HEAD request
+    pub async fn check_url_health(&self, url: &str) -> URLValidationResult {
+        let validation_result = self.url_validator.validate(url);
+        if !validation_result.is_valid {
+            return validation_result;
+        }
+
+        match self.http_client.head(url).send().await {
+            // Some servers reject HEAD but are otherwise reachable
+            Ok(response) if response.status().as_u16() < 400
+                || response.status() == reqwest::StatusCode::METHOD_NOT_ALLOWED => validation_result,
+            Ok(response) => URLValidationResult::invalid(format!("HTTP {}", response.status())),
+            Err(e) if e.is_timeout() => URLValidationResult::invalid("Request timed out".to_string()),
+            Err(e) if e.is_connect() => URLValidationResult::invalid(format!("Connection failed: {}", e)),
+            Err(e) => URLValidationResult::invalid(e.to_string()),
+        }
+    }
+
+    fn health_check_client() -> reqwest::Client {
+        reqwest::Client::builder()
+            .timeout(URL_HEALTH_TIMEOUT)
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new())
+    }
+
     /// Get URL suggestions for common mistakes
     pub fn get_url_suggestions(&self, url: &str) -> Vec<String> {
         self.url_validator.suggest_corrections(url)
@@ -210,6 +311,51 @@ impl Default for BrowserActionService {
     }
 }
 
+/// Split a command line into the program and its arguments without a shell
+///
+/// Whitespace separates arguments; single or double quotes group text containing
+/// spaces. No variables, globs, pipes or escapes are interpreted, so backslashes
+/// in Windows paths are kept as-is.
+pub fn parse_command_line(command_line: &str) -> Result<Vec<String>, BrowserActionError> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+
+    for c in command_line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            None if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err(BrowserActionError::CommandFailed(format!("Unterminated quote in command: {}", command_line)));
+    }
+    if in_arg {
+        args.push(current);
+    }
+    if args.first().map_or(true, |program| program.is_empty()) {
+        return Err(BrowserActionError::CommandFailed("Command is empty".to_string()));
+    }
+
+    Ok(args)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -220,6 +366,7 @@ mod tests {
     // Mock shell executor for testing
     struct MockShellExecutor {
         call_count: AtomicUsize,
+        commands: std::sync::Mutex<Vec<(String, Vec<String>)>>,
         should_fail: bool,
     }
 
@@ -227,6 +374,7 @@ mod tests {
         fn new(should_fail: bool) -> Self {
             Self {
                 call_count: AtomicUsize::new(0),
+                commands: std::sync::Mutex::new(Vec::new()),
                 should_fail,
             }
         }
@@ -249,6 +397,11 @@ mod tests {
                 }
             })
         }
+
+        fn spawn_command(&self, program: &str, args: &[String]) -> Pin<Box<dyn Future<Output = Result<(), BrowserActionError>> + Send + '_>> {
+            self.commands.lock().unwrap().push((program.to_string(), args.to_vec()));
+            Box::pin(async { Ok(()) })
+        }
     }
 
 
@@ -260,7 +413,9 @@ mod tests {
         let action = BrowserAction {
             id: "test".to_string(),
             label: "Test Action".to_string(),
+            kind: BrowserActionKind::Url,
             url: "https://www.google.com".to_string(),
+            command: None,
             enabled: true,
             order: 1,
             created_at: Utc::now(),
@@ -279,7 +434,9 @@ mod tests {
         let action = BrowserAction {
             id: "test".to_string(),
             label: "Test Action".to_string(),
+            kind: BrowserActionKind::Url,
             url: "https://www.google.com".to_string(),
+            command: None,
             enabled: false,
             order: 1,
             created_at: Utc::now(),
@@ -298,7 +455,9 @@ mod tests {
         let action = BrowserAction {
             id: "test".to_string(),
             label: "Test Action".to_string(),
+            kind: BrowserActionKind::Url,
             url: "javascript:alert('xss')".to_string(),
+            command: None,
             enabled: true,
             order: 1,
             created_at: Utc::now(),
@@ -318,7 +477,9 @@ mod tests {
             BrowserAction {
                 id: "test1".to_string(),
                 label: "Test Action 1".to_string(),
+                kind: BrowserActionKind::Url,
                 url: "https://www.google.com".to_string(),
+                command: None,
                 enabled: true,
                 order: 1,
                 created_at: Utc::now(),
@@ -326,7 +487,9 @@ mod tests {
             BrowserAction {
                 id: "test2".to_string(),
                 label: "Test Action 2".to_string(),
+                kind: BrowserActionKind::Url,
                 url: "https://www.github.com".to_string(),
+                command: None,
                 enabled: true,
                 order: 2,
                 created_at: Utc::now(),
@@ -347,7 +510,9 @@ mod tests {
             BrowserAction {
                 id: "test1".to_string(),
                 label: "Test Action 1".to_string(),
+                kind: BrowserActionKind::Url,
                 url: "https://www.google.com".to_string(),
+                command: None,
                 enabled: true,
                 order: 1,
                 created_at: Utc::now(),
@@ -355,7 +520,9 @@ mod tests {
             BrowserAction {
                 id: "test2".to_string(),
                 label: "Test Action 2".to_string(),
+                kind: BrowserActionKind::Url,
                 url: "https://www.github.com".to_string(),
+                command: None,
                 enabled: true,
                 order: 2,
                 created_at: Utc::now(),
@@ -368,6 +535,54 @@ mod tests {
         assert_eq!(mock_shell.get_call_count(), 2); // Both should be attempted
     }
 
+    #[test]
+    fn test_parse_command_line() {
+        assert_eq!(
+            parse_command_line(r#"C:\Tools\sync.exe --profile "Work Laptop" 'a b' plain"#).unwrap(),
+            vec!["C:\\Tools\\sync.exe", "--profile", "Work Laptop", "a b", "plain"]
+        );
+        assert_eq!(parse_command_line("  echo   hello  ").unwrap(), vec!["echo", "hello"]);
+        assert_eq!(parse_command_line(r#"say """#).unwrap(), vec!["say", ""]);
+
+        // Shell syntax is passed through literally
+        assert_eq!(
+            parse_command_line("rm -rf $HOME; echo `id` | sh").unwrap(),
+            vec!["rm", "-rf", "$HOME;", "echo", "`id`", "|", "sh"]
+        );
+
+        assert!(parse_command_line("").is_err());
+        assert!(parse_command_line("   ").is_err());
+        assert!(parse_command_line(r#"open "unterminated"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_command_actions_skipped_when_disabled() {
+        let mock_shell = Arc::new(MockShellExecutor::new(false));
+        let service = BrowserActionService::with_shell(mock_shell.clone());
+
+        let command = BrowserAction::command("Backup".to_string(), r#"backup.sh --target "D:\Backups""#.to_string(), 1);
+        let url = BrowserAction::new("Docs".to_string(), "https://docs.rs".to_string(), 2);
+        let actions = vec![command.clone(), url];
+
+        // Disabled by default: only the URL is opened
+        assert!(!service.command_actions_allowed());
+        service.execute_actions(&actions).await.unwrap();
+        assert!(mock_shell.commands.lock().unwrap().is_empty());
+        assert_eq!(mock_shell.get_call_count(), 1);
+        assert!(matches!(
+            service.execute_single_action(&command).await,
+            Err(BrowserActionError::SecurityViolation(_))
+        ));
+
+        // Once allowed, the command is split into arguments and started
+        service.set_command_actions_allowed(true);
+        service.execute_actions(&actions).await.unwrap();
+        assert_eq!(
+            *mock_shell.commands.lock().unwrap(),
+            vec![("backup.sh".to_string(), vec!["--target".to_string(), "D:\\Backups".to_string()])]
+        );
+    }
+
     #[test]
     fn test_url_validation() {
         let service = BrowserActionService::new();
@@ -389,4 +604,27 @@ mod tests {
         assert!(!suggestions.is_empty());
         assert!(suggestions.contains(&"https://google".to_string()));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_check_url_health() {
+        let service = BrowserActionService::new();
+
+        let _ok = mockito::mock("HEAD", "/healthy").with_status(200).create();
+        let healthy = service
+            .check_url_health(&format!("{}/healthy", mockito::server_url()))
+            .await;
+        assert!(healthy.is_valid);
+
+        // Bind then release a port so nothing is listening on it
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let refused = service
+            .check_url_health(&format!("http://127.0.0.1:{}/", port))
+            .await;
+        assert!(!refused.is_valid);
+        assert!(refused.error.is_some());
+    }
+}
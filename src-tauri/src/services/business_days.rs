@@ -0,0 +1,49 @@
+use chrono::{Datelike, NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// 営業日・休日の設定（翌営業日へのスヌーズなどで使う）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusinessDaySettings {
+    /// 営業日の曜日（0=日曜〜6=土曜）
+    pub business_days: BTreeSet<u32>,
+    /// 曜日に関係なく休みにする日（祝日など）
+    #[serde(default)]
+    pub holidays: BTreeSet<NaiveDate>,
+    /// 始業時刻（HH:MM形式）
+    pub workday_start: String,
+}
+
+impl Default for BusinessDaySettings {
+    fn default() -> Self {
+        Self {
+            business_days: (1..=5).collect(),
+            holidays: BTreeSet::new(),
+            workday_start: "09:00".to_string(),
+        }
+    }
+}
+
+impl BusinessDaySettings {
+    /// 設定キー（agent_configテーブル）
+    pub const SETTINGS_KEY: &'static str = "business_day_settings";
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.business_days.is_empty() {
+            return Err("At least one business day is required".to_string());
+        }
+        if let Some(day) = self.business_days.iter().find(|day| **day > 6) {
+            return Err(format!("Business day must be between 0 (Sunday) and 6 (Saturday): {}", day));
+        }
+        if NaiveTime::parse_from_str(&self.workday_start, "%H:%M").is_err() {
+            return Err(format!("Invalid workday start (expected HH:MM): {}", self.workday_start));
+        }
+        Ok(())
+    }
+
+    /// 営業日か（営業日の曜日で、休日に含まれない）
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        self.business_days.contains(&date.weekday().num_days_from_sunday()) && !self.holidays.contains(&date)
+    }
+}
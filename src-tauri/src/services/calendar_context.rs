@@ -0,0 +1,222 @@
+use crate::services::app_timezone::AppTimezone;
+use crate::services::context_service::{ContextData, ContextError};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+/// カレンダーに並べる予定1件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub title: String,
+    /// 開始日時（アプリのタイムゾーンのローカル時刻）
+    pub start: DateTime<FixedOffset>,
+    /// 時刻を持たない（日付だけの）予定
+    pub all_day: bool,
+    /// 予定の取得元（"task"など）
+    pub source: String,
+}
+
+/// 予定の取得元
+///
+/// 今はタスクの期日だけだが、外部カレンダー（ICSファイルやGoogleカレンダーなど）も
+/// このトレイトを実装して`ContextService::add_calendar_source`で登録すれば同じ要約に加わる。
+#[async_trait]
+pub trait CalendarSource: Send + Sync {
+    /// `from`以上`to`未満に始まる予定
+    async fn events_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        timezone: &AppTimezone,
+    ) -> Result<Vec<CalendarEvent>, ContextError>;
+}
+
+/// 未完了タスクの期日を予定として扱う取得元
+pub struct TaskDueDateSource {
+    db: SqlitePool,
+}
+
+impl TaskDueDateSource {
+    pub fn new(db: SqlitePool) -> Self {
+        Self { db }
+    }
+
+    /// 期日の文字列を実時刻に変換（日付だけの期日はローカルの0時の終日予定として扱う）
+    fn parse_due_date(due_date: &str, timezone: &AppTimezone) -> Option<(DateTime<Utc>, bool)> {
+        if let Ok(due) = DateTime::parse_from_rfc3339(due_date) {
+            return Some((due.with_timezone(&Utc), false));
+        }
+        let date = NaiveDate::parse_from_str(due_date, "%Y-%m-%d").ok()?;
+        Some((timezone.at_local_time(date, "00:00")?, true))
+    }
+}
+
+#[async_trait]
+impl CalendarSource for TaskDueDateSource {
+    async fn events_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        timezone: &AppTimezone,
+    ) -> Result<Vec<CalendarEvent>, ContextError> {
+        // 日付だけの期日はUTCの0時として比較されるため、前後1日広く取ってから絞り込む
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT title, due_date FROM tasks
+            WHERE due_date IS NOT NULL AND status != 'done' AND deleted_at IS NULL AND archived_at IS NULL
+              AND datetime(due_date) >= datetime(?1) AND datetime(due_date) < datetime(?2)
+            "#,
+        )
+        .bind((from - Duration::days(1)).to_rfc3339())
+        .bind((to + Duration::days(1)).to_rfc3339())
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(title, due_date)| {
+                let (start, all_day) = Self::parse_due_date(&due_date, timezone)?;
+                (from <= start && start < to).then(|| CalendarEvent {
+                    title,
+                    start: timezone.to_fixed_offset(start),
+                    all_day,
+                    source: "task".to_string(),
+                })
+            })
+            .collect())
+    }
+}
+
+/// 今日と今週（明日〜日曜）の予定を時系列に並べたコンテキスト
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarContext {
+    pub today: Vec<CalendarEvent>,
+    pub this_week: Vec<CalendarEvent>,
+}
+
+impl CalendarContext {
+    /// すべての取得元から予定を集める（日の区切りはアプリのタイムゾーン）
+    pub async fn build(
+        sources: &[Box<dyn CalendarSource>],
+        timezone: &AppTimezone,
+        now: DateTime<Utc>,
+    ) -> Result<Self, ContextError> {
+        let today = timezone.local_date(now);
+        let tomorrow = today + Duration::days(1);
+        let next_monday = today + Duration::days(7 - today.weekday().num_days_from_monday() as i64);
+        let start_of = |date: NaiveDate| {
+            timezone
+                .at_local_time(date, "00:00")
+                .ok_or_else(|| ContextError::CollectionError(format!("Start of {} does not exist in the app timezone", date)))
+        };
+        let (today_start, tomorrow_start, week_end) = (start_of(today)?, start_of(tomorrow)?, start_of(next_monday)?);
+
+        let mut today_events = Vec::new();
+        let mut week_events = Vec::new();
+        for source in sources {
+            today_events.extend(source.events_between(today_start, tomorrow_start, timezone).await?);
+            week_events.extend(source.events_between(tomorrow_start, week_end, timezone).await?);
+        }
+        for events in [&mut today_events, &mut week_events] {
+            events.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.title.cmp(&b.title)));
+        }
+
+        Ok(Self {
+            today: today_events,
+            this_week: week_events,
+        })
+    }
+
+    fn format_event(event: &CalendarEvent, with_date: bool) -> String {
+        let date = if with_date {
+            format!("{} ", event.start.format("%m/%d(%a)"))
+        } else {
+            String::new()
+        };
+        let time = if event.all_day {
+            "終日".to_string()
+        } else {
+            event.start.format("%H:%M").to_string()
+        };
+        format!("{}{} {}", date, time, event.title)
+    }
+
+    pub fn to_context_data(&self) -> ContextData {
+        let lines = |events: &[CalendarEvent], with_date: bool| {
+            events
+                .iter()
+                .map(|event| Self::format_event(event, with_date))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        ContextData::new("calendar")
+            .with("today_count", self.today.len().to_string())
+            .with("this_week_count", self.this_week.len().to_string())
+            .with("today_events", lines(&self.today, false))
+            .with("this_week_events", lines(&self.this_week, true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[tokio::test]
+    async fn test_calendar_context_from_task_due_dates() {
+        let db = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::migrations::run_migrations(&db).await.unwrap();
+        let timezone = AppTimezone::parse("Asia/Tokyo").unwrap();
+
+        // 基準時刻: 2025-06-11（水）12:00 JST
+        let now = Utc.with_ymd_and_hms(2025, 6, 11, 3, 0, 0).unwrap();
+        let seed = [
+            ("午後の会議", "todo", "2025-06-11T15:00:00+09:00"),
+            ("朝の打ち合わせ", "in_progress", "2025-06-11T09:00:00+09:00"),
+            // 日付だけの期日はJSTの終日予定
+            ("レポート提出", "todo", "2025-06-11"),
+            ("金曜の締切", "todo", "2025-06-13T18:00:00+09:00"),
+            // UTCでは日曜だがJSTでは翌週の月曜
+            ("来週の作業", "todo", "2025-06-15T16:00:00Z"),
+            ("完了済み", "done", "2025-06-11T10:00:00+09:00"),
+            ("昨日の締切", "todo", "2025-06-10T10:00:00+09:00"),
+        ];
+        for (i, (title, status, due_date)) in seed.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO tasks (id, title, status, due_date, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?5)"
+            )
+            .bind(format!("task-{}", i))
+            .bind(title)
+            .bind(status)
+            .bind(due_date)
+            .bind(now.to_rfc3339())
+            .execute(&db)
+            .await
+            .unwrap();
+        }
+
+        let sources: Vec<Box<dyn CalendarSource>> = vec![Box::new(TaskDueDateSource::new(db.clone()))];
+        let calendar = CalendarContext::build(&sources, &timezone, now).await.unwrap();
+        let titles = |events: &[CalendarEvent]| events.iter().map(|e| e.title.clone()).collect::<Vec<_>>();
+        assert_eq!(titles(&calendar.today), vec!["レポート提出", "朝の打ち合わせ", "午後の会議"]);
+        assert_eq!(titles(&calendar.this_week), vec!["金曜の締切"]);
+
+        let data = calendar.to_context_data();
+        assert_eq!(data.context_type, "calendar");
+        assert_eq!(data.get("today_count"), Some(&"3".to_string()));
+        assert_eq!(data.get("this_week_count"), Some(&"1".to_string()));
+        assert_eq!(
+            data.get("today_events"),
+            Some(&"終日 レポート提出\n09:00 朝の打ち合わせ\n15:00 午後の会議".to_string())
+        );
+        assert_eq!(data.get("this_week_events"), Some(&"06/13(Fri) 18:00 金曜の締切".to_string()));
+
+        // スコープに"calendar"を指定して取得できる
+        let service = crate::services::ContextService::new(db);
+        let contexts = service.collect_context_for_scope(&["calendar"]).await.unwrap();
+        assert_eq!(contexts.len(), 1);
+        assert_eq!(contexts[0].context_type, "calendar");
+    }
+}
@@ -0,0 +1,95 @@
+use crate::error::AppError;
+use crate::services::SettingsService;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::sync::RwLock;
+
+/// 「閉じる」を確認する場合にフロントエンドへ送るイベント名
+pub const CLOSE_REQUESTED_EVENT: &str = "close_requested";
+
+/// ウィンドウを閉じたときの動作
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseBehavior {
+    /// ウィンドウを隠してトレイに常駐する
+    #[default]
+    Minimize,
+    /// アプリを終了する
+    Quit,
+    /// その都度ユーザーに確認する
+    Ask,
+}
+
+impl CloseBehavior {
+    /// 設定キー（agent_configテーブル）
+    pub const SETTINGS_KEY: &'static str = "close_behavior";
+}
+
+/// 閉じる操作に対して行う処理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseAction {
+    Hide,
+    Quit,
+    /// 閉じるのを止めて、フロントエンドに確認を求める
+    Ask,
+}
+
+/// 設定から閉じる操作に対する処理を決める
+pub fn decide_close_action(behavior: CloseBehavior) -> CloseAction {
+    match behavior {
+        CloseBehavior::Minimize => CloseAction::Hide,
+        CloseBehavior::Quit => CloseAction::Quit,
+        CloseBehavior::Ask => CloseAction::Ask,
+    }
+}
+
+/// ウィンドウイベントのハンドラー（同期処理）から参照する閉じる動作の設定
+///
+/// 起動時にDBから読み込み、変更時はDBとメモリの両方を更新する。
+pub struct CloseBehaviorState {
+    db: SqlitePool,
+    behavior: RwLock<CloseBehavior>,
+}
+
+impl CloseBehaviorState {
+    pub async fn load(db: SqlitePool) -> Self {
+        let behavior = SettingsService::get_json(&db, CloseBehavior::SETTINGS_KEY)
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        Self {
+            db,
+            behavior: RwLock::new(behavior),
+        }
+    }
+
+    pub fn behavior(&self) -> CloseBehavior {
+        self.behavior.read().map(|b| *b).unwrap_or_default()
+    }
+
+    pub async fn set_behavior(&self, behavior: CloseBehavior) -> Result<(), AppError> {
+        SettingsService::set_json(&self.db, CloseBehavior::SETTINGS_KEY, &behavior).await?;
+        if let Ok(mut current) = self.behavior.write() {
+            *current = behavior;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decide_close_action() {
+        assert_eq!(decide_close_action(CloseBehavior::Minimize), CloseAction::Hide);
+        assert_eq!(decide_close_action(CloseBehavior::Quit), CloseAction::Quit);
+        assert_eq!(decide_close_action(CloseBehavior::Ask), CloseAction::Ask);
+
+        // 既定は従来どおりトレイに最小化
+        assert_eq!(CloseBehavior::default(), CloseBehavior::Minimize);
+        assert_eq!(serde_json::from_str::<CloseBehavior>("\"ask\"").unwrap(), CloseBehavior::Ask);
+        assert!(serde_json::from_str::<CloseBehavior>("\"exit\"").is_err());
+    }
+}
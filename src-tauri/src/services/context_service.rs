@@ -1,7 +1,14 @@
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
-use chrono::{DateTime, Utc, Local, Weekday, Duration, Datelike, Timelike};
+use chrono::{DateTime, Utc, FixedOffset, NaiveDate, Weekday, Duration, Datelike, Timelike};
+use crate::services::app_timezone::AppTimezone;
+use crate::services::business_days::BusinessDaySettings;
+use crate::services::calendar_context::{CalendarContext, CalendarSource, TaskDueDateSource};
+use crate::services::SettingsService;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -42,7 +49,8 @@ impl ContextData {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemporalContext {
-    pub current_datetime: DateTime<Local>,
+    /// アプリのタイムゾーンでのローカル日時
+    pub current_datetime: DateTime<FixedOffset>,
     pub utc_datetime: DateTime<Utc>,
     pub weekday: Weekday,
     pub is_business_day: bool,
@@ -55,8 +63,12 @@ pub struct TemporalContext {
 
 impl TemporalContext {
     pub fn new() -> Self {
-        let now_local = Local::now();
-        let now_utc = now_local.with_timezone(&Utc);
+        Self::at(Utc::now(), &AppTimezone::System)
+    }
+    
+    /// 指定時刻の時間コンテキスト（曜日・時間帯などは`timezone`のローカル時刻で判定）
+    pub fn at(now_utc: DateTime<Utc>, timezone: &AppTimezone) -> Self {
+        let now_local = timezone.to_fixed_offset(now_utc);
         let weekday = now_local.weekday();
         
         Self {
@@ -124,8 +136,54 @@ impl TemporalContext {
         
         current.format("%Y-%m-%d").to_string()
     }
+    
+    /// 指定日より後の最初の営業日（営業日・休日の設定に従う）
+    pub fn next_business_day(date: NaiveDate, settings: &BusinessDaySettings) -> Option<NaiveDate> {
+        // 営業日の曜日が1つでもあれば、休日を考慮しても数年以内に見つかる
+        date.iter_days()
+            .skip(1)
+            .take(366 * 5)
+            .find(|candidate| settings.is_business_day(*candidate))
+    }
+    
+    /// 指定日から`days`営業日後の日付（営業日・休日の設定に従う）
+    pub fn add_business_days(date: NaiveDate, days: u32, settings: &BusinessDaySettings) -> Option<NaiveDate> {
+        (0..days).try_fold(date, |current, _| Self::next_business_day(current, settings))
+    }
 }
 
+// 総タスク数
+const TOTAL_TASKS_SQL: &str = "SELECT COUNT(*) FROM tasks WHERE deleted_at IS NULL";
+// 今日完了したタスク数
+const COMPLETED_TODAY_SQL: &str = "SELECT COUNT(*) FROM tasks WHERE status = 'done' AND DATE(updated_at) = DATE('now') AND deleted_at IS NULL";
+// ペンディングタスク数
+const PENDING_TASKS_SQL: &str = "SELECT COUNT(*) FROM tasks WHERE status IN ('todo', 'in_progress') AND deleted_at IS NULL";
+// 期限切れタスク数
+const OVERDUE_TASKS_SQL: &str = "SELECT COUNT(*) FROM tasks WHERE due_date < DATE('now') AND status != 'done' AND deleted_at IS NULL";
+// 今週完了したタスク数
+const COMPLETED_THIS_WEEK_SQL: &str = "SELECT COUNT(*) FROM tasks WHERE status = 'done' AND updated_at >= ? AND deleted_at IS NULL";
+// 今日が期限のタスク数
+const DUE_TODAY_SQL: &str = "SELECT COUNT(*) FROM tasks WHERE DATE(due_date) = DATE('now') AND status != 'done' AND deleted_at IS NULL";
+// 今週期限のタスク数
+const DUE_THIS_WEEK_SQL: &str = "SELECT COUNT(*) FROM tasks WHERE due_date BETWEEN DATE('now') AND DATE('now', '+7 days') AND status != 'done' AND deleted_at IS NULL";
+// 指定日時以降に完了したタスクの作成日時と完了日時
+const RECENT_COMPLETIONS_SQL: &str = "SELECT created_at, completed_at FROM tasks WHERE status = 'done' AND completed_at IS NOT NULL AND datetime(completed_at) >= datetime(?) AND deleted_at IS NULL";
+// 使われている回数の多いタグ名（同数ならタグ名順）
+const MOST_COMMON_TAGS_SQL: &str = r#"
+    SELECT tg.name FROM task_tags tt
+    INNER JOIN tags tg ON tg.id = tt.tag_id
+    INNER JOIN tasks t ON t.id = tt.task_id
+    WHERE t.deleted_at IS NULL
+    GROUP BY tg.id, tg.name
+    ORDER BY COUNT(*) DESC, tg.name ASC
+    LIMIT 5
+"#;
+
+/// 平均完了時間の集計対象にする期間（日）
+const COMPLETION_TIME_WINDOW_DAYS: i64 = 30;
+/// 平均完了時間の集計で異常値とみなす所要日数
+const MAX_COMPLETION_DAYS: f32 = 365.0;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskContext {
     pub total_tasks: i32,
@@ -142,65 +200,36 @@ pub struct TaskContext {
 
 impl TaskContext {
     pub async fn build(db: &SqlitePool) -> Result<Self, ContextError> {
-        let now = Utc::now();
-        let today_start = now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
-        let week_start = today_start - Duration::days(now.weekday().num_days_from_monday() as i64);
-        
-        // 総タスク数
-        let total_tasks: i32 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks")
-            .fetch_one(db)
-            .await?;
-        
-        // 今日完了したタスク数
-        let completed_today: i32 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM tasks WHERE status = 'completed' AND DATE(updated_at) = DATE('now')"
-        )
-        .fetch_one(db)
-        .await?;
-        
-        // ペンディングタスク数
-        let pending_tasks: i32 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM tasks WHERE status IN ('todo', 'in_progress')"
-        )
-        .fetch_one(db)
-        .await?;
-        
-        // 期限切れタスク数
-        let overdue_tasks: i32 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM tasks WHERE due_date < DATE('now') AND status != 'completed'"
-        )
-        .fetch_one(db)
-        .await?;
+        // 週の区切りは設定されたタイムゾーンの月曜0時
+        let timezone = AppTimezone::load(db).await;
+        let today = timezone.local_date(Utc::now());
+        let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        let week_start = timezone
+            .from_local(monday.and_hms_opt(0, 0, 0).unwrap())
+            .unwrap_or_else(|| monday.and_hms_opt(0, 0, 0).unwrap().and_utc());
         
-        // 今週完了したタスク数
-        let completed_this_week: i32 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM tasks WHERE status = 'completed' AND updated_at >= ?"
-        )
-        .bind(week_start.to_rfc3339())
-        .fetch_one(db)
-        .await?;
-        
-        // 今日が期限のタスク数
-        let tasks_due_today: i32 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM tasks WHERE DATE(due_date) = DATE('now') AND status != 'completed'"
-        )
-        .fetch_one(db)
-        .await?;
-        
-        // 今週期限のタスク数
-        let tasks_due_this_week: i32 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM tasks WHERE due_date BETWEEN DATE('now') AND DATE('now', '+7 days') AND status != 'completed'"
-        )
-        .fetch_one(db)
-        .await?;
-        
-        // よく使われるタグ (上位5つ)
-        let most_common_tags: Vec<String> = sqlx::query_scalar::<_, String>(
-            "SELECT tag FROM task_tags GROUP BY tag ORDER BY COUNT(*) DESC LIMIT 5"
-        )
-        .fetch_all(db)
-        .await
-        .unwrap_or_default();
+        // 互いに独立した集計クエリは並行して実行する
+        let (
+            total_tasks,
+            completed_today,
+            pending_tasks,
+            overdue_tasks,
+            completed_this_week,
+            tasks_due_today,
+            tasks_due_this_week,
+            most_common_tags,
+            average_completion_time,
+        ) = tokio::try_join!(
+            Self::count(db, TOTAL_TASKS_SQL, None),
+            Self::count(db, COMPLETED_TODAY_SQL, None),
+            Self::count(db, PENDING_TASKS_SQL, None),
+            Self::count(db, OVERDUE_TASKS_SQL, None),
+            Self::count(db, COMPLETED_THIS_WEEK_SQL, Some(week_start.to_rfc3339())),
+            Self::count(db, DUE_TODAY_SQL, None),
+            Self::count(db, DUE_THIS_WEEK_SQL, None),
+            Self::most_common_tags(db),
+            Self::average_completion_time(db),
+        )?;
         
         // ワークロードレベルを判定
         let current_workload_level = Self::calculate_workload_level(pending_tasks, tasks_due_this_week);
@@ -211,7 +240,7 @@ impl TaskContext {
             pending_tasks,
             overdue_tasks,
             completed_this_week,
-            average_completion_time: None, // TODO: 実装
+            average_completion_time,
             most_common_tags,
             current_workload_level,
             tasks_due_today,
@@ -219,6 +248,62 @@ impl TaskContext {
         })
     }
     
+    async fn count(db: &SqlitePool, sql: &str, bind: Option<String>) -> Result<i32, ContextError> {
+        let mut query = sqlx::query_scalar(sql);
+        if let Some(value) = bind {
+            query = query.bind(value);
+        }
+        Ok(query.fetch_one(db).await?)
+    }
+    
+    // よく使われるタグ (上位5つ、タグがなければ空)
+    // 補助的な情報なので、取得に失敗してもコンテキスト全体は失敗させない
+    async fn most_common_tags(db: &SqlitePool) -> Result<Vec<String>, ContextError> {
+        Ok(sqlx::query_scalar::<_, String>(MOST_COMMON_TAGS_SQL)
+            .fetch_all(db)
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to collect most common tags: {}", e);
+                Vec::new()
+            }))
+    }
+    
+    // 直近30日に完了したタスクの平均所要日数（完了したタスクがなければNone）
+    // よく使われるタグと同じく、取得に失敗してもコンテキスト全体は失敗させない
+    async fn average_completion_time(db: &SqlitePool) -> Result<Option<f32>, ContextError> {
+        let since = Utc::now() - Duration::days(COMPLETION_TIME_WINDOW_DAYS);
+        let rows: Vec<(String, String)> = match sqlx::query_as(RECENT_COMPLETIONS_SQL)
+            .bind(since.to_rfc3339())
+            .fetch_all(db)
+            .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                log::warn!("Failed to collect completion times: {}", e);
+                return Ok(None);
+            }
+        };
+        
+        let durations = rows.iter().filter_map(|(created_at, completed_at)| {
+            let created_at = DateTime::parse_from_rfc3339(created_at).ok()?;
+            let completed_at = DateTime::parse_from_rfc3339(completed_at).ok()?;
+            Some((completed_at - created_at).num_seconds() as f32 / 86_400.0)
+        });
+        Ok(Self::average_completion_days(durations))
+    }
+    
+    /// 所要日数の平均（負の値や極端に大きい値は異常値として除く）
+    fn average_completion_days(durations: impl IntoIterator<Item = f32>) -> Option<f32> {
+        let valid: Vec<f32> = durations
+            .into_iter()
+            .filter(|days| (0.0..=MAX_COMPLETION_DAYS).contains(days))
+            .collect();
+        if valid.is_empty() {
+            return None;
+        }
+        Some(valid.iter().sum::<f32>() / valid.len() as f32)
+    }
+    
     fn calculate_workload_level(pending_tasks: i32, due_this_week: i32) -> String {
         let workload_score = pending_tasks + (due_this_week * 2); // 今週期限は重み2倍
         
@@ -240,28 +325,197 @@ impl TaskContext {
             .with("tasks_due_today", self.tasks_due_today.to_string())
             .with("tasks_due_this_week", self.tasks_due_this_week.to_string())
             .with("most_common_tags", self.most_common_tags.join(", "))
+            .with(
+                "average_completion_time",
+                self.average_completion_time.map(|days| format!("{:.1}", days)).unwrap_or_default(),
+            )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgendaItem {
+    pub title: String,
+    pub due_date: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgendaContext {
+    pub upcoming: Vec<AgendaItem>,
+}
+
+impl AgendaContext {
+    /// 直近7日以内に期日を迎える未完了タスク（期限切れを含む）
+    pub async fn build(db: &SqlitePool) -> Result<Self, ContextError> {
+        let limit = (Utc::now() + Duration::days(7)).to_rfc3339();
+        
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT title, due_date FROM tasks
+            WHERE due_date IS NOT NULL AND status != 'done' AND deleted_at IS NULL AND datetime(due_date) <= datetime(?1)
+            ORDER BY datetime(due_date) ASC
+            LIMIT 10
+            "#
+        )
+        .bind(limit)
+        .fetch_all(db)
+        .await?;
+        
+        Ok(Self {
+            upcoming: rows
+                .into_iter()
+                .map(|(title, due_date)| AgendaItem { title, due_date })
+                .collect(),
+        })
+    }
+    
+    pub fn to_context_data(&self) -> ContextData {
+        let lines: Vec<String> = self.upcoming
+            .iter()
+            .map(|item| format!("{} ({})", item.title, item.due_date))
+            .collect();
+        
+        ContextData::new("agenda")
+            .with("upcoming_count", self.upcoming.len().to_string())
+            .with("upcoming_tasks", lines.join("\n"))
+    }
+}
+
+/// タスクコンテキストのキャッシュを使うかの設定キー（既定は使う）
+pub const CONTEXT_CACHE_ENABLED_KEY: &str = "context_cache_enabled";
+/// キャッシュしたタスクコンテキストの有効期間（秒）
+const CONTEXT_CACHE_TTL_SECONDS: u64 = 60;
+
+/// タスクが変更されるたびに進む世代番号（ContextServiceはインスタンスごとにキャッシュを持つため全体で共有する）
+static TASK_DATA_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// タスクの作成・更新・削除後に呼び、キャッシュ済みのタスクコンテキストを無効にする
+pub fn invalidate_task_context_cache() {
+    TASK_DATA_GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// 現在のタスクデータの世代番号（タスク由来の集計結果のキャッシュ判定に使う）
+pub fn task_data_generation() -> u64 {
+    TASK_DATA_GENERATION.load(Ordering::SeqCst)
+}
+
+struct CachedTaskContext {
+    context: TaskContext,
+    computed_at: Instant,
+    generation: u64,
+}
+
+/// 直近に集計したタスクコンテキスト
+///
+/// 有効期間を過ぎるか、集計後にタスクが変更されて世代番号が進むと使わない。
+#[derive(Default)]
+struct TaskContextCache {
+    entry: Mutex<Option<CachedTaskContext>>,
+}
+
+impl TaskContextCache {
+    fn get(&self, generation: u64, now: Instant) -> Option<TaskContext> {
+        let entry = self.entry.lock().unwrap();
+        entry
+            .as_ref()
+            .filter(|cached| {
+                cached.generation == generation
+                    && now.saturating_duration_since(cached.computed_at).as_secs() < CONTEXT_CACHE_TTL_SECONDS
+            })
+            .map(|cached| cached.context.clone())
+    }
+
+    fn store(&self, context: TaskContext, generation: u64, computed_at: Instant) {
+        *self.entry.lock().unwrap() = Some(CachedTaskContext { context, computed_at, generation });
+    }
+
+    fn clear(&self) {
+        *self.entry.lock().unwrap() = None;
     }
 }
 
+/// 収集可能なコンテキストタイプ
+pub const CONTEXT_TYPES: [&str; 4] = ["temporal", "task", "agenda", "calendar"];
+
+/// 操作ごとのデフォルトのコンテキスト範囲
+///
+/// 簡単なチャットは時間情報のみ、計画立案はタスク状況と直近の予定まで含める。
+pub fn default_context_scope(operation: &str) -> Vec<String> {
+    let scope: &[&str] = match operation {
+        "chat" => &["temporal"],
+        "planning_assistant" | "project_planning" => &["temporal", "task", "agenda"],
+        _ => &["temporal", "task"],
+    };
+    scope.iter().map(|s| s.to_string()).collect()
+}
+
 pub struct ContextService {
     db: SqlitePool,
+    /// "calendar"コンテキストの予定の取得元（既定ではタスクの期日のみ）
+    calendar_sources: Vec<Box<dyn CalendarSource>>,
+    task_context_cache: TaskContextCache,
 }
 
 impl ContextService {
     pub fn new(db: SqlitePool) -> Self {
-        Self { db }
+        let calendar_sources: Vec<Box<dyn CalendarSource>> = vec![Box::new(TaskDueDateSource::new(db.clone()))];
+        Self {
+            db,
+            calendar_sources,
+            task_context_cache: TaskContextCache::default(),
+        }
+    }
+    
+    /// 外部カレンダーなどの予定の取得元を追加
+    pub fn add_calendar_source(&mut self, source: Box<dyn CalendarSource>) {
+        self.calendar_sources.push(source);
     }
     
-    pub fn get_temporal_context(&self) -> TemporalContext {
-        TemporalContext::new()
+    /// 設定されたタイムゾーンでの現在の時間コンテキスト
+    pub async fn get_temporal_context(&self) -> TemporalContext {
+        TemporalContext::at(Utc::now(), &AppTimezone::load(&self.db).await)
     }
     
+    /// タスクの集計（キャッシュが有効なら直近の結果を再利用する）
     pub async fn get_task_context(&self) -> Result<TaskContext, ContextError> {
-        TaskContext::build(&self.db).await
+        if !self.is_cache_enabled().await {
+            return TaskContext::build(&self.db).await;
+        }
+        
+        // 集計中にタスクが変更された場合に備え、集計前の世代番号で保存する
+        let generation = task_data_generation();
+        if let Some(context) = self.task_context_cache.get(generation, Instant::now()) {
+            return Ok(context);
+        }
+        let context = TaskContext::build(&self.db).await?;
+        self.task_context_cache.store(context.clone(), generation, Instant::now());
+        Ok(context)
+    }
+    
+    /// タスクコンテキストのキャッシュを使うか（設定を読めない場合も使う）
+    pub async fn is_cache_enabled(&self) -> bool {
+        match SettingsService::get(&self.db, CONTEXT_CACHE_ENABLED_KEY).await {
+            Ok(value) => value.as_deref() != Some("false"),
+            Err(e) => {
+                log::warn!("Failed to load context cache setting: {}", e);
+                true
+            }
+        }
+    }
+    
+    pub async fn set_cache_enabled(&self, enabled: bool) -> Result<(), ContextError> {
+        SettingsService::set(&self.db, CONTEXT_CACHE_ENABLED_KEY, if enabled { "true" } else { "false" }).await?;
+        self.task_context_cache.clear();
+        Ok(())
+    }
+    
+    /// 設定されたタイムゾーンでの今日・今週の予定
+    pub async fn get_calendar_context(&self) -> Result<CalendarContext, ContextError> {
+        let timezone = AppTimezone::load(&self.db).await;
+        CalendarContext::build(&self.calendar_sources, &timezone, Utc::now()).await
     }
     
     pub async fn collect_basic_context(&self) -> Result<Vec<ContextData>, ContextError> {
-        let temporal = self.get_temporal_context();
+        let temporal = self.get_temporal_context().await;
         let task = self.get_task_context().await?;
         
         Ok(vec![
@@ -276,13 +530,21 @@ impl ContextService {
         for context_type in scope {
             match *context_type {
                 "temporal" => {
-                    let temporal = self.get_temporal_context();
+                    let temporal = self.get_temporal_context().await;
                     contexts.push(temporal.to_context_data());
                 },
                 "task" => {
                     let task = self.get_task_context().await?;
                     contexts.push(task.to_context_data());
                 },
+                "agenda" => {
+                    let agenda = AgendaContext::build(&self.db).await?;
+                    contexts.push(agenda.to_context_data());
+                },
+                "calendar" => {
+                    let calendar = self.get_calendar_context().await?;
+                    contexts.push(calendar.to_context_data());
+                },
                 _ => {
                     // 未知のコンテキストタイプは無視
                     continue;
@@ -360,49 +622,42 @@ mod tests {
     
     #[tokio::test]
     async fn test_context_service_basic_functionality() {
-        // メモリ内データベースでのテスト
+        // メモリ内データベースでのテスト（実スキーマ）
         let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+        crate::database::migrations::run_migrations(&pool).await.unwrap();
         
-        // テスト用のテーブルを作成
-        sqlx::query(r#"
-            CREATE TABLE tasks (
-                id TEXT PRIMARY KEY,
-                title TEXT NOT NULL,
-                status TEXT NOT NULL,
-                due_date TEXT,
-                updated_at TEXT NOT NULL
-            )
-        "#).execute(&pool).await.unwrap();
-        
+        // テストデータを挿入
         sqlx::query(r#"
-            CREATE TABLE task_tags (
-                task_id TEXT,
-                tag TEXT,
-                PRIMARY KEY (task_id, tag)
-            )
+            INSERT INTO tasks (id, title, status, due_date, created_at, updated_at) 
+            VALUES 
+                ('1', 'Test Task 1', 'todo', date('now', '+1 day'), datetime('now'), datetime('now')),
+                ('2', 'Test Task 2', 'done', date('now'), datetime('now'), datetime('now')),
+                ('3', 'Test Task 3', 'todo', date('now', '-1 day'), datetime('now'), datetime('now'))
         "#).execute(&pool).await.unwrap();
         
-        // テストデータを挿入
         sqlx::query(r#"
-            INSERT INTO tasks (id, title, status, due_date, updated_at) 
+            INSERT INTO tags (id, name, color, created_at, updated_at) 
             VALUES 
-                ('1', 'Test Task 1', 'todo', date('now', '+1 day'), datetime('now')),
-                ('2', 'Test Task 2', 'completed', date('now'), datetime('now')),
-                ('3', 'Test Task 3', 'todo', date('now', '-1 day'), datetime('now'))
+                ('tag-work', 'work', '#3b82f6', datetime('now'), datetime('now')),
+                ('tag-personal', 'personal', '#10b981', datetime('now'), datetime('now')),
+                ('tag-urgent', 'urgent', '#ef4444', datetime('now'), datetime('now')),
+                ('tag-unused', 'unused', '#6b7280', datetime('now'), datetime('now'))
         "#).execute(&pool).await.unwrap();
         
         sqlx::query(r#"
-            INSERT INTO task_tags (task_id, tag) 
+            INSERT INTO task_tags (task_id, tag_id) 
             VALUES 
-                ('1', 'work'),
-                ('2', 'personal'),
-                ('3', 'urgent')
+                ('1', 'tag-work'),
+                ('2', 'tag-work'),
+                ('3', 'tag-work'),
+                ('2', 'tag-personal'),
+                ('3', 'tag-urgent')
         "#).execute(&pool).await.unwrap();
         
         let service = ContextService::new(pool);
         
         // TemporalContextのテスト
-        let temporal = service.get_temporal_context();
+        let temporal = service.get_temporal_context().await;
         assert!(!temporal.formatted_date.is_empty());
         
         // TaskContextのテスト
@@ -411,6 +666,10 @@ mod tests {
         assert_eq!(task_context.completed_today, 1);
         assert_eq!(task_context.pending_tasks, 2);
         assert_eq!(task_context.overdue_tasks, 1);
+        // 今日が期日でも完了済みのタスクは数えない
+        assert_eq!(task_context.tasks_due_today, 0);
+        // 使われている回数順のタグ名（同数ならタグ名順、未使用のタグは含まない）
+        assert_eq!(task_context.most_common_tags, vec!["work", "personal", "urgent"]);
         
         // 基本コンテキスト収集のテスト
         let contexts = service.collect_basic_context().await.unwrap();
@@ -428,4 +687,161 @@ mod tests {
         assert!(variables.contains_key("task_total_tasks"));
         assert_eq!(variables.get("task_total_tasks"), Some(&"3".to_string()));
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_task_context_concurrent_build_matches_sequential() {
+        let db = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::migrations::run_migrations(&db).await.unwrap();
+        
+        let now = Utc::now();
+        let seed = [
+            ("todo", Some(now - Duration::days(3)), now),
+            ("todo", Some(now), now),
+            ("in_progress", Some(now + Duration::days(2)), now),
+            ("in_progress", None, now),
+            ("done", None, now),
+            // 期日を過ぎていても完了済みなら期限切れに数えない
+            ("done", Some(now - Duration::days(5)), now),
+            ("done", None, now - Duration::days(20)),
+            ("inbox", Some(now + Duration::days(30)), now),
+        ];
+        for (i, (status, due_date, updated_at)) in seed.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO tasks (id, title, status, due_date, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+            )
+            .bind(format!("task-{}", i))
+            .bind(format!("Task {}", i))
+            .bind(status)
+            .bind(due_date.map(|d| d.format("%Y-%m-%d").to_string()))
+            .bind(updated_at.to_rfc3339())
+            .bind(updated_at.to_rfc3339())
+            .execute(&db)
+            .await
+            .unwrap();
+        }
+        
+        let context = TaskContext::build(&db).await.unwrap();
+        
+        // 並行化前の順次実行で得られていた値（シードから手計算したもの）と一致すること
+        assert_eq!(context.total_tasks, 8);
+        assert_eq!(context.pending_tasks, 4);
+        assert_eq!(context.overdue_tasks, 1);
+        // 実際の完了ステータス'done'で数える（20日前に完了したものは今日・今週に含まない）
+        assert_eq!(context.completed_today, 2);
+        assert_eq!(context.completed_this_week, 2);
+        // 完了済み・30日後のものは除き、今日と2日後が期日のタスクだけを数える
+        assert_eq!(context.tasks_due_today, 1);
+        assert_eq!(context.tasks_due_this_week, 2);
+        assert!(context.most_common_tags.is_empty());
+        assert_eq!(
+            context.current_workload_level,
+            TaskContext::calculate_workload_level(context.pending_tasks, context.tasks_due_this_week)
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_average_completion_time_of_recent_tasks() {
+        let db = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::migrations::run_migrations(&db).await.unwrap();
+        
+        // 完了したタスクがなければNone
+        assert_eq!(TaskContext::build(&db).await.unwrap().average_completion_time, None);
+        
+        let now = Utc::now();
+        let seed = [
+            ("done", now - Duration::days(2), Some(now)),
+            ("done", now - Duration::days(5), Some(now - Duration::days(1))),
+            // 作成日時より前に完了している異常値は除く
+            ("done", now, Some(now - Duration::days(1))),
+            // 極端に長くかかったものは除く
+            ("done", now - Duration::days(800), Some(now)),
+            // 30日より前に完了したものは対象外
+            ("done", now - Duration::days(60), Some(now - Duration::days(40))),
+            ("todo", now - Duration::days(10), None),
+        ];
+        for (i, (status, created_at, completed_at)) in seed.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO tasks (id, title, status, completed_at, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?5)"
+            )
+            .bind(format!("task-{}", i))
+            .bind(format!("Task {}", i))
+            .bind(status)
+            .bind(completed_at.map(|d| d.to_rfc3339()))
+            .bind(created_at.to_rfc3339())
+            .execute(&db)
+            .await
+            .unwrap();
+        }
+        
+        let context = TaskContext::build(&db).await.unwrap();
+        assert_eq!(context.average_completion_time, Some(3.0));
+        assert_eq!(context.to_context_data().get("average_completion_time"), Some(&"3.0".to_string()));
+        
+        assert_eq!(TaskContext::average_completion_days([1.0, -0.5, 2.0, 400.0]), Some(1.5));
+        assert_eq!(TaskContext::average_completion_days([-1.0]), None);
+    }
+    
+    #[test]
+    fn test_task_context_cache_expiry_and_generation() {
+        let cache = TaskContextCache::default();
+        let context = TaskContext {
+            total_tasks: 5,
+            completed_today: 0,
+            pending_tasks: 5,
+            overdue_tasks: 0,
+            completed_this_week: 0,
+            average_completion_time: None,
+            most_common_tags: Vec::new(),
+            current_workload_level: "medium".to_string(),
+            tasks_due_today: 0,
+            tasks_due_this_week: 0,
+        };
+        let computed_at = Instant::now();
+        assert!(cache.get(1, computed_at).is_none());
+        
+        cache.store(context, 1, computed_at);
+        let within_ttl = computed_at + std::time::Duration::from_secs(CONTEXT_CACHE_TTL_SECONDS - 1);
+        assert_eq!(cache.get(1, within_ttl).map(|c| c.total_tasks), Some(5));
+        // 有効期間を過ぎたもの・集計後にタスクが変更されたものは使わない
+        assert!(cache.get(1, computed_at + std::time::Duration::from_secs(CONTEXT_CACHE_TTL_SECONDS)).is_none());
+        assert!(cache.get(2, within_ttl).is_none());
+        
+        cache.clear();
+        assert!(cache.get(1, computed_at).is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_task_context_cache_invalidated_by_task_changes() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::migrations::run_migrations(&pool).await.unwrap();
+        let tasks = crate::services::TaskService::new(crate::database::Database { pool: pool.clone() });
+        let service = ContextService::new(pool.clone());
+        assert!(service.is_cache_enabled().await);
+        assert_eq!(service.get_task_context().await.unwrap().total_tasks, 0);
+        
+        let create = |title: &str| crate::models::CreateTaskRequest {
+            title: title.to_string(),
+            description: None,
+            status: Some(crate::models::TaskStatus::Todo),
+            parent_id: None,
+            due_date: None,
+            notification_settings: None,
+            browser_actions: None,
+        };
+        let task = tasks.create_task(create("キャッシュ確認")).await.unwrap();
+        assert_eq!(service.get_task_context().await.unwrap().total_tasks, 1);
+        tasks.delete_task(&task.id).await.unwrap();
+        assert_eq!(service.get_task_context().await.unwrap().total_tasks, 0);
+        
+        // キャッシュを切ると、TaskServiceを通さない変更も毎回反映される
+        service.set_cache_enabled(false).await.unwrap();
+        assert!(!service.is_cache_enabled().await);
+        sqlx::query(
+            "INSERT INTO tasks (id, title, status, created_at, updated_at) VALUES ('direct', '直接追加', 'todo', datetime('now'), datetime('now'))"
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert_eq!(service.get_task_context().await.unwrap().total_tasks, 1);
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::services::app_timezone::AppTimezone;
+
+/// 一日の終わりのまとめ通知の設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailySummarySettings {
+    pub enabled: bool,
+    /// 通知するローカル時刻（HH:MM形式）
+    pub time: String,
+    /// AIで文面を整えるか
+    pub use_ai: bool,
+}
+
+impl Default for DailySummarySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            time: "18:00".to_string(),
+            use_ai: false,
+        }
+    }
+}
+
+impl DailySummarySettings {
+    /// 設定キー（agent_configテーブル）
+    pub const SETTINGS_KEY: &'static str = "daily_summary";
+    /// 最後にまとめを通知したローカル日付の設定キー
+    pub const LAST_SENT_KEY: &'static str = "daily_summary_last_sent";
+
+    pub fn validate(&self) -> Result<(), String> {
+        NaiveTime::parse_from_str(&self.time, "%H:%M")
+            .map(|_| ())
+            .map_err(|_| format!("Invalid summary time (expected HH:MM): {}", self.time))
+    }
+}
+
+/// まとめ通知に使う集計値
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailySummaryStats {
+    pub completed_today: i64,
+    pub overdue: i64,
+    pub remaining: i64,
+}
+
+/// 集計値からまとめの文面を作成
+pub fn build_summary_text(stats: &DailySummaryStats) -> String {
+    let completed = if stats.completed_today > 0 {
+        format!("今日は{}件のタスクを完了しました。", stats.completed_today)
+    } else {
+        "今日完了したタスクはありません。".to_string()
+    };
+
+    let overdue = if stats.overdue > 0 {
+        format!("期限切れのタスクが{}件残っています。", stats.overdue)
+    } else {
+        "期限切れのタスクはありません。".to_string()
+    };
+
+    format!("{}{}未完了のタスクは{}件です。", completed, overdue, stats.remaining)
+}
+
+/// まとめを今通知すべきか判定
+///
+/// 設定時刻を過ぎていて、今日（ローカル日付）まだ通知していなければ通知する。
+/// 設定時刻にアプリが起動していなくても、その日のうちに起動すれば通知される。
+pub fn should_fire_summary(
+    now: DateTime<Utc>,
+    settings: &DailySummarySettings,
+    timezone: &AppTimezone,
+    last_sent: Option<NaiveDate>,
+) -> bool {
+    if !settings.enabled {
+        return false;
+    }
+
+    let today = timezone.local_date(now);
+    if last_sent == Some(today) {
+        return false;
+    }
+
+    match timezone.at_local_time(today, &settings.time) {
+        Some(target) => now >= target,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_build_summary_text() {
+        let text = build_summary_text(&DailySummaryStats { completed_today: 4, overdue: 2, remaining: 7 });
+        assert_eq!(text, "今日は4件のタスクを完了しました。期限切れのタスクが2件残っています。未完了のタスクは7件です。");
+
+        let quiet_day = build_summary_text(&DailySummaryStats { completed_today: 0, overdue: 0, remaining: 3 });
+        assert_eq!(quiet_day, "今日完了したタスクはありません。期限切れのタスクはありません。未完了のタスクは3件です。");
+    }
+
+    #[test]
+    fn test_should_fire_summary() {
+        let tz = AppTimezone::parse("Asia/Tokyo").unwrap();
+        let settings = DailySummarySettings { enabled: true, time: "18:00".to_string(), use_ai: false };
+        let today = NaiveDate::from_ymd_opt(2025, 6, 10).unwrap();
+
+        // 17:59 JST は早すぎる、18:00 JST 以降は通知
+        let before = Utc.with_ymd_and_hms(2025, 6, 10, 8, 59, 0).unwrap();
+        let at_time = Utc.with_ymd_and_hms(2025, 6, 10, 9, 0, 0).unwrap();
+        let late = Utc.with_ymd_and_hms(2025, 6, 10, 13, 30, 0).unwrap();
+        assert!(!should_fire_summary(before, &settings, &tz, None));
+        assert!(should_fire_summary(at_time, &settings, &tz, None));
+        assert!(should_fire_summary(late, &settings, &tz, Some(today.pred_opt().unwrap())));
+
+        // 同じ日に二度は通知しない
+        assert!(!should_fire_summary(late, &settings, &tz, Some(today)));
+
+        // 無効時は通知しない
+        let disabled = DailySummarySettings { enabled: false, ..settings };
+        assert!(!should_fire_summary(at_time, &disabled, &tz, None));
+    }
+}
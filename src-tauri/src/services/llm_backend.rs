@@ -0,0 +1,213 @@
+use crate::services::ollama_client::{GenerateOptions, GenerateResponse, ModelInfo, OllamaClient, OllamaError, RetryPolicy};
+use crate::services::openai_compat_client::OpenAiCompatClient;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+/// Kind of server the agent sends model requests to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendType {
+    /// Ollama's native API (`/api/generate`, `/api/tags`)
+    #[default]
+    Ollama,
+    /// OpenAI-compatible API (`/chat/completions`, `/models`), e.g. LM Studio or vLLM
+    OpenAiCompat,
+}
+
+impl BackendType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackendType::Ollama => "ollama",
+            BackendType::OpenAiCompat => "open_ai_compat",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "ollama" => Some(BackendType::Ollama),
+            "open_ai_compat" => Some(BackendType::OpenAiCompat),
+            _ => None,
+        }
+    }
+}
+
+/// Text generation server used by `AgentService`
+///
+/// Responses of every backend are mapped onto Ollama's `GenerateResponse` so the
+/// rest of the agent does not depend on which server answered.
+#[async_trait]
+pub trait LlmBackend: Send + Sync + std::fmt::Debug {
+    fn base_url(&self) -> &str;
+
+    /// Model used when none is given
+    fn model(&self) -> &str;
+
+    /// Same server and settings with a different default model
+    fn with_model(&self, model: &str) -> Arc<dyn LlmBackend>;
+
+    /// Model used by `embeddings`
+    fn embedding_model(&self) -> &str;
+
+    async fn test_connection(&self) -> Result<bool, OllamaError>;
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, OllamaError>;
+
+    /// Embedding vector of `text` computed by the embedding model
+    async fn embeddings(&self, text: &str) -> Result<Vec<f32>, OllamaError>;
+
+    async fn generate_with_model(
+        &self,
+        model: &str,
+        prompt: &str,
+        options: Option<GenerateOptions>,
+    ) -> Result<GenerateResponse, OllamaError>;
+
+    /// Generate with the default model, asking for a JSON response
+    async fn generate_json_response(
+        &self,
+        prompt: &str,
+        options: Option<GenerateOptions>,
+    ) -> Result<GenerateResponse, OllamaError>;
+
+    /// Generate with the default model, passing each chunk to `on_chunk` as it arrives
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        options: Option<GenerateOptions>,
+        // The explicit `for<'c>` keeps the closure higher-ranked through async_trait's lifetime rewriting
+        on_chunk: &mut (dyn for<'c> FnMut(&'c str) -> ControlFlow<()> + Send),
+    ) -> Result<GenerateResponse, OllamaError>;
+
+    async fn generate(&self, prompt: &str, options: Option<GenerateOptions>) -> Result<GenerateResponse, OllamaError> {
+        let model = self.model().to_string();
+        self.generate_with_model(&model, prompt, options).await
+    }
+
+    async fn generate_json(&self, prompt: &str, options: Option<GenerateOptions>) -> Result<serde_json::Value, OllamaError> {
+        let response = self.generate_json_response(prompt, options).await?;
+        OllamaClient::parse_json_content(&response)
+    }
+}
+
+/// Settings needed to build a backend
+#[derive(Debug, Clone)]
+pub struct BackendSettings {
+    pub backend_type: BackendType,
+    pub base_url: String,
+    pub model: String,
+    pub timeout_seconds: u64,
+    pub retry_policy: RetryPolicy,
+    pub embedding_model: String,
+    /// Only used by OpenAI-compatible servers
+    pub api_key: Option<String>,
+}
+
+/// Build the backend selected by `settings.backend_type`
+pub fn build_backend(settings: BackendSettings) -> Arc<dyn LlmBackend> {
+    match settings.backend_type {
+        BackendType::Ollama => Arc::new(
+            OllamaClient::new(settings.base_url, settings.model, settings.timeout_seconds)
+                .with_retry_policy(settings.retry_policy)
+                .with_embedding_model(settings.embedding_model),
+        ),
+        BackendType::OpenAiCompat => Arc::new(
+            OpenAiCompatClient::new(settings.base_url, settings.model, settings.timeout_seconds, settings.api_key)
+                .with_retry_policy(settings.retry_policy)
+                .with_embedding_model(settings.embedding_model),
+        ),
+    }
+}
+
+#[async_trait]
+impl LlmBackend for OllamaClient {
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn model(&self) -> &str {
+        self.get_model()
+    }
+
+    fn with_model(&self, model: &str) -> Arc<dyn LlmBackend> {
+        Arc::new(
+            OllamaClient::new(self.base_url.clone(), model.to_string(), self.timeout_seconds)
+                .with_retry_policy(self.retry_policy)
+                .with_embedding_model(self.get_embedding_model()),
+        )
+    }
+
+    fn embedding_model(&self) -> &str {
+        self.get_embedding_model()
+    }
+
+    async fn test_connection(&self) -> Result<bool, OllamaError> {
+        OllamaClient::test_connection(self).await
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, OllamaError> {
+        OllamaClient::list_models(self).await
+    }
+
+    async fn embeddings(&self, text: &str) -> Result<Vec<f32>, OllamaError> {
+        OllamaClient::embeddings(self, text).await
+    }
+
+    async fn generate_with_model(
+        &self,
+        model: &str,
+        prompt: &str,
+        options: Option<GenerateOptions>,
+    ) -> Result<GenerateResponse, OllamaError> {
+        OllamaClient::generate_with_model(self, model, prompt, options).await
+    }
+
+    async fn generate_json_response(
+        &self,
+        prompt: &str,
+        options: Option<GenerateOptions>,
+    ) -> Result<GenerateResponse, OllamaError> {
+        OllamaClient::generate_json_response(self, prompt, options).await
+    }
+
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        options: Option<GenerateOptions>,
+        on_chunk: &mut (dyn for<'c> FnMut(&'c str) -> ControlFlow<()> + Send),
+    ) -> Result<GenerateResponse, OllamaError> {
+        OllamaClient::generate_stream(self, prompt, options, on_chunk).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_backend_by_type() {
+        let settings = |backend_type| BackendSettings {
+            backend_type,
+            base_url: "http://localhost:1234/v1".to_string(),
+            model: "qwen2.5-7b".to_string(),
+            timeout_seconds: 30,
+            retry_policy: RetryPolicy::default(),
+            embedding_model: "nomic-embed-text".to_string(),
+            api_key: None,
+        };
+
+        let ollama = build_backend(settings(BackendType::Ollama));
+        assert!(format!("{:?}", ollama).starts_with("OllamaClient"));
+        let compat = build_backend(settings(BackendType::OpenAiCompat));
+        assert!(format!("{:?}", compat).starts_with("OpenAiCompatClient"));
+
+        let switched = compat.with_model("llama-3.1-8b");
+        assert_eq!(switched.model(), "llama-3.1-8b");
+        assert_eq!(switched.base_url(), "http://localhost:1234/v1");
+        assert_eq!(switched.embedding_model(), "nomic-embed-text");
+
+        assert_eq!(BackendType::parse(BackendType::OpenAiCompat.as_str()), Some(BackendType::OpenAiCompat));
+        assert_eq!(BackendType::parse("anthropic"), None);
+    }
+}
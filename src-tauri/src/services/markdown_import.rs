@@ -0,0 +1,58 @@
+/// Markdownのチェックリスト項目
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecklistItem {
+    /// 行頭のインデント幅（タブは4スペースとして数える）
+    pub indent: usize,
+    pub title: String,
+    pub checked: bool,
+}
+
+/// `- [ ]` / `- [x]` 形式のチェックリストを解析
+///
+/// 空行・見出し・通常の文章は無視する。チェックボックスの形式が崩れた項目は
+/// 読み飛ばし、行番号付きの警告として返す。
+pub fn parse_checklist(markdown: &str) -> (Vec<ChecklistItem>, Vec<String>) {
+    let mut items = Vec::new();
+    let mut warnings = Vec::new();
+
+    for (index, line) in markdown.lines().enumerate() {
+        let line_number = index + 1;
+        let trimmed = line.trim_start();
+
+        let Some(rest) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        else {
+            continue;
+        };
+
+        let indent = line[..line.len() - trimmed.len()]
+            .chars()
+            .map(|c| if c == '\t' { 4 } else { 1 })
+            .sum();
+
+        let rest = rest.trim_start();
+        let (checked, title) = if let Some(title) = rest.strip_prefix("[ ]") {
+            (false, title)
+        } else if let Some(title) = rest.strip_prefix("[x]").or_else(|| rest.strip_prefix("[X]")) {
+            (true, title)
+        } else {
+            warnings.push(format!("Line {}: not a checklist item, skipped: {}", line_number, line.trim()));
+            continue;
+        };
+
+        let title = title.trim();
+        if title.is_empty() {
+            warnings.push(format!("Line {}: checklist item without a title, skipped", line_number));
+            continue;
+        }
+
+        items.push(ChecklistItem {
+            indent,
+            title: title.to_string(),
+            checked,
+        });
+    }
+
+    (items, warnings)
+}
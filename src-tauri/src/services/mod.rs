@@ -1,13 +1,35 @@
 pub mod task_service;
 pub mod tag_service;
 pub mod ollama_client;
+pub mod openai_compat_client;
+pub mod llm_backend;
 pub mod agent_service;
 pub mod personality_manager;
 pub mod url_validator;
 pub mod browser_action_service;
 pub mod notification_service;
 pub mod context_service;
+pub mod calendar_context;
 pub mod prompt_manager;
+pub mod settings_service;
+pub mod app_timezone;
+pub mod business_days;
+pub mod urgency_score;
+pub mod task_similarity;
+pub mod daily_summary;
+pub mod markdown_import;
+pub mod notification_level;
+pub mod notification_escalation;
+pub mod notification_presentation;
+pub mod notification_profile;
+pub mod quiet_hours;
+pub mod task_limits;
+pub mod subtask_completion;
+pub mod task_order;
+pub mod recurrence;
+pub mod status_report;
+pub mod tray_behavior;
+pub mod close_behavior;
 
 pub use task_service::TaskService;
 pub use tag_service::TagService;
@@ -17,4 +39,5 @@ pub use personality_manager::PersonalityManager;
 pub use url_validator::URLValidator;
 pub use browser_action_service::BrowserActionService;
 pub use notification_service::NotificationService;
-pub use context_service::ContextService;
\ No newline at end of file
+pub use context_service::ContextService;
+pub use settings_service::SettingsService;
\ No newline at end of file
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+/// 無視され続けている通知のレベルを段階的に引き上げる設定
+///
+/// 完了していないタスクの通知が`fires_per_step`回発火するごとにレベルを1つ上げ、
+/// `max_level`で頭打ちにする。発火回数はタスクを完了すると数え直す。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationEscalationSettings {
+    pub enabled: bool,
+    /// レベルを1つ上げるまでの発火回数
+    pub fires_per_step: i64,
+    /// 引き上げる上限のレベル
+    pub max_level: i32,
+}
+
+impl Default for NotificationEscalationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fires_per_step: 3,
+            max_level: 3,
+        }
+    }
+}
+
+impl NotificationEscalationSettings {
+    /// 設定キー（agent_configテーブル）
+    pub const SETTINGS_KEY: &'static str = "notification_escalation";
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=100).contains(&self.fires_per_step) {
+            return Err(format!("Fires per step must be between 1 and 100: {}", self.fires_per_step));
+        }
+        if !(1..=3).contains(&self.max_level) {
+            return Err(format!("Maximum escalation level must be between 1 and 3: {}", self.max_level));
+        }
+        Ok(())
+    }
+
+    /// これまでの発火回数に応じて引き上げたレベル（元のレベルより下げることはない）
+    pub fn escalate(&self, level: i32, fire_count: i64) -> i32 {
+        if !self.enabled || level >= self.max_level {
+            return level;
+        }
+        let steps = (fire_count / self.fires_per_step).min(i64::from(self.max_level - level)) as i32;
+        level + steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escalate_by_fire_count() {
+        let settings = NotificationEscalationSettings { enabled: true, ..Default::default() };
+        assert!(settings.validate().is_ok());
+        assert_eq!(settings.escalate(1, 2), 1);
+        assert_eq!(settings.escalate(1, 3), 2);
+        assert_eq!(settings.escalate(1, 6), 3);
+        assert_eq!(settings.escalate(1, 100), 3);
+        assert_eq!(settings.escalate(3, 100), 3);
+
+        let capped = NotificationEscalationSettings { enabled: true, fires_per_step: 2, max_level: 2 };
+        assert_eq!(capped.escalate(1, 10), 2);
+        assert_eq!(capped.escalate(3, 10), 3);
+
+        let disabled = NotificationEscalationSettings::default();
+        assert_eq!(disabled.escalate(1, 100), 1);
+
+        assert!(NotificationEscalationSettings { fires_per_step: 0, ..Default::default() }.validate().is_err());
+        assert!(NotificationEscalationSettings { max_level: 4, ..Default::default() }.validate().is_err());
+    }
+}
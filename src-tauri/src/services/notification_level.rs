@@ -0,0 +1,108 @@
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 実際に発火する通知レベルを決めるルール
+///
+/// 1. タスク自身の通知レベル
+/// 2. タグごとのレベル指定があれば、該当するタグの中で最も高いレベルで上書き
+/// 3. 夜間帯（ローカル時刻）は`night_downgrade`だけレベルを下げる
+///
+/// 結果は常に1〜3に収める。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationLevelRules {
+    /// タグ名 → 通知レベル
+    #[serde(default)]
+    pub tag_levels: HashMap<String, i32>,
+    /// 夜間帯の開始時刻（HH:MM形式、未設定なら夜間の調整なし）
+    #[serde(default)]
+    pub night_start: Option<String>,
+    /// 夜間帯の終了時刻（HH:MM形式）
+    #[serde(default)]
+    pub night_end: Option<String>,
+    /// 夜間帯に下げるレベル数
+    #[serde(default = "default_night_downgrade")]
+    pub night_downgrade: i32,
+}
+
+fn default_night_downgrade() -> i32 {
+    1
+}
+
+impl Default for NotificationLevelRules {
+    fn default() -> Self {
+        Self {
+            tag_levels: HashMap::new(),
+            night_start: None,
+            night_end: None,
+            night_downgrade: default_night_downgrade(),
+        }
+    }
+}
+
+impl NotificationLevelRules {
+    /// 設定キー（agent_configテーブル）
+    pub const SETTINGS_KEY: &'static str = "notification_level_rules";
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some((tag, level)) = self.tag_levels.iter().find(|(_, level)| !(1..=3).contains(*level)) {
+            return Err(format!("Level for tag '{}' must be between 1 and 3: {}", tag, level));
+        }
+
+        match (&self.night_start, &self.night_end) {
+            (Some(start), Some(end)) => {
+                for time in [start, end] {
+                    if parse_time(time).is_none() {
+                        return Err(format!("Invalid night time (expected HH:MM): {}", time));
+                    }
+                }
+            }
+            (None, None) => {}
+            _ => return Err("Night start and end must be set together".to_string()),
+        }
+
+        if !(0..=2).contains(&self.night_downgrade) {
+            return Err("Night downgrade must be between 0 and 2".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// ローカル時刻が夜間帯か（日付をまたぐ範囲にも対応）
+    pub fn is_night(&self, local_time: NaiveTime) -> bool {
+        is_within_time_range(self.night_start.as_deref(), self.night_end.as_deref(), local_time)
+    }
+
+    /// ルールを適用して実際の通知レベルを計算
+    pub fn apply(&self, base_level: i32, tag_names: &[String], local_time: NaiveTime) -> i32 {
+        let mut level = tag_names
+            .iter()
+            .filter_map(|name| self.tag_levels.get(name).copied())
+            .max()
+            .unwrap_or(base_level);
+
+        if self.is_night(local_time) {
+            level -= self.night_downgrade;
+        }
+
+        level.clamp(1, 3)
+    }
+}
+
+pub(crate) fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M").ok()
+}
+
+/// ローカル時刻がHH:MM形式の`start`〜`end`の範囲内か（日付をまたぐ範囲にも対応）
+pub(crate) fn is_within_time_range(start: Option<&str>, end: Option<&str>, local_time: NaiveTime) -> bool {
+    let (Some(start), Some(end)) = (start.and_then(parse_time), end.and_then(parse_time)) else {
+        return false;
+    };
+
+    if start <= end {
+        local_time >= start && local_time < end
+    } else {
+        local_time >= start || local_time < end
+    }
+}
@@ -0,0 +1,234 @@
+use crate::models::NotificationChannel;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 通知をどう見せるか
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationPresentation {
+    /// トースト表示のみ
+    Toast,
+    /// 音のみ（トーストは出さない）
+    Sound,
+    /// トースト表示と音
+    ToastAndSound,
+    /// 何も出さない
+    Silent,
+}
+
+impl NotificationPresentation {
+    pub fn shows_toast(self) -> bool {
+        matches!(self, Self::Toast | Self::ToastAndSound)
+    }
+
+    pub fn plays_sound(self) -> bool {
+        matches!(self, Self::Sound | Self::ToastAndSound)
+    }
+}
+
+/// 通知レベルごとの見せ方
+///
+/// 指定のないレベルは従来どおり、レベル1はトーストのみ、レベル2以上はトーストと音。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationPresentationSettings {
+    /// 通知レベル → 見せ方
+    #[serde(default)]
+    pub levels: BTreeMap<i32, NotificationPresentation>,
+    /// 定期通知でアプリを前面に出す
+    #[serde(default = "default_focus_window")]
+    pub focus_recurring: bool,
+    /// 期日ベース通知でアプリを前面に出す
+    #[serde(default = "default_focus_window")]
+    pub focus_due_date: bool,
+}
+
+fn default_focus_window() -> bool {
+    true
+}
+
+impl Default for NotificationPresentationSettings {
+    fn default() -> Self {
+        Self {
+            levels: BTreeMap::new(),
+            focus_recurring: default_focus_window(),
+            focus_due_date: default_focus_window(),
+        }
+    }
+}
+
+impl NotificationPresentationSettings {
+    /// 設定キー（agent_configテーブル）
+    pub const SETTINGS_KEY: &'static str = "notification_presentation";
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(level) = self.levels.keys().find(|level| !(1..=3).contains(*level)) {
+            return Err(format!("Notification level must be between 1 and 3: {}", level));
+        }
+        Ok(())
+    }
+
+    /// 通知レベルに対応する見せ方
+    pub fn for_level(&self, level: i32) -> NotificationPresentation {
+        self.levels.get(&level).copied().unwrap_or(if level >= 2 {
+            NotificationPresentation::ToastAndSound
+        } else {
+            NotificationPresentation::Toast
+        })
+    }
+
+    /// 通知でアプリを前面に出すか
+    ///
+    /// `focus_level`以上のレベルで、通知の種類ごとの設定が有効な場合に前面に出す
+    /// （`focus_level`がNoneなら出さない）。
+    pub fn focuses_window(&self, notification_type: &str, level: i32, focus_level: Option<i32>) -> bool {
+        let enabled_for_type = match notification_type {
+            "recurring" => self.focus_recurring,
+            "due_date_based" => self.focus_due_date,
+            _ => true,
+        };
+        enabled_for_type && focus_level.is_some_and(|focus_level| level >= focus_level)
+    }
+}
+
+/// 1件の通知で実際に行う操作
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NotificationActions {
+    pub show_toast: bool,
+    pub play_sound: bool,
+    pub focus_window: bool,
+    pub update_tray: bool,
+    pub run_browser_actions: bool,
+}
+
+impl NotificationActions {
+    /// タスクの通知経路から行う操作を決める
+    ///
+    /// 経路が未設定なら従来どおりレベルごとの見せ方・前面表示の設定に従う。
+    /// 経路を指定した場合はレベルによらず指定した経路だけを使う（音はトーストに付随し、レベルの設定に従う）。
+    pub fn resolve(
+        channels: Option<&[NotificationChannel]>,
+        presentation: NotificationPresentation,
+        focus_window: bool,
+    ) -> Self {
+        let Some(channels) = channels else {
+            return Self {
+                show_toast: presentation.shows_toast(),
+                play_sound: presentation.plays_sound(),
+                focus_window: focus_window && presentation != NotificationPresentation::Silent,
+                update_tray: false,
+                run_browser_actions: false,
+            };
+        };
+
+        let toast = channels.contains(&NotificationChannel::Toast);
+        Self {
+            show_toast: toast,
+            play_sound: toast && presentation.plays_sound(),
+            focus_window: channels.contains(&NotificationChannel::Window),
+            update_tray: channels.contains(&NotificationChannel::Tray),
+            run_browser_actions: channels.contains(&NotificationChannel::Browser),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presentation_for_level() {
+        // 既定は従来の動作
+        let defaults = NotificationPresentationSettings::default();
+        assert_eq!(defaults.for_level(1), NotificationPresentation::Toast);
+        assert_eq!(defaults.for_level(2), NotificationPresentation::ToastAndSound);
+        assert_eq!(defaults.for_level(3), NotificationPresentation::ToastAndSound);
+
+        // レベル2は音だけ、レベル1は何も出さない
+        let settings: NotificationPresentationSettings =
+            serde_json::from_str(r#"{"levels":{"1":"silent","2":"sound"}}"#).unwrap();
+        assert!(settings.validate().is_ok());
+        assert_eq!(settings.for_level(1), NotificationPresentation::Silent);
+        assert_eq!(settings.for_level(2), NotificationPresentation::Sound);
+        assert_eq!(settings.for_level(3), NotificationPresentation::ToastAndSound);
+
+        let sound_only = settings.for_level(2);
+        assert!(sound_only.plays_sound() && !sound_only.shows_toast());
+        let silent = settings.for_level(1);
+        assert!(!silent.plays_sound() && !silent.shows_toast());
+
+        // 不正な値・レベルは拒否
+        assert!(serde_json::from_str::<NotificationPresentationSettings>(r#"{"levels":{"2":"popup"}}"#).is_err());
+        let out_of_range: NotificationPresentationSettings =
+            serde_json::from_str(r#"{"levels":{"4":"toast"}}"#).unwrap();
+        assert!(out_of_range.validate().is_err());
+    }
+
+    #[test]
+    fn test_focuses_window_per_notification_type() {
+        // 既定は種類によらずレベル3で前面に出す
+        let defaults = NotificationPresentationSettings::default();
+        assert!(defaults.focuses_window("recurring", 3, Some(3)));
+        assert!(defaults.focuses_window("due_date_based", 3, Some(3)));
+        assert!(!defaults.focuses_window("due_date_based", 2, Some(3)));
+
+        // 定期通知だけ前面に出さない
+        let settings: NotificationPresentationSettings =
+            serde_json::from_str(r#"{"focusRecurring":false}"#).unwrap();
+        assert!(settings.focus_due_date);
+        assert!(!settings.focuses_window("recurring", 3, Some(3)));
+        assert!(settings.focuses_window("due_date_based", 3, Some(3)));
+        assert!(settings.focuses_window("inbox_aging", 3, Some(3)));
+
+        // プロファイルで前面に出さない場合は種類の設定によらず出さない
+        assert!(!settings.focuses_window("due_date_based", 3, None));
+        assert!(settings.focuses_window("due_date_based", 2, Some(2)));
+    }
+
+    #[test]
+    fn test_notification_actions_for_channels() {
+        use NotificationChannel::*;
+        let resolve = |channels: &[NotificationChannel]| {
+            NotificationActions::resolve(Some(channels), NotificationPresentation::ToastAndSound, false)
+        };
+
+        // 未設定なら従来どおりレベルの設定に従う
+        let default = NotificationActions::resolve(None, NotificationPresentation::ToastAndSound, true);
+        assert_eq!(default, NotificationActions {
+            show_toast: true,
+            play_sound: true,
+            focus_window: true,
+            update_tray: false,
+            run_browser_actions: false,
+        });
+        let silent = NotificationActions::resolve(None, NotificationPresentation::Silent, true);
+        assert_eq!(silent, NotificationActions::default());
+
+        // トレイだけ
+        assert_eq!(resolve(&[Tray]), NotificationActions { update_tray: true, ..Default::default() });
+        // トーストは音を伴う（レベルの設定に従う）
+        assert_eq!(resolve(&[Toast]), NotificationActions { show_toast: true, play_sound: true, ..Default::default() });
+        let toast_only = NotificationActions::resolve(Some(&[Toast]), NotificationPresentation::Toast, false);
+        assert!(toast_only.show_toast && !toast_only.play_sound);
+        // トーストとブラウザアクション
+        assert_eq!(
+            resolve(&[Toast, Browser]),
+            NotificationActions { show_toast: true, play_sound: true, run_browser_actions: true, ..Default::default() }
+        );
+        // 前面表示はレベルによらず指定どおり
+        assert_eq!(resolve(&[Window]), NotificationActions { focus_window: true, ..Default::default() });
+        assert_eq!(
+            resolve(&[Tray, Toast, Browser, Window]),
+            NotificationActions {
+                show_toast: true,
+                play_sound: true,
+                focus_window: true,
+                update_tray: true,
+                run_browser_actions: true,
+            }
+        );
+
+        assert_eq!(serde_json::from_str::<Vec<NotificationChannel>>(r#"["tray","browser"]"#).unwrap(), vec![Tray, Browser]);
+        assert!(serde_json::from_str::<Vec<NotificationChannel>>(r#"["email"]"#).is_err());
+    }
+}
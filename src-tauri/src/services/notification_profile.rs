@@ -0,0 +1,168 @@
+use crate::services::notification_level::{is_within_time_range, parse_time, NotificationLevelRules};
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+
+/// 状況（仕事・自宅など）ごとにまとめた通知の設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationProfile {
+    /// プロファイル名（一意）
+    pub name: String,
+    /// 通知を出さない時間帯の開始時刻（HH:MM形式、未設定なら常に通知）
+    #[serde(default)]
+    pub quiet_start: Option<String>,
+    /// 通知を出さない時間帯の終了時刻（HH:MM形式）
+    #[serde(default)]
+    pub quiet_end: Option<String>,
+    /// 通知時刻が未設定の期日ベース通知を出す時刻（HH:MM形式、未設定なら09:00）
+    #[serde(default)]
+    pub default_notification_time: Option<String>,
+    /// これより低いレベルの通知は出さない
+    #[serde(default = "default_min_level")]
+    pub min_level: i32,
+    /// 通知レベルのルール（未設定なら全体の設定を使う）
+    #[serde(default)]
+    pub level_rules: Option<NotificationLevelRules>,
+    /// このレベル以上の通知でアプリを前面に出す（nullなら前面に出さない）
+    #[serde(default = "default_focus_window_level")]
+    pub focus_window_level: Option<i32>,
+}
+
+fn default_min_level() -> i32 {
+    1
+}
+
+fn default_focus_window_level() -> Option<i32> {
+    Some(3)
+}
+
+impl NotificationProfile {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Profile name must not be empty".to_string());
+        }
+
+        match (&self.quiet_start, &self.quiet_end) {
+            (Some(start), Some(end)) => {
+                for time in [start, end] {
+                    if parse_time(time).is_none() {
+                        return Err(format!("Invalid quiet time (expected HH:MM): {}", time));
+                    }
+                }
+            }
+            (None, None) => {}
+            _ => return Err("Quiet start and end must be set together".to_string()),
+        }
+
+        if let Some(time) = &self.default_notification_time {
+            if parse_time(time).is_none() {
+                return Err(format!("Invalid default notification time (expected HH:MM): {}", time));
+            }
+        }
+
+        if !(1..=3).contains(&self.min_level) {
+            return Err(format!("Minimum level must be between 1 and 3: {}", self.min_level));
+        }
+
+        if let Some(level) = self.focus_window_level.filter(|level| !(1..=3).contains(level)) {
+            return Err(format!("Focus window level must be between 1 and 3: {}", level));
+        }
+
+        if let Some(rules) = &self.level_rules {
+            rules.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// ローカル時刻が通知しない時間帯か（日付をまたぐ範囲にも対応）
+    pub fn is_quiet(&self, local_time: NaiveTime) -> bool {
+        is_within_time_range(self.quiet_start.as_deref(), self.quiet_end.as_deref(), local_time)
+    }
+
+    /// このレベルの通知をこの時刻に出してよいか
+    pub fn allows(&self, level: i32, local_time: NaiveTime) -> bool {
+        level >= self.min_level && !self.is_quiet(local_time)
+    }
+
+    /// このレベルの通知でアプリを前面に出すか
+    pub fn focuses_window(&self, level: i32) -> bool {
+        self.focus_window_level.is_some_and(|focus_level| level >= focus_level)
+    }
+}
+
+/// 保存済みの通知プロファイルと、有効なプロファイル名
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationProfiles {
+    #[serde(default)]
+    pub profiles: Vec<NotificationProfile>,
+    /// 有効なプロファイル名（未設定ならプロファイルを使わない）
+    #[serde(default)]
+    pub active: Option<String>,
+}
+
+impl NotificationProfiles {
+    /// 設定キー（agent_configテーブル）
+    pub const SETTINGS_KEY: &'static str = "notification_profiles";
+
+    pub fn get(&self, name: &str) -> Option<&NotificationProfile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    /// 有効なプロファイル
+    pub fn active_profile(&self) -> Option<&NotificationProfile> {
+        self.active.as_deref().and_then(|name| self.get(name))
+    }
+
+    /// 同じ名前のプロファイルがあれば置き換え、なければ追加
+    pub fn upsert(&mut self, profile: NotificationProfile) {
+        match self.profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+
+    /// プロファイルを削除（有効なプロファイルを削除した場合は無効に戻す）
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.profiles.len();
+        self.profiles.retain(|profile| profile.name != name);
+        if self.active.as_deref() == Some(name) {
+            self.active = None;
+        }
+        self.profiles.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(s: &str) -> NaiveTime {
+        parse_time(s).unwrap()
+    }
+
+    #[test]
+    fn test_profile_allows_and_focus() {
+        let profile: NotificationProfile = serde_json::from_str(
+            r#"{"name":"home","quietStart":"19:00","quietEnd":"08:00","minLevel":2}"#,
+        )
+        .unwrap();
+        assert!(profile.validate().is_ok());
+        assert_eq!(profile.focus_window_level, Some(3));
+
+        assert!(profile.allows(2, time("12:00")));
+        assert!(!profile.allows(1, time("12:00")));
+        assert!(!profile.allows(3, time("20:00")));
+        assert!(!profile.allows(3, time("07:59")));
+        assert!(profile.focuses_window(3) && !profile.focuses_window(2));
+
+        let no_focus: NotificationProfile =
+            serde_json::from_str(r#"{"name":"meeting","focusWindowLevel":null}"#).unwrap();
+        assert!(!no_focus.focuses_window(3));
+
+        let invalid: NotificationProfile =
+            serde_json::from_str(r#"{"name":"work","quietStart":"19:00"}"#).unwrap();
+        assert!(invalid.validate().is_err());
+    }
+}
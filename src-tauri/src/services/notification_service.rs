@@ -1,21 +1,74 @@
 use crate::database::Database;
 use crate::error::AppError;
-use crate::models::{Task, TaskNotification};
+use crate::models::{
+    BrowserActionSettings, FocusSession, MissedNotification, NotificationLog, NotificationSelfTestReport, NotificationSelfTestStep,
+    ScheduledNotification, Task, TaskNotification,
+};
+use crate::services::app_timezone::AppTimezone;
+use crate::services::business_days::BusinessDaySettings;
+use crate::services::context_service::{invalidate_task_context_cache, TemporalContext};
 use crate::services::browser_action_service::BrowserActionService;
-use chrono::{DateTime, Utc, Duration, Datelike, Timelike};
-use std::sync::Arc;
+use crate::services::daily_summary::{build_summary_text, should_fire_summary, DailySummarySettings, DailySummaryStats};
+use crate::services::notification_escalation::NotificationEscalationSettings;
+use crate::services::notification_level::NotificationLevelRules;
+use crate::services::notification_presentation::NotificationPresentationSettings;
+use crate::services::notification_profile::{NotificationProfile, NotificationProfiles};
+use crate::services::quiet_hours::QuietHours;
+use crate::services::{SettingsService, TagService};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Timelike, Utc, Duration};
+use std::collections::{BTreeSet, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// 受信箱の放置日数の設定キー
+const INBOX_AGING_DAYS_KEY: &str = "inbox_aging_days";
+const DEFAULT_INBOX_AGING_DAYS: i64 = 3;
+/// 受信箱リマインドを出し始めるローカル時刻
+const INBOX_AGING_REMINDER_TIME: &str = "09:00";
+/// 最後に通知チェックが成功した時刻の設定キー
+const LAST_SCHEDULER_TICK_KEY: &str = "last_scheduler_tick";
+/// 見逃し通知を遡って調べる最大日数
+const MAX_MISSED_LOOKBACK_DAYS: i64 = 7;
+/// 一日中通知しない日（ローカル日付）の設定キー
+const NO_NAG_DAYS_KEY: &str = "no_nag_days";
+/// 発火予定を列挙できる最大日数
+const MAX_SCHEDULE_DAYS: i64 = 366;
+/// 通知時刻が未設定の期日ベース通知を出すローカル時刻
+const DEFAULT_NOTIFICATION_TIME: &str = "09:00";
+/// 発火ヒートマップで集計できる最大日数
+const MAX_HEATMAP_DAYS: i64 = 366;
+/// 集中セッションの長さの上限（分）
+const MAX_FOCUS_SESSION_MINUTES: i64 = 240;
+/// 発火済みキーを残しておく日数
+const FIRED_KEY_RETENTION_DAYS: i64 = 2;
+/// 通知履歴として一度に取得できる最大件数
+const MAX_NOTIFICATION_HISTORY: i64 = 1000;
+/// 1回でスヌーズできる最大時間（分）
+const MAX_SNOOZE_MINUTES: i64 = 7 * 24 * 60;
+/// ブラウザアクションでローカルのコマンド実行を許可するかの設定キー
+const ALLOW_COMMAND_ACTIONS_KEY: &str = "allow_command_actions";
+/// 同じタスクのブラウザアクションを再び開くまでの最短間隔（分）の設定キー
+const BROWSER_ACTION_MIN_INTERVAL_KEY: &str = "browser_action_min_interval_minutes";
+/// ブラウザアクションの最短間隔の上限（分、1週間）
+const MAX_BROWSER_ACTION_MIN_INTERVAL_MINUTES: i64 = 7 * 24 * 60;
+/// 通知をPOSTするWebhookのURLの設定キー
+const WEBHOOK_URL_KEY: &str = "webhook_url";
+/// Webhook送信1回あたりのタイムアウト
+const WEBHOOK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+/// Webhook送信に失敗したときの再試行回数
+const WEBHOOK_MAX_RETRIES: u32 = 2;
+/// Webhookの再試行までの待ち時間（再試行ごとにこの倍数で延ばす）
+const WEBHOOK_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
 
 pub struct NotificationService {
     db: Database,
     browser_action_service: Arc<BrowserActionService>,
+    missed_on_startup: Mutex<Vec<MissedNotification>>,
+    webhook_client: reqwest::Client,
 }
 
 impl NotificationService {
     pub fn new(db: Database) -> Self {
-        Self {
-            db,
-            browser_action_service: Arc::new(BrowserActionService::new()),
-        }
+        Self::with_browser_action_service(db, Arc::new(BrowserActionService::new()))
     }
 
     /// Create service with custom browser action service (for testing)
@@ -23,13 +76,34 @@ impl NotificationService {
         Self {
             db,
             browser_action_service,
+            missed_on_startup: Mutex::new(Vec::new()),
+            webhook_client: reqwest::Client::builder()
+                .timeout(WEBHOOK_TIMEOUT)
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
         }
     }
 
-    /// 現在の通知をチェックして返すメイン関数
+    /// 指定時刻に発火する通知を、何も記録・実行せずに返す
+    ///
+    /// 「待機中のリマインド」表示用。トースト表示・ブラウザアクション・通知ログ・
+    /// スケジューラーの最終実行時刻には一切触れない。
+    pub async fn peek_notifications(&self, current_time: DateTime<Utc>) -> Result<Vec<TaskNotification>, AppError> {
+        self.check_notifications(current_time).await
+    }
+
+    /// 現在の通知をチェックして返すメイン関数（読み取りのみ）
     pub async fn check_notifications(&self, current_time: DateTime<Utc>) -> Result<Vec<TaskNotification>, AppError> {
         let mut notifications = Vec::new();
         
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        if self.list_no_nag_days().await?.contains(&timezone.local_date(current_time)) {
+            return Ok(notifications);
+        }
+        
+        let profile = self.active_profile().await?;
+        let default_time = default_notification_time(profile.as_ref());
+        
         // アクティブなタスクを取得
         let tasks = self.get_active_tasks().await?;
         
@@ -47,12 +121,12 @@ impl NotificationService {
             
             match notification_type.as_str() {
                 "due_date_based" => {
-                    if let Some(notification) = self.check_due_date_notification(&task, current_time) {
+                    if let Some(notification) = self.check_due_date_notification(&task, current_time, default_time, &timezone) {
                         notifications.push(notification);
                     }
                 }
                 "recurring" => {
-                    if let Some(notification) = self.check_recurring_notification(&task, current_time) {
+                    if let Some(notification) = self.check_recurring_notification(&task, current_time, &timezone) {
                         notifications.push(notification);
                     }
                 }
@@ -60,25 +134,737 @@ impl NotificationService {
             }
         }
         
+        let resumed = self.check_snooze_expirations(current_time, &notifications).await?;
+        notifications.extend(resumed);
+        
+        // タグ・時間帯のルールを反映した実際のレベルで通知する
+        let rules = self.level_rules_for(profile.as_ref()).await?;
+        for notification in &mut notifications {
+            notification.level = self
+                .apply_level_rules(&notification.task_id, notification.level, current_time, &rules, &timezone)
+                .await?;
+        }
+        
+        notifications.extend(self.check_inbox_aging(current_time).await?);
+        
+        // クワイエットタイム中は抑制し、終了直後は保留していたレベル3の通知を出す
+        self.apply_quiet_hours(&mut notifications, current_time).await?;
+        
+        Self::retain_allowed_by_profile(profile.as_ref(), &mut notifications, current_time, &timezone);
+        
+        // 集中セッション中は対象タスク以外の通知を出さない
+        self.apply_focus_session(&mut notifications, current_time).await?;
+        
+        // スヌーズ中のタスクは発火させない
+        let snoozed = self.snoozed_task_ids(current_time).await?;
+        notifications.retain(|notification| !snoozed.contains(&notification.task_id));
+        
+        // 同じ発火枠で発火済みの通知は出さない
+        self.retain_unfired(&mut notifications).await?;
+        
         Ok(notifications)
     }
 
-    /// 通知を発火し、ブラウザアクションを実行
-    pub async fn fire_notification(&self, notification: &TaskNotification) -> Result<(), AppError> {
+    /// 通知の一連の流れを実際のDBで確認するセルフテスト（QA用）
+    ///
+    /// `current_time`に発火する一時タスクを作成し、`check_notifications`で検出、
+    /// `fire_notification`で発火させた後、タスクと通知ログを削除する。
+    /// 表示には本番と同じ`present`を使う。途中のステップが失敗しても後片付けは必ず行う。
+    pub async fn run_self_test<F>(&self, current_time: DateTime<Utc>, present: F) -> Result<NotificationSelfTestReport, AppError>
+    where
+        F: FnOnce() -> Result<(), String>,
+    {
+        let mut steps = Vec::new();
+        let task_id = format!("self-test-{}", uuid::Uuid::new_v4());
+        
+        let created = self.insert_self_test_task(&task_id, current_time).await;
+        steps.push(self_test_step("create_task", created.as_ref().err().map(|e| e.to_string())));
+        
+        if created.is_ok() {
+            let detected = match self.check_notifications(current_time).await {
+                Ok(notifications) => notifications.into_iter().find(|n| n.task_id == task_id).ok_or_else(|| {
+                    "Temporary task was not detected in the firing window".to_string()
+                }),
+                Err(e) => Err(e.to_string()),
+            };
+            steps.push(self_test_step("detect", detected.as_ref().err().cloned()));
+            
+            if let Ok(notification) = detected {
+                let fired = match self.fire_notification(&notification, "通知セルフテスト", false, present).await {
+                    Ok(()) => self.self_test_fired(&task_id).await,
+                    Err(e) => Err(e.to_string()),
+                };
+                steps.push(self_test_step("fire", fired.err()));
+            }
+            
+            let cleaned = self.delete_self_test_task(&task_id).await;
+            steps.push(self_test_step("cleanup", cleaned.err().map(|e| e.to_string())));
+        }
+        
+        let passed = steps.iter().all(|step| step.success);
+        Ok(NotificationSelfTestReport { steps, passed })
+    }
+    
+    async fn insert_self_test_task(&self, task_id: &str, current_time: DateTime<Utc>) -> Result<(), AppError> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO tasks (id, title, status, due_date, created_at, updated_at,
+                               notification_type, notification_days_before, notification_time, notification_level)
+            VALUES (?1, ?2, 'todo', ?3, ?4, ?4, 'due_date_based', 0, ?5, 1)
+            "#,
+        )
+        .bind(task_id)
+        .bind("通知セルフテスト")
+        .bind(current_time.to_rfc3339())
+        .bind(&now)
+        .bind(current_time.format("%H:%M").to_string())
+        .execute(&self.db.pool)
+        .await?;
+        Ok(())
+    }
+    
+    /// 発火した通知が成功として記録されたか
+    async fn self_test_fired(&self, task_id: &str) -> Result<(), String> {
+        let logged = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM notification_logs WHERE task_id = ?1 AND success = 1",
+        )
+        .bind(task_id)
+        .fetch_one(&self.db.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+        
+        if logged > 0 {
+            Ok(())
+        } else {
+            Err("Notification was fired but not logged".to_string())
+        }
+    }
+    
+    async fn delete_self_test_task(&self, task_id: &str) -> Result<(), AppError> {
+        let mut tx = self.db.pool.begin().await?;
+        for table in ["notification_logs", "browser_action_acks", "notification_fired_keys"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE task_id = ?1", table))
+                .bind(task_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        sqlx::query("DELETE FROM tasks WHERE id = ?1")
+            .bind(task_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// 通知レベルのルールを取得
+    pub async fn get_level_rules(&self) -> Result<NotificationLevelRules, AppError> {
+        Ok(SettingsService::get_json(&self.db.pool, NotificationLevelRules::SETTINGS_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+    
+    /// 通知レベルのルールを保存
+    pub async fn set_level_rules(&self, rules: NotificationLevelRules) -> Result<NotificationLevelRules, AppError> {
+        rules.validate().map_err(AppError::InvalidInput)?;
+        SettingsService::set_json(&self.db.pool, NotificationLevelRules::SETTINGS_KEY, &rules).await?;
+        Ok(rules)
+    }
+    
+    /// 通知レベルごとの見せ方（トースト・音）を取得
+    pub async fn get_presentation_settings(&self) -> Result<NotificationPresentationSettings, AppError> {
+        Ok(SettingsService::get_json(&self.db.pool, NotificationPresentationSettings::SETTINGS_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+    
+    /// 通知レベルごとの見せ方を保存
+    pub async fn set_presentation_settings(
+        &self,
+        settings: NotificationPresentationSettings,
+    ) -> Result<NotificationPresentationSettings, AppError> {
+        settings.validate().map_err(AppError::InvalidInput)?;
+        SettingsService::set_json(&self.db.pool, NotificationPresentationSettings::SETTINGS_KEY, &settings).await?;
+        Ok(settings)
+    }
+    
+    /// 保存済みの通知プロファイルと有効なプロファイル名を取得
+    pub async fn get_notification_profiles(&self) -> Result<NotificationProfiles, AppError> {
+        Ok(SettingsService::get_json(&self.db.pool, NotificationProfiles::SETTINGS_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+    
+    /// 通知プロファイルを保存（同じ名前のプロファイルは上書き）
+    pub async fn save_notification_profile(&self, profile: NotificationProfile) -> Result<NotificationProfile, AppError> {
+        profile.validate().map_err(AppError::InvalidInput)?;
+        let mut profiles = self.get_notification_profiles().await?;
+        profiles.upsert(profile.clone());
+        SettingsService::set_json(&self.db.pool, NotificationProfiles::SETTINGS_KEY, &profiles).await?;
+        Ok(profile)
+    }
+    
+    /// 通知プロファイルを削除（有効なプロファイルを削除した場合はプロファイルなしに戻る）
+    pub async fn delete_notification_profile(&self, name: &str) -> Result<(), AppError> {
+        let mut profiles = self.get_notification_profiles().await?;
+        if !profiles.remove(name) {
+            return Err(AppError::NotFound(format!("Notification profile {} not found", name)));
+        }
+        SettingsService::set_json(&self.db.pool, NotificationProfiles::SETTINGS_KEY, &profiles).await?;
+        Ok(())
+    }
+    
+    /// スケジューラーが使う通知プロファイルを切り替える
+    pub async fn activate_profile(&self, name: &str) -> Result<NotificationProfile, AppError> {
+        let mut profiles = self.get_notification_profiles().await?;
+        let profile = profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Notification profile {} not found", name)))?;
+        profiles.active = Some(name.to_string());
+        SettingsService::set_json(&self.db.pool, NotificationProfiles::SETTINGS_KEY, &profiles).await?;
+        Ok(profile)
+    }
+    
+    /// 通知プロファイルを使わない状態に戻す
+    pub async fn deactivate_profile(&self) -> Result<(), AppError> {
+        let mut profiles = self.get_notification_profiles().await?;
+        profiles.active = None;
+        SettingsService::set_json(&self.db.pool, NotificationProfiles::SETTINGS_KEY, &profiles).await?;
+        Ok(())
+    }
+    
+    /// 有効な通知プロファイル
+    pub async fn active_profile(&self) -> Result<Option<NotificationProfile>, AppError> {
+        Ok(self.get_notification_profiles().await?.active_profile().cloned())
+    }
+    
+    /// 有効な通知プロファイルの通知しない時間帯・最低レベルに当たる通知を取り除く
+    pub async fn apply_active_profile(&self, notifications: &mut Vec<TaskNotification>, current_time: DateTime<Utc>) -> Result<(), AppError> {
+        let profile = self.active_profile().await?;
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        Self::retain_allowed_by_profile(profile.as_ref(), notifications, current_time, &timezone);
+        Ok(())
+    }
+    
+    fn retain_allowed_by_profile(
+        profile: Option<&NotificationProfile>,
+        notifications: &mut Vec<TaskNotification>,
+        current_time: DateTime<Utc>,
+        timezone: &AppTimezone,
+    ) {
+        if let Some(profile) = profile {
+            let local_time = timezone.to_local(current_time).time();
+            notifications.retain(|notification| profile.allows(notification.level, local_time));
+        }
+    }
+    
+    /// 通知に使うレベルのルール（プロファイルに指定があればそちらを優先）
+    async fn level_rules_for(&self, profile: Option<&NotificationProfile>) -> Result<NotificationLevelRules, AppError> {
+        match profile.and_then(|p| p.level_rules.clone()) {
+            Some(rules) => Ok(rules),
+            None => self.get_level_rules().await,
+        }
+    }
+    
+    /// 指定時刻に発火した場合の実際の通知レベル
+    pub async fn effective_level(&self, task_id: &str, at: DateTime<Utc>) -> Result<i32, AppError> {
+        let task = self.get_task_by_id(task_id).await?;
+        let rules = self.level_rules_for(self.active_profile().await?.as_ref()).await?;
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        
+        self.apply_level_rules(&task.id, task.notification_level.unwrap_or(1), at, &rules, &timezone)
+            .await
+    }
+    
+    async fn apply_level_rules(
+        &self,
+        task_id: &str,
+        base_level: i32,
+        at: DateTime<Utc>,
+        rules: &NotificationLevelRules,
+        timezone: &AppTimezone,
+    ) -> Result<i32, AppError> {
+        let tag_names: Vec<String> = if rules.tag_levels.is_empty() {
+            Vec::new()
+        } else {
+            TagService::get_tags_for_task(&self.db.pool, task_id)
+                .await?
+                .into_iter()
+                .map(|tag| tag.name)
+                .collect()
+        };
+        let level = rules.apply(base_level, &tag_names, timezone.to_local(at).time());
+        
+        // 無視され続けている通知はレベルを引き上げる
+        let escalation = self.get_escalation_settings().await?;
+        if !escalation.enabled {
+            return Ok(level);
+        }
+        Ok(escalation.escalate(level, self.unresolved_fire_count(task_id).await?))
+    }
+    
+    /// タスクが最後に完了して（まだ完了していなければ作成されて）以降に発火した回数
+    ///
+    /// 完了以外のステータス変更では数え直さない。
+    pub async fn unresolved_fire_count(&self, task_id: &str) -> Result<i64, AppError> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM notification_logs l
+            INNER JOIN tasks t ON t.id = l.task_id
+            WHERE l.task_id = ?1
+              AND l.success = 1
+              AND l.notification_type != 'inbox_aging'
+              AND datetime(l.fired_at) > datetime(COALESCE(t.last_completed_at, t.created_at))
+            "#,
+        )
+        .bind(task_id)
+        .fetch_one(&self.db.pool)
+        .await?;
+        Ok(count)
+    }
+    
+    /// 通知レベルのエスカレーション設定を取得
+    pub async fn get_escalation_settings(&self) -> Result<NotificationEscalationSettings, AppError> {
+        Ok(SettingsService::get_json(&self.db.pool, NotificationEscalationSettings::SETTINGS_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+    
+    /// 通知レベルのエスカレーション設定を保存
+    pub async fn set_escalation_settings(&self, settings: NotificationEscalationSettings) -> Result<NotificationEscalationSettings, AppError> {
+        settings.validate().map_err(AppError::InvalidInput)?;
+        SettingsService::set_json(&self.db.pool, NotificationEscalationSettings::SETTINGS_KEY, &settings).await?;
+        Ok(settings)
+    }
+    
+    /// 通知チェックが成功した時刻を記録
+    pub async fn record_scheduler_tick(&self, tick: DateTime<Utc>) -> Result<(), AppError> {
+        SettingsService::set(&self.db.pool, LAST_SCHEDULER_TICK_KEY, &tick.to_rfc3339()).await?;
+        Ok(())
+    }
+
+    /// 最後に通知チェックが成功した時刻
+    pub async fn last_scheduler_tick(&self) -> Result<Option<DateTime<Utc>>, AppError> {
+        let tick = SettingsService::get(&self.db.pool, LAST_SCHEDULER_TICK_KEY).await?;
+        Ok(tick
+            .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+            .map(|t| t.with_timezone(&Utc)))
+    }
+
+    /// 起動時に、前回のチェック以降アプリ停止中に見逃した通知を集計して保持
+    pub async fn detect_missed_notifications_on_startup(&self, now: DateTime<Utc>) -> Result<(), AppError> {
+        let missed = match self.last_scheduler_tick().await? {
+            Some(last_tick) => self.find_missed_notifications(last_tick, now).await?,
+            None => Vec::new(),
+        };
+        if !missed.is_empty() {
+            log::info!("Missed {} notifications while the app was not running", missed.len());
+        }
+        
+        if let Ok(mut stored) = self.missed_on_startup.lock() {
+            *stored = missed;
+        }
+        self.record_scheduler_tick(now).await
+    }
+
+    /// 起動時に検出した見逃し通知
+    pub fn get_missed_notifications(&self) -> Vec<MissedNotification> {
+        self.missed_on_startup
+            .lock()
+            .map(|missed| missed.clone())
+            .unwrap_or_default()
+    }
+
+    /// `last_tick`より後、`now`より前に発火予定だった通知を列挙
+    ///
+    /// 遡る期間は最大`MAX_MISSED_LOOKBACK_DAYS`日に制限する。
+    pub async fn find_missed_notifications(&self, last_tick: DateTime<Utc>, now: DateTime<Utc>) -> Result<Vec<MissedNotification>, AppError> {
+        let since = last_tick.max(now - Duration::days(MAX_MISSED_LOOKBACK_DAYS));
+        if since >= now {
+            return Ok(Vec::new());
+        }
+        
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let no_nag_days = self.list_no_nag_days().await?;
+        let profile = self.active_profile().await?;
+        let default_time = default_notification_time(profile.as_ref());
+        let mut missed = Vec::new();
+        
+        for task in self.get_active_tasks().await? {
+            for scheduled_at in self
+                .scheduled_targets(&task, since, now, &timezone, default_time)
+                .into_iter()
+                .filter(|t| *t > since && *t < now)
+                .filter(|t| !no_nag_days.contains(&timezone.local_date(*t)))
+            {
+                missed.push(MissedNotification {
+                    task_id: task.id.clone(),
+                    title: task.title.clone(),
+                    level: task.notification_level.unwrap_or(1),
+                    notification_type: task.notification_type.clone().unwrap_or_default(),
+                    scheduled_at,
+                });
+            }
+        }
+        
+        missed.sort_by_key(|m| m.scheduled_at);
+        Ok(missed)
+    }
+
+    /// 今後`days`日間に発火する予定の通知をすべて時刻順に列挙（カレンダー表示用）
+    pub async fn enumerate_schedule(&self, days: i64) -> Result<Vec<ScheduledNotification>, AppError> {
+        self.enumerate_schedule_at(Utc::now(), days).await
+    }
+    
+    /// `from`から`days`日間の発火予定を列挙
+    ///
+    /// 通知しない日の発火は除き、レベルはその時刻のタグ・夜間帯のルールを反映する。
+    /// 有効な通知プロファイルがあれば、その通知しない時間帯・最低レベルも反映する。
+    pub async fn enumerate_schedule_at(&self, from: DateTime<Utc>, days: i64) -> Result<Vec<ScheduledNotification>, AppError> {
+        if !(1..=MAX_SCHEDULE_DAYS).contains(&days) {
+            return Err(AppError::InvalidInput(format!(
+                "Days must be between 1 and {}: {}",
+                MAX_SCHEDULE_DAYS, days
+            )));
+        }
+        let until = from + Duration::days(days);
+        
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let no_nag_days = self.list_no_nag_days().await?;
+        let profile = self.active_profile().await?;
+        let default_time = default_notification_time(profile.as_ref());
+        let rules = self.level_rules_for(profile.as_ref()).await?;
+        let quiet_hours = self.get_quiet_hours().await?;
+        let mut schedule: Vec<ScheduledNotification> = Vec::new();
+        
+        for task in self.get_active_tasks().await? {
+            for target in self
+                .scheduled_targets(&task, from, until, &timezone, default_time)
+                .into_iter()
+                .filter(|t| *t >= from && *t < until)
+            {
+                let level = self
+                    .apply_level_rules(&task.id, task.notification_level.unwrap_or(1), target, &rules, &timezone)
+                    .await?;
+                // クワイエットタイム中は出さない（保留する設定ならレベル3は終了時刻に出す）
+                let fire_at = match &quiet_hours {
+                    Some(quiet_hours) if quiet_hours.is_quiet(timezone.to_local(target).time()) => {
+                        if !quiet_hours.defer_critical || level < QuietHours::DEFERRED_LEVEL {
+                            continue;
+                        }
+                        let Some(deferred) = quiet_hours
+                            .window_end_after(timezone.to_local(target))
+                            .and_then(|end| timezone.from_local(end))
+                        else {
+                            continue;
+                        };
+                        deferred
+                    }
+                    _ => target,
+                };
+                if fire_at >= until || no_nag_days.contains(&timezone.local_date(fire_at)) {
+                    continue;
+                }
+                if profile.as_ref().is_some_and(|p| !p.allows(level, timezone.to_local(fire_at).time())) {
+                    continue;
+                }
+                // 同じ時間帯に保留された通知はまとめて1回
+                if schedule.iter().any(|s| s.task_id == task.id && s.fire_at == fire_at) {
+                    continue;
+                }
+                schedule.push(ScheduledNotification {
+                    task_id: task.id.clone(),
+                    title: task.title.clone(),
+                    fire_at,
+                    level,
+                    notification_type: task.notification_type.clone().unwrap_or_default(),
+                });
+            }
+        }
+        
+        schedule.sort_by_key(|s| s.fire_at);
+        Ok(schedule)
+    }
+    
+    /// タスクの通知設定が期間内に発火する時刻
+    ///
+    /// 通知しない日・プロファイルによる絞り込みは反映せず、タスクの設定だけで判定する。
+    pub async fn task_firings_between(&self, task: &Task, from: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<DateTime<Utc>>, AppError> {
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let profile = self.active_profile().await?;
+        Ok(self
+            .scheduled_targets(task, from, until, &timezone, default_notification_time(profile.as_ref()))
+            .into_iter()
+            .filter(|t| *t >= from && *t < until)
+            .collect())
+    }
+    
+    /// 期間内にかかる可能性のある発火時刻（期間での絞り込みは呼び出し側で行う）
+    fn scheduled_targets(
+        &self,
+        task: &Task,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        timezone: &AppTimezone,
+        default_time: &str,
+    ) -> Vec<DateTime<Utc>> {
+        match task.notification_type.as_deref() {
+            Some("recurring") => self.recurring_targets_between(task, since, until, timezone),
+            Some("due_date_based") => self.due_date_targets_between(task, since, until, timezone, default_time),
+            _ => Vec::new(),
+        }
+    }
+
+    /// 期間内の各ローカル日付について繰り返し通知の発火時刻を計算
+    fn recurring_targets_between(&self, task: &Task, since: DateTime<Utc>, until: DateTime<Utc>, timezone: &AppTimezone) -> Vec<DateTime<Utc>> {
+        let (Some(time_str), Some(days_str)) = (&task.notification_time, &task.notification_days_of_week) else {
+            return Vec::new();
+        };
+        let Ok(days_of_week) = serde_json::from_str::<Vec<u32>>(days_str) else {
+            return Vec::new();
+        };
+        
+        timezone
+            .local_date(since)
+            .iter_days()
+            .take_while(|date| *date <= timezone.local_date(until))
+            .filter(|date| days_of_week.contains(&date.weekday().num_days_from_sunday()))
+            .filter(|date| !task.is_past_notification_until(*date))
+            .filter_map(|date| timezone.at_local_time(date, time_str))
+            .collect()
+    }
+
+    /// 期間内にかかる期日ベース通知の毎時の発火時刻
+    fn due_date_targets_between(
+        &self,
+        task: &Task,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        timezone: &AppTimezone,
+        default_time: &str,
+    ) -> Vec<DateTime<Utc>> {
+        let Some((start, end)) = self.due_date_window(task, timezone, default_time) else {
+            return Vec::new();
+        };
+        
+        let mut target = start + Duration::hours((since - start).num_hours().max(0));
+        let mut targets = Vec::new();
+        while target <= end && target <= until {
+            targets.push(target);
+            target += Duration::hours(1);
+        }
+        targets
+    }
+
+    /// 期日ベース通知で毎時通知する期間
+    ///
+    /// 期日の指定日数前のローカル通知時刻から、期日当日の同じ時刻まで。
+    fn due_date_window(&self, task: &Task, timezone: &AppTimezone, default_time: &str) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let due_date = DateTime::parse_from_rfc3339(task.due_date.as_ref()?).ok()?.with_timezone(&Utc);
+        let days_before = task.notification_days_before.unwrap_or(1) as i64;
+        let time_str = task.notification_time.as_deref().unwrap_or(default_time);
+        
+        let due_local_date = timezone.local_date(due_date);
+        let start = timezone.at_local_time(due_local_date - Duration::days(days_before), time_str)?;
+        let end = timezone.at_local_time(due_local_date, time_str)?;
+        Some((start, end))
+    }
+
+    /// 通知を発火する
+    ///
+    /// 表示は`present`（アプリではトーストや音）に任せ、その成否と表示した文面`message`を通知ログに残す。
+    /// 表示できた通知は発火済みとして記録し、`run_browser_actions`ならタスクのブラウザアクションも実行する。
+    /// ログなどの記録に失敗しても警告を残すだけで、表示の結果は変えない。
+    pub async fn fire_notification<F>(
+        &self,
+        notification: &TaskNotification,
+        message: &str,
+        run_browser_actions: bool,
+        present: F,
+    ) -> Result<(), AppError>
+    where
+        F: FnOnce() -> Result<(), String>,
+    {
         log::info!("Firing notification for task: {} - {}", notification.task_id, notification.title);
         
-        // タスクの詳細情報を取得
-        let task = self.get_task_by_id(&notification.task_id).await?;
+        let presented = present();
+        // 「通知が来なかった」調査用に、失敗した発火も記録する
+        if let Err(e) = self
+            .log_notification_execution(notification, presented.is_ok(), presented.as_ref().err().map(String::as_str), Some(message))
+            .await
+        {
+            log::warn!("Failed to record notification log for task {}: {}", notification.task_id, e);
+        }
+        presented.map_err(AppError::Internal)?;
+        
+        if let Err(e) = self.mark_fired(notification, Utc::now()).await {
+            log::warn!("Failed to record fired notification for task {}: {}", notification.task_id, e);
+        }
+        if run_browser_actions {
+            let executed = match self.get_task_by_id(&notification.task_id).await {
+                Ok(task) => self.run_browser_actions(&task).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = executed {
+                log::warn!("Failed to run browser actions for task {}: {}", notification.task_id, e);
+            }
+        }
+        Ok(())
+    }
+    
+    /// 通知判定に使うタイムゾーンのIANA名（未設定ならNoneで、システムローカルを使う）
+    pub async fn get_timezone(&self) -> Result<Option<String>, AppError> {
+        Ok(SettingsService::get(&self.db.pool, AppTimezone::SETTINGS_KEY).await?)
+    }
+    
+    /// タイムゾーンを保存（Noneでシステムローカルに戻す）
+    pub async fn set_timezone(&self, name: Option<String>) -> Result<(), AppError> {
+        let Some(name) = name.filter(|name| !name.trim().is_empty()) else {
+            SettingsService::delete(&self.db.pool, AppTimezone::SETTINGS_KEY).await?;
+            return Ok(());
+        };
+        AppTimezone::parse(&name).map_err(AppError::InvalidInput)?;
+        SettingsService::set(&self.db.pool, AppTimezone::SETTINGS_KEY, name.trim()).await?;
+        Ok(())
+    }
+    
+    /// Webhookの送信先URL（未設定ならNone）
+    pub async fn get_webhook_url(&self) -> Result<Option<String>, AppError> {
+        Ok(SettingsService::get(&self.db.pool, WEBHOOK_URL_KEY).await?)
+    }
+    
+    /// Webhookの送信先URLを保存（Noneで送信しない）
+    pub async fn set_webhook_url(&self, url: Option<String>) -> Result<(), AppError> {
+        let Some(url) = url.filter(|url| !url.trim().is_empty()) else {
+            SettingsService::delete(&self.db.pool, WEBHOOK_URL_KEY).await?;
+            return Ok(());
+        };
+        let parsed = url::Url::parse(url.trim())
+            .map_err(|e| AppError::InvalidInput(format!("Invalid webhook URL '{}': {}", url, e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(AppError::InvalidInput(format!("Webhook URL must use http or https: {}", url)));
+        }
+        SettingsService::set(&self.db.pool, WEBHOOK_URL_KEY, parsed.as_str()).await?;
+        Ok(())
+    }
+    
+    /// Webhookに通知を送る（失敗してもデスクトップ通知は続けるため、ログに残すだけ）
+    pub async fn notify_webhook(&self, notification: &TaskNotification) {
+        if let Err(e) = self.send_webhook(notification).await {
+            log::warn!("Failed to send webhook for task {}: {}", notification.task_id, e);
+        }
+    }
+    
+    /// 設定されたWebhookにタスク情報をJSONでPOSTする
+    ///
+    /// タイムアウト・接続エラー・5xxは最大`WEBHOOK_MAX_RETRIES`回再試行する。
+    /// 4xxは送り直しても結果が変わらないため、すぐにエラーを返す。
+    pub async fn send_webhook(&self, notification: &TaskNotification) -> Result<(), AppError> {
+        let Some(url) = self.get_webhook_url().await? else {
+            return Ok(());
+        };
+        let payload = serde_json::json!({
+            "id": notification.task_id,
+            "title": notification.title,
+            "level": notification.level,
+            "notification_type": notification.notification_type,
+        });
+        
+        let mut attempt = 0;
+        loop {
+            let (error, retryable) = match self.webhook_client.post(&url).json(&payload).send().await {
+                Ok(response) if response.status().is_success() => return Ok(()),
+                Ok(response) => (format!("HTTP {}", response.status()), response.status().is_server_error()),
+                Err(e) if e.is_timeout() => ("Request timed out".to_string(), true),
+                Err(e) => (e.to_string(), e.is_connect()),
+            };
+            if !retryable || attempt >= WEBHOOK_MAX_RETRIES {
+                return Err(AppError::Internal(format!(
+                    "Webhook failed after {} attempts: {}",
+                    attempt + 1,
+                    error
+                )));
+            }
+            attempt += 1;
+            log::info!("Retrying webhook ({}/{}): {}", attempt, WEBHOOK_MAX_RETRIES, error);
+            tokio::time::sleep(WEBHOOK_RETRY_DELAY * attempt).await;
+        }
+    }
+    
+    /// 同じ発火枠（`notification_key`）で発火済みの通知を取り除く
+    pub async fn retain_unfired(&self, notifications: &mut Vec<TaskNotification>) -> Result<(), AppError> {
+        let mut unfired = Vec::with_capacity(notifications.len());
+        for notification in notifications.drain(..) {
+            if let Some(key) = &notification.notification_key {
+                let fired: Option<i64> = sqlx::query_scalar(
+                    "SELECT 1 FROM notification_fired_keys WHERE task_id = ?1 AND notification_key = ?2",
+                )
+                .bind(&notification.task_id)
+                .bind(key)
+                .fetch_optional(&self.db.pool)
+                .await?;
+                if fired.is_some() {
+                    continue;
+                }
+            }
+            unfired.push(notification);
+        }
+        *notifications = unfired;
+        Ok(())
+    }
+    
+    /// 通知を発火済みとして記録する（キーには日付が含まれるため、翌日の同じ時刻は再び発火する）
+    pub async fn mark_fired(&self, notification: &TaskNotification, fired_at: DateTime<Utc>) -> Result<(), AppError> {
+        let Some(key) = &notification.notification_key else {
+            return Ok(());
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO notification_fired_keys (task_id, notification_key, fired_at)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT(task_id, notification_key) DO NOTHING
+            "#,
+        )
+        .bind(&notification.task_id)
+        .bind(key)
+        .bind(fired_at.to_rfc3339())
+        .execute(&self.db.pool)
+        .await?;
         
-        // ブラウザアクションが設定されている場合、実行
+        // 判定に使わなくなった古いキーは消しておく
+        sqlx::query("DELETE FROM notification_fired_keys WHERE datetime(fired_at) < datetime(?1)")
+            .bind((fired_at - Duration::days(FIRED_KEY_RETENTION_DAYS)).to_rfc3339())
+            .execute(&self.db.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// タスクのブラウザアクションを実行（実行に失敗しても通知は続けるため、ログに残すだけ）
+    pub async fn run_browser_actions(&self, task: &Task) -> Result<(), AppError> {
         if let Some(browser_actions_json) = &task.browser_actions {
             match self.parse_browser_action_settings(browser_actions_json) {
                 Ok(browser_action_settings) => {
-                    if browser_action_settings.enabled && !browser_action_settings.actions.is_empty() {
+                    let has_actions = browser_action_settings.enabled && !browser_action_settings.actions.is_empty();
+                    // 間隔を確認できなければ、ブラウザアクションを止めないよう間引かない
+                    let throttled = has_actions
+                        && self.browser_actions_throttled(&task.id, Utc::now()).await.unwrap_or_else(|e| {
+                            log::warn!("Failed to check browser action interval for task {}: {}", task.id, e);
+                            false
+                        });
+                    if throttled {
+                        // 通知は出すが、同じタブを短時間に何度も開かない
+                        log::info!("Skipping browser actions for task {}: opened too recently", task.id);
+                    } else if has_actions {
                         log::info!("Executing {} browser actions for notification", browser_action_settings.actions.len());
                         match self.browser_action_service.execute_actions(&browser_action_settings.actions).await {
                             Ok(_) => {
                                 log::info!("Successfully executed browser actions for task: {}", task.id);
+                                // 開いた記録に失敗しても、アクション自体は実行済みなので続ける
+                                if let Err(e) = self.record_browser_actions_acted(task, &browser_action_settings).await {
+                                    log::warn!("Failed to record browser actions for task {}: {}", task.id, e);
+                                }
                             }
                             Err(e) => {
                                 log::warn!("Failed to execute browser actions for task {}: {}. Notification will still be shown.", task.id, e);
@@ -94,12 +880,58 @@ impl NotificationService {
             }
         }
         
-        // TODO: 実際の通知システム（システムトレイ、デスクトップ通知等）の実装
-        log::info!("Desktop notification shown for: {}", notification.title);
-        
         Ok(())
     }
 
+    /// 保存済みのコマンド実行の許可設定をブラウザアクションに反映（起動時に呼ぶ）
+    pub async fn load_command_actions_setting(&self) -> Result<(), AppError> {
+        let allowed = self.get_command_actions_allowed().await?;
+        self.browser_action_service.set_command_actions_allowed(allowed);
+        Ok(())
+    }
+    
+    /// ブラウザアクションでローカルのコマンド実行を許可するか（既定は許可しない）
+    pub async fn get_command_actions_allowed(&self) -> Result<bool, AppError> {
+        Ok(SettingsService::get(&self.db.pool, ALLOW_COMMAND_ACTIONS_KEY).await?.as_deref() == Some("true"))
+    }
+    
+    /// コマンド実行の許可を保存し、ブラウザアクションに反映
+    pub async fn set_command_actions_allowed(&self, allowed: bool) -> Result<(), AppError> {
+        SettingsService::set(&self.db.pool, ALLOW_COMMAND_ACTIONS_KEY, if allowed { "true" } else { "false" }).await?;
+        self.browser_action_service.set_command_actions_allowed(allowed);
+        Ok(())
+    }
+
+    /// 同じタスクのブラウザアクションを再び開くまでの最短間隔（分、0なら制限しない）
+    pub async fn get_browser_action_min_interval(&self) -> Result<i64, AppError> {
+        Ok(SettingsService::get(&self.db.pool, BROWSER_ACTION_MIN_INTERVAL_KEY)
+            .await?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0))
+    }
+    
+    pub async fn set_browser_action_min_interval(&self, minutes: i64) -> Result<(), AppError> {
+        if !(0..=MAX_BROWSER_ACTION_MIN_INTERVAL_MINUTES).contains(&minutes) {
+            return Err(AppError::InvalidInput(format!(
+                "Minimum interval must be between 0 and {} minutes: {}",
+                MAX_BROWSER_ACTION_MIN_INTERVAL_MINUTES, minutes
+            )));
+        }
+        SettingsService::set(&self.db.pool, BROWSER_ACTION_MIN_INTERVAL_KEY, &minutes.to_string()).await?;
+        Ok(())
+    }
+    
+    /// 前回ブラウザアクションを開いてから最短間隔が経っていなければtrue
+    async fn browser_actions_throttled(&self, task_id: &str, now: DateTime<Utc>) -> Result<bool, AppError> {
+        let min_interval_minutes = self.get_browser_action_min_interval().await?;
+        let last_acted_at = self
+            .get_browser_actions_acted_at(task_id)
+            .await?
+            .and_then(|acted_at| DateTime::parse_from_rfc3339(&acted_at).ok())
+            .map(|acted_at| acted_at.with_timezone(&Utc));
+        Ok(is_browser_action_throttled(last_acted_at, now, min_interval_minutes))
+    }
+
     /// 通知レベルに基づく重要度判定
     pub fn should_execute_browser_actions(&self, notification_level: Option<i32>) -> bool {
         match notification_level {
@@ -111,33 +943,24 @@ impl NotificationService {
     }
 
     /// 期日ベース通知のチェック
-    fn check_due_date_notification(&self, task: &Task, current_time: DateTime<Utc>) -> Option<TaskNotification> {
+    ///
+    /// 期日の指定日数前の通知時刻から期日当日の同じ時刻まで、1時間ごとに通知する。
+    /// 発火枠ごとに`notification_key`が変わるため、毎時の通知はそれぞれ1回だけ出る。
+    fn check_due_date_notification(&self, task: &Task, current_time: DateTime<Utc>, default_time: &str, timezone: &AppTimezone) -> Option<TaskNotification> {
         let due_date_str = task.due_date.as_ref()?;
         let due_date = DateTime::parse_from_rfc3339(due_date_str).ok()?.with_timezone(&Utc);
         
-        let days_before = task.notification_days_before.unwrap_or(1);
-        let default_time = "09:00".to_string();
-        let notification_time = task.notification_time.as_ref().unwrap_or(&default_time);
-        
-        // Parse notification time
-        let time_parts: Vec<&str> = notification_time.split(':').collect();
-        if time_parts.len() != 2 {
+        // 通知期間はアプリのタイムゾーンでのローカル日付・時刻として計算する
+        let (start, end) = self.due_date_window(task, timezone, default_time)?;
+        let elapsed = (current_time - start).num_seconds();
+        if elapsed < -60 {
             return None;
         }
-        
-        let hour = time_parts[0].parse::<u32>().ok()?;
-        let minute = time_parts[1].parse::<u32>().ok()?;
-        
-        // Calculate notification date
-        let notification_date = due_date - Duration::days(days_before as i64);
-        let notification_datetime = notification_date
-            .date_naive()
-            .and_hms_opt(hour, minute, 0)?
-            .and_utc();
+        let notification_datetime = start + Duration::hours((elapsed + 60).div_euclid(3600));
         
         // Check if it's time for notification (within 1 minute window)
         let time_diff = (current_time - notification_datetime).num_seconds().abs();
-        if time_diff <= 60 {
+        if notification_datetime <= end && time_diff <= 60 {
             let days_until_due = (due_date - current_time).num_days();
             Some(TaskNotification {
                 task_id: task.id.clone(),
@@ -145,6 +968,7 @@ impl NotificationService {
                 notification_type: "due_date_based".to_string(),
                 level: task.notification_level.unwrap_or(1),
                 days_until_due: Some(days_until_due),
+                notification_key: Some(notification_key(timezone.to_local(notification_datetime))),
             })
         } else {
             None
@@ -152,36 +976,40 @@ impl NotificationService {
     }
 
     /// 繰り返し通知のチェック
-    fn check_recurring_notification(&self, task: &Task, current_time: DateTime<Utc>) -> Option<TaskNotification> {
+    ///
+    /// 通知時刻はアプリのタイムゾーンでのローカル時刻として扱うため、DST切り替えの前後でも同じ時刻に通知される
+    fn check_recurring_notification(&self, task: &Task, current_time: DateTime<Utc>, timezone: &AppTimezone) -> Option<TaskNotification> {
         let notification_time = task.notification_time.as_ref()?;
         let days_of_week_str = task.notification_days_of_week.as_ref()?;
         
+        // 終了日を過ぎたら通知しない
+        if task.is_past_notification_until(timezone.local_date(current_time)) {
+            return None;
+        }
+        
         // Parse days of week
         let days_of_week: Vec<u32> = serde_json::from_str(days_of_week_str).ok()?;
         
         // Check if current day is in the list
-        let current_weekday = current_time.weekday().num_days_from_monday() + 1; // Monday = 1
+        let current_weekday = timezone.weekday_from_sunday(current_time); // Sunday = 0
         if !days_of_week.contains(&current_weekday) {
             return None;
         }
         
-        // Parse notification time
-        let time_parts: Vec<&str> = notification_time.split(':').collect();
-        if time_parts.len() != 2 {
-            return None;
-        }
-        
-        let hour = time_parts[0].parse::<u32>().ok()?;
-        let minute = time_parts[1].parse::<u32>().ok()?;
+        // ローカル日付の通知時刻を実時刻に変換
+        let local_date = timezone.local_date(current_time);
+        let notification_datetime = timezone.at_local_time(local_date, notification_time)?;
         
         // Check if it's the right time (within 1 minute window)
-        if current_time.hour() == hour && current_time.minute() == minute {
+        let time_diff = (current_time - notification_datetime).num_seconds();
+        if (0..60).contains(&time_diff) {
             Some(TaskNotification {
                 task_id: task.id.clone(),
                 title: task.title.clone(),
                 notification_type: "recurring".to_string(),
                 level: task.notification_level.unwrap_or(1),
                 days_until_due: None,
+                notification_key: Some(notification_key(timezone.to_local(notification_datetime))),
             })
         } else {
             None
@@ -194,9 +1022,9 @@ impl NotificationService {
             r#"
             SELECT id, title, description, status, parent_id, due_date, completed_at, 
                    created_at, updated_at, progress, notification_type, notification_days_before, 
-                   notification_time, notification_days_of_week, notification_level, browser_actions
+                   notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
             FROM tasks
-            WHERE status != 'done' AND notification_type IS NOT NULL AND notification_type != 'none'
+            WHERE status != 'done' AND archived_at IS NULL AND deleted_at IS NULL AND notification_type IS NOT NULL AND notification_type != 'none'
             ORDER BY notification_level DESC, created_at DESC
             "#,
         )
@@ -212,9 +1040,9 @@ impl NotificationService {
             r#"
             SELECT id, title, description, status, parent_id, due_date, completed_at, 
                    created_at, updated_at, progress, notification_type, notification_days_before, 
-                   notification_time, notification_days_of_week, notification_level, browser_actions
+                   notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
             FROM tasks
-            WHERE id = ?1
+            WHERE id = ?1 AND deleted_at IS NULL
             "#,
         )
         .bind(id)
@@ -229,10 +1057,7 @@ impl NotificationService {
     /// JSONからBrowserActionSettingsをパース
     fn parse_browser_action_settings(&self, json: &str) -> Result<crate::models::browser_action::BrowserActionSettings, AppError> {
         if json.trim().is_empty() {
-            return Ok(crate::models::browser_action::BrowserActionSettings {
-                enabled: false,
-                actions: Vec::new(),
-            });
+            return Ok(crate::models::browser_action::BrowserActionSettings::new(false));
         }
         
         serde_json::from_str(json)
@@ -245,8 +1070,62 @@ impl NotificationService {
         self.browser_action_service.is_available().await
     }
 
+    /// ブラウザアクションを開いたことを記録し、設定に応じてタスクを着手中に進める
+    async fn record_browser_actions_acted(&self, task: &Task, settings: &BrowserActionSettings) -> Result<(), AppError> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO browser_action_acks (task_id, acted_at)
+            VALUES (?1, ?2)
+            ON CONFLICT(task_id) DO UPDATE SET acted_at = excluded.acted_at
+            "#,
+        )
+        .bind(&task.id)
+        .bind(&now)
+        .execute(&self.db.pool)
+        .await?;
+        
+        if settings.should_auto_advance(&task.status) {
+            log::info!("Auto-advancing task {} to in_progress after browser actions", task.id);
+            let result = sqlx::query(
+                r#"
+                UPDATE tasks
+                SET status = 'in_progress', updated_at = ?2, status_changed_at = ?2
+                WHERE id = ?1 AND status = 'todo'
+                "#,
+            )
+            .bind(&task.id)
+            .bind(&now)
+            .execute(&self.db.pool)
+            .await?;
+            if result.rows_affected() > 0 {
+                invalidate_task_context_cache();
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// タスクのブラウザアクションを最後に開いた日時
+    pub async fn get_browser_actions_acted_at(&self, task_id: &str) -> Result<Option<String>, AppError> {
+        let acted_at = sqlx::query_scalar::<_, String>(
+            "SELECT acted_at FROM browser_action_acks WHERE task_id = ?1",
+        )
+        .bind(task_id)
+        .fetch_optional(&self.db.pool)
+        .await?;
+        
+        Ok(acted_at)
+    }
+    
     /// 実行ログと監査証跡の記録
-    pub async fn log_notification_execution(&self, notification: &TaskNotification, success: bool, error: Option<&str>) -> Result<(), AppError> {
+    pub async fn log_notification_execution(
+        &self,
+        notification: &TaskNotification,
+        success: bool,
+        error: Option<&str>,
+        message: Option<&str>,
+    ) -> Result<(), AppError> {
         let log_message = if success {
             format!("Successfully fired notification for task {}: {}", notification.task_id, notification.title)
         } else {
@@ -256,11 +1135,640 @@ impl NotificationService {
         
         log::info!("{}", log_message);
         
-        // TODO: 将来的にはデータベースに実行ログを保存することも検討
-        // INSERT INTO notification_logs (task_id, notification_id, executed_at, success, error_message)
+        sqlx::query(
+            r#"
+            INSERT INTO notification_logs (id, task_id, fired_at, notification_type, level, success, error_message, message)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(&notification.task_id)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&notification.notification_type)
+        .bind(notification.level)
+        .bind(success)
+        .bind(error)
+        .bind(message)
+        .execute(&self.db.pool)
+        .await?;
+        
+        Ok(())
+    }
+
+    /// 直近`days`日間に発火した通知を（曜日, 時）ごとに数える（ヒートマップ表示用）
+    pub async fn firing_heatmap(&self, days: i64) -> Result<[[u32; 24]; 7], AppError> {
+        self.firing_heatmap_at(Utc::now(), days).await
+    }
+    
+    /// `now`までの`days`日間の発火回数を集計
+    ///
+    /// 行は曜日（0=日曜）、列はアプリのタイムゾーンでの時（0〜23）。失敗した発火は数えない。
+    pub async fn firing_heatmap_at(&self, now: DateTime<Utc>, days: i64) -> Result<[[u32; 24]; 7], AppError> {
+        if !(1..=MAX_HEATMAP_DAYS).contains(&days) {
+            return Err(AppError::InvalidInput(format!(
+                "Days must be between 1 and {}: {}",
+                MAX_HEATMAP_DAYS, days
+            )));
+        }
+        
+        let fired_at = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT fired_at
+            FROM notification_logs
+            WHERE success = 1 AND datetime(fired_at) >= datetime(?1) AND datetime(fired_at) <= datetime(?2)
+            "#,
+        )
+        .bind((now - Duration::days(days)).to_rfc3339())
+        .bind(now.to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let mut grid = [[0u32; 24]; 7];
+        for fired_at in fired_at.iter().filter_map(|t| DateTime::parse_from_rfc3339(t).ok()) {
+            let local = timezone.to_local(fired_at.with_timezone(&Utc));
+            grid[local.weekday().num_days_from_sunday() as usize][local.hour() as usize] += 1;
+        }
+        Ok(grid)
+    }
+
+    /// 直近の通知ログを新しい順に取得（件数は1〜`MAX_NOTIFICATION_HISTORY`件）
+    pub async fn get_notification_logs(&self, limit: i64) -> Result<Vec<NotificationLog>, AppError> {
+        if !(1..=MAX_NOTIFICATION_HISTORY).contains(&limit) {
+            return Err(AppError::InvalidInput(format!(
+                "Limit must be between 1 and {}: {}",
+                MAX_NOTIFICATION_HISTORY, limit
+            )));
+        }
+        let logs = sqlx::query_as::<_, NotificationLog>(
+            r#"
+            SELECT id, task_id, fired_at, notification_type, level, success, error_message, message
+            FROM notification_logs
+            ORDER BY datetime(fired_at) DESC
+            LIMIT ?1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        Ok(logs)
+    }
+    
+    /// 有効なタスクで使われている通知時刻と、その件数を取得（件数の多い順）
+    pub async fn get_configured_times(&self) -> Result<Vec<(String, i32)>, AppError> {
+        let times = sqlx::query_as::<_, (String, i32)>(
+            r#"
+            SELECT notification_time, COUNT(*) AS task_count
+            FROM tasks
+            WHERE status != 'done'
+              AND archived_at IS NULL
+              AND deleted_at IS NULL
+              AND notification_type IS NOT NULL
+              AND notification_type != 'none'
+              AND notification_time IS NOT NULL
+              AND notification_time != ''
+            GROUP BY notification_time
+            ORDER BY task_count DESC, notification_time ASC
+            "#,
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        Ok(times)
+    }
+    
+    /// 受信箱の放置日数の閾値を取得（0は無効）
+    pub async fn get_inbox_aging_days(&self) -> Result<i64, AppError> {
+        let days = SettingsService::get(&self.db.pool, INBOX_AGING_DAYS_KEY)
+            .await?
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(DEFAULT_INBOX_AGING_DAYS);
+        Ok(days)
+    }
+    
+    /// 受信箱の放置日数の閾値を設定（0で無効化）
+    pub async fn set_inbox_aging_days(&self, days: i64) -> Result<(), AppError> {
+        if !(0..=365).contains(&days) {
+            return Err(AppError::InvalidInput("Inbox aging days must be between 0 and 365".to_string()));
+        }
+        SettingsService::set(&self.db.pool, INBOX_AGING_DAYS_KEY, &days.to_string()).await?;
+        Ok(())
+    }
+    
+    /// 一日中通知しない日の一覧（日付順）
+    pub async fn list_no_nag_days(&self) -> Result<BTreeSet<NaiveDate>, AppError> {
+        Ok(SettingsService::get_json(&self.db.pool, NO_NAG_DAYS_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+    
+    /// 通知しない日を追加
+    pub async fn add_no_nag_day(&self, date: NaiveDate) -> Result<BTreeSet<NaiveDate>, AppError> {
+        let mut days = self.list_no_nag_days().await?;
+        days.insert(date);
+        SettingsService::set_json(&self.db.pool, NO_NAG_DAYS_KEY, &days).await?;
+        Ok(days)
+    }
+    
+    /// 通知しない日を削除
+    pub async fn remove_no_nag_day(&self, date: NaiveDate) -> Result<BTreeSet<NaiveDate>, AppError> {
+        let mut days = self.list_no_nag_days().await?;
+        if !days.remove(&date) {
+            return Err(AppError::NotFound(format!("No-nag day {} not found", date)));
+        }
+        SettingsService::set_json(&self.db.pool, NO_NAG_DAYS_KEY, &days).await?;
+        Ok(days)
+    }
+    
+    /// 指定時刻が通知しない日（ローカル日付）に含まれるか
+    pub async fn is_no_nag_day(&self, current_time: DateTime<Utc>) -> Result<bool, AppError> {
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        Ok(self.list_no_nag_days().await?.contains(&timezone.local_date(current_time)))
+    }
+    
+    /// 指定時刻までタスクの通知をスヌーズ（既存のスヌーズは置き換える）
+    pub async fn snooze_task_until(&self, task_id: &str, until: DateTime<Utc>) -> Result<(), AppError> {
+        self.get_task_by_id(task_id).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO notification_snoozes (task_id, snooze_until)
+            VALUES (?1, ?2)
+            ON CONFLICT(task_id) DO UPDATE SET snooze_until = excluded.snooze_until
+            "#,
+        )
+        .bind(task_id)
+        .bind(until.to_rfc3339())
+        .execute(&self.db.pool)
+        .await?;
+        Ok(())
+    }
+    
+    /// 現在時刻から指定分数だけタスクの通知をスヌーズし、終了時刻を返す
+    pub async fn snooze_notification(&self, task_id: &str, minutes: i64) -> Result<DateTime<Utc>, AppError> {
+        self.snooze_notification_at(task_id, minutes, Utc::now()).await
+    }
+    
+    pub async fn snooze_notification_at(&self, task_id: &str, minutes: i64, current_time: DateTime<Utc>) -> Result<DateTime<Utc>, AppError> {
+        if !(1..=MAX_SNOOZE_MINUTES).contains(&minutes) {
+            return Err(AppError::InvalidInput(format!(
+                "Snooze length must be between 1 and {} minutes: {}",
+                MAX_SNOOZE_MINUTES, minutes
+            )));
+        }
+        
+        let until = current_time + Duration::minutes(minutes);
+        self.snooze_task_until(task_id, until).await?;
+        Ok(until)
+    }
+    
+    /// タスクのスヌーズを解除する（スヌーズしていなくてもエラーにしない）
+    pub async fn clear_snooze(&self, task_id: &str) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM notification_snoozes WHERE task_id = ?1")
+            .bind(task_id)
+            .execute(&self.db.pool)
+            .await?;
+        Ok(())
+    }
+    
+    /// スヌーズが終了した直後（1分以内）のタスクの通知を再度発火させる
+    ///
+    /// 元の通知時刻はスヌーズ中に過ぎているため、ここで拾わないと次の通知時刻まで鳴らない。
+    /// `pending`に同じタスクの通知が既にあれば重複させない。
+    pub async fn check_snooze_expirations(&self, current_time: DateTime<Utc>, pending: &[TaskNotification]) -> Result<Vec<TaskNotification>, AppError> {
+        let tasks = sqlx::query_as::<_, Task>(
+            r#"
+            SELECT t.id, t.title, t.description, t.status, t.parent_id, t.due_date, t.completed_at, 
+                   t.created_at, t.updated_at, t.progress, t.notification_type, t.notification_days_before, 
+                   t.notification_time, t.notification_days_of_week, t.notification_level, t.notification_until, t.browser_actions, t.is_pinned, t.status_changed_at, t.is_optional, t.notification_channels, t.recurrence_rule, t.recurrence_end, t.archived_at, t.deleted_at
+            FROM notification_snoozes s
+            INNER JOIN tasks t ON t.id = s.task_id
+            WHERE datetime(s.snooze_until) <= datetime(?1)
+              AND datetime(s.snooze_until) > datetime(?2)
+              AND t.status != 'done' AND t.archived_at IS NULL AND t.deleted_at IS NULL
+              AND t.notification_type IS NOT NULL AND t.notification_type != 'none'
+            "#,
+        )
+        .bind(current_time.to_rfc3339())
+        .bind((current_time - Duration::seconds(60)).to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let mut resumed = Vec::new();
+        for task in tasks {
+            if pending.iter().any(|notification| notification.task_id == task.id) {
+                continue;
+            }
+            let snooze_until = self.get_snooze_until(&task.id).await?.unwrap_or(current_time);
+            let days_until_due = task.due_date.as_deref()
+                .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+                .map(|due| (due.with_timezone(&Utc) - current_time).num_days());
+            resumed.push(TaskNotification {
+                task_id: task.id,
+                title: task.title,
+                level: task.notification_level.unwrap_or(1),
+                days_until_due,
+                notification_type: task.notification_type.unwrap_or_default(),
+                notification_key: Some(notification_key(timezone.to_local(snooze_until))),
+            });
+        }
+        Ok(resumed)
+    }
+    
+    /// タスクのスヌーズ終了時刻（スヌーズしていなければNone）
+    pub async fn get_snooze_until(&self, task_id: &str) -> Result<Option<DateTime<Utc>>, AppError> {
+        let until = sqlx::query_scalar::<_, String>("SELECT snooze_until FROM notification_snoozes WHERE task_id = ?1")
+            .bind(task_id)
+            .fetch_optional(&self.db.pool)
+            .await?;
+        
+        until
+            .map(|until| {
+                DateTime::parse_from_rfc3339(&until)
+                    .map(|until| until.with_timezone(&Utc))
+                    .map_err(|e| AppError::ParseError(format!("Invalid snooze time '{}': {}", until, e)))
+            })
+            .transpose()
+    }
+    
+    /// 指定時刻にスヌーズ中のタスクID
+    pub async fn snoozed_task_ids(&self, current_time: DateTime<Utc>) -> Result<HashSet<String>, AppError> {
+        let task_ids = sqlx::query_scalar::<_, String>(
+            "SELECT task_id FROM notification_snoozes WHERE datetime(snooze_until) > datetime(?1)",
+        )
+        .bind(current_time.to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await?;
+        Ok(task_ids.into_iter().collect())
+    }
+    
+    /// 翌営業日の始業時刻（ローカル時刻）までスヌーズし、終了時刻を返す
+    pub async fn snooze_until_next_business_day(&self, task_id: &str, current_time: DateTime<Utc>) -> Result<DateTime<Utc>, AppError> {
+        let settings = self.get_business_day_settings().await?;
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        
+        let until = TemporalContext::next_business_day(timezone.local_date(current_time), &settings)
+            .and_then(|date| timezone.at_local_time(date, &settings.workday_start))
+            .ok_or_else(|| AppError::Internal("Could not determine the next business day".to_string()))?;
+        
+        self.snooze_task_until(task_id, until).await?;
+        Ok(until)
+    }
+    
+    /// タスクの集中セッションを開始（実行中のセッションは置き換える）
+    pub async fn start_focus_session(&self, task_id: &str, minutes: i64, current_time: DateTime<Utc>) -> Result<FocusSession, AppError> {
+        if !(1..=MAX_FOCUS_SESSION_MINUTES).contains(&minutes) {
+            return Err(AppError::InvalidInput(format!(
+                "Focus session length must be between 1 and {} minutes: {}",
+                MAX_FOCUS_SESSION_MINUTES, minutes
+            )));
+        }
+        let task = self.get_task_by_id(task_id).await?;
+        
+        let session = FocusSession {
+            task_id: task.id,
+            task_title: task.title,
+            started_at: current_time,
+            ends_at: current_time + Duration::minutes(minutes),
+        };
+        SettingsService::set_json(&self.db.pool, FocusSession::SETTINGS_KEY, &session).await?;
+        Ok(session)
+    }
+    
+    /// 集中セッションを終了し、終了したセッションを返す（なければNone）
+    pub async fn end_focus_session(&self) -> Result<Option<FocusSession>, AppError> {
+        let session = self.get_focus_session().await?;
+        if session.is_some() {
+            SettingsService::delete(&self.db.pool, FocusSession::SETTINGS_KEY).await?;
+        }
+        Ok(session)
+    }
+    
+    /// 実行中（または終了時刻を過ぎて未処理）の集中セッション
+    pub async fn get_focus_session(&self) -> Result<Option<FocusSession>, AppError> {
+        Ok(SettingsService::get_json(&self.db.pool, FocusSession::SETTINGS_KEY).await?)
+    }
+    
+    /// 終了時刻を過ぎた集中セッションがあれば終了して返す（スケジューラーから呼ぶ）
+    pub async fn take_finished_focus_session(&self, current_time: DateTime<Utc>) -> Result<Option<FocusSession>, AppError> {
+        match self.get_focus_session().await? {
+            Some(session) if current_time >= session.ends_at => self.end_focus_session().await,
+            _ => Ok(None),
+        }
+    }
+    
+    /// 集中セッション中は対象タスク以外の通知を取り除く
+    pub async fn apply_focus_session(&self, notifications: &mut Vec<TaskNotification>, current_time: DateTime<Utc>) -> Result<(), AppError> {
+        if let Some(session) = self.get_focus_session().await? {
+            notifications.retain(|notification| session.allows(&notification.task_id, current_time));
+        }
+        Ok(())
+    }
+    
+    /// クワイエットタイムの設定を取得（未設定ならNone）
+    pub async fn get_quiet_hours(&self) -> Result<Option<QuietHours>, AppError> {
+        let start = SettingsService::get(&self.db.pool, QuietHours::START_KEY).await?;
+        let end = SettingsService::get(&self.db.pool, QuietHours::END_KEY).await?;
+        let (Some(start), Some(end)) = (start, end) else {
+            return Ok(None);
+        };
+        let defer_critical = SettingsService::get(&self.db.pool, QuietHours::DEFER_CRITICAL_KEY)
+            .await?
+            .is_some_and(|v| v == "true");
+        Ok(Some(QuietHours { start, end, defer_critical }))
+    }
+    
+    /// クワイエットタイムの設定を保存（Noneで解除）
+    pub async fn set_quiet_hours(&self, quiet_hours: Option<QuietHours>) -> Result<Option<QuietHours>, AppError> {
+        match &quiet_hours {
+            Some(quiet_hours) => {
+                quiet_hours.validate().map_err(AppError::InvalidInput)?;
+                SettingsService::set(&self.db.pool, QuietHours::START_KEY, &quiet_hours.start).await?;
+                SettingsService::set(&self.db.pool, QuietHours::END_KEY, &quiet_hours.end).await?;
+                SettingsService::set(&self.db.pool, QuietHours::DEFER_CRITICAL_KEY, &quiet_hours.defer_critical.to_string()).await?;
+            }
+            None => {
+                for key in [QuietHours::START_KEY, QuietHours::END_KEY, QuietHours::DEFER_CRITICAL_KEY] {
+                    SettingsService::delete(&self.db.pool, key).await?;
+                }
+            }
+        }
+        Ok(quiet_hours)
+    }
+    
+    /// クワイエットタイムを反映する
+    ///
+    /// 時間帯中の通知はすべて取り除く。`defer_critical`が有効なら、時間帯の終了直後（1分以内）に
+    /// 時間帯中に発火予定だったレベル3の通知をまとめて追加する（保留分は状態を持たず発火予定から再計算する）。
+    pub async fn apply_quiet_hours(&self, notifications: &mut Vec<TaskNotification>, current_time: DateTime<Utc>) -> Result<(), AppError> {
+        let Some(quiet_hours) = self.get_quiet_hours().await? else {
+            return Ok(());
+        };
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let local = timezone.to_local(current_time);
+        
+        if quiet_hours.is_quiet(local.time()) {
+            notifications.clear();
+            return Ok(());
+        }
+        if !quiet_hours.defer_critical {
+            return Ok(());
+        }
+        let Some((window_start, window_end)) = quiet_hours.just_ended_window(local) else {
+            return Ok(());
+        };
+        let (Some(from), Some(until)) = (timezone.from_local(window_start), timezone.from_local(window_end)) else {
+            return Ok(());
+        };
         
+        let profile = self.active_profile().await?;
+        let default_time = default_notification_time(profile.as_ref());
+        let rules = self.level_rules_for(profile.as_ref()).await?;
+        for task in self.get_active_tasks().await? {
+            if notifications.iter().any(|notification| notification.task_id == task.id) {
+                continue;
+            }
+            let Some(fire_at) = self
+                .scheduled_targets(&task, from, until, &timezone, default_time)
+                .into_iter()
+                .rev()
+                .find(|t| *t >= from && *t < until)
+            else {
+                continue;
+            };
+            let level = self
+                .apply_level_rules(&task.id, task.notification_level.unwrap_or(1), fire_at, &rules, &timezone)
+                .await?;
+            if level < QuietHours::DEFERRED_LEVEL {
+                continue;
+            }
+            let days_until_due = task.due_date.as_deref()
+                .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+                .map(|due| (due.with_timezone(&Utc) - current_time).num_days());
+            notifications.push(TaskNotification {
+                task_id: task.id.clone(),
+                title: task.title.clone(),
+                notification_type: task.notification_type.clone().unwrap_or_default(),
+                level,
+                days_until_due,
+                notification_key: Some(notification_key(window_end)),
+            });
+        }
         Ok(())
     }
+    
+    /// 営業日・休日の設定を取得
+    pub async fn get_business_day_settings(&self) -> Result<BusinessDaySettings, AppError> {
+        Ok(SettingsService::get_json(&self.db.pool, BusinessDaySettings::SETTINGS_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+    
+    /// 営業日・休日の設定を保存
+    pub async fn set_business_day_settings(&self, settings: BusinessDaySettings) -> Result<BusinessDaySettings, AppError> {
+        settings.validate().map_err(AppError::InvalidInput)?;
+        SettingsService::set_json(&self.db.pool, BusinessDaySettings::SETTINGS_KEY, &settings).await?;
+        Ok(settings)
+    }
+    
+    /// 一日のまとめ通知の設定を取得
+    pub async fn get_daily_summary_settings(&self) -> Result<DailySummarySettings, AppError> {
+        Ok(SettingsService::get_json(&self.db.pool, DailySummarySettings::SETTINGS_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+    
+    /// 一日のまとめ通知の設定を保存
+    pub async fn set_daily_summary_settings(&self, settings: DailySummarySettings) -> Result<DailySummarySettings, AppError> {
+        settings.validate().map_err(AppError::InvalidInput)?;
+        SettingsService::set_json(&self.db.pool, DailySummarySettings::SETTINGS_KEY, &settings).await?;
+        Ok(settings)
+    }
+    
+    /// 今日（ローカル日付）の完了数・期限切れ数・未完了数を集計
+    pub async fn daily_summary_stats(&self, current_time: DateTime<Utc>) -> Result<DailySummaryStats, AppError> {
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let day_start = timezone
+            .from_local(timezone.local_date(current_time).and_hms_opt(0, 0, 0).unwrap_or_default())
+            .unwrap_or(current_time);
+        
+        let (completed_today, overdue, remaining): (i64, i64, i64) = sqlx::query_as(
+            r#"
+            SELECT
+                COUNT(CASE WHEN status = 'done' AND datetime(completed_at) >= datetime(?1) THEN 1 END),
+                COUNT(CASE WHEN status != 'done' AND due_date IS NOT NULL AND datetime(due_date) < datetime(?2) THEN 1 END),
+                COUNT(CASE WHEN status != 'done' THEN 1 END)
+            FROM tasks
+            WHERE deleted_at IS NULL
+            "#,
+        )
+        .bind(day_start.to_rfc3339())
+        .bind(current_time.to_rfc3339())
+        .fetch_one(&self.db.pool)
+        .await?;
+        
+        Ok(DailySummaryStats { completed_today, overdue, remaining })
+    }
+    
+    /// 一日のまとめを通知すべき時刻であれば文面を返す
+    ///
+    /// 通知した日付を記録し、同じ日には一度だけ返す。
+    pub async fn check_daily_summary(&self, current_time: DateTime<Utc>) -> Result<Option<String>, AppError> {
+        let settings = self.get_daily_summary_settings().await?;
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let last_sent = SettingsService::get(&self.db.pool, DailySummarySettings::LAST_SENT_KEY)
+            .await?
+            .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
+        
+        if !should_fire_summary(current_time, &settings, &timezone, last_sent) {
+            return Ok(None);
+        }
+        
+        let stats = self.daily_summary_stats(current_time).await?;
+        let today = timezone.local_date(current_time);
+        SettingsService::set(&self.db.pool, DailySummarySettings::LAST_SENT_KEY, &today.format("%Y-%m-%d").to_string()).await?;
+        
+        Ok(Some(build_summary_text(&stats)))
+    }
+    
+    /// 受信箱に放置されたタスクのリマインドをチェック
+    ///
+    /// 閾値の日数を超えて`inbox`にあるタスクについて、ローカル時刻で1日1回（通知時刻以降）レベル1の通知を返す。
+    /// 同じ日にすでに通知ログがあるタスクは除外する。
+    pub async fn check_inbox_aging(&self, current_time: DateTime<Utc>) -> Result<Vec<TaskNotification>, AppError> {
+        let aging_days = self.get_inbox_aging_days().await?;
+        if aging_days <= 0 {
+            return Ok(Vec::new());
+        }
+        
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let local_date = timezone.local_date(current_time);
+        let Some(reminder_time) = timezone.at_local_time(local_date, INBOX_AGING_REMINDER_TIME) else {
+            return Ok(Vec::new());
+        };
+        if current_time < reminder_time {
+            return Ok(Vec::new());
+        }
+        let day_start = timezone
+            .from_local(local_date.and_hms_opt(0, 0, 0).unwrap_or_default())
+            .unwrap_or(reminder_time);
+        
+        let threshold = current_time - Duration::days(aging_days);
+        let tasks = sqlx::query_as::<_, Task>(
+            r#"
+            SELECT id, title, description, status, parent_id, due_date, completed_at, 
+                   created_at, updated_at, progress, notification_type, notification_days_before, 
+                   notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
+            FROM tasks
+            WHERE status = 'inbox'
+              AND archived_at IS NULL
+              AND deleted_at IS NULL
+              AND datetime(created_at) <= datetime(?1)
+              AND NOT EXISTS (
+                  SELECT 1 FROM notification_logs l
+                  WHERE l.task_id = tasks.id
+                    AND l.notification_type = 'inbox_aging'
+                    AND datetime(l.fired_at) >= datetime(?2)
+              )
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(threshold.to_rfc3339())
+        .bind(day_start.to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        Ok(tasks
+            .into_iter()
+            .map(|task| TaskNotification {
+                task_id: task.id,
+                title: task.title,
+                level: 1,
+                days_until_due: None,
+                notification_type: "inbox_aging".to_string(),
+                notification_key: Some(notification_key(timezone.to_local(reminder_time))),
+            })
+            .collect())
+    }
+
+    /// 期間内の通知ログをCSV形式で出力
+    pub async fn export_logs_csv(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<String, AppError> {
+        let logs = sqlx::query_as::<_, NotificationLog>(
+            r#"
+            SELECT id, task_id, fired_at, notification_type, level, success, error_message, message
+            FROM notification_logs
+            WHERE datetime(fired_at) >= datetime(?1) AND datetime(fired_at) <= datetime(?2)
+            ORDER BY datetime(fired_at) ASC
+            "#,
+        )
+        .bind(from.to_rfc3339())
+        .bind(to.to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        let mut csv = String::from("id,task_id,fired_at,notification_type,level,success,error_message,message\n");
+        for log in &logs {
+            let fields = [
+                log.id.clone(),
+                log.task_id.clone(),
+                log.fired_at.clone(),
+                log.notification_type.clone(),
+                log.level.to_string(),
+                log.success.to_string(),
+                log.error_message.clone().unwrap_or_default(),
+                log.message.clone().unwrap_or_default(),
+            ];
+            let line: Vec<String> = fields.iter().map(|f| csv_escape(f)).collect();
+            csv.push_str(&line.join(","));
+            csv.push('\n');
+        }
+        
+        Ok(csv)
+    }
+}
+
+/// 通知の見出しと本文（トーストに表示し、本文は通知ログにも残す）
+pub fn notification_text(notification: &TaskNotification) -> (String, String) {
+    let task = &notification.title;
+    match notification.notification_type.as_str() {
+        "due_date_based" => {
+            let days = notification.days_until_due.unwrap_or(0);
+            let heading = match days {
+                0 => "【期限当日】",
+                1 => "【期限明日】",
+                d if d <= 3 => "【期限間近】",
+                _ => "【期限通知】",
+            };
+            let body = match days {
+                d if d < 0 => format!("「{}」の期限を{}日過ぎています", task, -d),
+                0 => format!("「{}」の期限は今日です", task),
+                1 => format!("「{}」の期限は明日です", task),
+                d => format!("「{}」の期限まであと{}日です", task, d),
+            };
+            (format!("📅 {}", heading), body)
+        }
+        "recurring" => ("🔔 定期リマインド".to_string(), format!("「{}」のリマインドです", task)),
+        "inbox_aging" => ("📥 未整理のタスク".to_string(), format!("「{}」が受信箱で整理を待っています", task)),
+        _ => ("📋 タスク通知".to_string(), task.clone()),
+    }
+}
+
+/// CSVフィールドのエスケープ（区切り文字・引用符・改行を含む場合は引用符で囲む）
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn self_test_step(name: &str, error: Option<String>) -> NotificationSelfTestStep {
+    NotificationSelfTestStep {
+        name: name.to_string(),
+        success: error.is_none(),
+        detail: error,
+    }
 }
 
 impl Default for NotificationService {
@@ -269,6 +1777,24 @@ impl Default for NotificationService {
     }
 }
 
+/// 通知時刻が未設定の期日ベース通知を出す時刻
+fn default_notification_time(profile: Option<&NotificationProfile>) -> &str {
+    profile
+        .and_then(|p| p.default_notification_time.as_deref())
+        .unwrap_or(DEFAULT_NOTIFICATION_TIME)
+}
+
+/// 重複発火の判定に使うキー（発火枠のローカル日時を分単位で表す）
+pub fn notification_key(local: NaiveDateTime) -> String {
+    local.format("%Y-%m-%d %H:%M").to_string()
+}
+
+/// 前回ブラウザアクションを開いた日時から、今回は開かずに済ませるかを判定
+pub fn is_browser_action_throttled(last_acted_at: Option<DateTime<Utc>>, now: DateTime<Utc>, min_interval_minutes: i64) -> bool {
+    min_interval_minutes > 0
+        && last_acted_at.is_some_and(|acted_at| now - acted_at < Duration::minutes(min_interval_minutes))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -285,6 +1811,23 @@ mod tests {
         assert!(!service.should_execute_browser_actions(None)); // None
     }
 
+    #[test]
+    fn test_browser_action_throttle_decision() {
+        let now = DateTime::parse_from_rfc3339("2025-06-10T09:00:00Z").unwrap().with_timezone(&Utc);
+        let minutes_ago = |minutes| Some(now - Duration::minutes(minutes));
+
+        // まだ開いたことがなければ開く
+        assert!(!is_browser_action_throttled(None, now, 60));
+        // 最短間隔内は開かない
+        assert!(is_browser_action_throttled(minutes_ago(10), now, 60));
+        assert!(is_browser_action_throttled(minutes_ago(59), now, 60));
+        // 最短間隔が経てば開く
+        assert!(!is_browser_action_throttled(minutes_ago(60), now, 60));
+        assert!(!is_browser_action_throttled(minutes_ago(120), now, 60));
+        // 0なら制限しない
+        assert!(!is_browser_action_throttled(minutes_ago(1), now, 0));
+    }
+
     #[tokio::test]
     async fn test_browser_action_settings_parsing() {
         let db = Database::new_placeholder();
@@ -316,4 +1859,4 @@ mod tests {
         let result = service.parse_browser_action_settings(invalid_json);
         assert!(result.is_err());
     }
-}
\ No newline at end of file
+}
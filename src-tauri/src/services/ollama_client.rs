@@ -1,5 +1,7 @@
+use futures::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::ops::ControlFlow;
 use std::time::Duration;
 use thiserror::Error;
 
@@ -19,6 +21,9 @@ pub enum OllamaError {
     
     #[error("Timeout after {0} seconds")]
     Timeout(u64),
+    
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
 }
 
 #[derive(Debug, Clone)]
@@ -27,6 +32,68 @@ pub struct OllamaClient {
     client: Client,
     default_model: String,
     pub timeout_seconds: u64,
+    pub retry_policy: RetryPolicy,
+    embedding_model: String,
+}
+
+/// Model used for embeddings unless configured otherwise
+pub const DEFAULT_EMBEDDING_MODEL: &str = "nomic-embed-text";
+
+/// How generation requests are retried when the server is temporarily unreachable
+///
+/// Only connection errors and timeouts are retried (e.g. right after Ollama starts
+/// or while a model is loading); HTTP error responses are returned immediately.
+/// The delay doubles after each attempt.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay_ms: 500,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the given retry (1-based)
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        Duration::from_millis(self.initial_delay_ms.saturating_mul(1u64 << (retry - 1).min(16)))
+    }
+    
+    /// Send the request built by `build`, rebuilding and resending it after connection
+    /// errors and timeouts. Every attempt is logged with `label`.
+    pub async fn send<F>(&self, label: &str, build: F) -> Result<reqwest::Response, reqwest::Error>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        let max_attempts = self.max_retries + 1;
+        let mut attempt = 1;
+        loop {
+            log::info!("LLM request attempt {}/{}: {}", attempt, max_attempts, label);
+            
+            match build().send().await {
+                Ok(response) => return Ok(response),
+                Err(e) if (e.is_connect() || e.is_timeout()) && attempt < max_attempts => {
+                    let delay = self.delay_for(attempt);
+                    log::warn!(
+                        "LLM request attempt {}/{} failed: {}. Retrying in {}ms",
+                        attempt, max_attempts, e, delay.as_millis()
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    log::error!("LLM request attempt {}/{} failed: {}", attempt, max_attempts, e);
+                    return Err(e);
+                }
+            }
+        }
+    }
 }
 
 #[derive(Serialize, Debug)]
@@ -40,7 +107,7 @@ pub struct GenerateRequest {
     pub format: Option<String>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct GenerateOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
@@ -72,6 +139,17 @@ pub struct GenerateResponse {
     pub eval_duration: Option<u64>,
 }
 
+#[derive(Serialize, Debug)]
+pub struct EmbeddingsRequest {
+    pub model: String,
+    pub prompt: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EmbeddingsResponse {
+    pub embedding: Vec<f32>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ModelInfo {
     pub name: String,
@@ -84,6 +162,14 @@ pub struct ListModelsResponse {
     pub models: Vec<ModelInfo>,
 }
 
+/// Unparsed `/api/tags` response together with the server it came from
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTagsResponse {
+    pub base_url: String,
+    pub response: serde_json::Value,
+}
+
 impl Default for OllamaClient {
     fn default() -> Self {
         Self::new(
@@ -106,14 +192,39 @@ impl OllamaClient {
             client,
             default_model,
             timeout_seconds,
+            retry_policy: RetryPolicy::default(),
+            embedding_model: DEFAULT_EMBEDDING_MODEL.to_string(),
         }
     }
     
+    /// Use a different model for `embeddings`
+    pub fn with_embedding_model(mut self, embedding_model: impl Into<String>) -> Self {
+        self.embedding_model = embedding_model.into();
+        self
+    }
+    
+    /// Use a different retry policy for generation requests
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+    
+    /// POST a generation request, retrying connection errors and timeouts with exponential backoff
+    async fn post_with_retry(&self, url: &str, request: &GenerateRequest) -> Result<reqwest::Response, OllamaError> {
+        let label = format!("{} (model: {})", url, request.model);
+        Ok(self.retry_policy.send(&label, || self.client.post(url).json(request)).await?)
+    }
+    
     /// Get current default model
     pub fn get_model(&self) -> &String {
         &self.default_model
     }
     
+    /// Get the model used for embeddings
+    pub fn get_embedding_model(&self) -> &str {
+        &self.embedding_model
+    }
+    
     /// Test connection to Ollama server
     pub async fn test_connection(&self) -> Result<bool, OllamaError> {
         let url = format!("{}/api/tags", self.base_url);
@@ -160,6 +271,22 @@ impl OllamaClient {
         Ok(models_response.models)
     }
     
+    /// Fetch `/api/tags` without mapping it onto `ModelInfo`
+    ///
+    /// Useful for diagnosing why `list_models` fails to parse a server's response.
+    pub async fn raw_tags(&self) -> Result<serde_json::Value, OllamaError> {
+        let url = format!("{}/api/tags", self.base_url);
+        
+        let response = self.client.get(&url).send().await?;
+        
+        if !response.status().is_success() {
+            return Err(OllamaError::ServerNotAvailable(self.base_url.clone()));
+        }
+        
+        let body = response.text().await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+    
     /// Generate text completion
     pub async fn generate(
         &self,
@@ -186,11 +313,7 @@ impl OllamaClient {
             format: None,
         };
         
-        let response = self.client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await?;
+        let response = self.post_with_retry(&url, &request).await?;
         
         if !response.status().is_success() {
             if response.status().as_u16() == 404 {
@@ -203,6 +326,112 @@ impl OllamaClient {
         Ok(generate_response)
     }
     
+    /// Generate text completion, receiving the response chunk by chunk
+    ///
+    /// Ollama streams newline-delimited JSON objects. `on_chunk` is called with the
+    /// text of each one as it arrives; returning `ControlFlow::Break` stops reading
+    /// and drops the connection. The returned response carries the accumulated text
+    /// and the statistics of the final chunk, with `done == false` if it was stopped early.
+    pub async fn generate_stream<F>(
+        &self,
+        prompt: &str,
+        options: Option<GenerateOptions>,
+        mut on_chunk: F,
+    ) -> Result<GenerateResponse, OllamaError>
+    where
+        F: FnMut(&str) -> ControlFlow<()>,
+    {
+        let url = format!("{}/api/generate", self.base_url);
+        
+        let request = GenerateRequest {
+            model: self.default_model.clone(),
+            prompt: prompt.to_string(),
+            stream: true,
+            options,
+            format: None,
+        };
+        
+        let response = self.post_with_retry(&url, &request).await?;
+        
+        if !response.status().is_success() {
+            if response.status().as_u16() == 404 {
+                return Err(OllamaError::ModelNotFound(self.default_model.clone()));
+            }
+            return Err(OllamaError::ServerNotAvailable(self.base_url.clone()));
+        }
+        
+        let mut text = String::new();
+        let mut thinking = String::new();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut body = response.bytes_stream();
+        
+        while let Some(bytes) = body.next().await {
+            buffer.extend_from_slice(&bytes?);
+            
+            // Chunks may split a JSON line, so only parse complete lines
+            while let Some(newline) = buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=newline).collect();
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                
+                let mut chunk: GenerateResponse = serde_json::from_slice(&line)?;
+                text.push_str(&chunk.response);
+                if let Some(part) = &chunk.thinking {
+                    thinking.push_str(part);
+                }
+                
+                let flow = on_chunk(&chunk.response);
+                if chunk.done || flow.is_break() {
+                    chunk.response = text;
+                    chunk.thinking = (!thinking.is_empty()).then_some(thinking);
+                    return Ok(chunk);
+                }
+            }
+        }
+        
+        // Connection closed without a final chunk
+        Ok(GenerateResponse {
+            response: text,
+            done: false,
+            thinking: (!thinking.is_empty()).then_some(thinking),
+            context: None,
+            total_duration: None,
+            load_duration: None,
+            prompt_eval_count: None,
+            eval_count: None,
+            eval_duration: None,
+        })
+    }
+    
+    /// Embedding vector of `text` computed by the embedding model (`/api/embeddings`)
+    pub async fn embeddings(&self, text: &str) -> Result<Vec<f32>, OllamaError> {
+        let url = format!("{}/api/embeddings", self.base_url);
+        
+        let request = EmbeddingsRequest {
+            model: self.embedding_model.clone(),
+            prompt: text.to_string(),
+        };
+        
+        let label = format!("{} (model: {})", url, request.model);
+        let response = self.retry_policy.send(&label, || self.client.post(&url).json(&request)).await?;
+        
+        if !response.status().is_success() {
+            if response.status().as_u16() == 404 {
+                return Err(OllamaError::ModelNotFound(self.embedding_model.clone()));
+            }
+            return Err(OllamaError::ServerNotAvailable(self.base_url.clone()));
+        }
+        
+        let embeddings_response: EmbeddingsResponse = response.json().await?;
+        Ok(embeddings_response.embedding)
+    }
+    
+    /// Elapsed time of the whole request in milliseconds (Ollama reports nanoseconds)
+    pub fn duration_ms(response: &GenerateResponse) -> Option<i64> {
+        response.total_duration.map(|ns| (ns / 1_000_000) as i64)
+    }
+    
     /// Get actual response content (either response or thinking field)
     pub fn get_response_content(response: &GenerateResponse) -> String {
         if !response.response.is_empty() {
@@ -230,6 +459,23 @@ impl OllamaClient {
         prompt: &str,
         options: Option<GenerateOptions>,
     ) -> Result<serde_json::Value, OllamaError> {
+        let generate_response = self.generate_json_response(prompt, options).await?;
+        Self::parse_json_content(&generate_response)
+    }
+    
+    /// Parse the text of a `format: "json"` response
+    pub fn parse_json_content(response: &GenerateResponse) -> Result<serde_json::Value, OllamaError> {
+        let json_value: serde_json::Value = serde_json::from_str(&response.response)?;
+        log::info!("JSON パース成功");
+        Ok(json_value)
+    }
+    
+    /// Generate a `format: "json"` response without parsing its text
+    pub async fn generate_json_response(
+        &self,
+        prompt: &str,
+        options: Option<GenerateOptions>,
+    ) -> Result<GenerateResponse, OllamaError> {
         let url = format!("{}/api/generate", self.base_url);
         log::info!("JSON生成リクエスト URL: {}, モデル: {}", url, self.default_model);
         
@@ -243,11 +489,7 @@ impl OllamaClient {
         };
         
         log::info!("リクエスト送信中...");
-        let response = self.client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await?;
+        let response = self.post_with_retry(&url, &request).await?;
         
         let status = response.status();
         log::info!("レスポンスステータス: {}", status);
@@ -264,9 +506,7 @@ impl OllamaClient {
         log::info!("レスポンス内容（最初の200文字）: {}", 
                   &generate_response.response.chars().take(200).collect::<String>());
         
-        let json_value: serde_json::Value = serde_json::from_str(&generate_response.response)?;
-        log::info!("JSON パース成功");
-        Ok(json_value)
+        Ok(generate_response)
     }
 }
 
@@ -280,6 +520,42 @@ mod tests {
         assert_eq!(client.base_url, "http://localhost:11434");
         assert_eq!(client.default_model, "llama3:latest");
         assert_eq!(client.timeout_seconds, 30);
+        assert_eq!(client.retry_policy, RetryPolicy { max_retries: 3, initial_delay_ms: 500 });
+    }
+    
+    #[test]
+    fn test_retry_delay_doubles() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay_for(1), Duration::from_millis(500));
+        assert_eq!(policy.delay_for(2), Duration::from_millis(1000));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(2000));
+    }
+    
+    #[tokio::test]
+    async fn test_connection_errors_are_retried() {
+        // Nothing listens on port 1, so every attempt is refused
+        let client = OllamaClient::new("http://127.0.0.1:1".to_string(), "gemma3:12b".to_string(), 5)
+            .with_retry_policy(RetryPolicy { max_retries: 2, initial_delay_ms: 20 });
+        
+        let started = std::time::Instant::now();
+        let result = client.generate("hi", None).await;
+        assert!(matches!(result, Err(OllamaError::RequestError(ref e)) if e.is_connect()));
+        // 20ms + 40ms of backoff between the three attempts
+        assert!(started.elapsed() >= Duration::from_millis(60));
+    }
+    
+    #[tokio::test]
+    async fn test_client_errors_are_not_retried() {
+        let generate = mockito::mock("POST", "/api/generate")
+            .match_body(mockito::Matcher::PartialJsonString(r#"{"model":"missing-model"}"#.to_string()))
+            .with_status(404)
+            .expect(1)
+            .create();
+        
+        let client = OllamaClient::new(mockito::server_url(), "missing-model".to_string(), 5);
+        let result = client.generate("hi", None).await;
+        assert!(matches!(result, Err(OllamaError::ModelNotFound(_))));
+        generate.assert();
     }
     
     #[tokio::test]
@@ -293,4 +569,81 @@ mod tests {
         assert_eq!(client.default_model, "mistral:latest");
         assert_eq!(client.timeout_seconds, 60);
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_raw_tags_returns_response_verbatim() {
+        // Includes fields that ModelInfo doesn't know about
+        let body = r#"{"models":[{"name":"gemma3:12b","model":"gemma3:12b","modified_at":"2025-05-01T10:00:00+09:00","size":8149190253,"digest":"f4031aab","details":{"family":"gemma3","parameter_size":"12.2B","quantization_level":"Q4_K_M"}}]}"#;
+        let _m = mockito::mock("GET", "/api/tags")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+        
+        let client = OllamaClient::new(mockito::server_url(), "gemma3:12b".to_string(), 5);
+        let raw = client.raw_tags().await.unwrap();
+        
+        let expected: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(raw, expected);
+        assert_eq!(raw["models"][0]["details"]["parameter_size"], "12.2B");
+    }
+    
+    #[tokio::test]
+    async fn test_embeddings_use_embedding_model() {
+        let _m = mockito::mock("POST", "/api/embeddings")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"model":"mxbai-embed-large","prompt":"牛乳を買う"}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"embedding":[0.5,-0.25,1.0]}"#)
+            .create();
+        
+        let client = OllamaClient::new(mockito::server_url(), "gemma3:12b".to_string(), 5)
+            .with_embedding_model("mxbai-embed-large");
+        assert_eq!(client.embeddings("牛乳を買う").await.unwrap(), vec![0.5, -0.25, 1.0]);
+    }
+    
+    #[tokio::test]
+    async fn test_generate_stream_delivers_chunks_in_order() {
+        let body = concat!(
+            r#"{"model":"gemma3:12b","response":"こんに","done":false}"#, "\n",
+            r#"{"model":"gemma3:12b","response":"ちは","done":false}"#, "\n",
+            r#"{"model":"gemma3:12b","response":"","done":true,"eval_count":3}"#, "\n",
+        );
+        let _m = mockito::mock("POST", "/api/generate")
+            .match_body(mockito::Matcher::PartialJsonString(r#"{"stream":true}"#.to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/x-ndjson")
+            .with_body(body)
+            .create();
+        
+        let client = OllamaClient::new(mockito::server_url(), "gemma3:12b".to_string(), 5);
+        let mut chunks = Vec::new();
+        let response = client
+            .generate_stream("挨拶して", None, |chunk| {
+                chunks.push(chunk.to_string());
+                ControlFlow::Continue(())
+            })
+            .await
+            .unwrap();
+        
+        assert_eq!(chunks, vec!["こんに", "ちは", ""]);
+        assert_eq!(response.response, "こんにちは");
+        assert!(response.done);
+        assert_eq!(response.eval_count, Some(3));
+        
+        // Stopping early keeps what has been received so far
+        let mut received = 0;
+        let stopped = client
+            .generate_stream("挨拶して", None, |_| {
+                received += 1;
+                ControlFlow::Break(())
+            })
+            .await
+            .unwrap();
+        assert_eq!(received, 1);
+        assert_eq!(stopped.response, "こんに");
+        assert!(!stopped.done);
+    }
+}
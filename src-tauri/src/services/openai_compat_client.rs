@@ -0,0 +1,456 @@
+use crate::services::llm_backend::LlmBackend;
+use crate::services::ollama_client::{GenerateOptions, GenerateResponse, ModelInfo, OllamaError, RetryPolicy, DEFAULT_EMBEDDING_MODEL};
+use async_trait::async_trait;
+use futures::StreamExt;
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Client for servers exposing the OpenAI chat completions API (LM Studio, vLLM, ...)
+///
+/// `base_url` includes the API prefix, e.g. `http://localhost:1234/v1`. Prompts are
+/// sent as a single user message and responses are mapped onto `GenerateResponse`.
+#[derive(Debug, Clone)]
+pub struct OpenAiCompatClient {
+    pub base_url: String,
+    client: Client,
+    default_model: String,
+    api_key: Option<String>,
+    pub timeout_seconds: u64,
+    pub retry_policy: RetryPolicy,
+    embedding_model: String,
+}
+
+#[derive(Serialize, Debug)]
+struct ChatCompletionRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage<'a>>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Debug)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChatCompletionResponse {
+    choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChatChoice {
+    #[serde(default)]
+    message: Option<ChatChoiceMessage>,
+    #[serde(default)]
+    delta: Option<ChatChoiceMessage>,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChatChoiceMessage {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChatUsage {
+    #[serde(default)]
+    prompt_tokens: Option<i32>,
+    #[serde(default)]
+    completion_tokens: Option<i32>,
+}
+
+#[derive(Serialize, Debug)]
+struct EmbeddingsRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+struct EmbeddingsResponse {
+    data: Vec<EmbeddingEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+struct EmbeddingEntry {
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ListModelsResponse {
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ModelEntry {
+    id: String,
+    #[serde(default)]
+    created: Option<i64>,
+}
+
+impl OpenAiCompatClient {
+    pub fn new(base_url: String, default_model: String, timeout_seconds: u64, api_key: Option<String>) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(timeout_seconds))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
+            default_model,
+            api_key: api_key.filter(|key| !key.trim().is_empty()),
+            timeout_seconds,
+            retry_policy: RetryPolicy::default(),
+            embedding_model: DEFAULT_EMBEDDING_MODEL.to_string(),
+        }
+    }
+
+    /// Use a different retry policy for generation requests
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Use a different model for `embeddings`
+    pub fn with_embedding_model(mut self, embedding_model: impl Into<String>) -> Self {
+        self.embedding_model = embedding_model.into();
+        self
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+
+    fn completion_request<'a>(
+        model: &'a str,
+        prompt: &'a str,
+        options: Option<GenerateOptions>,
+        stream: bool,
+        json: bool,
+    ) -> ChatCompletionRequest<'a> {
+        let options = options.as_ref();
+        ChatCompletionRequest {
+            model,
+            messages: vec![ChatMessage { role: "user", content: prompt }],
+            stream,
+            temperature: options.and_then(|o| o.temperature),
+            max_tokens: options.and_then(|o| o.num_predict),
+            top_p: options.and_then(|o| o.top_p),
+            response_format: json.then(|| serde_json::json!({ "type": "json_object" })),
+        }
+    }
+
+    async fn send_completion(&self, request: &ChatCompletionRequest<'_>) -> Result<reqwest::Response, OllamaError> {
+        let url = format!("{}/chat/completions", self.base_url);
+        let label = format!("{} (model: {})", url, request.model);
+        let response = self
+            .retry_policy
+            .send(&label, || self.authorized(self.client.post(&url)).json(request))
+            .await?;
+
+        if !response.status().is_success() {
+            if response.status().as_u16() == 404 {
+                return Err(OllamaError::ModelNotFound(request.model.to_string()));
+            }
+            log::error!("OpenAI-compatible request failed with status {}", response.status());
+            return Err(OllamaError::ServerNotAvailable(self.base_url.clone()));
+        }
+        Ok(response)
+    }
+
+    async fn complete(&self, request: &ChatCompletionRequest<'_>) -> Result<GenerateResponse, OllamaError> {
+        let started = Instant::now();
+        let response = self.send_completion(request).await?;
+        let completion: ChatCompletionResponse = serde_json::from_str(&response.text().await?)?;
+
+        let content = completion
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message)
+            .and_then(|message| message.content)
+            .unwrap_or_default();
+        let usage = completion.usage;
+
+        Ok(GenerateResponse {
+            response: content,
+            done: true,
+            thinking: None,
+            context: None,
+            total_duration: Some(started.elapsed().as_nanos() as u64),
+            load_duration: None,
+            prompt_eval_count: usage.as_ref().and_then(|u| u.prompt_tokens),
+            eval_count: usage.as_ref().and_then(|u| u.completion_tokens),
+            eval_duration: None,
+        })
+    }
+}
+
+#[async_trait]
+impl LlmBackend for OpenAiCompatClient {
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn model(&self) -> &str {
+        &self.default_model
+    }
+
+    fn with_model(&self, model: &str) -> Arc<dyn LlmBackend> {
+        Arc::new(Self {
+            default_model: model.to_string(),
+            ..self.clone()
+        })
+    }
+
+    fn embedding_model(&self) -> &str {
+        &self.embedding_model
+    }
+
+    async fn test_connection(&self) -> Result<bool, OllamaError> {
+        let url = format!("{}/models", self.base_url);
+        match self.authorized(self.client.get(&url)).send().await {
+            Ok(response) if response.status().is_success() => Ok(true),
+            Ok(response) => {
+                log::error!("OpenAI-compatible server returned status {}", response.status());
+                Err(OllamaError::ServerNotAvailable(self.base_url.clone()))
+            }
+            Err(e) if e.is_timeout() => Err(OllamaError::Timeout(self.timeout_seconds)),
+            Err(e) if e.is_connect() => Err(OllamaError::ServerNotAvailable(self.base_url.clone())),
+            Err(e) => Err(OllamaError::RequestError(e)),
+        }
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, OllamaError> {
+        let url = format!("{}/models", self.base_url);
+        let response = self.authorized(self.client.get(&url)).send().await?;
+
+        if !response.status().is_success() {
+            return Err(OllamaError::ServerNotAvailable(self.base_url.clone()));
+        }
+
+        let models: ListModelsResponse = response.json().await?;
+        Ok(models
+            .data
+            .into_iter()
+            .map(|model| ModelInfo {
+                modified_at: model
+                    .created
+                    .and_then(|created| chrono::DateTime::from_timestamp(created, 0))
+                    .map(|created| created.to_rfc3339())
+                    .unwrap_or_default(),
+                name: model.id,
+                size: 0,
+            })
+            .collect())
+    }
+
+    async fn embeddings(&self, text: &str) -> Result<Vec<f32>, OllamaError> {
+        let url = format!("{}/embeddings", self.base_url);
+        let request = EmbeddingsRequest { model: &self.embedding_model, input: text };
+        let label = format!("{} (model: {})", url, request.model);
+        let response = self
+            .retry_policy
+            .send(&label, || self.authorized(self.client.post(&url)).json(&request))
+            .await?;
+
+        if !response.status().is_success() {
+            if response.status().as_u16() == 404 {
+                return Err(OllamaError::ModelNotFound(self.embedding_model.clone()));
+            }
+            return Err(OllamaError::ServerNotAvailable(self.base_url.clone()));
+        }
+
+        let embeddings: EmbeddingsResponse = response.json().await?;
+        embeddings
+            .data
+            .into_iter()
+            .next()
+            .map(|entry| entry.embedding)
+            .ok_or_else(|| OllamaError::InvalidResponse("Embeddings response contained no data".to_string()))
+    }
+
+    async fn generate_with_model(
+        &self,
+        model: &str,
+        prompt: &str,
+        options: Option<GenerateOptions>,
+    ) -> Result<GenerateResponse, OllamaError> {
+        self.complete(&Self::completion_request(model, prompt, options, false, false)).await
+    }
+
+    async fn generate_json_response(
+        &self,
+        prompt: &str,
+        options: Option<GenerateOptions>,
+    ) -> Result<GenerateResponse, OllamaError> {
+        self.complete(&Self::completion_request(&self.default_model, prompt, options, false, true)).await
+    }
+
+    /// Reads the server-sent events of a `stream: true` completion until `data: [DONE]`
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        options: Option<GenerateOptions>,
+        on_chunk: &mut (dyn for<'c> FnMut(&'c str) -> ControlFlow<()> + Send),
+    ) -> Result<GenerateResponse, OllamaError> {
+        let started = Instant::now();
+        let request = Self::completion_request(&self.default_model, prompt, options, true, false);
+        let response = self.send_completion(&request).await?;
+
+        let mut text = String::new();
+        let mut done = false;
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut body = response.bytes_stream();
+
+        'read: while let Some(bytes) = body.next().await {
+            buffer.extend_from_slice(&bytes?);
+
+            while let Some(newline) = buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=newline).collect();
+                let line = String::from_utf8_lossy(&line);
+                let Some(data) = line.trim().strip_prefix("data:").map(str::trim) else {
+                    continue;
+                };
+                if data == "[DONE]" {
+                    done = true;
+                    break 'read;
+                }
+
+                let chunk: ChatCompletionResponse = serde_json::from_str(data)?;
+                let Some(choice) = chunk.choices.into_iter().next() else {
+                    continue;
+                };
+                let content = choice.delta.and_then(|delta| delta.content).unwrap_or_default();
+                text.push_str(&content);
+                if choice.finish_reason.is_some() {
+                    done = true;
+                }
+
+                if on_chunk(&content).is_break() {
+                    break 'read;
+                }
+            }
+        }
+
+        Ok(GenerateResponse {
+            response: text,
+            done,
+            thinking: None,
+            context: None,
+            total_duration: Some(started.elapsed().as_nanos() as u64),
+            load_duration: None,
+            prompt_eval_count: None,
+            eval_count: None,
+            eval_duration: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_chat_completion_mapped_to_generate_response() {
+        let completion = mockito::mock("POST", "/v1/chat/completions")
+            .match_header("authorization", "Bearer sk-local")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "model": "compat-model",
+                "messages": [{ "role": "user", "content": "要約して" }],
+                "stream": false,
+                "max_tokens": 100,
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices":[{"index":0,"message":{"role":"assistant","content":"要約です"},"finish_reason":"stop"}],"usage":{"prompt_tokens":9,"completion_tokens":4}}"#)
+            .create();
+
+        let client = OpenAiCompatClient::new(
+            format!("{}/v1/", mockito::server_url()),
+            "compat-model".to_string(),
+            5,
+            Some("sk-local".to_string()),
+        );
+        let options = GenerateOptions { temperature: None, num_predict: Some(100), top_k: None, top_p: None };
+        let response = client.generate("要約して", Some(options)).await.unwrap();
+
+        assert_eq!(response.response, "要約です");
+        assert!(response.done);
+        assert_eq!(response.prompt_eval_count, Some(9));
+        assert_eq!(response.eval_count, Some(4));
+        completion.assert();
+    }
+
+    #[tokio::test]
+    async fn test_list_models_and_stream() {
+        let _models = mockito::mock("GET", "/compat/models")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"object":"list","data":[{"id":"qwen2.5-7b-instruct","object":"model","created":1735689600,"owned_by":"organization_owner"}]}"#)
+            .create();
+        let body = concat!(
+            "data: {\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"こん\"}}]}\n\n",
+            "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"にちは\"}}]}\n\n",
+            "data: {\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let _stream = mockito::mock("POST", "/compat/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({ "stream": true })))
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(body)
+            .create();
+
+        let client = OpenAiCompatClient::new(format!("{}/compat", mockito::server_url()), "qwen2.5-7b-instruct".to_string(), 5, None);
+        assert!(client.test_connection().await.unwrap());
+
+        let models = client.list_models().await.unwrap();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].name, "qwen2.5-7b-instruct");
+        assert_eq!(models[0].modified_at, "2025-01-01T00:00:00+00:00");
+
+        let mut chunks = Vec::new();
+        let response = client
+            .generate_stream("挨拶して", None, &mut |chunk: &str| {
+                chunks.push(chunk.to_string());
+                ControlFlow::Continue(())
+            })
+            .await
+            .unwrap();
+        assert_eq!(chunks, vec!["こん", "にちは", ""]);
+        assert_eq!(response.response, "こんにちは");
+        assert!(response.done);
+    }
+
+    #[tokio::test]
+    async fn test_missing_model_is_not_found() {
+        let _m = mockito::mock("POST", "/missing/chat/completions")
+            .with_status(404)
+            .with_body(r#"{"error":{"message":"model not found"}}"#)
+            .create();
+
+        let client = OpenAiCompatClient::new(format!("{}/missing", mockito::server_url()), "nope".to_string(), 5, None);
+        let result = client.generate("hi", None).await;
+        assert!(matches!(result, Err(OllamaError::ModelNotFound(model)) if model == "nope"));
+    }
+}
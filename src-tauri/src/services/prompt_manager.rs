@@ -219,6 +219,42 @@ impl EnhancedPromptManager {
         })
     }
     
+    /// 指定した範囲のコンテキストでプロンプトを生成
+    pub async fn generate_prompt_with_scope(
+        &self,
+        template_id: &str,
+        scope: &[&str],
+    ) -> Result<GeneratedPrompt, PromptError> {
+        if !self.templates.contains_key(template_id) {
+            return Err(PromptError::TemplateNotFound(template_id.to_string()));
+        }
+        
+        let context_data = self.context_service.collect_context_for_scope(scope).await?;
+        let context_map = self.context_data_to_map(context_data);
+        
+        self.generate_prompt_with_variables(template_id, &context_map)
+    }
+    
+    /// 指定した変数でプロンプトを生成（コンテキスト収集は行わない）
+    pub fn generate_prompt_with_variables(
+        &self,
+        template_id: &str,
+        variables: &HashMap<String, String>,
+    ) -> Result<GeneratedPrompt, PromptError> {
+        let template = self.templates.get(template_id)
+            .ok_or(PromptError::TemplateNotFound(template_id.to_string()))?;
+        
+        let (final_prompt, used_context, missing_context) = 
+            self.process_template(template, variables)?;
+            
+        Ok(GeneratedPrompt {
+            template_id: template_id.to_string(),
+            final_prompt,
+            used_context,
+            missing_context,
+        })
+    }
+    
     fn context_data_to_map(&self, context_data: Vec<ContextData>) -> HashMap<String, String> {
         let mut result = HashMap::new();
         for context in context_data {
@@ -375,6 +411,7 @@ mod tests {
                 difficulty INTEGER DEFAULT 1,
                 progress INTEGER DEFAULT 0,
                 notification_settings TEXT,
+                deleted_at TEXT,
                 FOREIGN KEY (parent_id) REFERENCES tasks (id)
             )
         "#)
@@ -0,0 +1,106 @@
+use crate::services::notification_level::{is_within_time_range, parse_time};
+use chrono::{Duration, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+/// 通知を出さない時間帯（毎日、アプリのタイムゾーンのローカル時刻で判定）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietHours {
+    /// 開始時刻（HH:MM形式）
+    pub start: String,
+    /// 終了時刻（HH:MM形式、開始より前なら翌日の時刻として扱う）
+    pub end: String,
+    /// レベル3の通知をスキップせず、時間帯の終了後にまとめて発火する
+    #[serde(default)]
+    pub defer_critical: bool,
+}
+
+impl QuietHours {
+    /// 設定キー（agent_configテーブル）
+    pub const START_KEY: &'static str = "quiet_hours_start";
+    pub const END_KEY: &'static str = "quiet_hours_end";
+    pub const DEFER_CRITICAL_KEY: &'static str = "quiet_hours_defer_critical";
+
+    /// 終了後にまとめて発火する通知のレベル
+    pub const DEFERRED_LEVEL: i32 = 3;
+
+    pub fn validate(&self) -> Result<(), String> {
+        for time in [&self.start, &self.end] {
+            if parse_time(time).is_none() {
+                return Err(format!("Invalid quiet hours time (expected HH:MM): {}", time));
+            }
+        }
+        if self.start == self.end {
+            return Err("Quiet hours start and end must differ".to_string());
+        }
+        Ok(())
+    }
+
+    /// ローカル時刻が通知しない時間帯か（22:00〜07:00のように日付をまたぐ範囲にも対応）
+    pub fn is_quiet(&self, local_time: NaiveTime) -> bool {
+        is_within_time_range(Some(&self.start), Some(&self.end), local_time)
+    }
+
+    /// `local`が時間帯の終了直後（1分以内）なら、終わったばかりの時間帯の開始・終了日時を返す
+    pub fn just_ended_window(&self, local: NaiveDateTime) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let (start, end) = (parse_time(&self.start)?, parse_time(&self.end)?);
+        let window_end = local.date().and_time(end);
+        if local < window_end || local >= window_end + Duration::minutes(1) {
+            return None;
+        }
+
+        let window_start = if start < end {
+            local.date().and_time(start)
+        } else {
+            local.date().pred_opt()?.and_time(start)
+        };
+        Some((window_start, window_end))
+    }
+
+    /// 時間帯中の`local`から見て、その時間帯が終わる日時
+    pub fn window_end_after(&self, local: NaiveDateTime) -> Option<NaiveDateTime> {
+        let end = parse_time(&self.end)?;
+        // 終了時刻を過ぎているなら、日付をまたいだ翌日に終わる
+        let end_date = if local.time() < end { local.date() } else { local.date().succ_opt()? };
+        Some(end_date.and_time(end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn quiet_hours(start: &str, end: &str) -> QuietHours {
+        QuietHours { start: start.to_string(), end: end.to_string(), defer_critical: true }
+    }
+
+    fn at(day: u32, time: &str) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 6, day).unwrap().and_time(parse_time(time).unwrap())
+    }
+
+    #[test]
+    fn test_quiet_hours_across_midnight() {
+        let night = quiet_hours("22:00", "07:00");
+        assert!(night.validate().is_ok());
+        assert!(night.is_quiet(parse_time("22:00").unwrap()));
+        assert!(night.is_quiet(parse_time("00:30").unwrap()));
+        assert!(night.is_quiet(parse_time("06:59").unwrap()));
+        assert!(!night.is_quiet(parse_time("07:00").unwrap()));
+        assert!(!night.is_quiet(parse_time("21:59").unwrap()));
+
+        // 終了直後だけ、前日の開始時刻からの範囲を返す
+        assert_eq!(night.just_ended_window(at(10, "07:00")), Some((at(9, "22:00"), at(10, "07:00"))));
+        assert_eq!(night.just_ended_window(at(10, "07:01")), None);
+        assert_eq!(night.just_ended_window(at(10, "06:59")), None);
+        assert_eq!(night.window_end_after(at(9, "23:00")), Some(at(10, "07:00")));
+        assert_eq!(night.window_end_after(at(10, "02:00")), Some(at(10, "07:00")));
+
+        let lunch = quiet_hours("12:00", "13:00");
+        assert!(!lunch.is_quiet(parse_time("00:30").unwrap()));
+        assert_eq!(lunch.just_ended_window(at(10, "13:00")), Some((at(10, "12:00"), at(10, "13:00"))));
+
+        assert!(quiet_hours("22:00", "22:00").validate().is_err());
+        assert!(quiet_hours("25:00", "07:00").validate().is_err());
+    }
+}
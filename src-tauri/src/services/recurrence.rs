@@ -0,0 +1,168 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::collections::BTreeSet;
+
+/// 繰り返しタスクの次回分を作る規則
+///
+/// 文字列表現（タスクの`recurrence_rule`に保存する形式）:
+/// - `daily`            : 毎日
+/// - `weekly:1,3,5`     : 毎週の指定曜日（0=日曜〜6=土曜）
+/// - `monthly:15`       : 毎月の指定日（その月にない日は月末）
+/// - `monthly:2:2`      : 毎月の第n週の指定曜日（第2火曜日。-1は最終週）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecurrenceRule {
+    Daily,
+    Weekly(BTreeSet<u32>),
+    MonthlyDay(u32),
+    MonthlyOrdinalWeekday { ordinal: i32, weekday: u32 },
+}
+
+impl RecurrenceRule {
+    pub fn parse(rule: &str) -> Result<Self, String> {
+        let rule = rule.trim();
+        let (kind, args) = rule.split_once(':').unwrap_or((rule, ""));
+        let parse_number = |value: &str| {
+            value
+                .trim()
+                .parse::<i32>()
+                .map_err(|_| format!("Invalid number in recurrence rule '{}': {}", rule, value))
+        };
+
+        match (kind, args) {
+            ("daily", "") => Ok(Self::Daily),
+            ("weekly", args) if !args.is_empty() => {
+                let days = args
+                    .split(',')
+                    .map(|day| match parse_number(day)? {
+                        day @ 0..=6 => Ok(day as u32),
+                        day => Err(format!("Weekday must be between 0 (Sunday) and 6 (Saturday): {}", day)),
+                    })
+                    .collect::<Result<BTreeSet<u32>, String>>()?;
+                Ok(Self::Weekly(days))
+            }
+            ("monthly", args) => match args.split_once(':') {
+                None => match parse_number(args)? {
+                    day @ 1..=31 => Ok(Self::MonthlyDay(day as u32)),
+                    day => Err(format!("Day of month must be between 1 and 31: {}", day)),
+                },
+                Some((ordinal, weekday)) => {
+                    let ordinal = parse_number(ordinal)?;
+                    if !matches!(ordinal, 1..=4 | -1) {
+                        return Err(format!("Week ordinal must be 1-4 or -1 (last): {}", ordinal));
+                    }
+                    match parse_number(weekday)? {
+                        weekday @ 0..=6 => Ok(Self::MonthlyOrdinalWeekday { ordinal, weekday: weekday as u32 }),
+                        weekday => Err(format!("Weekday must be between 0 (Sunday) and 6 (Saturday): {}", weekday)),
+                    }
+                }
+            },
+            _ => Err(format!("Invalid recurrence rule: {}", rule)),
+        }
+    }
+
+    /// `after`より後で規則に合う最初の日付
+    pub fn next_after(&self, after: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Self::Daily => after.succ_opt(),
+            Self::Weekly(days) => after
+                .iter_days()
+                .skip(1)
+                .take(7)
+                .find(|date| days.contains(&date.weekday().num_days_from_sunday())),
+            Self::MonthlyDay(_) | Self::MonthlyOrdinalWeekday { .. } => {
+                // 当月から順に、その月の該当日が`after`より後になる月を探す
+                let mut year = after.year();
+                let mut month = after.month();
+                for _ in 0..13 {
+                    if let Some(date) = self.date_in_month(year, month).filter(|date| *date > after) {
+                        return Some(date);
+                    }
+                    (year, month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+                }
+                None
+            }
+        }
+    }
+
+    /// 月ごとの規則のその月の該当日
+    fn date_in_month(&self, year: i32, month: u32) -> Option<NaiveDate> {
+        match *self {
+            Self::MonthlyDay(day) => NaiveDate::from_ymd_opt(year, month, day.min(days_in_month(year, month)?)),
+            Self::MonthlyOrdinalWeekday { ordinal, weekday } => {
+                let weekday = weekday_from_sunday(weekday)?;
+                if ordinal == -1 {
+                    let last = NaiveDate::from_ymd_opt(year, month, days_in_month(year, month)?)?;
+                    let back = (7 + last.weekday().num_days_from_sunday() - weekday.num_days_from_sunday()) % 7;
+                    Some(last - Duration::days(back as i64))
+                } else {
+                    NaiveDate::from_weekday_of_month_opt(year, month, weekday, ordinal as u8)
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    let first_of_next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)?
+    };
+    Some(first_of_next.pred_opt()?.day())
+}
+
+fn weekday_from_sunday(day: u32) -> Option<Weekday> {
+    match day {
+        0 => Some(Weekday::Sun),
+        1..=6 => Weekday::try_from(day as u8 - 1).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_next_after_each_rule() {
+        // 2025-06-13は金曜
+        let weekly = RecurrenceRule::parse("weekly:1,3").unwrap();
+        assert_eq!(weekly.next_after(date(2025, 6, 13)), Some(date(2025, 6, 16)));
+        assert_eq!(weekly.next_after(date(2025, 6, 16)), Some(date(2025, 6, 18)));
+        // 月をまたぐ
+        assert_eq!(weekly.next_after(date(2025, 6, 30)), Some(date(2025, 7, 2)));
+
+        let monthly = RecurrenceRule::parse("monthly:15").unwrap();
+        assert_eq!(monthly.next_after(date(2025, 6, 10)), Some(date(2025, 6, 15)));
+        assert_eq!(monthly.next_after(date(2025, 6, 15)), Some(date(2025, 7, 15)));
+        assert_eq!(monthly.next_after(date(2025, 12, 20)), Some(date(2026, 1, 15)));
+
+        // 31日はその月の末日に丸める
+        let month_end = RecurrenceRule::parse("monthly:31").unwrap();
+        assert_eq!(month_end.next_after(date(2025, 1, 31)), Some(date(2025, 2, 28)));
+        assert_eq!(month_end.next_after(date(2025, 2, 28)), Some(date(2025, 3, 31)));
+        assert_eq!(month_end.next_after(date(2024, 1, 31)), Some(date(2024, 2, 29)));
+
+        // 第2火曜日
+        let second_tuesday = RecurrenceRule::parse("monthly:2:2").unwrap();
+        assert_eq!(second_tuesday.next_after(date(2025, 6, 1)), Some(date(2025, 6, 10)));
+        assert_eq!(second_tuesday.next_after(date(2025, 6, 10)), Some(date(2025, 7, 8)));
+        // 最終金曜日
+        let last_friday = RecurrenceRule::parse("monthly:-1:5").unwrap();
+        assert_eq!(last_friday.next_after(date(2025, 6, 1)), Some(date(2025, 6, 27)));
+        assert_eq!(last_friday.next_after(date(2025, 6, 27)), Some(date(2025, 7, 25)));
+
+        assert_eq!(RecurrenceRule::parse("daily").unwrap().next_after(date(2025, 6, 30)), Some(date(2025, 7, 1)));
+    }
+
+    #[test]
+    fn test_parse_invalid_rules() {
+        for rule in ["", "hourly", "daily:1", "weekly", "weekly:7", "monthly:0", "monthly:32", "monthly:5:1", "monthly:1:9", "monthly:x"] {
+            assert!(RecurrenceRule::parse(rule).is_err(), "{}", rule);
+        }
+    }
+}
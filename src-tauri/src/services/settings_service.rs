@@ -0,0 +1,63 @@
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::{Pool, Sqlite};
+
+/// agent_configテーブルを使ったキー・バリュー形式のアプリ設定
+pub struct SettingsService;
+
+impl SettingsService {
+    /// 設定値を取得
+    pub async fn get(pool: &Pool<Sqlite>, key: &str) -> Result<Option<String>, sqlx::Error> {
+        let value = sqlx::query_scalar::<_, String>(
+            "SELECT value FROM agent_config WHERE key = ?1"
+        )
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(value)
+    }
+
+    /// 設定値を保存（既存の値は上書き）
+    pub async fn set(pool: &Pool<Sqlite>, key: &str, value: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO agent_config (key, value, updated_at) VALUES (?1, ?2, datetime('now'))"
+        )
+        .bind(key)
+        .bind(value)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 設定値を削除
+    pub async fn delete(pool: &Pool<Sqlite>, key: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM agent_config WHERE key = ?1")
+            .bind(key)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// JSON形式の設定値を取得（解析できない値は未設定として扱う）
+    pub async fn get_json<T: DeserializeOwned>(pool: &Pool<Sqlite>, key: &str) -> Result<Option<T>, sqlx::Error> {
+        let Some(raw) = Self::get(pool, key).await? else {
+            return Ok(None);
+        };
+
+        match serde_json::from_str(&raw) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) => {
+                log::warn!("Ignoring malformed setting '{}': {}", key, e);
+                Ok(None)
+            }
+        }
+    }
+
+    /// JSON形式で設定値を保存
+    pub async fn set_json<T: Serialize>(pool: &Pool<Sqlite>, key: &str, value: &T) -> Result<(), sqlx::Error> {
+        let raw = serde_json::to_string(value).unwrap_or_default();
+        Self::set(pool, key, &raw).await
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+/// スタンドアップ用の進捗報告に使うタスク一覧
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusReportData {
+    /// 昨日（ローカル日付）以降に完了したタスク
+    pub completed: Vec<String>,
+    /// 着手中のタスク
+    pub in_progress: Vec<String>,
+    /// 未完了の依存先（blocks / requires）を待っている未完了タスク
+    pub blockers: Vec<String>,
+}
+
+/// 集計したタスクから進捗報告の文面を作成（AIが使えない場合もこのまま使う）
+pub fn build_status_report_text(data: &StatusReportData) -> String {
+    let sections = [
+        ("昨日からの完了", &data.completed),
+        ("進行中", &data.in_progress),
+        ("ブロッカー", &data.blockers),
+    ];
+
+    sections
+        .iter()
+        .map(|(heading, titles)| {
+            let body = if titles.is_empty() {
+                "- なし".to_string()
+            } else {
+                titles.iter().map(|title| format!("- {}", title)).collect::<Vec<_>>().join("\n")
+            };
+            format!("【{}】\n{}", heading, body)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_status_report_text() {
+        let data = StatusReportData {
+            completed: vec!["請求書の送付".to_string(), "週報".to_string()],
+            in_progress: vec!["新機能の設計".to_string()],
+            blockers: Vec::new(),
+        };
+
+        assert_eq!(
+            build_status_report_text(&data),
+            "【昨日からの完了】\n- 請求書の送付\n- 週報\n\n【進行中】\n- 新機能の設計\n\n【ブロッカー】\n- なし"
+        );
+    }
+}
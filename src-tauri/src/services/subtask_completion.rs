@@ -0,0 +1,82 @@
+use crate::models::Task;
+use serde::{Deserialize, Serialize};
+
+/// 親タスクの完了判定・進捗率の計算で子タスクをどう扱うか
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtaskCompletionRules {
+    /// 未完了の子タスクがあれば親タスクを完了にできない
+    #[serde(default)]
+    pub require_children_complete: bool,
+    /// 任意の子タスクを完了判定・進捗率の計算から外す
+    #[serde(default = "default_ignore_optional_children")]
+    pub ignore_optional_children: bool,
+}
+
+fn default_ignore_optional_children() -> bool {
+    true
+}
+
+impl Default for SubtaskCompletionRules {
+    fn default() -> Self {
+        Self {
+            require_children_complete: false,
+            ignore_optional_children: default_ignore_optional_children(),
+        }
+    }
+}
+
+impl SubtaskCompletionRules {
+    /// 設定キー（agent_configテーブル）
+    pub const SETTINGS_KEY: &'static str = "subtask_completion_rules";
+
+    /// 親の完了判定・進捗率に数える子タスク
+    ///
+    /// すべて任意の子タスクの場合は、任意の子タスクをそのまま数える。
+    pub fn counted_children<'a>(&self, children: &'a [Task]) -> Vec<&'a Task> {
+        let required: Vec<&Task> = children
+            .iter()
+            .filter(|child| !(self.ignore_optional_children && child.is_optional))
+            .collect();
+
+        if required.is_empty() {
+            children.iter().collect()
+        } else {
+            required
+        }
+    }
+
+    /// 子タスクの進捗率の平均（完了済みは100%）
+    pub fn progress(&self, children: &[Task]) -> i32 {
+        let counted = self.counted_children(children);
+        if counted.is_empty() {
+            return 0;
+        }
+
+        let total_progress: i32 = counted
+            .iter()
+            .map(|child| {
+                if child.status == "done" {
+                    100
+                } else {
+                    child.progress.unwrap_or(0)
+                }
+            })
+            .sum();
+
+        total_progress / counted.len() as i32
+    }
+
+    /// 親の完了を妨げている未完了の子タスク
+    pub fn blocking_children<'a>(&self, children: &'a [Task]) -> Vec<&'a Task> {
+        if !self.require_children_complete {
+            return Vec::new();
+        }
+
+        children
+            .iter()
+            .filter(|child| child.status != "done")
+            .filter(|child| !(self.ignore_optional_children && child.is_optional))
+            .collect()
+    }
+}
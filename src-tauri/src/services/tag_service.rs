@@ -1,16 +1,20 @@
 use chrono::Utc;
 use sqlx::{Pool, Sqlite};
+use std::collections::{HashMap, HashSet};
 
 use crate::error::AppError;
-use crate::models::tag::{Tag, CreateTagRequest, UpdateTagRequest};
+use crate::models::tag::{Tag, TagTreeNode, CreateTagRequest, UpdateTagRequest};
 
 pub struct TagService;
 
+/// タグ候補として許容する編集距離の上限
+const SUGGEST_MAX_DISTANCE: usize = 2;
+
 impl TagService {
     /// すべてのタグを取得
     pub async fn get_all_tags(pool: &Pool<Sqlite>) -> Result<Vec<Tag>, AppError> {
         let tags = sqlx::query_as::<_, Tag>(
-            "SELECT id, name, color, created_at, updated_at FROM tags ORDER BY created_at ASC"
+            "SELECT id, name, color, created_at, updated_at, parent_id FROM tags ORDER BY created_at ASC"
         )
         .fetch_all(pool)
         .await?;
@@ -21,7 +25,7 @@ impl TagService {
     /// IDでタグを取得
     pub async fn get_tag_by_id(pool: &Pool<Sqlite>, id: &str) -> Result<Tag, AppError> {
         let tag = sqlx::query_as::<_, Tag>(
-            "SELECT id, name, color, created_at, updated_at FROM tags WHERE id = ?"
+            "SELECT id, name, color, created_at, updated_at, parent_id FROM tags WHERE id = ?"
         )
         .bind(id)
         .fetch_optional(pool)
@@ -114,29 +118,171 @@ impl TagService {
         Ok(tag)
     }
 
-    /// タグを削除
+    /// タグを削除（子タグは最上位のタグに戻す）
     pub async fn delete_tag(pool: &Pool<Sqlite>, id: &str) -> Result<(), AppError> {
         // タグが存在するかチェック
         let _ = Self::get_tag_by_id(pool, id).await?;
 
+        let mut tx = pool.begin().await?;
+        sqlx::query("UPDATE tags SET parent_id = NULL, updated_at = ? WHERE parent_id = ?")
+            .bind(Utc::now().to_rfc3339())
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
         // 関連するtask_tagsも自動削除される（CASCADE設定済み）
         let result = sqlx::query("DELETE FROM tags WHERE id = ?")
             .bind(id)
-            .execute(pool)
+            .execute(&mut *tx)
             .await?;
 
         if result.rows_affected() == 0 {
             return Err(AppError::NotFound(format!("Tag with id {} not found", id)));
         }
 
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// タグを子孫のタグごと削除し、削除した件数を返す
+    pub async fn delete_tag_cascade(pool: &Pool<Sqlite>, id: &str) -> Result<usize, AppError> {
+        let tag_ids = Self::get_descendant_tag_ids(pool, id).await?;
+
+        let mut tx = pool.begin().await?;
+        let placeholders = vec!["?"; tag_ids.len()].join(", ");
+        for table_column in ["task_tags WHERE tag_id", "tags WHERE id"] {
+            let sql = format!("DELETE FROM {} IN ({})", table_column, placeholders);
+            let mut query = sqlx::query(&sql);
+            for tag_id in &tag_ids {
+                query = query.bind(tag_id);
+            }
+            query.execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+
+        Ok(tag_ids.len())
+    }
+
+    /// タグの親を変更（Noneなら最上位のタグにする）
+    pub async fn set_tag_parent(pool: &Pool<Sqlite>, id: &str, parent_id: Option<&str>) -> Result<Tag, AppError> {
+        let mut tag = Self::get_tag_by_id(pool, id).await?;
+        if let Some(parent_id) = parent_id {
+            Self::validate_parent(pool, id, parent_id).await?;
+        }
+
+        tag.parent_id = parent_id.map(str::to_string);
+        tag.updated_at = Utc::now().to_rfc3339();
+        sqlx::query("UPDATE tags SET parent_id = ?, updated_at = ? WHERE id = ?")
+            .bind(&tag.parent_id)
+            .bind(&tag.updated_at)
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(tag)
+    }
+
+    /// タグの親を`parent_id`にできるか確認
+    ///
+    /// 親候補から祖先をたどって自タグに行き着く場合（親候補が自タグかその子孫）は循環になるため拒否する。
+    async fn validate_parent(pool: &Pool<Sqlite>, tag_id: &str, parent_id: &str) -> Result<(), AppError> {
+        // 既存データが循環していても無限ループにならないよう、たどったタグを覚えておく
+        let mut visited = HashSet::new();
+        let mut current = Some(parent_id.to_string());
+        while let Some(ancestor_id) = current {
+            if ancestor_id == tag_id {
+                return Err(AppError::InvalidInput(format!(
+                    "Tag {} cannot be moved under itself or its descendant {}",
+                    tag_id, parent_id
+                )));
+            }
+            if !visited.insert(ancestor_id.clone()) {
+                break;
+            }
+            current = sqlx::query_scalar::<_, Option<String>>("SELECT parent_id FROM tags WHERE id = ?")
+                .bind(&ancestor_id)
+                .fetch_optional(pool)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Tag with id {} not found", ancestor_id)))?;
+        }
         Ok(())
     }
 
+    /// 直下の子タグを取得
+    pub async fn get_child_tags(pool: &Pool<Sqlite>, parent_id: &str) -> Result<Vec<Tag>, AppError> {
+        let _ = Self::get_tag_by_id(pool, parent_id).await?;
+
+        let tags = sqlx::query_as::<_, Tag>(
+            "SELECT id, name, color, created_at, updated_at, parent_id FROM tags WHERE parent_id = ? ORDER BY created_at ASC"
+        )
+        .bind(parent_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tags)
+    }
+
+    /// すべてのタグを親子関係の木として取得（最上位のタグが根）
+    pub async fn get_tag_tree(pool: &Pool<Sqlite>) -> Result<Vec<TagTreeNode>, AppError> {
+        let tags = Self::get_all_tags(pool).await?;
+        let ids: HashSet<String> = tags.iter().map(|tag| tag.id.clone()).collect();
+
+        let mut roots = Vec::new();
+        let mut children_by_parent: HashMap<String, Vec<Tag>> = HashMap::new();
+        for tag in tags {
+            // 親が見つからないタグも最上位として扱う
+            match tag.parent_id.clone().filter(|parent_id| ids.contains(parent_id)) {
+                Some(parent_id) => children_by_parent.entry(parent_id).or_default().push(tag),
+                None => roots.push(tag),
+            }
+        }
+
+        Ok(roots
+            .into_iter()
+            .map(|tag| Self::build_tag_tree(tag, &mut children_by_parent))
+            .collect())
+    }
+
+    fn build_tag_tree(tag: Tag, children_by_parent: &mut HashMap<String, Vec<Tag>>) -> TagTreeNode {
+        let children = children_by_parent
+            .remove(&tag.id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| Self::build_tag_tree(child, children_by_parent))
+            .collect();
+        TagTreeNode { tag, children }
+    }
+
+    /// タグ自身とその子孫のタグのID
+    pub async fn get_descendant_tag_ids(pool: &Pool<Sqlite>, id: &str) -> Result<Vec<String>, AppError> {
+        // UNIONで重複を除くため、既存データが循環していても終了する
+        let tag_ids: Vec<String> = sqlx::query_scalar(
+            r#"
+            WITH RECURSIVE subtree(id) AS (
+                SELECT id FROM tags WHERE id = ?1
+                UNION
+                SELECT t.id
+                FROM tags t
+                INNER JOIN subtree s ON t.parent_id = s.id
+            )
+            SELECT id FROM subtree
+            "#,
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await?;
+
+        if tag_ids.is_empty() {
+            return Err(AppError::NotFound(format!("Tag with id {} not found", id)));
+        }
+        Ok(tag_ids)
+    }
+
     /// タスクにタグを追加
     pub async fn add_tag_to_task(pool: &Pool<Sqlite>, task_id: &str, tag_id: &str) -> Result<(), AppError> {
         // タスクとタグが存在するかチェック
         let task_exists = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM tasks WHERE id = ?"
+            "SELECT COUNT(*) FROM tasks WHERE id = ? AND deleted_at IS NULL"
         )
         .bind(task_id)
         .fetch_one(pool)
@@ -194,7 +340,7 @@ impl TagService {
     /// タスクに付与されているタグを取得
     pub async fn get_tags_for_task(pool: &Pool<Sqlite>, task_id: &str) -> Result<Vec<Tag>, AppError> {
         let tags = sqlx::query_as::<_, Tag>(
-            "SELECT t.id, t.name, t.color, t.created_at, t.updated_at 
+            "SELECT t.id, t.name, t.color, t.created_at, t.updated_at, t.parent_id 
              FROM tags t 
              INNER JOIN task_tags tt ON t.id = tt.tag_id 
              WHERE tt.task_id = ? 
@@ -206,4 +352,85 @@ impl TagService {
 
         Ok(tags)
     }
-}
\ No newline at end of file
+
+    /// 複数タスクのタグを1回のクエリでまとめて取得（タスクID → タグ）
+    pub async fn get_tags_for_tasks(pool: &Pool<Sqlite>, task_ids: &[String]) -> Result<HashMap<String, Vec<Tag>>, AppError> {
+        let mut tags_by_task: HashMap<String, Vec<Tag>> = HashMap::new();
+        if task_ids.is_empty() {
+            return Ok(tags_by_task);
+        }
+
+        let placeholders = vec!["?"; task_ids.len()].join(", ");
+        let sql = format!(
+            "SELECT tt.task_id, t.id, t.name, t.color, t.created_at, t.updated_at, t.parent_id 
+             FROM tags t 
+             INNER JOIN task_tags tt ON t.id = tt.tag_id 
+             WHERE tt.task_id IN ({}) 
+             ORDER BY t.created_at ASC",
+            placeholders
+        );
+        let mut query = sqlx::query_as::<_, (String, String, String, String, String, String, Option<String>)>(&sql);
+        for task_id in task_ids {
+            query = query.bind(task_id);
+        }
+
+        for (task_id, id, name, color, created_at, updated_at, parent_id) in query.fetch_all(pool).await? {
+            tags_by_task.entry(task_id).or_default().push(Tag { id, name, color, created_at, updated_at, parent_id });
+        }
+        Ok(tags_by_task)
+    }
+
+    /// 入力文字列に近い既存タグを候補として取得
+    ///
+    /// 前方一致（大文字小文字を区別しない）を優先し、続いて編集距離の近い順に並べる。
+    /// 編集距離はタグ名全体と、入力と同じ長さのタグ名先頭部分の小さい方を採用する。
+    pub async fn suggest_tags(pool: &Pool<Sqlite>, query: &str, limit: usize) -> Result<Vec<Tag>, AppError> {
+        let query = query.trim().to_lowercase();
+        let tags = Self::get_all_tags(pool).await?;
+
+        let mut candidates: Vec<(usize, usize, Tag)> = tags
+            .into_iter()
+            .filter_map(|tag| {
+                let name = tag.name.to_lowercase();
+                if name.starts_with(&query) {
+                    return Some((0, name.chars().count(), tag));
+                }
+
+                let head: String = name.chars().take(query.chars().count()).collect();
+                let distance = levenshtein(&query, &name).min(levenshtein(&query, &head));
+                if distance <= SUGGEST_MAX_DISTANCE && distance < query.chars().count() {
+                    Some((1, distance, tag))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then(a.1.cmp(&b.1))
+                .then_with(|| a.2.name.cmp(&b.2.name))
+        });
+
+        Ok(candidates.into_iter().take(limit).map(|(_, _, tag)| tag).collect())
+    }
+}
+
+/// 文字単位のレーベンシュタイン距離
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+/// タスクのタイトル・説明の最大文字数
+///
+/// 長すぎるタイトルはトレイや通知の表示を崩すため、作成・更新時に制限する。
+/// 文字数はバイト数ではなく文字（char）単位で数える。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskFieldLimits {
+    pub max_title_length: usize,
+    pub max_description_length: usize,
+}
+
+impl Default for TaskFieldLimits {
+    fn default() -> Self {
+        Self {
+            max_title_length: 500,
+            max_description_length: 20_000,
+        }
+    }
+}
+
+impl TaskFieldLimits {
+    /// 設定キー（agent_configテーブル）
+    pub const SETTINGS_KEY: &'static str = "task_field_limits";
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_title_length == 0 || self.max_description_length == 0 {
+            return Err("Maximum lengths must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// タイトルと説明が上限以内か確認
+    pub fn check(&self, title: Option<&str>, description: Option<&str>) -> Result<(), String> {
+        if let Some(title) = title {
+            let length = title.chars().count();
+            if length > self.max_title_length {
+                return Err(format!(
+                    "Title is too long ({} characters, limit is {})",
+                    length, self.max_title_length
+                ));
+            }
+        }
+
+        if let Some(description) = description {
+            let length = description.chars().count();
+            if length > self.max_description_length {
+                return Err(format!(
+                    "Description is too long ({} characters, limit is {})",
+                    length, self.max_description_length
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use crate::models::TaskDependencyRecord;
+use std::collections::{HashMap, VecDeque};
+
+/// 依存関係の種別
+pub const DEPENDENCY_TYPES: [&str; 3] = ["blocks", "requires", "relates_to"];
+
+/// タスク間の依存関係（`AgentService`の`TaskDependency`と同じ種別）
+///
+/// - `blocks`    : `from_task`が終わるまで`to_task`に着手できない
+/// - `requires`  : `from_task`は`to_task`が終わっていることを前提にする
+/// - `relates_to`: 関連のみで、順序には影響しない
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyEdge {
+    pub from_task: String,
+    pub to_task: String,
+    pub dependency_type: String,
+}
+
+impl DependencyEdge {
+    /// (先に行うタスク, 後に行うタスク)。順序に影響しない依存はNone
+    pub fn ordering(&self) -> Option<(&str, &str)> {
+        match self.dependency_type.as_str() {
+            "blocks" => Some((&self.from_task, &self.to_task)),
+            "requires" => Some((&self.to_task, &self.from_task)),
+            _ => None,
+        }
+    }
+}
+
+impl From<TaskDependencyRecord> for DependencyEdge {
+    fn from(dependency: TaskDependencyRecord) -> Self {
+        Self {
+            from_task: dependency.from_task_id,
+            to_task: dependency.to_task_id,
+            dependency_type: dependency.dependency_type,
+        }
+    }
+}
+
+/// 依存関係を満たす順にタスクIDを並べる
+///
+/// 同時に着手できるタスク同士は`task_ids`の順序を保つ。`task_ids`に含まれない
+/// タスクへの依存は無視する。循環がある場合は、循環に関わって並べられなかった
+/// タスクIDを`Err`で返す。
+pub fn topological_order(task_ids: &[String], edges: &[DependencyEdge]) -> Result<Vec<String>, Vec<String>> {
+    let position: HashMap<&str, usize> = task_ids
+        .iter()
+        .enumerate()
+        .map(|(index, id)| (id.as_str(), index))
+        .collect();
+
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); task_ids.len()];
+    let mut in_degree = vec![0usize; task_ids.len()];
+    for (before, after) in edges.iter().filter_map(DependencyEdge::ordering) {
+        if let (Some(&before), Some(&after)) = (position.get(before), position.get(after)) {
+            successors[before].push(after);
+            in_degree[after] += 1;
+        }
+    }
+
+    // 入次数0のタスクを元の順序で取り出す（Kahnのアルゴリズム）
+    let mut ready: VecDeque<usize> = (0..task_ids.len()).filter(|&i| in_degree[i] == 0).collect();
+    let mut order = Vec::with_capacity(task_ids.len());
+    while let Some(index) = ready.pop_front() {
+        order.push(task_ids[index].clone());
+
+        let mut unlocked = Vec::new();
+        for &next in &successors[index] {
+            in_degree[next] -= 1;
+            if in_degree[next] == 0 {
+                unlocked.push(next);
+            }
+        }
+        unlocked.sort_unstable();
+        ready.extend(unlocked);
+    }
+
+    if order.len() == task_ids.len() {
+        Ok(order)
+    } else {
+        Err((0..task_ids.len())
+            .filter(|&i| in_degree[i] > 0)
+            .map(|i| task_ids[i].clone())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn edge(from: &str, to: &str, dependency_type: &str) -> DependencyEdge {
+        DependencyEdge {
+            from_task: from.to_string(),
+            to_task: to.to_string(),
+            dependency_type: dependency_type.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_topological_order_on_dag() {
+        // 設計 → 実装 → テスト → リリース、ドキュメントは設計の後
+        let tasks = ids(&["release", "test", "docs", "implement", "design"]);
+        let edges = vec![
+            edge("design", "implement", "blocks"),
+            edge("test", "implement", "requires"),
+            edge("release", "test", "requires"),
+            edge("design", "docs", "blocks"),
+            edge("docs", "release", "relates_to"),
+        ];
+
+        let order = topological_order(&tasks, &edges).unwrap();
+        assert_eq!(order, ids(&["design", "docs", "implement", "test", "release"]));
+
+        // すべての依存を満たしていること
+        let index = |id: &str| order.iter().position(|t| t == id).unwrap();
+        for (before, after) in edges.iter().filter_map(DependencyEdge::ordering) {
+            assert!(index(before) < index(after), "{} should come before {}", before, after);
+        }
+    }
+
+    #[test]
+    fn test_topological_order_detects_cycle() {
+        let tasks = ids(&["a", "b", "c", "d"]);
+        let edges = vec![
+            edge("a", "b", "blocks"),
+            edge("b", "c", "blocks"),
+            edge("a", "c", "requires"),
+            edge("d", "a", "blocks"),
+        ];
+
+        let cycle = topological_order(&tasks, &edges).unwrap_err();
+        assert_eq!(cycle, ids(&["a", "b", "c"]));
+    }
+}
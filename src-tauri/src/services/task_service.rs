@@ -1,37 +1,107 @@
 use crate::database::Database;
 use crate::error::AppError;
-use crate::models::{CreateTaskRequest, Task, UpdateTaskRequest, Tag, CreateTagRequest, UpdateTagRequest};
-use crate::services::TagService;
-use chrono::Utc;
+use crate::models::{CompleteTaskResult, CompletionCheck, CreateTaskRequest, CreateTaskResult, DueBucket, DueDateTimezoneAudit, MarkdownImportResult, NotificationChannel, NotificationPreset, Task, TaskDependencyRecord, TaskNotificationSettings, TaskStatus, TaskTemplate, TaskTreeNode, TemplateTask, UpdateTaskRequest, Tag, TagTreeNode, CreateTagRequest, UpdateTagRequest};
+use crate::models::browser_action::{BrowserAction, BrowserActionKind, BrowserActionSettings, UnreachableBrowserAction};
+use crate::services::{BrowserActionService, NotificationService, SettingsService, TagService};
+use crate::services::agent_service::SubtaskSuggestion;
+use crate::services::markdown_import::parse_checklist;
+use crate::services::browser_action_service::URL_HEALTH_CONCURRENCY;
+use crate::services::app_timezone::AppTimezone;
+use crate::services::business_days::BusinessDaySettings;
+use crate::services::context_service::{invalidate_task_context_cache, task_data_generation, TemporalContext};
+use crate::services::recurrence::RecurrenceRule;
+use crate::services::subtask_completion::SubtaskCompletionRules;
+use crate::services::task_limits::TaskFieldLimits;
+use crate::services::task_order::{topological_order, DependencyEdge, DEPENDENCY_TYPES};
+use crate::services::task_similarity::{cosine_similarity, decode_embedding, encode_embedding, SimilarTask};
+use crate::services::urgency_score::{task_urgency_score, TaskUrgency, UrgencyScoreCache, UrgencyWeights};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use uuid::Uuid;
 
+/// 通知プリセットの設定キー（agent_configテーブル）
+const NOTIFICATION_PRESETS_KEY: &str = "notification_presets";
+/// ステータス省略時に使う既定ステータスの設定キー
+const DEFAULT_TASK_STATUS_KEY: &str = "default_task_status";
+/// 完了予定日の見積もりに使う完了ペースの集計期間（日）
+const ETA_VELOCITY_WINDOW_DAYS: i64 = 14;
+/// 発火しない通知設定の検出で先読みする日数
+const INERT_NOTIFICATION_LOOKAHEAD_DAYS: i64 = 30;
+/// 期日をまとめてずらせる最大日数
+const MAX_RELATIVE_DUE_DAYS: i64 = 366;
+/// 1ページで取得できる最大タスク数
+const MAX_PAGE_SIZE: i64 = 500;
+/// 親子関係の最大階層数（ルートのタスクを1階層目とする）
+const MAX_TASK_DEPTH: i64 = 10;
+/// ゴミ箱のタスクを自動で完全削除するまでの日数
+const TRASH_RETENTION_DAYS: i64 = 30;
+
 pub struct TaskService {
     db: Database,
+    urgency_cache: UrgencyScoreCache,
 }
 
 impl TaskService {
     pub fn new(db: Database) -> Self {
-        Self { db }
+        Self {
+            db,
+            urgency_cache: UrgencyScoreCache::default(),
+        }
+    }
+    
+    /// 通知設定の妥当性を検証
+    ///
+    /// 曜日が未指定・空の繰り返し通知は設定済みに見えても発火しないので受け付けない。
+    fn validate_notification_settings(settings: &TaskNotificationSettings) -> Result<(), AppError> {
+        if settings.notification_type == "recurring"
+            && settings.days_of_week.as_ref().map_or(true, |days| days.is_empty())
+        {
+            return Err(AppError::InvalidInput(
+                "Recurring notifications require at least one day of week".to_string(),
+            ));
+        }
+        settings.validate().map_err(AppError::Validation)
     }
     
     pub async fn create_task(&self, request: CreateTaskRequest) -> Result<Task, AppError> {
+        Ok(self.create_task_with_warnings(request).await?.task)
+    }
+    
+    /// タスクを作成し、確認を促したい点（過去の期日など）を警告として返す
+    pub async fn create_task_with_warnings(&self, request: CreateTaskRequest) -> Result<CreateTaskResult, AppError> {
+        self.get_field_limits()
+            .await?
+            .check(Some(&request.title), request.description.as_deref())
+            .map_err(AppError::InvalidInput)?;
+        
+        if let Some(parent_id) = &request.parent_id {
+            let mut conn = self.db.pool.acquire().await?;
+            Self::validate_parent(&mut conn, None, parent_id).await?;
+        }
+        
+        let status = match request.status {
+            Some(status) => status,
+            None => self.get_default_status().await?.unwrap_or(TaskStatus::Todo),
+        };
+        
         let now = Utc::now().to_rfc3339();
         let id = Uuid::new_v4().to_string();
         
         // 通知設定のデフォルト値またはリクエストの値を使用
         let notification_settings = request.notification_settings.unwrap_or_default();
+        Self::validate_notification_settings(&notification_settings)?;
         
         let task = Task {
             id: id.clone(),
             title: request.title,
             description: request.description,
-            status: request.status.to_string(),
+            status: status.to_string(),
             // priority field removed as per .kiro/specs/notification-system-redesign
             parent_id: request.parent_id,
             due_date: request.due_date.map(|d| d.to_rfc3339()),
             completed_at: None,
             created_at: now.clone(),
-            updated_at: now,
+            updated_at: now.clone(),
             progress: Some(0),
             // 新通知設定フィールド
             notification_type: Some(notification_settings.notification_type),
@@ -41,22 +111,47 @@ impl TaskService {
                 serde_json::to_string(&days).unwrap_or_default()
             ),
             notification_level: Some(notification_settings.level),
+            notification_until: notification_settings.notification_until,
             // Browser actions
             browser_actions: request.browser_actions.map(|ba| 
                 serde_json::to_string(&ba).unwrap_or_default()
             ),
+            is_pinned: false,
+            status_changed_at: Some(now),
+            is_optional: false,
+            notification_channels: None,
+            recurrence_rule: None,
+            recurrence_end: None,
+            archived_at: None,
+            deleted_at: None,
             // Tag system
             tags: None,
         };
         
+        Self::insert_task(&self.db.pool, &task).await?;
+        // 集計済みのタスクコンテキストを使わないようにする
+        invalidate_task_context_cache();
+        
+        let mut warnings = Vec::new();
+        if let Some(due_date) = request.due_date.filter(|due_date| *due_date < Utc::now()) {
+            warnings.push(format!("Due date {} is in the past", due_date.to_rfc3339()));
+        }
+        
+        Ok(CreateTaskResult { task, warnings })
+    }
+    
+    async fn insert_task<'e, E>(executor: E, task: &Task) -> Result<(), AppError>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+    {
         sqlx::query(
             r#"
             INSERT INTO tasks (
                 id, title, description, status, parent_id, due_date, completed_at, 
                 created_at, updated_at, progress, notification_type, notification_days_before, 
-                notification_time, notification_days_of_week, notification_level, browser_actions
+                notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)
             "#,
         )
         .bind(&task.id)
@@ -74,18 +169,29 @@ impl TaskService {
         .bind(&task.notification_time)
         .bind(&task.notification_days_of_week)
         .bind(task.notification_level)
+        .bind(&task.notification_until)
         .bind(&task.browser_actions)
-        .execute(&self.db.pool)
+        .bind(task.is_pinned)
+        .bind(&task.status_changed_at)
+        .bind(task.is_optional)
+        .bind(&task.notification_channels)
+        .bind(&task.recurrence_rule)
+        .bind(&task.recurrence_end)
+        .bind(&task.archived_at)
+        .bind(&task.deleted_at)
+        .execute(executor)
         .await?;
         
-        Ok(task)
+        Ok(())
     }
     
+    /// アーカイブ済みを除くタスクを取得
     pub async fn get_tasks(&self) -> Result<Vec<Task>, AppError> {
         let mut tasks = sqlx::query_as::<_, Task>(
             r#"
-            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, browser_actions
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
             FROM tasks
+            WHERE archived_at IS NULL AND deleted_at IS NULL
             ORDER BY 
                 CASE status 
                     WHEN 'inbox' THEN 1
@@ -93,6 +199,7 @@ impl TaskService {
                     WHEN 'in_progress' THEN 3
                     WHEN 'done' THEN 4
                 END,
+                is_pinned DESC,
                 CASE notification_level
                     WHEN 3 THEN 1
                     WHEN 2 THEN 2
@@ -113,12 +220,117 @@ impl TaskService {
         Ok(tasks)
     }
     
+    /// 1ページ分のタスクと全タスク数を取得
+    ///
+    /// 対象と並び順は`get_tasks`と同じ。タグはページ内のタスク分を1回のクエリでまとめて取得する。
+    pub async fn get_tasks_paginated(&self, limit: i64, offset: i64) -> Result<(Vec<Task>, i64), AppError> {
+        if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+            return Err(AppError::InvalidInput(format!("Limit must be between 1 and {}: {}", MAX_PAGE_SIZE, limit)));
+        }
+        if offset < 0 {
+            return Err(AppError::InvalidInput(format!("Offset must not be negative: {}", offset)));
+        }
+
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE archived_at IS NULL AND deleted_at IS NULL")
+            .fetch_one(&self.db.pool)
+            .await?;
+
+        let mut tasks = sqlx::query_as::<_, Task>(
+            r#"
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
+            FROM tasks
+            WHERE archived_at IS NULL AND deleted_at IS NULL
+            ORDER BY 
+                CASE status 
+                    WHEN 'inbox' THEN 1
+                    WHEN 'todo' THEN 2
+                    WHEN 'in_progress' THEN 3
+                    WHEN 'done' THEN 4
+                END,
+                is_pinned DESC,
+                CASE notification_level
+                    WHEN 3 THEN 1
+                    WHEN 2 THEN 2
+                    WHEN 1 THEN 3
+                    ELSE 4
+                END,
+                created_at DESC,
+                id
+            LIMIT ?1 OFFSET ?2
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        let task_ids: Vec<String> = tasks.iter().map(|task| task.id.clone()).collect();
+        let mut tags_by_task = TagService::get_tags_for_tasks(&self.db.pool, &task_ids).await?;
+        for task in &mut tasks {
+            task.tags = Some(tags_by_task.remove(&task.id).unwrap_or_default());
+        }
+
+        Ok((tasks, total))
+    }
+    
+    /// タイトル・説明文の部分一致でタスクを検索
+    ///
+    /// 大文字小文字を区別せず、スペース区切りの複数キーワードはすべてを含むタスクに絞り込む。
+    pub async fn search_tasks(&self, query: &str) -> Result<Vec<Task>, AppError> {
+        let keywords: Vec<&str> = query.split_whitespace().collect();
+        if keywords.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        // LIKEのワイルドカードはキーワードの文字としてそのまま扱う
+        let conditions = (1..=keywords.len())
+            .map(|i| format!("(title LIKE ?{i} ESCAPE '\\' OR COALESCE(description, '') LIKE ?{i} ESCAPE '\\')"))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let sql = format!(
+            r#"
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
+            FROM tasks
+            WHERE deleted_at IS NULL AND {}
+            ORDER BY 
+                CASE status 
+                    WHEN 'inbox' THEN 1
+                    WHEN 'todo' THEN 2
+                    WHEN 'in_progress' THEN 3
+                    WHEN 'done' THEN 4
+                END,
+                is_pinned DESC,
+                CASE notification_level
+                    WHEN 3 THEN 1
+                    WHEN 2 THEN 2
+                    WHEN 1 THEN 3
+                    ELSE 4
+                END,
+                created_at DESC
+            "#,
+            conditions
+        );
+        
+        let mut search = sqlx::query_as::<_, Task>(&sql);
+        for keyword in keywords {
+            let escaped = keyword.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+            search = search.bind(format!("%{}%", escaped));
+        }
+        let mut tasks = search.fetch_all(&self.db.pool).await?;
+        
+        for task in &mut tasks {
+            task.tags = self.get_tags_for_task(&task.id).await.ok();
+        }
+        
+        Ok(tasks)
+    }
+    
     pub async fn get_task_by_id(&self, id: &str) -> Result<Task, AppError> {
         let mut task = sqlx::query_as::<_, Task>(
             r#"
-            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, browser_actions
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
             FROM tasks
-            WHERE id = ?1
+            WHERE id = ?1 AND deleted_at IS NULL
             "#,
         )
         .bind(id)
@@ -133,15 +345,36 @@ impl TaskService {
     }
     
     pub async fn update_task(&self, id: &str, request: UpdateTaskRequest) -> Result<Task, AppError> {
+        let (task, _) = self.apply_task_update(id, request, Utc::now()).await?;
+        Ok(task)
+    }
+    
+    /// タスクを更新し、繰り返しタスクを完了にした場合は同じトランザクションで次回分を作成する
+    async fn apply_task_update(&self, id: &str, request: UpdateTaskRequest, now: DateTime<Utc>) -> Result<(Task, Option<Task>), AppError> {
+        self.get_field_limits()
+            .await?
+            .check(request.title.as_deref(), request.description.as_deref())
+            .map_err(AppError::InvalidInput)?;
+        
+        if matches!(request.status, Some(TaskStatus::Done)) {
+            self.ensure_children_complete(id).await?;
+        }
+        
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        
         // トランザクションを開始
         let mut tx = self.db.pool.begin().await?;
         
+        if let Some(parent_id) = &request.parent_id {
+            Self::validate_parent(&mut tx, Some(id), parent_id).await?;
+        }
+        
         // Get existing task first (トランザクション内で実行)
         let mut task = sqlx::query_as::<_, Task>(
             r#"
-            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, browser_actions
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
             FROM tasks
-            WHERE id = ?1
+            WHERE id = ?1 AND deleted_at IS NULL
             "#,
         )
         .bind(id)
@@ -149,6 +382,13 @@ impl TaskService {
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Task with id {} not found", id)))?;
         
+        // 完了にする場合は、完了前の期日を基準に次回の期日を決める
+        let next_due = if matches!(request.status, Some(TaskStatus::Done)) && task.status != TaskStatus::Done.to_string() {
+            Self::next_recurrence_due(&task, &timezone, now)?
+        } else {
+            None
+        };
+        
         // Update fields if provided
         if let Some(title) = request.title {
             task.title = title;
@@ -157,7 +397,11 @@ impl TaskService {
             task.description = Some(description);
         }
         if let Some(status) = request.status {
-            task.status = status.to_string();
+            let status = status.to_string();
+            if status != task.status {
+                task.status_changed_at = Some(Utc::now().to_rfc3339());
+            }
+            task.status = status;
             // Set completed_at if status is Done
             if task.status == "done" {
                 task.completed_at = Some(Utc::now().to_rfc3339());
@@ -175,6 +419,7 @@ impl TaskService {
         
         // 通知設定の更新
         if let Some(notification_settings) = request.notification_settings {
+            Self::validate_notification_settings(&notification_settings)?;
             task.notification_type = Some(notification_settings.notification_type);
             task.notification_days_before = notification_settings.days_before;
             task.notification_time = notification_settings.notification_time;
@@ -182,6 +427,7 @@ impl TaskService {
                 serde_json::to_string(&days).unwrap_or_default()
             );
             task.notification_level = Some(notification_settings.level);
+            task.notification_until = notification_settings.notification_until;
         }
         
         // ブラウザアクションの更新
@@ -192,14 +438,16 @@ impl TaskService {
         task.updated_at = Utc::now().to_rfc3339();
         
         // メインのタスクレコードを先に更新
-        println!("UpdateTask: About to update main task record for task {}", task.id);
+        log::debug!("UpdateTask: About to update main task record for task {}", task.id);
         match sqlx::query(
             r#"
             UPDATE tasks
             SET title = ?2, description = ?3, status = ?4, 
                 parent_id = ?5, due_date = ?6, completed_at = ?7, updated_at = ?8, progress = ?9,
                 notification_type = ?10, notification_days_before = ?11, notification_time = ?12,
-                notification_days_of_week = ?13, notification_level = ?14, browser_actions = ?15
+                notification_days_of_week = ?13, notification_level = ?14, browser_actions = ?15,
+                notification_until = ?16, status_changed_at = ?17,
+                last_completed_at = COALESCE(?7, last_completed_at)
             WHERE id = ?1
             "#,
         )
@@ -218,43 +466,45 @@ impl TaskService {
         .bind(&task.notification_days_of_week)
         .bind(task.notification_level)
         .bind(&task.browser_actions)
+        .bind(&task.notification_until)
+        .bind(&task.status_changed_at)
         .execute(&mut *tx)
         .await {
             Ok(result) => {
-                println!("UpdateTask: Successfully updated main task record for task {}, rows_affected: {}", task.id, result.rows_affected());
+                log::debug!("UpdateTask: Successfully updated main task record for task {}, rows_affected: {}", task.id, result.rows_affected());
             },
             Err(e) => {
-                println!("UpdateTask: FAILED to update main task record for task {}: {:?}", task.id, e);
+                log::warn!("UpdateTask: FAILED to update main task record for task {}: {:?}", task.id, e);
                 return Err(e.into());
             }
         }
         
         // タグの更新処理（メインタスク更新後に実行）
         if let Some(tags) = request.tags {
-            println!("UpdateTask: Processing {} tags for task {}", tags.len(), task.id);
+            log::debug!("UpdateTask: Processing {} tags for task {}", tags.len(), task.id);
             for tag in &tags {
-                println!("UpdateTask: Tag ID: {}, Name: {}", tag.id, tag.name);
+                log::debug!("UpdateTask: Tag ID: {}, Name: {}", tag.id, tag.name);
             }
             
             // 既存のタグ関連付けを削除
-            println!("UpdateTask: Deleting existing tag relations for task {}", task.id);
+            log::debug!("UpdateTask: Deleting existing tag relations for task {}", task.id);
             let delete_result = sqlx::query("DELETE FROM task_tags WHERE task_id = ?1")
                 .bind(&task.id)
                 .execute(&mut *tx)
                 .await?;
-            println!("UpdateTask: Deleted {} existing tag relations", delete_result.rows_affected());
+            log::debug!("UpdateTask: Deleted {} existing tag relations", delete_result.rows_affected());
             
             // 新しいタグ関連付けを追加（存在するタグのみ）
             for tag in tags {
                 // タスクが存在するかチェック（念のため）
                 let task_exists: Option<(String,)> = sqlx::query_as(
-                    "SELECT id FROM tasks WHERE id = ?1"
+                    "SELECT id FROM tasks WHERE id = ?1 AND deleted_at IS NULL"
                 )
                 .bind(&task.id)
                 .fetch_optional(&mut *tx)
                 .await?;
                 
-                println!("UpdateTask: Task {} exists: {}", task.id, task_exists.is_some());
+                log::debug!("UpdateTask: Task {} exists: {}", task.id, task_exists.is_some());
                 
                 // タグが存在するかチェック
                 let tag_exists: Option<(String, String, String)> = sqlx::query_as(
@@ -265,15 +515,15 @@ impl TaskService {
                 .await?;
                 
                 let tag_found = if let Some((found_id, found_name, found_color)) = &tag_exists {
-                    println!("UpdateTask: Tag found - ID: {}, Name: {}, Color: {}", found_id, found_name, found_color);
+                    log::debug!("UpdateTask: Tag found - ID: {}, Name: {}, Color: {}", found_id, found_name, found_color);
                     true
                 } else {
-                    println!("UpdateTask: Tag {} does not exist", tag.id);
+                    log::debug!("UpdateTask: Tag {} does not exist", tag.id);
                     false
                 };
                 
                 if task_exists.is_some() && tag_found {
-                    println!("UpdateTask: About to insert task_tag relation: task_id={}, tag_id={}", task.id, tag.id);
+                    log::debug!("UpdateTask: About to insert task_tag relation: task_id={}, tag_id={}", task.id, tag.id);
                     
                     let current_time = Utc::now().to_rfc3339();
                     match sqlx::query(
@@ -288,10 +538,10 @@ impl TaskService {
                     .execute(&mut *tx)
                     .await {
                         Ok(result) => {
-                            println!("UpdateTask: Successfully added tag {} to task {}, rows_affected: {}", tag.id, task.id, result.rows_affected());
+                            log::debug!("UpdateTask: Successfully added tag {} to task {}, rows_affected: {}", tag.id, task.id, result.rows_affected());
                         },
                         Err(e) => {
-                            println!("UpdateTask: FAILED to add tag {} to task {}: {:?}", tag.id, task.id, e);
+                            log::warn!("UpdateTask: FAILED to add tag {} to task {}: {:?}", tag.id, task.id, e);
                             
                             // FOREIGN KEY制約の詳細なデバッグ情報を取得
                             let fk_check: Result<Vec<(String, String, String, String)>, _> = sqlx::query_as(
@@ -303,16 +553,16 @@ impl TaskService {
                             match fk_check {
                                 Ok(violations) => {
                                     if !violations.is_empty() {
-                                        println!("UpdateTask: FOREIGN KEY violations found:");
+                                        log::debug!("UpdateTask: FOREIGN KEY violations found:");
                                         for (table, rowid, parent, fkid) in violations {
-                                            println!("  - Table: {}, RowID: {}, Parent: {}, ForeignKeyID: {}", table, rowid, parent, fkid);
+                                            log::debug!("  - Table: {}, RowID: {}, Parent: {}, ForeignKeyID: {}", table, rowid, parent, fkid);
                                         }
                                     } else {
-                                        println!("UpdateTask: No FOREIGN KEY violations found in entire database");
+                                        log::debug!("UpdateTask: No FOREIGN KEY violations found in entire database");
                                     }
                                 },
                                 Err(fk_err) => {
-                                    println!("UpdateTask: Failed to check FOREIGN KEY constraints: {:?}", fk_err);
+                                    log::debug!("UpdateTask: Failed to check FOREIGN KEY constraints: {:?}", fk_err);
                                 }
                             }
                             
@@ -325,15 +575,15 @@ impl TaskService {
                             
                             match fk_status {
                                 Ok((enabled,)) => {
-                                    println!("UpdateTask: FOREIGN KEY constraints enabled: {}", enabled == 1);
+                                    log::debug!("UpdateTask: FOREIGN KEY constraints enabled: {}", enabled == 1);
                                 },
                                 Err(status_err) => {
-                                    println!("UpdateTask: Failed to check FOREIGN KEY status: {:?}", status_err);
+                                    log::debug!("UpdateTask: Failed to check FOREIGN KEY status: {:?}", status_err);
                                 }
                             }
                             
                             // 手動でINSERTを試行して詳細エラーを取得
-                            println!("UpdateTask: Attempting manual INSERT to identify specific constraint failure");
+                            log::debug!("UpdateTask: Attempting manual INSERT to identify specific constraint failure");
                             let manual_insert_result = sqlx::query(
                                 "INSERT INTO task_tags (task_id, tag_id, created_at) VALUES (?1, ?2, ?3)"
                             )
@@ -345,7 +595,7 @@ impl TaskService {
                             
                             match manual_insert_result {
                                 Ok(result) => {
-                                    println!("UpdateTask: Manual INSERT succeeded, rows_affected: {}", result.rows_affected());
+                                    log::debug!("UpdateTask: Manual INSERT succeeded, rows_affected: {}", result.rows_affected());
                                     // 成功したので重複を避けるためにロールバック要素を削除
                                     sqlx::query("DELETE FROM task_tags WHERE task_id = ?1 AND tag_id = ?2")
                                         .bind(&task.id)
@@ -355,7 +605,7 @@ impl TaskService {
                                         .ok();
                                 },
                                 Err(manual_err) => {
-                                    println!("UpdateTask: Manual INSERT also failed: {:?}", manual_err);
+                                    log::warn!("UpdateTask: Manual INSERT also failed: {:?}", manual_err);
                                 }
                             }
                             
@@ -363,39 +613,228 @@ impl TaskService {
                         }
                     }
                 } else {
-                    println!("UpdateTask: Tag {} does not exist, skipping", tag.id);
+                    log::debug!("UpdateTask: Tag {} does not exist, skipping", tag.id);
                 }
             }
         }
         
+        let mut next_task = match next_due {
+            Some(next_due) => Self::insert_next_recurrence(&mut tx, &task, next_due, &timezone).await?,
+            None => None,
+        };
+        
         // トランザクションをコミット
         tx.commit().await?;
-        println!("UpdateTask: Transaction committed successfully for task {}", task.id);
+        invalidate_task_context_cache();
+        log::debug!("UpdateTask: Transaction committed successfully for task {}", task.id);
+        
+        if let Some(next_task) = next_task.as_mut() {
+            next_task.tags = self.get_tags_for_task(&next_task.id).await.ok();
+        }
         
         // 更新後のタスクを最新のタグ情報と一緒に返す
-        self.get_task_by_id(id).await
+        Ok((self.get_task_by_id(id).await?, next_task))
     }
     
+    /// タスクを子孫ごとゴミ箱に移す（完全には削除しない）
     pub async fn delete_task(&self, id: &str) -> Result<(), AppError> {
-        let result = sqlx::query("DELETE FROM tasks WHERE id = ?1")
-            .bind(id)
-            .execute(&self.db.pool)
-            .await?;
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.db.pool.begin().await?;
+        let result = sqlx::query(
+            r#"
+            WITH RECURSIVE subtree(id) AS (
+                SELECT id FROM tasks WHERE id = ?1 AND deleted_at IS NULL
+                UNION
+                SELECT t.id
+                FROM tasks t
+                INNER JOIN subtree s ON t.parent_id = s.id
+                WHERE t.deleted_at IS NULL
+            )
+            UPDATE tasks SET deleted_at = ?2, updated_at = ?2 WHERE id IN (SELECT id FROM subtree)
+            "#,
+        )
+        .bind(id)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
         
         if result.rows_affected() == 0 {
             return Err(AppError::NotFound(format!("Task with id {} not found", id)));
         }
         
+        tx.commit().await?;
+        invalidate_task_context_cache();
+        
         Ok(())
     }
     
+    /// ゴミ箱のタスクを、一緒にゴミ箱に入った子孫ごと元に戻す
+    pub async fn restore_task(&self, id: &str) -> Result<Task, AppError> {
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.db.pool.begin().await?;
+        let deleted_at = sqlx::query_scalar::<_, String>(
+            "SELECT deleted_at FROM tasks WHERE id = ?1 AND deleted_at IS NOT NULL",
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Task with id {} not found in trash", id)))?;
+        
+        // 先に個別に削除されていた子孫はゴミ箱に残す
+        sqlx::query(
+            r#"
+            WITH RECURSIVE subtree(id) AS (
+                SELECT id FROM tasks WHERE id = ?1
+                UNION
+                SELECT t.id
+                FROM tasks t
+                INNER JOIN subtree s ON t.parent_id = s.id
+                WHERE t.deleted_at = ?2
+            )
+            UPDATE tasks SET deleted_at = NULL, updated_at = ?3 WHERE id IN (SELECT id FROM subtree)
+            "#,
+        )
+        .bind(id)
+        .bind(&deleted_at)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+        
+        tx.commit().await?;
+        invalidate_task_context_cache();
+        
+        self.get_task_by_id(id).await
+    }
+    
+    /// ゴミ箱のタスクを取得（新しく削除した順）
+    pub async fn get_deleted_tasks(&self) -> Result<Vec<Task>, AppError> {
+        let mut tasks = sqlx::query_as::<_, Task>(
+            r#"
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
+            FROM tasks
+            WHERE deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC
+            "#,
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        for task in &mut tasks {
+            task.tags = self.get_tags_for_task(&task.id).await.ok();
+        }
+        
+        Ok(tasks)
+    }
+    
+    /// ゴミ箱を空にし、完全に削除した件数を返す
+    pub async fn empty_trash(&self) -> Result<u64, AppError> {
+        self.purge_trash(None).await
+    }
+    
+    /// 保存期間を過ぎたゴミ箱のタスクを完全に削除する（起動時に呼ぶ）
+    pub async fn purge_expired_trash(&self) -> Result<u64, AppError> {
+        self.purge_expired_trash_at(Utc::now()).await
+    }
+    
+    pub async fn purge_expired_trash_at(&self, now: DateTime<Utc>) -> Result<u64, AppError> {
+        let cutoff = (now - Duration::days(TRASH_RETENTION_DAYS)).to_rfc3339();
+        self.purge_trash(Some(&cutoff)).await
+    }
+    
+    /// ゴミ箱のタスクを完全に削除する。cutoffを指定した場合はそれより前に削除したものだけが対象
+    async fn purge_trash(&self, cutoff: Option<&str>) -> Result<u64, AppError> {
+        let mut tx = self.db.pool.begin().await?;
+        
+        let target = "SELECT id FROM tasks WHERE deleted_at IS NOT NULL AND (?1 IS NULL OR deleted_at < ?1)";
+        
+        // ゴミ箱に入っていない子タスクは巻き添えにせずルートへ移す
+        sqlx::query(&format!(
+            "UPDATE tasks SET parent_id = NULL WHERE deleted_at IS NULL AND parent_id IN ({})",
+            target
+        ))
+        .bind(cutoff)
+        .execute(&mut *tx)
+        .await?;
+        
+        for table_condition in [
+            "task_tags WHERE task_id",
+            "task_dependencies WHERE from_task_id",
+            "task_dependencies WHERE to_task_id",
+            "task_embeddings WHERE task_id",
+        ] {
+            sqlx::query(&format!("DELETE FROM {} IN ({})", table_condition, target))
+                .bind(cutoff)
+                .execute(&mut *tx)
+                .await?;
+        }
+        
+        // 子孫は親の削除に連鎖して消え、rows_affectedに数えられないため先に数える
+        let purged = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM ({})", target))
+            .bind(cutoff)
+            .fetch_one(&mut *tx)
+            .await?;
+        sqlx::query(&format!("DELETE FROM tasks WHERE id IN ({})", target))
+            .bind(cutoff)
+            .execute(&mut *tx)
+            .await?;
+        
+        tx.commit().await?;
+        invalidate_task_context_cache();
+        
+        Ok(purged as u64)
+    }
+    
+    /// タスクを子孫ごと削除し、削除した件数を返す
+    ///
+    /// タグの関連付けも合わせて削除する。
+    pub async fn delete_task_cascade(&self, id: &str) -> Result<usize, AppError> {
+        let mut tx = self.db.pool.begin().await?;
+        
+        // UNIONで重複を除くため、既存データが循環していても終了する
+        let task_ids: Vec<String> = sqlx::query_scalar(
+            r#"
+            WITH RECURSIVE subtree(id) AS (
+                SELECT id FROM tasks WHERE id = ?1
+                UNION
+                SELECT t.id
+                FROM tasks t
+                INNER JOIN subtree s ON t.parent_id = s.id
+            )
+            SELECT id FROM subtree
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await?;
+        
+        if task_ids.is_empty() {
+            return Err(AppError::NotFound(format!("Task with id {} not found", id)));
+        }
+        
+        let placeholders = vec!["?"; task_ids.len()].join(", ");
+        for table_column in ["task_tags WHERE task_id", "task_embeddings WHERE task_id", "tasks WHERE id"] {
+            let sql = format!("DELETE FROM {} IN ({})", table_column, placeholders);
+            let mut query = sqlx::query(&sql);
+            for task_id in &task_ids {
+                query = query.bind(task_id);
+            }
+            query.execute(&mut *tx).await?;
+        }
+        
+        tx.commit().await?;
+        invalidate_task_context_cache();
+        
+        Ok(task_ids.len())
+    }
+    
     pub async fn get_tasks_by_status(&self, status: &str) -> Result<Vec<Task>, AppError> {
         let tasks = sqlx::query_as::<_, Task>(
             r#"
-            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, browser_actions
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
             FROM tasks
-            WHERE status = ?1
+            WHERE status = ?1 AND deleted_at IS NULL
             ORDER BY 
+                is_pinned DESC,
                 CASE notification_level
                     WHEN 3 THEN 1
                     WHEN 2 THEN 2
@@ -412,9 +851,82 @@ impl TaskService {
         Ok(tasks)
     }
     
+    /// タスクの親を`parent_id`にできるか確認（新規作成時は`task_id`をNoneにする）
+    ///
+    /// 親候補から祖先をたどって自タスクに行き着く場合（親候補が自タスクかその子孫）は循環になるため拒否する。
+    /// 自タスクの子孫も含めて最大階層数を超える場合も拒否する。
+    ///
+    /// 更新時は更新と同じトランザクションで呼び、検証後に親がゴミ箱へ移されたり階層が変わったりしないようにする。
+    async fn validate_parent(conn: &mut sqlx::SqliteConnection, task_id: Option<&str>, parent_id: &str) -> Result<(), AppError> {
+        let parent_depth = Self::parent_depth(&mut *conn, task_id, parent_id).await?;
+        
+        // 自タスクの下にある子孫の階層数
+        let subtree_height = match task_id {
+            Some(task_id) => sqlx::query_scalar::<_, Option<i64>>(
+                r#"
+                WITH RECURSIVE descendants(id, depth) AS (
+                    SELECT id, 0 FROM tasks WHERE id = ?1
+                    UNION ALL
+                    SELECT t.id, d.depth + 1
+                    FROM tasks t
+                    INNER JOIN descendants d ON t.parent_id = d.id
+                    WHERE d.depth < ?2
+                )
+                SELECT MAX(depth) FROM descendants
+                "#,
+            )
+            .bind(task_id)
+            .bind(MAX_TASK_DEPTH)
+            .fetch_one(&mut *conn)
+            .await?
+            .unwrap_or(0),
+            None => 0,
+        };
+        
+        if parent_depth + 1 + subtree_height > MAX_TASK_DEPTH {
+            return Err(AppError::InvalidInput(format!(
+                "Task hierarchy cannot be deeper than {} levels",
+                MAX_TASK_DEPTH
+            )));
+        }
+        
+        Ok(())
+    }
+    
+    /// 親候補`parent_id`の階層（ルートが1）
+    ///
+    /// 親候補が存在しない・ゴミ箱にある場合はNotFound、祖先に`task_id`がある（循環する）場合や
+    /// 親候補の下にもう1階層も作れない場合はInvalidInputを返す。
+    async fn parent_depth(conn: &mut sqlx::SqliteConnection, task_id: Option<&str>, parent_id: &str) -> Result<i64, AppError> {
+        let mut parent_depth = 0;
+        let mut current = Some(parent_id.to_string());
+        while let Some(ancestor_id) = current {
+            if task_id == Some(ancestor_id.as_str()) {
+                return Err(AppError::InvalidInput(format!(
+                    "Task {} cannot be moved under itself or its descendant {}",
+                    ancestor_id, parent_id
+                )));
+            }
+            parent_depth += 1;
+            // 既存データが循環していても無限ループにならないよう、上限を超えた時点で打ち切る
+            if parent_depth >= MAX_TASK_DEPTH {
+                return Err(AppError::InvalidInput(format!(
+                    "Task hierarchy cannot be deeper than {} levels",
+                    MAX_TASK_DEPTH
+                )));
+            }
+            current = sqlx::query_scalar::<_, Option<String>>("SELECT parent_id FROM tasks WHERE id = ?1 AND deleted_at IS NULL")
+                .bind(&ancestor_id)
+                .fetch_optional(&mut *conn)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Task with id {} not found", ancestor_id)))?;
+        }
+        
+        Ok(parent_depth)
+    }
+    
     pub async fn move_task(&self, id: &str, new_status: &str) -> Result<Task, AppError> {
         use std::str::FromStr;
-        use crate::models::TaskStatus;
         
         let status = TaskStatus::from_str(new_status)
             .map_err(AppError::InvalidInput)?;
@@ -431,305 +943,1911 @@ impl TaskService {
         }).await
     }
     
-    pub async fn get_incomplete_task_count(&self) -> Result<usize, AppError> {
-        let count: (i64,) = sqlx::query_as(
-            r#"
-            SELECT COUNT(*) as count
-            FROM tasks
-            WHERE status != 'done'
-            "#,
-        )
-        .fetch_one(&self.db.pool)
-        .await?;
+    /// 複数タスクのステータスを1つのトランザクションでまとめて変更
+    ///
+    /// 存在しないIDが1件でもあれば何も変更せず、見つからなかったIDをすべてエラーで返す。
+    /// 完了にする場合、必須の子タスクは同時に完了にするものを除いて完了済みである必要がある。
+    /// 変更したタスクの親の進捗率も再計算する。
+    pub async fn bulk_move_tasks(&self, ids: &[String], new_status: &str) -> Result<Vec<Task>, AppError> {
+        use std::str::FromStr;
         
-            Ok(count.0 as usize)
-    }
-    
-    // 子タスク管理機能
-    pub async fn get_children(&self, parent_id: &str) -> Result<Vec<Task>, AppError> {
-        let tasks = sqlx::query_as::<_, Task>(
-            r#"
-            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level
-            FROM tasks
-            WHERE parent_id = ?1
-            ORDER BY created_at ASC
-            "#,
-        )
-        .bind(parent_id)
-        .fetch_all(&self.db.pool)
-        .await?;
+        let status = TaskStatus::from_str(new_status)
+            .map_err(AppError::InvalidInput)?
+            .to_string();
+        let mut seen = HashSet::new();
+        let ids: Vec<&String> = ids.iter().filter(|id| seen.insert(id.as_str())).collect();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
         
-        Ok(tasks)
-    }
-    
-    pub async fn get_task_with_children(&self, id: &str) -> Result<Task, AppError> {
-        let mut task = self.get_task_by_id(id).await?;
-        let children = self.get_children(id).await?;
+        let rules = self.get_subtask_completion_rules().await?;
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let mut tx = self.db.pool.begin().await?;
         
-        // 子タスクがある場合は進捗率を計算
-        if !children.is_empty() {
-            task.progress = Some(self.calculate_progress(&children));
+        let mut tasks = Vec::with_capacity(ids.len());
+        let mut missing = Vec::new();
+        for id in &ids {
+            let task = sqlx::query_as::<_, Task>(
+                r#"
+                SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
+                FROM tasks
+                WHERE id = ?1 AND deleted_at IS NULL
+                "#,
+            )
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            match task {
+                Some(task) => tasks.push(task),
+                None => missing.push(id.as_str()),
+            }
+        }
+        if !missing.is_empty() {
+            return Err(AppError::NotFound(format!("Tasks not found: {}", missing.join(", "))));
         }
         
-        Ok(task)
-    }
-    
-    // 進捗率計算機能
-    pub async fn calculate_and_update_progress(&self, parent_id: &str) -> Result<i32, AppError> {
-        let children = self.get_children(parent_id).await?;
-        
+        if status == "done" {
+            for task in &tasks {
+                // 同時に完了にする子タスクは完了済みとして扱う
+                let children: Vec<Task> = Self::fetch_children(&mut *tx, &task.id)
+                    .await?
+                    .into_iter()
+                    .map(|mut child| {
+                        if seen.contains(child.id.as_str()) {
+                            child.status = status.clone();
+                        }
+                        child
+                    })
+                    .collect();
+                let blocking = rules.blocking_children(&children);
+                if !blocking.is_empty() {
+                    let titles: Vec<&str> = blocking.iter().map(|child| child.title.as_str()).collect();
+                    return Err(AppError::InvalidInput(format!(
+                        "Cannot complete task '{}': {} required subtask(s) incomplete ({})",
+                        task.title,
+                        blocking.len(),
+                        titles.join(", ")
+                    )));
+                }
+            }
+        }
+        
+        let moved_at = Utc::now();
+        let now = moved_at.to_rfc3339();
+        let mut parent_ids = BTreeSet::new();
+        for task in tasks.iter().filter(|task| task.status != status) {
+            let completed_at = (status == "done").then(|| now.clone());
+            sqlx::query(
+                r#"
+                UPDATE tasks 
+                SET status = ?2, completed_at = ?3, status_changed_at = ?4, updated_at = ?4,
+                    last_completed_at = COALESCE(?3, last_completed_at)
+                WHERE id = ?1
+                "#,
+            )
+            .bind(&task.id)
+            .bind(&status)
+            .bind(&completed_at)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+            parent_ids.extend(task.parent_id.clone());
+            
+            // 繰り返しタスクは次回分を作成する
+            if status == "done" {
+                if let Some(next_due) = Self::next_recurrence_due(task, &timezone, moved_at)? {
+                    Self::insert_next_recurrence(&mut tx, task, next_due, &timezone).await?;
+                }
+            }
+        }
+        
+        for parent_id in &parent_ids {
+            let children = Self::fetch_children(&mut *tx, parent_id).await?;
+            if children.is_empty() {
+                continue;
+            }
+            sqlx::query("UPDATE tasks SET progress = ?2, updated_at = ?3 WHERE id = ?1")
+                .bind(parent_id)
+                .bind(rules.progress(&children))
+                .bind(&now)
+                .execute(&mut *tx)
+                .await?;
+        }
+        
+        tx.commit().await?;
+        invalidate_task_context_cache();
+        
+        let mut moved = Vec::with_capacity(ids.len());
+        for id in ids {
+            moved.push(self.get_task_by_id(id).await?);
+        }
+        Ok(moved)
+    }
+    
+    /// Markdownのチェックリストからタスクを一括作成
+    ///
+    /// インデントで階層を表し、`[x]`は完了、`[ ]`はTODOとして取り込む。
+    /// 形式が崩れた行は読み飛ばして警告に含める。作成は1トランザクションで行う。
+    pub async fn import_markdown(&self, markdown: &str, parent_id: Option<String>) -> Result<MarkdownImportResult, AppError> {
+        let (items, warnings) = parse_checklist(markdown);
+        for warning in &warnings {
+            log::warn!("Markdown import: {}", warning);
+        }
+        
+        let limits = self.get_field_limits().await?;
+        for item in &items {
+            limits.check(Some(&item.title), None).map_err(AppError::InvalidInput)?;
+        }
+        
+        let mut tx = self.db.pool.begin().await?;
+        // 取り込み先の階層（親なしなら0）。入れ子の深さと合わせて最大階層数を超えないようにする
+        let base_depth = match &parent_id {
+            Some(parent_id) => Self::parent_depth(&mut tx, None, parent_id).await?,
+            None => 0,
+        };
+        let mut tasks = Vec::with_capacity(items.len());
+        // (インデント幅, タスクID) のスタック
+        let mut ancestors: Vec<(usize, String)> = Vec::new();
+        
+        for item in items {
+            while ancestors.last().is_some_and(|(indent, _)| *indent >= item.indent) {
+                ancestors.pop();
+            }
+            if base_depth + ancestors.len() as i64 + 1 > MAX_TASK_DEPTH {
+                return Err(AppError::InvalidInput(format!(
+                    "Task hierarchy cannot be deeper than {} levels: {}",
+                    MAX_TASK_DEPTH, item.title
+                )));
+            }
+            
+            let status = if item.checked { TaskStatus::Done } else { TaskStatus::Todo };
+            let mut task = Task::new(item.title, None, status);
+            task.parent_id = ancestors.last().map(|(_, id)| id.clone()).or_else(|| parent_id.clone());
+            if item.checked {
+                task.completed_at = Some(task.created_at.clone());
+                task.progress = Some(100);
+            }
+            
+            Self::insert_task(&mut *tx, &task).await?;
+            ancestors.push((item.indent, task.id.clone()));
+            tasks.push(task);
+        }
+        
+        tx.commit().await?;
+        invalidate_task_context_cache();
+        
+        Ok(MarkdownImportResult { tasks, warnings })
+    }
+    
+    /// AIが提案したサブタスクを子タスクとして作成
+    pub async fn apply_task_analysis(&self, task_id: &str, subtasks: Vec<SubtaskSuggestion>) -> Result<Vec<Task>, AppError> {
+        let parent = self.get_task_by_id(task_id).await?;
+        
+        let mut subtasks = subtasks;
+        subtasks.sort_by_key(|subtask| subtask.order);
+        
+        let mut created = Vec::with_capacity(subtasks.len());
+        for subtask in subtasks {
+            created.push(self.create_task(CreateTaskRequest {
+                title: subtask.title,
+                description: Some(subtask.description).filter(|d| !d.trim().is_empty()),
+                status: Some(TaskStatus::Todo),
+                parent_id: Some(parent.id.clone()),
+                due_date: None,
+                notification_settings: None,
+                browser_actions: None,
+            }).await?);
+        }
+        
+        Ok(created)
+    }
+    
+    /// タスクをピン留めし、同じステータス内で常に上位に表示する
+    pub async fn pin_task(&self, id: &str) -> Result<Task, AppError> {
+        self.set_pinned(id, true).await
+    }
+    
+    pub async fn unpin_task(&self, id: &str) -> Result<Task, AppError> {
+        self.set_pinned(id, false).await
+    }
+    
+    async fn set_pinned(&self, id: &str, pinned: bool) -> Result<Task, AppError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks 
+            SET is_pinned = ?2, updated_at = ?3
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .bind(pinned)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.db.pool)
+        .await?;
+        
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Task with id {} not found", id)));
+        }
+        invalidate_task_context_cache();
+        
+        self.get_task_by_id(id).await
+    }
+    
+    /// タスクをアーカイブし、通常の一覧・通知の対象から外す
+    pub async fn archive_task(&self, id: &str) -> Result<Task, AppError> {
+        self.set_archived(id, true).await
+    }
+    
+    pub async fn unarchive_task(&self, id: &str) -> Result<Task, AppError> {
+        self.set_archived(id, false).await
+    }
+    
+    async fn set_archived(&self, id: &str, archived: bool) -> Result<Task, AppError> {
+        let now = Utc::now().to_rfc3339();
+        // アーカイブ済みのタスクを再度アーカイブしても日時は変えない
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks 
+            SET archived_at = CASE WHEN ?2 THEN COALESCE(archived_at, ?3) ELSE NULL END, updated_at = ?3
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .bind(archived)
+        .bind(&now)
+        .execute(&self.db.pool)
+        .await?;
+        
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Task with id {} not found", id)));
+        }
+        
+        invalidate_task_context_cache();
+        
+        self.get_task_by_id(id).await
+    }
+    
+    /// アーカイブ済みのタスクを取得（新しくアーカイブした順）
+    pub async fn get_archived_tasks(&self) -> Result<Vec<Task>, AppError> {
+        let mut tasks = sqlx::query_as::<_, Task>(
+            r#"
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
+            FROM tasks
+            WHERE archived_at IS NOT NULL AND deleted_at IS NULL
+            ORDER BY archived_at DESC
+            "#,
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        for task in &mut tasks {
+            task.tags = self.get_tags_for_task(&task.id).await.ok();
+        }
+        
+        Ok(tasks)
+    }
+    
+    pub async fn get_incomplete_task_count(&self) -> Result<usize, AppError> {
+        let count: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) as count
+            FROM tasks
+            WHERE status != 'done' AND deleted_at IS NULL
+            "#,
+        )
+        .fetch_one(&self.db.pool)
+        .await?;
+        
+            Ok(count.0 as usize)
+    }
+    
+    // 子タスク管理機能
+    pub async fn get_children(&self, parent_id: &str) -> Result<Vec<Task>, AppError> {
+        Self::fetch_children(&self.db.pool, parent_id).await
+    }
+    
+    async fn fetch_children<'e, E>(executor: E, parent_id: &str) -> Result<Vec<Task>, AppError>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+    {
+        let tasks = sqlx::query_as::<_, Task>(
+            r#"
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
+            FROM tasks
+            WHERE parent_id = ?1 AND deleted_at IS NULL
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(parent_id)
+        .fetch_all(executor)
+        .await?;
+        
+        Ok(tasks)
+    }
+    
+    pub async fn get_task_with_children(&self, id: &str) -> Result<Task, AppError> {
+        let mut task = self.get_task_by_id(id).await?;
+        let children = self.get_children(id).await?;
+        
+        // 子タスクがある場合は進捗率を計算
+        if !children.is_empty() {
+            task.progress = Some(self.get_subtask_completion_rules().await?.progress(&children));
+        }
+        
+        Ok(task)
+    }
+    
+    // 進捗率計算機能
+    pub async fn calculate_and_update_progress(&self, parent_id: &str) -> Result<i32, AppError> {
+        let children = self.get_children(parent_id).await?;
+        
         if children.is_empty() {
             return Ok(0);
         }
         
-        let progress = self.calculate_progress(&children);
+        let progress = self.get_subtask_completion_rules().await?.progress(&children);
+        
+        // 親タスクの進捗率を更新
+        sqlx::query(
+            r#"
+            UPDATE tasks 
+            SET progress = ?2, updated_at = ?3
+            WHERE id = ?1
+            "#,
+        )
+        .bind(parent_id)
+        .bind(progress)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.db.pool)
+        .await?;
+        
+        Ok(progress)
+    }
+    
+    /// 完了にできない（必須の子タスクが未完了の）場合はエラー
+    async fn ensure_children_complete(&self, id: &str) -> Result<(), AppError> {
+        let children = self.get_children(id).await?;
+        let blocking = self.get_subtask_completion_rules().await?.blocking_children(&children);
+        if blocking.is_empty() {
+            return Ok(());
+        }
+        
+        let titles: Vec<&str> = blocking.iter().map(|child| child.title.as_str()).collect();
+        Err(AppError::InvalidInput(format!(
+            "Cannot complete task: {} required subtask(s) incomplete ({})",
+            blocking.len(),
+            titles.join(", ")
+        )))
+    }
+    
+    /// 子タスクを任意（親の完了を妨げない）にするか設定し、親の進捗率を再計算
+    pub async fn set_task_optional(&self, id: &str, optional: bool) -> Result<Task, AppError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks 
+            SET is_optional = ?2, updated_at = ?3
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .bind(optional)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.db.pool)
+        .await?;
+        
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Task with id {} not found", id)));
+        }
+        invalidate_task_context_cache();
+        
+        let task = self.get_task_by_id(id).await?;
+        if let Some(parent_id) = &task.parent_id {
+            self.calculate_and_update_progress(parent_id).await?;
+        }
+        Ok(task)
+    }
+    
+    /// タスクの通知経路を設定（Noneならレベルに応じた従来の動作に戻す）
+    pub async fn set_notification_channels(&self, id: &str, channels: Option<Vec<NotificationChannel>>) -> Result<Task, AppError> {
+        if channels.as_ref().is_some_and(|channels| channels.is_empty()) {
+            return Err(AppError::InvalidInput(
+                "At least one notification channel is required".to_string(),
+            ));
+        }
+        let channels = channels.map(|channels| serde_json::to_string(&channels).unwrap_or_default());
+        
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks 
+            SET notification_channels = ?2, updated_at = ?3
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .bind(&channels)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.db.pool)
+        .await?;
+        
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Task with id {} not found", id)));
+        }
+        invalidate_task_context_cache();
+        
+        self.get_task_by_id(id).await
+    }
+    
+    /// タスクの繰り返し規則と、次回分を作る最終日を設定（規則がNoneなら繰り返さない）
+    pub async fn set_recurrence_rule(&self, id: &str, rule: Option<String>, end: Option<NaiveDate>) -> Result<Task, AppError> {
+        let rule = rule.map(|rule| rule.trim().to_string()).filter(|rule| !rule.is_empty());
+        if let Some(rule) = &rule {
+            RecurrenceRule::parse(rule).map_err(AppError::InvalidInput)?;
+        }
+        // 繰り返さないタスクに最終日は残さない
+        let end = end.filter(|_| rule.is_some()).map(|end| end.format("%Y-%m-%d").to_string());
+        
+        let result = sqlx::query(
+            r#"
+            UPDATE tasks 
+            SET recurrence_rule = ?2, recurrence_end = ?3, updated_at = ?4
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .bind(&rule)
+        .bind(&end)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.db.pool)
+        .await?;
+        
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Task with id {} not found", id)));
+        }
+        invalidate_task_context_cache();
+        
+        self.get_task_by_id(id).await
+    }
+    
+    /// 繰り返しタスクの次回分の期日（繰り返し規則がなければNone）
+    pub async fn next_recurrence(&self, task_id: &str) -> Result<Option<DateTime<Utc>>, AppError> {
+        self.next_recurrence_at(task_id, Utc::now()).await
+    }
+    
+    /// 今の期日（なければ`now`の日付）の次に規則に合う日を計算し、期日の時刻を保つ
+    pub async fn next_recurrence_at(&self, task_id: &str, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, AppError> {
+        let task = self.get_task_by_id(task_id).await?;
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        Self::next_recurrence_due(&task, &timezone, now)
+    }
+    
+    fn next_recurrence_due(task: &Task, timezone: &AppTimezone, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, AppError> {
+        let Some(rule) = task.recurrence_rule.as_deref() else {
+            return Ok(None);
+        };
+        let rule = RecurrenceRule::parse(rule).map_err(AppError::InvalidInput)?;
+        
+        let base = task.due_date
+            .as_deref()
+            .and_then(|due| DateTime::parse_from_rfc3339(due).ok())
+            .map(|due| timezone.to_local(due.with_timezone(&Utc)))
+            .unwrap_or_else(|| timezone.local_date(now).and_time(NaiveTime::MIN));
+        
+        Ok(rule
+            .next_after(base.date())
+            .and_then(|date| timezone.from_local(date.and_time(base.time()))))
+    }
+    
+    /// タスクを完了にし、繰り返しタスクなら次回分を作成する
+    pub async fn complete_task(&self, id: &str) -> Result<CompleteTaskResult, AppError> {
+        self.complete_task_at(id, Utc::now()).await
+    }
+    
+    /// 完了と次回分の作成は1つのトランザクションで行う。すでに完了済みのタスクからは作らない。
+    pub async fn complete_task_at(&self, id: &str, now: DateTime<Utc>) -> Result<CompleteTaskResult, AppError> {
+        let task = self.get_task_by_id(id).await?;
+        if task.status == TaskStatus::Done.to_string() {
+            return Ok(CompleteTaskResult { task, next_task: None });
+        }
+        
+        let (task, next_task) = self.apply_task_update(id, UpdateTaskRequest {
+            title: None,
+            description: None,
+            status: Some(TaskStatus::Done),
+            parent_id: None,
+            due_date: None,
+            notification_settings: None,
+            browser_actions: None,
+            tags: None,
+        }, now).await?;
+        Ok(CompleteTaskResult { task, next_task })
+    }
+    
+    /// 完了にした繰り返しタスクの次回分を作成する
+    ///
+    /// 次回分は通知設定・タグ・ブラウザアクション・親タスクを引き継ぎ、期日を`next_due`にする。
+    /// 次の該当日が最終日（`recurrence_end`）より後なら作らない。
+    async fn insert_next_recurrence(
+        conn: &mut sqlx::SqliteConnection,
+        task: &Task,
+        next_due: DateTime<Utc>,
+        timezone: &AppTimezone,
+    ) -> Result<Option<Task>, AppError> {
+        let recurrence_end = task.recurrence_end
+            .as_deref()
+            .and_then(|end| NaiveDate::parse_from_str(end, "%Y-%m-%d").ok());
+        if recurrence_end.is_some_and(|end| timezone.local_date(next_due) > end) {
+            return Ok(None);
+        }
+        
+        let next_task = Task {
+            parent_id: task.parent_id.clone(),
+            due_date: Some(next_due.to_rfc3339()),
+            notification_type: task.notification_type.clone(),
+            notification_days_before: task.notification_days_before,
+            notification_time: task.notification_time.clone(),
+            notification_days_of_week: task.notification_days_of_week.clone(),
+            notification_level: task.notification_level,
+            notification_until: task.notification_until.clone(),
+            browser_actions: task.browser_actions.clone(),
+            is_optional: task.is_optional,
+            notification_channels: task.notification_channels.clone(),
+            recurrence_rule: task.recurrence_rule.clone(),
+            recurrence_end: task.recurrence_end.clone(),
+            ..Task::new(task.title.clone(), task.description.clone(), TaskStatus::Todo)
+        };
+        
+        Self::insert_task(&mut *conn, &next_task).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO task_tags (task_id, tag_id, created_at)
+            SELECT ?2, tag_id, ?3 FROM task_tags WHERE task_id = ?1
+            "#,
+        )
+        .bind(&task.id)
+        .bind(&next_task.id)
+        .bind(&next_task.created_at)
+        .execute(&mut *conn)
+        .await?;
+        
+        Ok(Some(next_task))
+    }
+    
+    /// タスク間の依存関係を追加（同じ組み合わせがあれば種別を置き換える）
+    ///
+    /// 順序のある依存（blocks/requires）が循環する場合は拒否する。
+    pub async fn add_dependency(&self, from_task_id: &str, to_task_id: &str, dependency_type: &str) -> Result<TaskDependencyRecord, AppError> {
+        if !DEPENDENCY_TYPES.contains(&dependency_type) {
+            return Err(AppError::InvalidInput(format!(
+                "Dependency type must be one of {}: {}",
+                DEPENDENCY_TYPES.join(", "),
+                dependency_type
+            )));
+        }
+        if from_task_id == to_task_id {
+            return Err(AppError::InvalidInput("A task cannot depend on itself".to_string()));
+        }
+        for task_id in [from_task_id, to_task_id] {
+            self.get_task_by_id(task_id).await?;
+        }
+        
+        // 置き換える組み合わせを除いた既存の依存に新しい依存を加えて、順に並べられるか確認する
+        let mut edges: Vec<DependencyEdge> = self
+            .get_all_dependencies()
+            .await?
+            .into_iter()
+            .filter(|dependency| !(dependency.from_task_id == from_task_id && dependency.to_task_id == to_task_id))
+            .map(DependencyEdge::from)
+            .collect();
+        edges.push(DependencyEdge {
+            from_task: from_task_id.to_string(),
+            to_task: to_task_id.to_string(),
+            dependency_type: dependency_type.to_string(),
+        });
+        let task_ids: Vec<String> = edges
+            .iter()
+            .flat_map(|edge| [edge.from_task.clone(), edge.to_task.clone()])
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if let Err(cycle) = topological_order(&task_ids, &edges) {
+            return Err(AppError::InvalidInput(format!(
+                "Dependency would create a cycle between tasks: {}",
+                cycle.join(", ")
+            )));
+        }
+        
+        let dependency = sqlx::query_as::<_, TaskDependencyRecord>(
+            r#"
+            INSERT INTO task_dependencies (from_task_id, to_task_id, dependency_type, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT(from_task_id, to_task_id) DO UPDATE SET dependency_type = excluded.dependency_type
+            RETURNING from_task_id, to_task_id, dependency_type, created_at
+            "#,
+        )
+        .bind(from_task_id)
+        .bind(to_task_id)
+        .bind(dependency_type)
+        .bind(Utc::now().to_rfc3339())
+        .fetch_one(&self.db.pool)
+        .await?;
+        
+        Ok(dependency)
+    }
+    
+    pub async fn remove_dependency(&self, from_task_id: &str, to_task_id: &str) -> Result<(), AppError> {
+        let result = sqlx::query("DELETE FROM task_dependencies WHERE from_task_id = ?1 AND to_task_id = ?2")
+            .bind(from_task_id)
+            .bind(to_task_id)
+            .execute(&self.db.pool)
+            .await?;
+        
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!(
+                "Dependency from {} to {} not found",
+                from_task_id, to_task_id
+            )));
+        }
+        
+        Ok(())
+    }
+    
+    /// タスクが依存元・依存先のどちらかになっている依存関係を取得
+    pub async fn get_dependencies(&self, task_id: &str) -> Result<Vec<TaskDependencyRecord>, AppError> {
+        let dependencies = sqlx::query_as::<_, TaskDependencyRecord>(
+            r#"
+            SELECT from_task_id, to_task_id, dependency_type, created_at
+            FROM task_dependencies
+            WHERE from_task_id = ?1 OR to_task_id = ?1
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        Ok(dependencies)
+    }
+    
+    async fn get_all_dependencies(&self) -> Result<Vec<TaskDependencyRecord>, AppError> {
+        let dependencies = sqlx::query_as::<_, TaskDependencyRecord>(
+            "SELECT from_task_id, to_task_id, dependency_type, created_at FROM task_dependencies",
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        Ok(dependencies)
+    }
+    
+    /// 依存関係（blocks/requires）を満たす順にタスクを並べる（おすすめの着手順）
+    ///
+    /// `root_id`を指定するとそのタスクと子孫、Noneならアーカイブ済みを除くすべてのタスクが対象。
+    /// 依存が循環している場合は、循環に関わるタスクを挙げたエラーを返す。
+    pub async fn get_topological_order(&self, root_id: Option<String>) -> Result<Vec<Task>, AppError> {
+        let tasks = match root_id.as_deref() {
+            Some(root_id) => {
+                let subtree = self.get_subtree(root_id).await?;
+                if subtree.is_empty() {
+                    return Err(AppError::NotFound(format!("Task with id {} not found", root_id)));
+                }
+                subtree
+            }
+            None => self.get_tasks().await?,
+        };
+        let task_ids: Vec<String> = tasks.iter().map(|task| task.id.clone()).collect();
+        let edges: Vec<DependencyEdge> = self
+            .get_all_dependencies()
+            .await?
+            .into_iter()
+            .map(DependencyEdge::from)
+            .collect();
+        
+        let order = topological_order(&task_ids, &edges).map_err(|cycle| {
+            let names: Vec<String> = cycle
+                .iter()
+                .filter_map(|id| tasks.iter().find(|task| &task.id == id))
+                .map(|task| format!("'{}' ({})", task.title, task.id))
+                .collect();
+            AppError::Validation(format!("Task dependencies contain a cycle: {}", names.join(", ")))
+        })?;
+        
+        let mut by_id: HashMap<String, Task> = tasks.into_iter().map(|task| (task.id.clone(), task)).collect();
+        Ok(order.into_iter().filter_map(|id| by_id.remove(&id)).collect())
+    }
+    
+    /// 先に終わらせるべきタスク（このタスクをblockするタスク・このタスクがrequireするタスク）が
+    /// すべて完了しているか確認し、未完了のものを警告として返す
+    pub async fn can_complete(&self, task_id: &str) -> Result<CompletionCheck, AppError> {
+        self.get_task_by_id(task_id).await?;
+        
+        let mut warnings = Vec::new();
+        for dependency in self.get_dependencies(task_id).await? {
+            let edge = DependencyEdge::from(dependency);
+            let Some((before, _)) = edge.ordering().filter(|(_, after)| *after == task_id) else {
+                continue;
+            };
+            let prerequisite = self.get_task_by_id(before).await?;
+            if prerequisite.status != TaskStatus::Done.to_string() {
+                warnings.push(format!(
+                    "Task '{}' ({}) must be completed first",
+                    prerequisite.title, prerequisite.id
+                ));
+            }
+        }
+        
+        Ok(CompletionCheck {
+            can_complete: warnings.is_empty(),
+            warnings,
+        })
+    }
+    
+    /// タスクと子孫を、親が子より先に来る順で取得（先頭が`task_id`のタスク）
+    async fn get_subtree(&self, task_id: &str) -> Result<Vec<Task>, AppError> {
+        let tasks = sqlx::query_as::<_, Task>(
+            r#"
+            WITH RECURSIVE subtree(id) AS (
+                SELECT id FROM tasks WHERE id = ?1
+                UNION
+                SELECT t.id
+                FROM tasks t
+                INNER JOIN subtree s ON t.parent_id = s.id
+            )
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
+            FROM tasks
+            WHERE id IN (SELECT id FROM subtree) AND deleted_at IS NULL
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        let mut root = None;
+        let mut children_of: HashMap<String, Vec<Task>> = HashMap::new();
+        for task in tasks {
+            if task.id == task_id {
+                root = Some(task);
+            } else if let Some(parent_id) = task.parent_id.clone() {
+                children_of.entry(parent_id).or_default().push(task);
+            }
+        }
+        let root = root.ok_or_else(|| AppError::NotFound(format!("Task with id {} not found", task_id)))?;
+        
+        // 子は取り出しながらたどるので、既存データが循環していても終わる
+        let mut ordered = vec![root];
+        let mut index = 0;
+        while index < ordered.len() {
+            let children = children_of.remove(&ordered[index].id).unwrap_or_default();
+            ordered.extend(children);
+            index += 1;
+        }
+        Ok(ordered)
+    }
+    
+    /// タスクを複製し、複製したタスクを返す
+    ///
+    /// タイトルに「(コピー)」を付け、元と同じ親の下に作る。`include_children`なら子孫もすべて複製する。
+    /// タグ・ブラウザアクション・通知設定は引き継ぎ、完了日時と進捗率はリセットする（完了済みは未着手に戻す）。
+    pub async fn duplicate_task(&self, id: &str, include_children: bool) -> Result<Task, AppError> {
+        let mut tasks = self.get_subtree(id).await?;
+        if !include_children {
+            tasks.truncate(1);
+        }
+        
+        let now = Utc::now().to_rfc3339();
+        // 元のID → 複製のID
+        let mut new_ids: HashMap<String, String> = HashMap::new();
+        let mut tx = self.db.pool.begin().await?;
+        
+        for (index, original) in tasks.into_iter().enumerate() {
+            let new_id = Uuid::new_v4().to_string();
+            let parent_id = if index == 0 {
+                original.parent_id.clone()
+            } else {
+                original.parent_id.as_ref().and_then(|parent_id| new_ids.get(parent_id)).cloned()
+            };
+            let title = if index == 0 {
+                format!("{} (コピー)", original.title)
+            } else {
+                original.title.clone()
+            };
+            let status = if original.status == TaskStatus::Done.to_string() {
+                TaskStatus::Todo.to_string()
+            } else {
+                original.status.clone()
+            };
+            let task = Task {
+                id: new_id.clone(),
+                title,
+                status,
+                parent_id,
+                completed_at: None,
+                created_at: now.clone(),
+                updated_at: now.clone(),
+                progress: Some(0),
+                status_changed_at: Some(now.clone()),
+                archived_at: None,
+                deleted_at: None,
+                tags: None,
+                ..original.clone()
+            };
+            
+            Self::insert_task(&mut *tx, &task).await?;
+            sqlx::query(
+                r#"
+                INSERT INTO task_tags (task_id, tag_id, created_at)
+                SELECT ?2, tag_id, ?3 FROM task_tags WHERE task_id = ?1
+                "#,
+            )
+            .bind(&original.id)
+            .bind(&new_id)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+            
+            new_ids.insert(original.id, new_id);
+        }
+        
+        tx.commit().await?;
+        invalidate_task_context_cache();
+        
+        self.get_task_by_id(&new_ids[id]).await
+    }
+    
+    /// タスクと子タスクの構造をテンプレートとして保存
+    pub async fn save_as_template(&self, task_id: &str, name: &str) -> Result<TaskTemplate, AppError> {
+        self.save_as_template_at(task_id, name, Utc::now()).await
+    }
+    
+    /// 期日・通知の終了日は`now`の日付（アプリのタイムゾーン）からの相対日数として保存する
+    pub async fn save_as_template_at(&self, task_id: &str, name: &str, now: DateTime<Utc>) -> Result<TaskTemplate, AppError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::InvalidInput("Template name must not be empty".to_string()));
+        }
+        
+        let tasks = self.get_subtree(task_id).await?;
+        let root = &tasks[0];
+        let mut children_of: HashMap<&str, Vec<&Task>> = HashMap::new();
+        for task in &tasks[1..] {
+            if let Some(parent_id) = task.parent_id.as_deref() {
+                children_of.entry(parent_id).or_default().push(task);
+            }
+        }
+        
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let template = TaskTemplate {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            root: Self::template_task(root, &mut children_of, &timezone, timezone.local_date(now)),
+            created_at: now.to_rfc3339(),
+        };
+        let structure = serde_json::to_string(&template.root)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize task template: {}", e)))?;
+        
+        sqlx::query("INSERT INTO task_templates (id, name, structure, created_at) VALUES (?1, ?2, ?3, ?4)")
+            .bind(&template.id)
+            .bind(&template.name)
+            .bind(&structure)
+            .bind(&template.created_at)
+            .execute(&self.db.pool)
+            .await?;
+        
+        Ok(template)
+    }
+    
+    /// タスクをテンプレート用の構造に変換（子タスクは取り出しながらたどるので、循環していても終わる）
+    fn template_task<'a>(
+        task: &'a Task,
+        children_of: &mut HashMap<&'a str, Vec<&'a Task>>,
+        timezone: &AppTimezone,
+        base_date: NaiveDate,
+    ) -> TemplateTask {
+        let due = task.due_date
+            .as_deref()
+            .and_then(|due| DateTime::parse_from_rfc3339(due).ok())
+            .map(|due| timezone.to_local(due.with_timezone(&Utc)));
+        let notification_until = task.notification_until
+            .as_deref()
+            .and_then(|until| NaiveDate::parse_from_str(until, "%Y-%m-%d").ok());
+        
+        let children = children_of.remove(task.id.as_str()).unwrap_or_default();
+        TemplateTask {
+            title: task.title.clone(),
+            description: task.description.clone(),
+            due_offset_days: due.map(|due| (due.date() - base_date).num_days()),
+            due_time: due.map(|due| due.time().format("%H:%M:%S").to_string()),
+            notification_type: task.notification_type.clone(),
+            notification_days_before: task.notification_days_before,
+            notification_time: task.notification_time.clone(),
+            notification_days_of_week: task.notification_days_of_week.clone(),
+            notification_level: task.notification_level,
+            notification_until_offset_days: notification_until.map(|until| (until - base_date).num_days()),
+            notification_channels: task.notification_channels.clone(),
+            browser_actions: task.browser_actions.clone(),
+            is_optional: task.is_optional,
+            children: children
+                .into_iter()
+                .map(|child| Self::template_task(child, children_of, timezone, base_date))
+                .collect(),
+        }
+    }
+    
+    /// 保存済みのテンプレートを取得（新しい順）
+    pub async fn get_task_templates(&self) -> Result<Vec<TaskTemplate>, AppError> {
+        let rows = sqlx::query_as::<_, (String, String, String, String)>(
+            "SELECT id, name, structure, created_at FROM task_templates ORDER BY created_at DESC",
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        rows.into_iter().map(Self::parse_template_row).collect()
+    }
+    
+    pub async fn delete_task_template(&self, template_id: &str) -> Result<(), AppError> {
+        let result = sqlx::query("DELETE FROM task_templates WHERE id = ?1")
+            .bind(template_id)
+            .execute(&self.db.pool)
+            .await?;
+        
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("Task template with id {} not found", template_id)));
+        }
+        
+        Ok(())
+    }
+    
+    fn parse_template_row((id, name, structure, created_at): (String, String, String, String)) -> Result<TaskTemplate, AppError> {
+        let root = serde_json::from_str(&structure)
+            .map_err(|e| AppError::ParseError(format!("Invalid task template {}: {}", id, e)))?;
+        Ok(TaskTemplate { id, name, root, created_at })
+    }
+    
+    /// テンプレートからタスクと子タスクを作成し、ルートのタスクを返す
+    pub async fn create_from_template(&self, template_id: &str) -> Result<Task, AppError> {
+        self.create_from_template_at(template_id, Utc::now()).await
+    }
+    
+    /// 期日・通知の終了日は`now`の日付（アプリのタイムゾーン）を基準に計算する
+    pub async fn create_from_template_at(&self, template_id: &str, now: DateTime<Utc>) -> Result<Task, AppError> {
+        let row = sqlx::query_as::<_, (String, String, String, String)>(
+            "SELECT id, name, structure, created_at FROM task_templates WHERE id = ?1",
+        )
+        .bind(template_id)
+        .fetch_optional(&self.db.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Task template with id {} not found", template_id)))?;
+        let template = Self::parse_template_row(row)?;
+        
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let base_date = timezone.local_date(now);
+        
+        let mut tx = self.db.pool.begin().await?;
+        let mut root_id = None;
+        // (テンプレートのタスク, 親タスクID)
+        let mut pending: Vec<(&TemplateTask, Option<String>)> = vec![(&template.root, None)];
+        while let Some((template_task, parent_id)) = pending.pop() {
+            let due_date = template_task.due_offset_days.and_then(|days| {
+                let time = template_task.due_time
+                    .as_deref()
+                    .and_then(|time| NaiveTime::parse_from_str(time, "%H:%M:%S").ok())
+                    .unwrap_or(NaiveTime::MIN);
+                timezone.from_local((base_date + Duration::days(days)).and_time(time))
+            });
+            let task = Task {
+                parent_id,
+                due_date: due_date.map(|due| due.to_rfc3339()),
+                notification_type: template_task.notification_type.clone(),
+                notification_days_before: template_task.notification_days_before,
+                notification_time: template_task.notification_time.clone(),
+                notification_days_of_week: template_task.notification_days_of_week.clone(),
+                notification_level: template_task.notification_level,
+                notification_until: template_task.notification_until_offset_days
+                    .map(|days| (base_date + Duration::days(days)).format("%Y-%m-%d").to_string()),
+                notification_channels: template_task.notification_channels.clone(),
+                browser_actions: template_task.browser_actions.clone(),
+                is_optional: template_task.is_optional,
+                ..Task::new(template_task.title.clone(), template_task.description.clone(), TaskStatus::Todo)
+            };
+            
+            Self::insert_task(&mut *tx, &task).await?;
+            root_id.get_or_insert_with(|| task.id.clone());
+            // 先頭の子から作られるよう逆順に積む
+            pending.extend(template_task.children.iter().rev().map(|child| (child, Some(task.id.clone()))));
+        }
+        tx.commit().await?;
+        invalidate_task_context_cache();
+        
+        let root_id = root_id.ok_or_else(|| AppError::Internal("Task template has no tasks".to_string()))?;
+        self.get_task_by_id(&root_id).await
+    }
+    
+    pub async fn update_progress(&self, id: &str, progress: i32) -> Result<Task, AppError> {
+        if !(0..=100).contains(&progress) {
+            return Err(AppError::InvalidInput("Progress must be between 0 and 100".to_string()));
+        }
+        
+        let mut task = self.get_task_by_id(id).await?;
+        task.progress = Some(progress);
+        task.updated_at = Utc::now().to_rfc3339();
+        
+        // タスクが100%完了の場合、ステータスをdoneに変更
+        if progress == 100 && task.status != "done" {
+            self.ensure_children_complete(id).await?;
+            task.status = "done".to_string();
+            task.completed_at = Some(Utc::now().to_rfc3339());
+            task.status_changed_at = Some(task.updated_at.clone());
+        }
+        
+        sqlx::query(
+            r#"
+            UPDATE tasks 
+            SET progress = ?2, status = ?3, completed_at = ?4, updated_at = ?5, status_changed_at = ?6,
+                last_completed_at = COALESCE(?4, last_completed_at)
+            WHERE id = ?1
+            "#,
+        )
+        .bind(&task.id)
+        .bind(task.progress)
+        .bind(&task.status)
+        .bind(&task.completed_at)
+        .bind(&task.updated_at)
+        .bind(&task.status_changed_at)
+        .execute(&self.db.pool)
+        .await?;
+        
+        // 親タスクがある場合は親の進捗率も更新
+        if let Some(parent_id) = &task.parent_id {
+            self.calculate_and_update_progress(parent_id).await?;
+        }
+        
+        Ok(task)
+    }
+    
+    /// 全タスクを親子の入れ子構造のJSONで出力（バックアップ用）
+    ///
+    /// 各タスクはタグと通知設定を含み、子タスクは`children`配列に作成日時順で並ぶ。
+    /// 親が存在しないタスクも失われないようルートとして出力する。
+    pub async fn export_tree_json(&self) -> Result<String, AppError> {
+        let mut tasks = self.get_tasks().await?;
+        tasks.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        
+        let ids: HashSet<String> = tasks.iter().map(|task| task.id.clone()).collect();
+        let mut roots = Vec::new();
+        let mut children_of: HashMap<String, Vec<Task>> = HashMap::new();
+        for task in tasks {
+            match task.parent_id.clone() {
+                Some(parent_id) if ids.contains(&parent_id) => children_of.entry(parent_id).or_default().push(task),
+                _ => roots.push(task),
+            }
+        }
+        
+        let mut tree: Vec<TaskTreeNode> = roots
+            .into_iter()
+            .map(|task| Self::build_tree_node(task, &mut children_of))
+            .collect();
+        // 親子関係が循環していてルートから辿れなかったタスクもルートとして残す
+        while let Some(parent_id) = children_of.keys().next().cloned() {
+            for task in children_of.remove(&parent_id).unwrap_or_default() {
+                tree.push(Self::build_tree_node(task, &mut children_of));
+            }
+        }
+        
+        serde_json::to_string_pretty(&tree)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize task tree: {}", e)))
+    }
+    
+    /// タスクとその子孫を入れ子にして取得
+    pub async fn get_task_tree(&self, task_id: &str) -> Result<TaskTreeNode, AppError> {
+        let mut tasks = self.get_tasks().await?;
+        tasks.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        
+        let mut root = None;
+        let mut children_of: HashMap<String, Vec<Task>> = HashMap::new();
+        for task in tasks {
+            if task.id == task_id {
+                root = Some(task);
+            } else if let Some(parent_id) = task.parent_id.clone() {
+                children_of.entry(parent_id).or_default().push(task);
+            }
+        }
+        
+        let root = root.ok_or_else(|| AppError::NotFound(format!("Task with id {} not found", task_id)))?;
+        Ok(Self::build_tree_node(root, &mut children_of))
+    }
+    
+    fn build_tree_node(task: Task, children_of: &mut HashMap<String, Vec<Task>>) -> TaskTreeNode {
+        // 取り出したら除くので、親子関係が循環していても無限に辿らない
+        let children = children_of
+            .remove(&task.id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| Self::build_tree_node(child, children_of))
+            .collect();
+        TaskTreeNode { task, children }
+    }
+    
+    pub async fn get_root_tasks(&self) -> Result<Vec<Task>, AppError> {
+        let tasks = sqlx::query_as::<_, Task>(
+            r#"
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
+            FROM tasks
+            WHERE parent_id IS NULL AND deleted_at IS NULL
+            ORDER BY 
+                CASE status 
+                    WHEN 'inbox' THEN 1
+                    WHEN 'todo' THEN 2
+                    WHEN 'in_progress' THEN 3
+                    WHEN 'done' THEN 4
+                END,
+                is_pinned DESC,
+                CASE notification_level
+                    WHEN 3 THEN 1
+                    WHEN 2 THEN 2
+                    WHEN 1 THEN 3
+                    ELSE 4
+                END,
+                created_at DESC
+            "#,
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        Ok(tasks)
+    }
+    
+    /// アプリのタイムゾーンで`from`〜`to`（両端を含む）に完了したタスクを完了日時順に取得
+    pub async fn get_tasks_completed_between(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Task>, AppError> {
+        if from > to {
+            return Err(AppError::InvalidInput(format!("Start date {} is after end date {}", from, to)));
+        }
+        
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let local_midnight = |date: NaiveDate| {
+            timezone
+                .from_local(date.and_time(NaiveTime::MIN))
+                .ok_or_else(|| AppError::Internal(format!("Invalid local date: {}", date)))
+        };
+        let start = local_midnight(from)?;
+        let end = local_midnight(to + Duration::days(1))?;
+        
+        let mut tasks = sqlx::query_as::<_, Task>(
+            r#"
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
+            FROM tasks
+            WHERE status = 'done' AND deleted_at IS NULL
+              AND completed_at IS NOT NULL
+              AND datetime(completed_at) >= datetime(?1)
+              AND datetime(completed_at) < datetime(?2)
+            ORDER BY datetime(completed_at) ASC
+            "#,
+        )
+        .bind(start.to_rfc3339())
+        .bind(end.to_rfc3339())
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        for task in &mut tasks {
+            task.tags = self.get_tags_for_task(&task.id).await.ok();
+        }
+        
+        Ok(tasks)
+    }
+    
+    /// 着手中になってからの経過時間が長い順に着手中タスクを取得
+    pub async fn get_longest_in_progress(&self, limit: usize) -> Result<Vec<Task>, AppError> {
+        let mut tasks = sqlx::query_as::<_, Task>(
+            r#"
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
+            FROM tasks
+            WHERE status = 'in_progress' AND deleted_at IS NULL
+            ORDER BY datetime(COALESCE(status_changed_at, updated_at)) ASC
+            LIMIT ?1
+            "#,
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        for task in &mut tasks {
+            task.tags = self.get_tags_for_task(&task.id).await.ok();
+        }
+        
+        Ok(tasks)
+    }
+    
+    /// タスクの埋め込みを保存する（既にあれば置き換える）
+    pub async fn save_task_embedding(&self, task_id: &str, model: &str, embedding: &[f32]) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO task_embeddings (task_id, model, embedding, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+        )
+        .bind(task_id)
+        .bind(model)
+        .bind(encode_embedding(embedding))
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.db.pool)
+        .await?;
+        Ok(())
+    }
+    
+    /// 埋め込みのコサイン類似度が高い順に、指定したタスクに似ているタスクを最大top_k件取得
+    ///
+    /// 同じモデルで計算した埋め込み同士だけを比較し、ゴミ箱のタスクは対象外にする。
+    pub async fn find_similar_tasks(&self, id: &str, top_k: usize) -> Result<Vec<SimilarTask>, AppError> {
+        let (model, bytes): (String, Vec<u8>) = sqlx::query_as(
+            "SELECT model, embedding FROM task_embeddings WHERE task_id = ?1",
+        )
+        .bind(id)
+        .fetch_optional(&self.db.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Embedding for task {} not found", id)))?;
+        let target = decode_embedding(&bytes)
+            .ok_or_else(|| AppError::Internal(format!("Stored embedding for task {} is corrupted", id)))?;
+        
+        let candidates: Vec<(String, Vec<u8>)> = sqlx::query_as(
+            r#"
+            SELECT e.task_id, e.embedding
+            FROM task_embeddings e
+            INNER JOIN tasks t ON t.id = e.task_id
+            WHERE e.model = ?1 AND e.task_id != ?2 AND t.deleted_at IS NULL
+            "#,
+        )
+        .bind(&model)
+        .bind(id)
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        let mut scored: Vec<(String, f32)> = candidates
+            .into_iter()
+            .filter_map(|(task_id, bytes)| {
+                let similarity = cosine_similarity(&target, &decode_embedding(&bytes)?)?;
+                Some((task_id, similarity))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(top_k);
+        
+        let mut similar = Vec::with_capacity(scored.len());
+        for (task_id, similarity) in scored {
+            let task = self.get_task_by_id(&task_id).await?;
+            similar.push(SimilarTask { task, similarity });
+        }
+        Ok(similar)
+    }
+    
+    /// 今後30日間に一度も発火しない通知設定の未完了タスク（設定ミスの検出用）
+    pub async fn find_inert_notifications(&self, notifications: &NotificationService) -> Result<Vec<Task>, AppError> {
+        self.find_inert_notifications_at(notifications, Utc::now()).await
+    }
+    
+    pub async fn find_inert_notifications_at(&self, notifications: &NotificationService, now: DateTime<Utc>) -> Result<Vec<Task>, AppError> {
+        let tasks = sqlx::query_as::<_, Task>(
+            r#"
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
+            FROM tasks
+            WHERE status != 'done' AND archived_at IS NULL AND deleted_at IS NULL AND notification_type IS NOT NULL AND notification_type != 'none'
+            ORDER BY created_at ASC
+            "#,
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        let until = now + Duration::days(INERT_NOTIFICATION_LOOKAHEAD_DAYS);
+        let mut inert = Vec::new();
+        for mut task in tasks {
+            if notifications.task_firings_between(&task, now, until).await?.is_empty() {
+                task.tags = self.get_tags_for_task(&task.id).await.ok();
+                inert.push(task);
+            }
+        }
+        
+        Ok(inert)
+    }
+    
+    // 緊急度スコア
+    pub async fn get_urgency_weights(&self) -> Result<UrgencyWeights, AppError> {
+        Ok(SettingsService::get_json(&self.db.pool, UrgencyWeights::SETTINGS_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+    
+    pub async fn set_urgency_weights(&self, weights: UrgencyWeights) -> Result<UrgencyWeights, AppError> {
+        weights.validate().map_err(AppError::InvalidInput)?;
+        SettingsService::set_json(&self.db.pool, UrgencyWeights::SETTINGS_KEY, &weights).await?;
+        self.urgency_cache.clear();
+        Ok(weights)
+    }
+    
+    // ステータス省略時の既定ステータス（未設定ならTODO）
+    pub async fn get_default_status(&self) -> Result<Option<TaskStatus>, AppError> {
+        Ok(SettingsService::get_json(&self.db.pool, DEFAULT_TASK_STATUS_KEY).await?)
+    }
+    
+    pub async fn set_default_status(&self, status: Option<TaskStatus>) -> Result<(), AppError> {
+        match status {
+            Some(status) => SettingsService::set_json(&self.db.pool, DEFAULT_TASK_STATUS_KEY, &status).await?,
+            None => SettingsService::delete(&self.db.pool, DEFAULT_TASK_STATUS_KEY).await?,
+        }
+        Ok(())
+    }
+    
+    // タイトル・説明の文字数上限
+    pub async fn get_field_limits(&self) -> Result<TaskFieldLimits, AppError> {
+        Ok(SettingsService::get_json(&self.db.pool, TaskFieldLimits::SETTINGS_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+    
+    pub async fn set_field_limits(&self, limits: TaskFieldLimits) -> Result<TaskFieldLimits, AppError> {
+        limits.validate().map_err(AppError::InvalidInput)?;
+        SettingsService::set_json(&self.db.pool, TaskFieldLimits::SETTINGS_KEY, &limits).await?;
+        Ok(limits)
+    }
+    
+    // 子タスクの完了判定ルール
+    pub async fn get_subtask_completion_rules(&self) -> Result<SubtaskCompletionRules, AppError> {
+        Ok(SettingsService::get_json(&self.db.pool, SubtaskCompletionRules::SETTINGS_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+    
+    pub async fn set_subtask_completion_rules(&self, rules: SubtaskCompletionRules) -> Result<SubtaskCompletionRules, AppError> {
+        SettingsService::set_json(&self.db.pool, SubtaskCompletionRules::SETTINGS_KEY, &rules).await?;
+        Ok(rules)
+    }
+    
+    /// 未完了タスクを緊急度スコアの高い順に取得
+    ///
+    /// スコアはタスクが変更されるか有効期間が過ぎるまでキャッシュを使い回す。
+    pub async fn get_tasks_by_urgency(&self, limit: usize) -> Result<Vec<TaskUrgency>, AppError> {
+        let weights = self.get_urgency_weights().await?;
+        let mut scored = self.urgency_scores(&weights).await?;
+        scored.truncate(limit);
+        
+        for entry in &mut scored {
+            entry.task.tags = self.get_tags_for_task(&entry.task.id).await.ok();
+        }
         
-        // 親タスクの進捗率を更新
-        sqlx::query(
+        Ok(scored)
+    }
+    
+    /// 未完了タスク全件の緊急度スコア（降順・タグなし）
+    async fn urgency_scores(&self, weights: &UrgencyWeights) -> Result<Vec<TaskUrgency>, AppError> {
+        // 読み込み前の世代番号で保存し、計算中の変更は次回の再計算に回す
+        let generation = task_data_generation();
+        if let Some(cached) = self.urgency_cache.get(generation, weights, std::time::Instant::now()) {
+            return Ok(cached);
+        }
+        
+        let now = Utc::now();
+        let tasks = sqlx::query_as::<_, Task>(
             r#"
-            UPDATE tasks 
-            SET progress = ?2, updated_at = ?3
-            WHERE id = ?1
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
+            FROM tasks
+            WHERE status != 'done' AND deleted_at IS NULL
             "#,
         )
-        .bind(parent_id)
-        .bind(progress)
-        .bind(Utc::now().to_rfc3339())
-        .execute(&self.db.pool)
+        .fetch_all(&self.db.pool)
         .await?;
         
-        Ok(progress)
+        let mut scored: Vec<TaskUrgency> = tasks
+            .into_iter()
+            .map(|task| {
+                let score = task_urgency_score(&task, now, weights);
+                TaskUrgency { task, score }
+            })
+            .collect();
+        
+        scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+        self.urgency_cache.store(scored.clone(), *weights, generation, std::time::Instant::now());
+        Ok(scored)
     }
     
-    fn calculate_progress(&self, children: &[Task]) -> i32 {
-        if children.is_empty() {
-            return 0;
-        }
+    /// 全タスクのブラウザアクションURLを確認し、到達できないものを報告
+    pub async fn audit_browser_action_urls(&self, checker: &BrowserActionService) -> Result<Vec<UnreachableBrowserAction>, AppError> {
+        use futures::stream::{self, StreamExt};
         
-        let total_progress: i32 = children.iter()
-            .map(|child| {
-                if child.status == "done" {
-                    100
-                } else {
-                    child.progress.unwrap_or(0)
+        let rows = sqlx::query_as::<_, (String, String, String)>(
+            r#"
+            SELECT id, title, browser_actions
+            FROM tasks
+            WHERE browser_actions IS NOT NULL AND browser_actions != '' AND deleted_at IS NULL
+            ORDER BY created_at ASC
+            "#,
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+        
+        let actions: Vec<(String, String, BrowserAction)> = rows
+            .into_iter()
+            .filter_map(|(id, title, json)| {
+                serde_json::from_str::<BrowserActionSettings>(&json)
+                    .map_err(|e| log::warn!("Failed to parse browser actions for task {}: {}", id, e))
+                    .ok()
+                    .map(|settings| (id, title, settings.actions))
+            })
+            .flat_map(|(id, title, actions)| {
+                actions.into_iter().map(move |action| (id.clone(), title.clone(), action))
+            })
+            // コマンドアクションはURLではないので対象外
+            .filter(|(_, _, action)| action.kind == BrowserActionKind::Url)
+            .collect();
+        
+        let unreachable = stream::iter(actions)
+            .map(|(task_id, task_title, action)| async move {
+                let result = checker.check_url_health(&action.url).await;
+                if result.is_valid {
+                    return None;
                 }
+                Some(UnreachableBrowserAction {
+                    task_id,
+                    task_title,
+                    action_id: action.id,
+                    label: action.label,
+                    url: action.url,
+                    error: result.error,
+                })
             })
-            .sum();
+            .buffered(URL_HEALTH_CONCURRENCY)
+            .filter_map(|issue| async move { issue })
+            .collect()
+            .await;
         
-        total_progress / children.len() as i32
+        Ok(unreachable)
     }
     
-    pub async fn update_progress(&self, id: &str, progress: i32) -> Result<Task, AppError> {
-        if !(0..=100).contains(&progress) {
-            return Err(AppError::InvalidInput("Progress must be between 0 and 100".to_string()));
+    // 通知プリセット
+    pub async fn get_notification_presets(&self) -> Result<Vec<NotificationPreset>, AppError> {
+        let presets = self.load_notification_presets().await?;
+        Ok(presets
+            .into_iter()
+            .map(|(name, settings)| NotificationPreset { name, settings })
+            .collect())
+    }
+    
+    pub async fn save_notification_preset(&self, preset: NotificationPreset) -> Result<NotificationPreset, AppError> {
+        let name = preset.name.trim().to_string();
+        if name.is_empty() {
+            return Err(AppError::InvalidInput("Preset name must not be empty".to_string()));
         }
+        Self::validate_notification_settings(&preset.settings)?;
         
-        let mut task = self.get_task_by_id(id).await?;
-        task.progress = Some(progress);
-        task.updated_at = Utc::now().to_rfc3339();
+        let mut presets = self.load_notification_presets().await?;
+        presets.insert(name.clone(), preset.settings.clone());
+        SettingsService::set_json(&self.db.pool, NOTIFICATION_PRESETS_KEY, &presets).await?;
         
-        // タスクが100%完了の場合、ステータスをdoneに変更
-        if progress == 100 && task.status != "done" {
-            task.status = "done".to_string();
-            task.completed_at = Some(Utc::now().to_rfc3339());
+        Ok(NotificationPreset { name, settings: preset.settings })
+    }
+    
+    pub async fn delete_notification_preset(&self, name: &str) -> Result<(), AppError> {
+        let mut presets = self.load_notification_presets().await?;
+        if presets.remove(name).is_none() {
+            return Err(AppError::NotFound(format!("Notification preset '{}' not found", name)));
         }
+        SettingsService::set_json(&self.db.pool, NOTIFICATION_PRESETS_KEY, &presets).await?;
+        Ok(())
+    }
+    
+    /// プリセットの通知設定をタスクに適用
+    pub async fn apply_notification_preset(&self, task_id: &str, preset_name: &str) -> Result<Task, AppError> {
+        let presets = self.load_notification_presets().await?;
+        let settings = presets
+            .get(preset_name)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Notification preset '{}' not found", preset_name)))?;
+        Self::validate_notification_settings(&settings)?;
         
-        sqlx::query(
-            r#"
-            UPDATE tasks 
-            SET progress = ?2, status = ?3, completed_at = ?4, updated_at = ?5
-            WHERE id = ?1
-            "#,
-        )
-        .bind(&task.id)
-        .bind(task.progress)
-        .bind(&task.status)
-        .bind(&task.completed_at)
-        .bind(&task.updated_at)
-        .execute(&self.db.pool)
-        .await?;
+        self.update_task(task_id, UpdateTaskRequest {
+            title: None,
+            description: None,
+            status: None,
+            parent_id: None,
+            due_date: None,
+            notification_settings: Some(settings),
+            browser_actions: None,
+            tags: None,
+        }).await
+    }
+    
+    /// あるタスクの通知設定を複数のタスクにまとめてコピー
+    ///
+    /// コピー先が1件でも存在しなければ、どのタスクも更新しない。
+    pub async fn copy_notification_settings(&self, from_id: &str, to_ids: Vec<String>) -> Result<Vec<Task>, AppError> {
+        let source = self.get_task_by_id(from_id).await?;
+        let settings = source.notification_settings();
+        Self::validate_notification_settings(&settings)?;
+        let days_of_week = settings.days_of_week
+            .as_ref()
+            .map(|days| serde_json::to_string(days).unwrap_or_default());
         
-        // 親タスクがある場合は親の進捗率も更新
-        if let Some(parent_id) = &task.parent_id {
-            self.calculate_and_update_progress(parent_id).await?;
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.db.pool.begin().await?;
+        for to_id in &to_ids {
+            let result = sqlx::query(
+                r#"
+                UPDATE tasks
+                SET notification_type = ?2, notification_days_before = ?3, notification_time = ?4,
+                    notification_days_of_week = ?5, notification_level = ?6, notification_until = ?7,
+                    updated_at = ?8
+                WHERE id = ?1
+                "#,
+            )
+            .bind(to_id)
+            .bind(&settings.notification_type)
+            .bind(settings.days_before)
+            .bind(&settings.notification_time)
+            .bind(&days_of_week)
+            .bind(settings.level)
+            .bind(&settings.notification_until)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+            
+            if result.rows_affected() == 0 {
+                return Err(AppError::NotFound(format!("Task with id {} not found", to_id)));
+            }
         }
+        tx.commit().await?;
+        invalidate_task_context_cache();
         
-        Ok(task)
+        let mut updated = Vec::with_capacity(to_ids.len());
+        for to_id in &to_ids {
+            updated.push(self.get_task_by_id(to_id).await?);
+        }
+        Ok(updated)
     }
     
-    pub async fn get_root_tasks(&self) -> Result<Vec<Task>, AppError> {
-        let tasks = sqlx::query_as::<_, Task>(
+    /// 複数のタスクのブラウザアクションをまとめて削除し、削除したタスク数を返す
+    ///
+    /// `all`がtrueなら`task_ids`によらずすべてのタスクから削除する。
+    pub async fn clear_browser_actions(&self, task_ids: Vec<String>, all: bool) -> Result<u64, AppError> {
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.db.pool.begin().await?;
+        let cleared = if all {
+            sqlx::query("UPDATE tasks SET browser_actions = NULL, updated_at = ?1 WHERE browser_actions IS NOT NULL")
+                .bind(&now)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected()
+        } else {
+            let mut cleared = 0;
+            for task_id in &task_ids {
+                cleared += sqlx::query(
+                    "UPDATE tasks SET browser_actions = NULL, updated_at = ?2 WHERE id = ?1 AND browser_actions IS NOT NULL",
+                )
+                .bind(task_id)
+                .bind(&now)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            }
+            cleared
+        };
+        tx.commit().await?;
+        if cleared > 0 {
+            invalidate_task_context_cache();
+        }
+        Ok(cleared)
+    }
+    
+    /// 複数のタスクの期日を今日から`days`日後（`business_days`なら営業日後）にまとめて設定
+    ///
+    /// 既存の期日の時刻は保ち、期日のないタスクはその日の0時にする。1件でも存在しなければどのタスクも更新しない。
+    pub async fn set_due_date_relative(&self, task_ids: Vec<String>, days: i64, business_days: bool) -> Result<Vec<Task>, AppError> {
+        self.set_due_date_relative_at(task_ids, days, business_days, Utc::now()).await
+    }
+    
+    pub async fn set_due_date_relative_at(
+        &self,
+        task_ids: Vec<String>,
+        days: i64,
+        business_days: bool,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Task>, AppError> {
+        if days.abs() > MAX_RELATIVE_DUE_DAYS {
+            return Err(AppError::InvalidInput(format!(
+                "Days must be between -{} and {}: {}",
+                MAX_RELATIVE_DUE_DAYS, MAX_RELATIVE_DUE_DAYS, days
+            )));
+        }
+        
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let today = timezone.local_date(now);
+        let target_date = if business_days {
+            let days = u32::try_from(days)
+                .map_err(|_| AppError::InvalidInput(format!("Business days must not be negative: {}", days)))?;
+            let settings: BusinessDaySettings = SettingsService::get_json(&self.db.pool, BusinessDaySettings::SETTINGS_KEY)
+                .await?
+                .unwrap_or_default();
+            TemporalContext::add_business_days(today, days, &settings)
+                .ok_or_else(|| AppError::Internal("No business day found".to_string()))?
+        } else {
+            today + Duration::days(days)
+        };
+        
+        let updated_at = now.to_rfc3339();
+        let mut tx = self.db.pool.begin().await?;
+        for task_id in &task_ids {
+            let current_due = sqlx::query_scalar::<_, Option<String>>("SELECT due_date FROM tasks WHERE id = ?1 AND deleted_at IS NULL")
+                .bind(task_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Task with id {} not found", task_id)))?;
+            let time = current_due
+                .as_deref()
+                .and_then(|due| DateTime::parse_from_rfc3339(due).ok())
+                .map(|due| timezone.to_local(due.with_timezone(&Utc)).time())
+                .unwrap_or(NaiveTime::MIN);
+            let due_date = timezone
+                .from_local(target_date.and_time(time))
+                .ok_or_else(|| AppError::Internal(format!("Invalid local due date: {}", target_date)))?;
+            
+            sqlx::query("UPDATE tasks SET due_date = ?2, updated_at = ?3 WHERE id = ?1")
+                .bind(task_id)
+                .bind(due_date.to_rfc3339())
+                .bind(&updated_at)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        invalidate_task_context_cache();
+        
+        let mut updated = Vec::with_capacity(task_ids.len());
+        for task_id in &task_ids {
+            updated.push(self.get_task_by_id(task_id).await?);
+        }
+        Ok(updated)
+    }
+    
+    async fn load_notification_presets(&self) -> Result<BTreeMap<String, TaskNotificationSettings>, AppError> {
+        Ok(SettingsService::get_json(&self.db.pool, NOTIFICATION_PRESETS_KEY)
+            .await?
+            .unwrap_or_default())
+    }
+    
+    /// 未完了タスクの期日に保存されているオフセットを、アプリのタイムゾーンと照合する
+    pub async fn audit_due_date_timezones(&self) -> Result<Vec<DueDateTimezoneAudit>, AppError> {
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let rows = sqlx::query_as::<_, (String, String, String)>(
             r#"
-            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level
+            SELECT id, title, due_date
             FROM tasks
-            WHERE parent_id IS NULL
-            ORDER BY 
-                CASE status 
-                    WHEN 'inbox' THEN 1
-                    WHEN 'todo' THEN 2
-                    WHEN 'in_progress' THEN 3
-                    WHEN 'done' THEN 4
-                END,
-                CASE notification_level
-                    WHEN 3 THEN 1
-                    WHEN 2 THEN 2
-                    WHEN 1 THEN 3
-                    ELSE 4
-                END,
-                created_at DESC
+            WHERE status != 'done' AND due_date IS NOT NULL AND deleted_at IS NULL
+            ORDER BY due_date ASC
             "#,
         )
         .fetch_all(&self.db.pool)
         .await?;
         
-        Ok(tasks)
+        Ok(rows
+            .into_iter()
+            .map(|(task_id, title, due_date)| {
+                let parsed = DateTime::parse_from_rfc3339(&due_date).ok();
+                let offset_seconds = parsed.map(|d| d.offset().local_minus_utc());
+                let app_offset_seconds = parsed.map(|d| timezone.utc_offset_seconds(d.with_timezone(&Utc)));
+                DueDateTimezoneAudit {
+                    matches_app_timezone: offset_seconds.is_some() && offset_seconds == app_offset_seconds,
+                    task_id,
+                    title,
+                    due_date,
+                    offset_seconds,
+                    app_offset_seconds,
+                }
+            })
+            .collect())
+    }
+    
+    /// 子タスクの最近の完了ペースから、残りの子タスクが終わる日を見積もる
+    pub async fn estimate_completion_eta(&self, parent_id: &str) -> Result<Option<NaiveDate>, AppError> {
+        self.estimate_completion_eta_at(parent_id, Utc::now()).await
+    }
+    
+    /// 指定時刻を基準に完了予定日を見積もる
+    ///
+    /// 直近`ETA_VELOCITY_WINDOW_DAYS`日間に完了した子タスク数から1日あたりの完了数を求め、
+    /// 未完了の子タスク数をそのペースで割って日数を切り上げる。完了ペースが0ならNone。
+    /// 未完了の子タスクがなければ今日（ローカル日付）を返す。
+    pub async fn estimate_completion_eta_at(&self, parent_id: &str, now: DateTime<Utc>) -> Result<Option<NaiveDate>, AppError> {
+        self.get_task_by_id(parent_id).await?;
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let today = timezone.local_date(now);
+        
+        let children = self.get_children(parent_id).await?;
+        let rules = self.get_subtask_completion_rules().await?;
+        let remaining = rules
+            .counted_children(&children)
+            .iter()
+            .filter(|child| child.status != "done")
+            .count() as i64;
+        if remaining == 0 {
+            return Ok(Some(today));
+        }
+        
+        let window_start = now - Duration::days(ETA_VELOCITY_WINDOW_DAYS);
+        let completed_recently = children
+            .iter()
+            .filter(|child| child.status == "done")
+            .filter_map(|child| child.completed_at.as_deref())
+            .filter_map(|completed_at| DateTime::parse_from_rfc3339(completed_at).ok())
+            .filter(|completed_at| *completed_at > window_start && *completed_at <= now)
+            .count() as i64;
+        if completed_recently == 0 {
+            return Ok(None);
+        }
+        
+        // remaining / (completed_recently / window) を切り上げ
+        let days = (remaining * ETA_VELOCITY_WINDOW_DAYS + completed_recently - 1) / completed_recently;
+        Ok(Some(today + Duration::days(days)))
+    }
+    
+    /// 未完了タスクを期日の区分ごとに取得
+    pub async fn get_tasks_by_due_bucket(&self) -> Result<BTreeMap<DueBucket, Vec<Task>>, AppError> {
+        self.get_tasks_by_due_bucket_at(Utc::now()).await
     }
     
-    // 新しい通知システム
-    pub async fn check_notifications(&self) -> Result<Vec<crate::models::TaskNotification>, AppError> {
-        use chrono::{DateTime, Utc, Local, Weekday, Datelike};
+    /// 指定時刻を基準に未完了タスクを期日の区分ごとに取得（日付はアプリのタイムゾーンで判定）
+    pub async fn get_tasks_by_due_bucket_at(&self, now: DateTime<Utc>) -> Result<BTreeMap<DueBucket, Vec<Task>>, AppError> {
+        let timezone = AppTimezone::load(&self.db.pool).await;
+        let today = timezone.local_date(now);
         
         let tasks = sqlx::query_as::<_, Task>(
             r#"
-            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
             FROM tasks
-            WHERE status != 'done' 
-              AND notification_type IS NOT NULL 
-              AND notification_type != 'none'
+            WHERE status != 'done' AND deleted_at IS NULL
+            ORDER BY due_date IS NULL, due_date ASC, created_at DESC
             "#,
         )
         .fetch_all(&self.db.pool)
         .await?;
         
-        if !tasks.is_empty() {
-            println!("NotificationCheck: Found {} tasks with notifications at {} (Local: {})", 
-                     tasks.len(), 
-                     Utc::now().format("%H:%M:%S UTC"),
-                     Local::now().format("%H:%M:%S JST"));
-        }
-        
-        let mut notifications = Vec::new();
-        let now_local = Local::now();
-        let now = now_local.naive_local().and_utc(); // ローカル時刻をnaive形式でUTCとして扱う
+        let mut buckets: BTreeMap<DueBucket, Vec<Task>> = DueBucket::ALL
+            .iter()
+            .map(|bucket| (*bucket, Vec::new()))
+            .collect();
         
-        for task in &tasks {
-            let notification_type = task.notification_type.as_deref().unwrap_or("none");
+        for mut task in tasks {
+            let due_date = task.due_date.as_deref()
+                .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+                .map(|d| timezone.local_date(d.with_timezone(&Utc)));
             
-            match notification_type {
-                "due_date_based" => {
-                    if let Some(due_date_str) = &task.due_date {
-                        if let Ok(due_date) = DateTime::parse_from_rfc3339(due_date_str) {
-                            // 期日もローカル時刻として解釈
-                            let due_date_local = due_date.naive_utc().and_local_timezone(chrono::Local).unwrap();
-                            
-                            // notification_timeが設定されている場合は、期限時刻として使用
-                            let target_due_time = if let Some(time_str) = &task.notification_time {
-                                if let Ok(target_time) = chrono::NaiveTime::parse_from_str(time_str, "%H:%M") {
-                                    // 期日の日付 + 指定された時刻
-                                    due_date_local.date_naive().and_time(target_time).and_local_timezone(chrono::Local).unwrap()
-                                } else {
-                                    due_date_local
-                                }
-                            } else {
-                                due_date_local
-                            };
-                            
-                            let target_due_as_utc = target_due_time.naive_local().and_utc();
-                            let hours_until_due = (target_due_as_utc - now).num_hours();
-                            let days_before = task.notification_days_before.unwrap_or(1);
-                            let notification_start_hours = days_before as i64 * 24;
-                            
-                            println!("NotificationCheck: Task '{}' - Target Due: {} JST, Current: {} JST, Hours until: {}", 
-                                     task.title, 
-                                     target_due_time.format("%m/%d %H:%M"),
-                                     now_local.format("%m/%d %H:%M"),
-                                     hours_until_due);
-                            
-                            // 期日ベース通知の判定：指定日数前から毎時0分に通知
-                            if hours_until_due <= notification_start_hours && hours_until_due >= 0 {
-                                // 毎時0分±1分（0分、1分）で通知
-                                use chrono::Timelike;
-                                let minutes = now_local.minute();
-                                let is_notification_time = minutes <= 1;
-                                
-                                if is_notification_time {
-                                    println!("NotificationCheck: ✅ Creating due-date notification for task: {} ({}h until target due time {}) at {}:{:02}", 
-                                             task.title, hours_until_due, target_due_time.format("%H:%M"), now_local.hour(), minutes);
-                                    notifications.push(crate::models::TaskNotification {
-                                        task_id: task.id.clone(),
-                                        title: task.title.clone(),
-                                        level: task.notification_level.unwrap_or(1),
-                                        days_until_due: Some(hours_until_due / 24),
-                                        notification_type: "due_date_based".to_string(),
-                                    });
-                                }
-                            }
-                        }
-                    }
-                },
-                "recurring" => {
-                    // 定期通知の判定
-                    if let (Some(days_str), Some(time_str)) = (&task.notification_days_of_week, &task.notification_time) {
-                        if let Ok(days_of_week) = serde_json::from_str::<Vec<i32>>(days_str) {
-                            let current_weekday = match now_local.weekday() {
-                                Weekday::Sun => 0,
-                                Weekday::Mon => 1,
-                                Weekday::Tue => 2,
-                                Weekday::Wed => 3,
-                                Weekday::Thu => 4,
-                                Weekday::Fri => 5,
-                                Weekday::Sat => 6,
-                            };
-                            
-                            if days_of_week.contains(&current_weekday) && should_notify_at_time(&now_local, time_str) {
-                                notifications.push(crate::models::TaskNotification {
-                                    task_id: task.id.clone(),
-                                    title: task.title.clone(),
-                                    level: task.notification_level.unwrap_or(1),
-                                    days_until_due: None,
-                                    notification_type: "recurring".to_string(),
-                                });
-                            }
-                        }
-                    }
+            let bucket = match due_date {
+                None => DueBucket::NoDate,
+                Some(date) => match (date - today).num_days() {
+                    d if d < 0 => DueBucket::Overdue,
+                    0 => DueBucket::Today,
+                    1..=6 => DueBucket::ThisWeek,
+                    _ => DueBucket::Later,
                 },
-                _ => {} // 'none' or unknown type
-            }
+            };
+            
+            task.tags = self.get_tags_for_task(&task.id).await.ok();
+            buckets.entry(bucket).or_default().push(task);
         }
         
-        if !notifications.is_empty() {
-            println!("NotificationCheck: Generated {} notifications:", notifications.len());
-            for notification in &notifications {
-                println!("  - {} (Level {}, {})", notification.title, notification.level, notification.notification_type);
-            }
+        Ok(buckets)
+    }
+    
+    /// 期限切れの未完了タスクを取得（期限超過が長い順）
+    pub async fn get_overdue_tasks(&self) -> Result<Vec<Task>, AppError> {
+        self.get_overdue_tasks_at(Utc::now()).await
+    }
+    
+    pub async fn get_overdue_tasks_at(&self, now: DateTime<Utc>) -> Result<Vec<Task>, AppError> {
+        let mut tasks = self.fetch_overdue_tasks(now).await?;
+        for task in &mut tasks {
+            task.tags = self.get_tags_for_task(&task.id).await.ok();
         }
-        
-        Ok(notifications)
+        Ok(tasks)
+    }
+    
+    /// 期限切れの未完了タスク数（トレイのバッジ表示用）
+    pub async fn get_overdue_count(&self) -> Result<usize, AppError> {
+        self.get_overdue_count_at(Utc::now()).await
+    }
+    
+    pub async fn get_overdue_count_at(&self, now: DateTime<Utc>) -> Result<usize, AppError> {
+        Ok(self.fetch_overdue_tasks(now).await?.len())
     }
-}
-
-// 指定時刻での通知判定（±30秒の範囲）
-fn should_notify_at_time<T>(now: &chrono::DateTime<T>, time_str: &str) -> bool 
-where T: chrono::TimeZone {
-    use chrono::{NaiveTime, Timelike};
     
-    if let Ok(target_time) = NaiveTime::parse_from_str(time_str, "%H:%M") {
-        let current_time = now.time();
-        let target_seconds = target_time.num_seconds_from_midnight();
-        let current_seconds = current_time.num_seconds_from_midnight();
+    /// 期日は保存時のオフセットがまちまちなため、文字列比較ではなく時刻に変換して判定する
+    async fn fetch_overdue_tasks(&self, now: DateTime<Utc>) -> Result<Vec<Task>, AppError> {
+        let tasks = sqlx::query_as::<_, Task>(
+            r#"
+            SELECT id, title, description, status, parent_id, due_date, completed_at, created_at, updated_at, progress, notification_type, notification_days_before, notification_time, notification_days_of_week, notification_level, notification_until, browser_actions, is_pinned, status_changed_at, is_optional, notification_channels, recurrence_rule, recurrence_end, archived_at, deleted_at
+            FROM tasks
+            WHERE status != 'done' AND due_date IS NOT NULL
+              AND archived_at IS NULL AND deleted_at IS NULL
+            "#,
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
         
-        let time_diff = (current_seconds as i32 - target_seconds as i32).abs();
+        let mut overdue: Vec<(DateTime<Utc>, Task)> = tasks
+            .into_iter()
+            .filter_map(|task| {
+                let due = task.due_date.as_deref()
+                    .and_then(|d| DateTime::parse_from_rfc3339(d).ok())?
+                    .with_timezone(&Utc);
+                (due < now).then_some((due, task))
+            })
+            .collect();
+        // 期日が古いほど超過が長い
+        overdue.sort_by(|(a, a_task), (b, b_task)| a.cmp(b).then_with(|| a_task.created_at.cmp(&b_task.created_at)));
         
-        // ±30秒の範囲
-        time_diff <= 30
-    } else {
-        false
+        Ok(overdue.into_iter().map(|(_, task)| task).collect())
     }
 }
 
 
 impl TaskService {
     // タグ関連メソッド
+    /// 指定したタグを持つタスクを取得
+    pub async fn get_tasks_by_tag(&self, tag_id: &str) -> Result<Vec<Task>, AppError> {
+        self.get_tasks_by_tags(&[tag_id.to_string()], true).await
+    }
+    
+    /// 指定したタグかその子孫のタグを持つタスクを取得
+    pub async fn get_tasks_by_tag_recursive(&self, tag_id: &str) -> Result<Vec<Task>, AppError> {
+        let tag_ids = TagService::get_descendant_tag_ids(&self.db.pool, tag_id).await?;
+        self.get_tasks_by_tags(&tag_ids, false).await
+    }
+    
+    /// 指定したタグを持つタスクを取得（`match_all`ならすべてのタグ、そうでなければいずれかのタグ）
+    pub async fn get_tasks_by_tags(&self, tag_ids: &[String], match_all: bool) -> Result<Vec<Task>, AppError> {
+        let tag_ids: Vec<&String> = tag_ids.iter().collect::<HashSet<_>>().into_iter().collect();
+        if tag_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let placeholders = (1..=tag_ids.len())
+            .map(|i| format!("?{}", i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            r#"
+            SELECT t.id, t.title, t.description, t.status, t.parent_id, t.due_date, t.completed_at, t.created_at, t.updated_at, t.progress, t.notification_type, t.notification_days_before, t.notification_time, t.notification_days_of_week, t.notification_level, t.notification_until, t.browser_actions, t.is_pinned, t.status_changed_at, t.is_optional, t.notification_channels, t.recurrence_rule, t.recurrence_end, t.archived_at, t.deleted_at
+            FROM tasks t
+            JOIN task_tags tt ON tt.task_id = t.id
+            WHERE tt.tag_id IN ({}) AND t.deleted_at IS NULL
+            GROUP BY t.id
+            HAVING COUNT(DISTINCT tt.tag_id) >= ?{}
+            ORDER BY 
+                CASE t.status 
+                    WHEN 'inbox' THEN 1
+                    WHEN 'todo' THEN 2
+                    WHEN 'in_progress' THEN 3
+                    WHEN 'done' THEN 4
+                END,
+                t.is_pinned DESC,
+                CASE t.notification_level
+                    WHEN 3 THEN 1
+                    WHEN 2 THEN 2
+                    WHEN 1 THEN 3
+                    ELSE 4
+                END,
+                t.created_at DESC
+            "#,
+            placeholders,
+            tag_ids.len() + 1
+        );
+        
+        let mut query = sqlx::query_as::<_, Task>(&sql);
+        for tag_id in &tag_ids {
+            query = query.bind(*tag_id);
+        }
+        let required = if match_all { tag_ids.len() as i64 } else { 1 };
+        let mut tasks = query.bind(required).fetch_all(&self.db.pool).await?;
+        
+        for task in &mut tasks {
+            task.tags = self.get_tags_for_task(&task.id).await.ok();
+        }
+        
+        Ok(tasks)
+    }
+    
     pub async fn get_all_tags(&self) -> Result<Vec<Tag>, AppError> {
         TagService::get_all_tags(&self.db.pool).await
     }
@@ -750,6 +2868,22 @@ impl TaskService {
         TagService::delete_tag(&self.db.pool, id).await
     }
     
+    pub async fn delete_tag_cascade(&self, id: &str) -> Result<usize, AppError> {
+        TagService::delete_tag_cascade(&self.db.pool, id).await
+    }
+    
+    pub async fn set_tag_parent(&self, id: &str, parent_id: Option<&str>) -> Result<Tag, AppError> {
+        TagService::set_tag_parent(&self.db.pool, id, parent_id).await
+    }
+    
+    pub async fn get_child_tags(&self, parent_id: &str) -> Result<Vec<Tag>, AppError> {
+        TagService::get_child_tags(&self.db.pool, parent_id).await
+    }
+    
+    pub async fn get_tag_tree(&self) -> Result<Vec<TagTreeNode>, AppError> {
+        TagService::get_tag_tree(&self.db.pool).await
+    }
+    
     pub async fn add_tag_to_task(&self, task_id: &str, tag_id: &str) -> Result<(), AppError> {
         TagService::add_tag_to_task(&self.db.pool, task_id, tag_id).await
     }
@@ -761,4 +2895,8 @@ impl TaskService {
     pub async fn get_tags_for_task(&self, task_id: &str) -> Result<Vec<Tag>, AppError> {
         TagService::get_tags_for_task(&self.db.pool, task_id).await
     }
+    
+    pub async fn suggest_tags(&self, query: &str, limit: usize) -> Result<Vec<Tag>, AppError> {
+        TagService::suggest_tags(&self.db.pool, query, limit).await
+    }
 }
\ No newline at end of file
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::Task;
+
+/// 類似度付きタスク
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarTask {
+    pub task: Task,
+    /// 埋め込みのコサイン類似度（-1〜1、大きいほど似ている）
+    pub similarity: f32,
+}
+
+/// 埋め込みを保存用のバイト列（f32のリトルエンディアン）に変換
+pub fn encode_embedding(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+/// `encode_embedding`で保存したバイト列を埋め込みに戻す（長さが4の倍数でなければNone）
+pub fn decode_embedding(bytes: &[u8]) -> Option<Vec<f32>> {
+    if bytes.len() % 4 != 0 {
+        return None;
+    }
+    Some(
+        bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect(),
+    )
+}
+
+/// 2つの埋め込みのコサイン類似度（次元が異なる・ゼロベクトルの場合はNone）
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.is_empty() || a.len() != b.len() {
+        return None;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return None;
+    }
+    Some(dot / (norm_a * norm_b))
+}
+
+/// 埋め込みを計算する文章（説明文、空ならタイトル）
+pub fn embedding_text(task: &Task) -> &str {
+    task.description
+        .as_deref()
+        .map(str::trim)
+        .filter(|description| !description.is_empty())
+        .unwrap_or(task.title.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_similarity_and_encoding() {
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]), Some(1.0));
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 3.0]), Some(0.0));
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[-2.0, 0.0]), Some(-1.0));
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0, 0.0, 0.0]), None);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), None);
+
+        let embedding = vec![0.25, -1.5, 3.0];
+        assert_eq!(decode_embedding(&encode_embedding(&embedding)), Some(embedding));
+        assert_eq!(decode_embedding(&[0, 0, 0]), None);
+    }
+}
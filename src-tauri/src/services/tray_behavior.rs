@@ -0,0 +1,101 @@
+use crate::error::AppError;
+use crate::services::SettingsService;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::sync::RwLock;
+
+/// システムトレイアイコンのID
+pub const TRAY_ID: &str = "main";
+
+/// トレイアイコンを左クリックしたときの動作
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayClickBehavior {
+    /// 表示中なら隠し、隠れていれば表示する
+    Toggle,
+    /// 常に表示する（非表示にはしない）
+    #[default]
+    Show,
+    /// トレイメニューを開く（ウィンドウは操作しない）
+    Menu,
+}
+
+impl TrayClickBehavior {
+    /// 設定キー（agent_configテーブル）
+    pub const SETTINGS_KEY: &'static str = "tray_click_behavior";
+}
+
+/// クリック時にウィンドウへ行う操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayClickAction {
+    Show,
+    Hide,
+    Nothing,
+}
+
+/// 設定と現在のウィンドウの表示状態からクリック時の操作を決める
+pub fn decide_tray_click_action(behavior: TrayClickBehavior, window_visible: bool) -> TrayClickAction {
+    match behavior {
+        TrayClickBehavior::Toggle if window_visible => TrayClickAction::Hide,
+        TrayClickBehavior::Toggle | TrayClickBehavior::Show => TrayClickAction::Show,
+        TrayClickBehavior::Menu => TrayClickAction::Nothing,
+    }
+}
+
+/// トレイのイベントハンドラー（同期処理）から参照するクリック動作の設定
+///
+/// 起動時にDBから読み込み、変更時はDBとメモリの両方を更新する。
+pub struct TrayClickState {
+    db: SqlitePool,
+    behavior: RwLock<TrayClickBehavior>,
+}
+
+impl TrayClickState {
+    pub async fn load(db: SqlitePool) -> Self {
+        let behavior = SettingsService::get_json(&db, TrayClickBehavior::SETTINGS_KEY)
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        Self {
+            db,
+            behavior: RwLock::new(behavior),
+        }
+    }
+
+    pub fn behavior(&self) -> TrayClickBehavior {
+        self.behavior.read().map(|b| *b).unwrap_or_default()
+    }
+
+    pub async fn set_behavior(&self, behavior: TrayClickBehavior) -> Result<(), AppError> {
+        SettingsService::set_json(&self.db, TrayClickBehavior::SETTINGS_KEY, &behavior).await?;
+        if let Ok(mut current) = self.behavior.write() {
+            *current = behavior;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decide_tray_click_action() {
+        let cases = [
+            (TrayClickBehavior::Toggle, true, TrayClickAction::Hide),
+            (TrayClickBehavior::Toggle, false, TrayClickAction::Show),
+            (TrayClickBehavior::Show, true, TrayClickAction::Show),
+            (TrayClickBehavior::Show, false, TrayClickAction::Show),
+            (TrayClickBehavior::Menu, true, TrayClickAction::Nothing),
+            (TrayClickBehavior::Menu, false, TrayClickAction::Nothing),
+        ];
+        for (behavior, visible, expected) in cases {
+            assert_eq!(decide_tray_click_action(behavior, visible), expected, "{:?} visible={}", behavior, visible);
+        }
+
+        // 既定は従来どおり表示のみ
+        assert_eq!(TrayClickBehavior::default(), TrayClickBehavior::Show);
+        assert_eq!(serde_json::to_string(&TrayClickBehavior::Toggle).unwrap(), "\"toggle\"");
+    }
+}
@@ -0,0 +1,235 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::models::Task;
+
+/// 緊急度スコアの重み設定
+///
+/// スコアは次の式で計算する:
+///
+/// ```text
+/// score = due_weight * due + level_weight * level + age_weight * age
+/// ```
+///
+/// - `due`   : 期日なし = 0、残り d 日 = 1 / (1 + d)、d 日超過 = 1 + min(d, 14) / 14（0〜2）
+/// - `level` : 通知レベル 1〜3 を (level - 1) / 2 で 0〜1 に正規化
+/// - `age`   : 作成からの経過日数を min(days, 30) / 30 で 0〜1 に正規化
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrgencyWeights {
+    pub due_weight: f64,
+    pub level_weight: f64,
+    pub age_weight: f64,
+}
+
+impl Default for UrgencyWeights {
+    fn default() -> Self {
+        Self {
+            due_weight: 3.0,
+            level_weight: 2.0,
+            age_weight: 1.0,
+        }
+    }
+}
+
+impl UrgencyWeights {
+    /// 設定キー（agent_configテーブル）
+    pub const SETTINGS_KEY: &'static str = "urgency_weights";
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("due_weight", self.due_weight),
+            ("level_weight", self.level_weight),
+            ("age_weight", self.age_weight),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!("{} must be a non-negative number", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// スコア付きタスク
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskUrgency {
+    pub task: Task,
+    pub score: f64,
+}
+
+/// 緊急度スコアのキャッシュ有効期間（経過日数・期日までの残りで値が変わるため短めにする）
+pub const URGENCY_CACHE_TTL_SECONDS: u64 = 60;
+
+struct CachedUrgencyScores {
+    scores: Vec<TaskUrgency>,
+    weights: UrgencyWeights,
+    generation: u64,
+    computed_at: Instant,
+}
+
+/// 直近に計算した未完了タスクの緊急度スコア（降順）
+///
+/// 有効期間を過ぎたとき、計算後にタスクが変更されて世代番号が進んだとき、
+/// 重み設定が変わったときは使わない。
+#[derive(Default)]
+pub struct UrgencyScoreCache {
+    entry: Mutex<Option<CachedUrgencyScores>>,
+}
+
+impl UrgencyScoreCache {
+    pub fn get(&self, generation: u64, weights: &UrgencyWeights, now: Instant) -> Option<Vec<TaskUrgency>> {
+        let entry = self.entry.lock().unwrap();
+        entry
+            .as_ref()
+            .filter(|cached| {
+                cached.generation == generation
+                    && cached.weights == *weights
+                    && now.saturating_duration_since(cached.computed_at).as_secs() < URGENCY_CACHE_TTL_SECONDS
+            })
+            .map(|cached| cached.scores.clone())
+    }
+
+    pub fn store(&self, scores: Vec<TaskUrgency>, weights: UrgencyWeights, generation: u64, computed_at: Instant) {
+        *self.entry.lock().unwrap() = Some(CachedUrgencyScores { scores, weights, generation, computed_at });
+    }
+
+    pub fn clear(&self) {
+        *self.entry.lock().unwrap() = None;
+    }
+}
+
+const OVERDUE_SATURATION_DAYS: f64 = 14.0;
+const AGE_SATURATION_DAYS: f64 = 30.0;
+
+/// 期日・通知レベル・経過日数から緊急度スコアを計算
+pub fn calculate_urgency_score(
+    due_date: Option<DateTime<Utc>>,
+    notification_level: i32,
+    created_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+    weights: &UrgencyWeights,
+) -> f64 {
+    let due = match due_date {
+        Some(due) => {
+            let days_until = (due - now).num_seconds() as f64 / 86_400.0;
+            if days_until >= 0.0 {
+                1.0 / (1.0 + days_until)
+            } else {
+                1.0 + (-days_until).min(OVERDUE_SATURATION_DAYS) / OVERDUE_SATURATION_DAYS
+            }
+        }
+        None => 0.0,
+    };
+
+    let level = (notification_level.clamp(1, 3) - 1) as f64 / 2.0;
+
+    let age_days = ((now - created_at).num_seconds() as f64 / 86_400.0).max(0.0);
+    let age = age_days.min(AGE_SATURATION_DAYS) / AGE_SATURATION_DAYS;
+
+    weights.due_weight * due + weights.level_weight * level + weights.age_weight * age
+}
+
+/// タスクの緊急度スコアを計算
+pub fn task_urgency_score(task: &Task, now: DateTime<Utc>, weights: &UrgencyWeights) -> f64 {
+    let parse = |s: &str| DateTime::parse_from_rfc3339(s).ok().map(|d| d.with_timezone(&Utc));
+
+    let due_date = task.due_date.as_deref().and_then(parse);
+    let created_at = parse(&task.created_at).unwrap_or(now);
+
+    calculate_urgency_score(
+        due_date,
+        task.notification_level.unwrap_or(1),
+        created_at,
+        now,
+        weights,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_overdue_high_level_beats_far_off_low_level() {
+        let weights = UrgencyWeights::default();
+        let now = now();
+
+        let overdue_high = calculate_urgency_score(
+            Some(now - Duration::days(2)), 3, now - Duration::days(5), now, &weights,
+        );
+        let far_low = calculate_urgency_score(
+            Some(now + Duration::days(60)), 1, now - Duration::days(5), now, &weights,
+        );
+
+        assert!(overdue_high > far_low);
+        // 2日超過: due = 1 + 2/14, level = 1, age = 5/30
+        let expected = 3.0 * (1.0 + 2.0 / 14.0) + 2.0 + 5.0 / 30.0;
+        assert!((overdue_high - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_score_components() {
+        let weights = UrgencyWeights::default();
+        let now = now();
+
+        // 期日なし・レベル1・作成直後はゼロ
+        assert_eq!(calculate_urgency_score(None, 1, now, now, &weights), 0.0);
+
+        // 期日が今日なら due = 1
+        let due_now = calculate_urgency_score(Some(now), 1, now, now, &weights);
+        assert!((due_now - 3.0).abs() < 1e-9);
+
+        // 近い期日ほど高い
+        let tomorrow = calculate_urgency_score(Some(now + Duration::days(1)), 1, now, now, &weights);
+        let next_week = calculate_urgency_score(Some(now + Duration::days(7)), 1, now, now, &weights);
+        assert!(tomorrow > next_week);
+
+        // 超過日数と経過日数は上限で頭打ち
+        let overdue_long = calculate_urgency_score(
+            Some(now - Duration::days(100)), 1, now - Duration::days(365), now, &weights,
+        );
+        assert!((overdue_long - (3.0 * 2.0 + 1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_custom_weights() {
+        let now = now();
+        let level_only = UrgencyWeights { due_weight: 0.0, level_weight: 1.0, age_weight: 0.0 };
+
+        let overdue_low = calculate_urgency_score(Some(now - Duration::days(3)), 1, now, now, &level_only);
+        let far_high = calculate_urgency_score(Some(now + Duration::days(30)), 3, now, now, &level_only);
+        assert!(far_high > overdue_low);
+
+        assert!(level_only.validate().is_ok());
+        assert!(UrgencyWeights { due_weight: -1.0, ..level_only }.validate().is_err());
+        assert!(UrgencyWeights { age_weight: f64::NAN, ..level_only }.validate().is_err());
+    }
+
+    #[test]
+    fn test_urgency_score_cache_validity() {
+        let cache = UrgencyScoreCache::default();
+        let weights = UrgencyWeights::default();
+        let computed_at = Instant::now();
+        assert!(cache.get(1, &weights, computed_at).is_none());
+
+        cache.store(Vec::new(), weights, 1, computed_at);
+        let within_ttl = computed_at + std::time::Duration::from_secs(URGENCY_CACHE_TTL_SECONDS - 1);
+        assert!(cache.get(1, &weights, within_ttl).is_some());
+        // 有効期間切れ・タスク変更後・重み変更後は使わない
+        assert!(cache.get(1, &weights, computed_at + std::time::Duration::from_secs(URGENCY_CACHE_TTL_SECONDS)).is_none());
+        assert!(cache.get(2, &weights, within_ttl).is_none());
+        let other = UrgencyWeights { due_weight: 0.0, ..weights };
+        assert!(cache.get(1, &other, within_ttl).is_none());
+
+        cache.clear();
+        assert!(cache.get(1, &weights, within_ttl).is_none());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::services::browser_action_service::BrowserActionService;
-use crate::models::browser_action::BrowserAction;
+use crate::models::browser_action::{BrowserAction, BrowserActionKind};
 use chrono::Utc;
 
 #[tokio::test]
@@ -22,7 +22,9 @@ async fn test_manual_browser_action_execution() {
     let test_action = BrowserAction {
         id: "test-action-1".to_string(),
         label: "Test Google".to_string(),
+        kind: BrowserActionKind::Url,
         url: test_url.to_string(),
+        command: None,
         enabled: true,
         order: 1,
         created_at: Utc::now(),
@@ -45,7 +47,9 @@ async fn test_manual_browser_action_execution() {
         BrowserAction {
             id: "test-action-2".to_string(),
             label: "Test GitHub".to_string(),
+            kind: BrowserActionKind::Url,
             url: "https://github.com".to_string(),
+            command: None,
             enabled: true,
             order: 1,
             created_at: Utc::now(),
@@ -53,7 +57,9 @@ async fn test_manual_browser_action_execution() {
         BrowserAction {
             id: "test-action-3".to_string(),
             label: "Test Stack Overflow".to_string(),
+            kind: BrowserActionKind::Url,
             url: "https://stackoverflow.com".to_string(),
+            command: None,
             enabled: true,
             order: 2,
             created_at: Utc::now(),
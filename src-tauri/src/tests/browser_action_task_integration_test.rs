@@ -1,7 +1,7 @@
 use crate::database::Database;
 use crate::services::TaskService;
 use crate::models::{CreateTaskRequest, UpdateTaskRequest, TaskStatus, TaskNotificationSettings};
-use crate::models::browser_action::{BrowserAction, BrowserActionSettings};
+use crate::models::browser_action::{BrowserAction, BrowserActionKind, BrowserActionSettings};
 use chrono::Utc;
 use tempfile::tempdir;
 
@@ -31,7 +31,9 @@ async fn test_create_task_with_browser_actions() {
         BrowserAction {
             id: "action-1".to_string(),
             label: "Google Search".to_string(),
+            kind: BrowserActionKind::Url,
             url: "https://www.google.com/search?q=rust".to_string(),
+            command: None,
             enabled: true,
             order: 1,
             created_at: Utc::now(),
@@ -39,7 +41,9 @@ async fn test_create_task_with_browser_actions() {
         BrowserAction {
             id: "action-2".to_string(),
             label: "GitHub Repo".to_string(),
+            kind: BrowserActionKind::Url,
             url: "https://github.com/tauri-apps/tauri".to_string(),
+            command: None,
             enabled: true,
             order: 2,
             created_at: Utc::now(),
@@ -49,13 +53,14 @@ async fn test_create_task_with_browser_actions() {
     let browser_action_settings = BrowserActionSettings {
         enabled: true,
         actions: browser_actions.clone(),
+        auto_advance: false,
     };
     
     // Create task with browser actions
     let create_request = CreateTaskRequest {
         title: "Test Task with Browser Actions".to_string(),
         description: Some("This task has browser actions".to_string()),
-        status: TaskStatus::Todo,
+        status: Some(TaskStatus::Todo),
         parent_id: None,
         due_date: None,
         notification_settings: Some(TaskNotificationSettings {
@@ -64,6 +69,7 @@ async fn test_create_task_with_browser_actions() {
             notification_time: Some("09:00".to_string()),
             days_of_week: None,
             level: 2,
+            notification_until: None,
         }),
         browser_actions: Some(browser_action_settings),
     };
@@ -167,7 +173,7 @@ async fn test_update_task_with_browser_actions() {
     let create_request = CreateTaskRequest {
         title: "Task to Update".to_string(),
         description: Some("Will add browser actions later".to_string()),
-        status: TaskStatus::Todo,
+        status: Some(TaskStatus::Todo),
         parent_id: None,
         due_date: None,
         notification_settings: None,
@@ -187,7 +193,9 @@ async fn test_update_task_with_browser_actions() {
         BrowserAction {
             id: "update-action-1".to_string(),
             label: "Stack Overflow".to_string(),
+            kind: BrowserActionKind::Url,
             url: "https://stackoverflow.com/questions/tagged/rust".to_string(),
+            command: None,
             enabled: true,
             order: 1,
             created_at: Utc::now(),
@@ -195,7 +203,9 @@ async fn test_update_task_with_browser_actions() {
         BrowserAction {
             id: "update-action-2".to_string(),
             label: "Rust Documentation".to_string(),
+            kind: BrowserActionKind::Url,
             url: "https://doc.rust-lang.org/".to_string(),
+            command: None,
             enabled: true,
             order: 2,
             created_at: Utc::now(),
@@ -203,7 +213,9 @@ async fn test_update_task_with_browser_actions() {
         BrowserAction {
             id: "update-action-3".to_string(),
             label: "Disabled Action".to_string(),
+            kind: BrowserActionKind::Url,
             url: "https://example.com".to_string(),
+            command: None,
             enabled: false,
             order: 3,
             created_at: Utc::now(),
@@ -213,6 +225,7 @@ async fn test_update_task_with_browser_actions() {
     let update_browser_settings = BrowserActionSettings {
         enabled: true,
         actions: new_browser_actions.clone(),
+        auto_advance: false,
     };
     
     let update_request = UpdateTaskRequest {
@@ -227,6 +240,7 @@ async fn test_update_task_with_browser_actions() {
             notification_time: Some("10:30".to_string()),
             days_of_week: Some(vec![1, 3, 5]), // Mon, Wed, Fri
             level: 3,
+            notification_until: None,
         }),
         browser_actions: Some(update_browser_settings),
         tags: None,
@@ -327,12 +341,15 @@ async fn test_task_list_includes_browser_actions() {
                     BrowserAction {
                         id: format!("list-action-{}", i),
                         label: format!("Action for {}", title),
+                        kind: BrowserActionKind::Url,
                         url: format!("https://example{}.com", i),
+                        command: None,
                         enabled: true,
                         order: 1,
                         created_at: Utc::now(),
                     },
                 ],
+                auto_advance: false,
             })
         } else {
             None
@@ -341,7 +358,7 @@ async fn test_task_list_includes_browser_actions() {
         let create_request = CreateTaskRequest {
             title: title.to_string(),
             description: Some(format!("Description for {}", title)),
-            status: TaskStatus::Todo,
+            status: Some(TaskStatus::Todo),
             parent_id: None,
             due_date: None,
             notification_settings: None,
@@ -418,4 +435,92 @@ async fn test_task_list_includes_browser_actions() {
             panic!("get_tasks_by_status failed");
         }
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_auto_advance_decision() {
+    let mut settings = BrowserActionSettings::new(true);
+    
+    // Disabled by default: never advance
+    assert!(!settings.auto_advance);
+    assert!(!settings.should_auto_advance("todo"));
+    
+    settings.auto_advance = true;
+    assert!(settings.should_auto_advance("todo"));
+    // Only tasks that haven't been started are advanced
+    assert!(!settings.should_auto_advance("inbox"));
+    assert!(!settings.should_auto_advance("in_progress"));
+    assert!(!settings.should_auto_advance("done"));
+}
+
+#[test]
+fn test_auto_advance_defaults_to_off_for_stored_settings() {
+    // Settings saved before the flag existed deserialize with auto-advance disabled
+    let settings: BrowserActionSettings = serde_json::from_str(r#"{"enabled":true,"actions":[]}"#).unwrap();
+    assert!(!settings.auto_advance);
+    
+    let settings: BrowserActionSettings = serde_json::from_str(r#"{"enabled":true,"actions":[],"autoAdvance":true}"#).unwrap();
+    assert!(settings.should_auto_advance("todo"));
+}
+
+#[tokio::test]
+async fn test_clear_browser_actions_for_subset() {
+    let temp_dir = tempdir().unwrap();
+    let db_path = temp_dir.path().join("test_clear_browser_actions.db");
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect(&db_url)
+        .await
+        .unwrap();
+    crate::database::migrations::run_migrations(&pool).await.unwrap();
+    let task_service = TaskService::new(Database { pool });
+    
+    let mut task_ids = Vec::new();
+    for i in 0..3 {
+        let created_task = task_service.create_task(CreateTaskRequest {
+            title: format!("Task {}", i),
+            description: None,
+            status: Some(TaskStatus::Todo),
+            parent_id: None,
+            due_date: None,
+            notification_settings: None,
+            browser_actions: Some(BrowserActionSettings {
+                enabled: true,
+                actions: vec![BrowserAction {
+                    id: format!("clear-action-{}", i),
+                    label: "Docs".to_string(),
+                    kind: BrowserActionKind::Url,
+                    url: format!("https://example{}.com", i),
+                    command: None,
+                    enabled: true,
+                    order: 1,
+                    created_at: Utc::now(),
+                }],
+                auto_advance: false,
+            }),
+        }).await.unwrap();
+        task_ids.push(created_task.id);
+    }
+    
+    // Clear the first two tasks; unknown IDs are ignored
+    let cleared = task_service
+        .clear_browser_actions(vec![task_ids[0].clone(), task_ids[1].clone(), "missing".to_string()], false)
+        .await
+        .unwrap();
+    assert_eq!(cleared, 2);
+    
+    for task_id in &task_ids[..2] {
+        let task = task_service.get_task_by_id(task_id).await.unwrap();
+        assert!(task.browser_actions.is_none());
+    }
+    let untouched = task_service.get_task_by_id(&task_ids[2]).await.unwrap();
+    assert!(untouched.browser_actions.is_some());
+    
+    // Clearing all only counts tasks that still had actions
+    let cleared = task_service.clear_browser_actions(Vec::new(), true).await.unwrap();
+    assert_eq!(cleared, 1);
+    let untouched = task_service.get_task_by_id(&task_ids[2]).await.unwrap();
+    assert!(untouched.browser_actions.is_none());
+}
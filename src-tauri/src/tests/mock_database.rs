@@ -91,8 +91,17 @@ pub fn create_test_task_with_notifications() -> Task {
         notification_time: Some("09:00".to_string()),
         notification_days_of_week: Some("[1,2,3,4,5]".to_string()),
         notification_level: Some(2),
+        notification_until: None,
         // Browser actions
         browser_actions: None,
+        is_pinned: false,
+        status_changed_at: None,
+        is_optional: false,
+        notification_channels: None,
+        recurrence_rule: None,
+        recurrence_end: None,
+        archived_at: None,
+        deleted_at: None,
         // Tag system
         tags: None,
     }
@@ -117,8 +126,17 @@ pub fn create_test_task_due_date_based() -> Task {
         notification_time: Some("10:30".to_string()),
         notification_days_of_week: None,
         notification_level: Some(3),
+        notification_until: None,
         // Browser actions
         browser_actions: None,
+        is_pinned: false,
+        status_changed_at: None,
+        is_optional: false,
+        notification_channels: None,
+        recurrence_rule: None,
+        recurrence_end: None,
+        archived_at: None,
+        deleted_at: None,
         // Tag system
         tags: None,
     }
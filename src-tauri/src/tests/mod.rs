@@ -24,4 +24,10 @@ pub mod browser_action_manual_test;
 pub mod browser_action_task_integration_test;
 #[cfg(test)]
 pub mod database_schema_validation_test;
+#[cfg(test)]
+pub mod notification_log_tests;
+#[cfg(test)]
+pub mod notification_service_tests;
+#[cfg(test)]
+pub mod task_query_tests;
 // pub mod subtask_notification_tests;
\ No newline at end of file
@@ -0,0 +1,226 @@
+use crate::database::Database;
+use crate::error::AppError;
+use crate::models::TaskNotification;
+use crate::services::NotificationService;
+use chrono::{TimeZone, Utc};
+use sqlx::{Pool, Sqlite, SqlitePool};
+
+// テスト用のインメモリデータベース接続を作成
+async fn create_test_pool() -> Pool<Sqlite> {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    crate::database::migrations::run_migrations(&pool).await.unwrap();
+    pool
+}
+
+async fn insert_task(pool: &Pool<Sqlite>, id: &str, title: &str) {
+    let now = Utc::now().to_rfc3339();
+    sqlx::query(
+        "INSERT INTO tasks (id, title, status, created_at, updated_at) VALUES (?1, ?2, 'todo', ?3, ?3)"
+    )
+    .bind(id)
+    .bind(title)
+    .bind(&now)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn insert_log(
+    pool: &Pool<Sqlite>,
+    id: &str,
+    task_id: &str,
+    fired_at: &str,
+    notification_type: &str,
+    level: i32,
+    success: bool,
+    error_message: Option<&str>,
+) {
+    sqlx::query(
+        r#"
+        INSERT INTO notification_logs (id, task_id, fired_at, notification_type, level, success, error_message)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        "#,
+    )
+    .bind(id)
+    .bind(task_id)
+    .bind(fired_at)
+    .bind(notification_type)
+    .bind(level)
+    .bind(success)
+    .bind(error_message)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+/// 通知ログのCSV出力テスト
+#[tokio::test]
+async fn test_export_logs_csv_round_trip() {
+    let pool = create_test_pool().await;
+    insert_task(&pool, "task-1", "レポート提出").await;
+    
+    insert_log(&pool, "log-1", "task-1", "2025-03-01T09:00:00+00:00", "recurring", 2, true, None).await;
+    insert_log(
+        &pool, "log-2", "task-1", "2025-03-02T09:00:00+00:00", "due_date_based", 3, false,
+        Some("toast failed, retry \"later\""),
+    ).await;
+    // 範囲外
+    insert_log(&pool, "log-3", "task-1", "2025-04-01T09:00:00+00:00", "recurring", 1, true, None).await;
+    sqlx::query("UPDATE notification_logs SET message = ?1 WHERE id = 'log-1'")
+        .bind("「レポート提出」のリマインドです,\n今日中に")
+        .execute(&pool)
+        .await
+        .unwrap();
+    
+    let service = NotificationService::new(Database { pool });
+    let from = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+    let to = Utc.with_ymd_and_hms(2025, 3, 31, 23, 59, 59).unwrap();
+    
+    let csv = service.export_logs_csv(from, to).await.unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], "id,task_id,fired_at,notification_type,level,success,error_message,message");
+    // 本文の区切り文字・改行も同じ規則で引用符で囲む
+    assert_eq!(lines[1], "log-1,task-1,2025-03-01T09:00:00+00:00,recurring,2,true,,\"「レポート提出」のリマインドです,");
+    assert_eq!(lines[2], "今日中に\"");
+    assert_eq!(
+        lines[3],
+        r#"log-2,task-1,2025-03-02T09:00:00+00:00,due_date_based,3,false,"toast failed, retry ""later""","#
+    );
+}
+
+/// 該当ログがない期間はヘッダーのみ
+#[tokio::test]
+async fn test_export_logs_csv_empty_range() {
+    let pool = create_test_pool().await;
+    let service = NotificationService::new(Database { pool });
+    
+    let from = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    let to = Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).unwrap();
+    
+    let csv = service.export_logs_csv(from, to).await.unwrap();
+    assert_eq!(csv, "id,task_id,fired_at,notification_type,level,success,error_message,message\n");
+}
+
+/// 発火時に生成された文面が通知ログに保存される
+#[tokio::test]
+async fn test_fired_notification_stores_generated_message() {
+    let pool = create_test_pool().await;
+    insert_task(&pool, "task-1", "週次レポート").await;
+    let service = NotificationService::new(Database { pool });
+    
+    let notification = TaskNotification {
+        task_id: "task-1".to_string(),
+        title: "週次レポート".to_string(),
+        level: 2,
+        days_until_due: Some(1),
+        notification_type: "due_date_based".to_string(),
+        notification_key: None,
+    };
+    let message = "明日が週次レポートの期限です。今日中に下書きを仕上げましょう。";
+    service.fire_notification(&notification, message, false, || Ok(())).await.unwrap();
+    
+    let logs = service.get_notification_logs(10).await.unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].task_id, "task-1");
+    assert!(logs[0].success);
+    assert_eq!(logs[0].message.as_deref(), Some(message));
+}
+
+/// 表示に失敗した発火も失敗として記録し、表示のエラーを返すテスト
+#[tokio::test]
+async fn test_failed_presentation_is_logged() {
+    let pool = create_test_pool().await;
+    insert_task(&pool, "task-1", "週次レポート").await;
+    let service = NotificationService::new(Database { pool });
+    
+    let notification = TaskNotification {
+        task_id: "task-1".to_string(),
+        title: "週次レポート".to_string(),
+        level: 2,
+        days_until_due: Some(1),
+        notification_type: "due_date_based".to_string(),
+        notification_key: None,
+    };
+    let result = service
+        .fire_notification(&notification, "週次レポート", false, || Err("toast failed".to_string()))
+        .await;
+    assert!(matches!(result, Err(AppError::Internal(message)) if message == "toast failed"));
+    
+    let logs = service.get_notification_logs(10).await.unwrap();
+    assert_eq!(logs.len(), 1);
+    assert!(!logs[0].success);
+    assert_eq!(logs[0].error_message.as_deref(), Some("toast failed"));
+}
+
+/// 通知履歴を新しい順に件数を絞って取得するテスト
+#[tokio::test]
+async fn test_get_notification_history() {
+    let pool = create_test_pool().await;
+    insert_task(&pool, "task-1", "レポート提出").await;
+    insert_log(&pool, "log-1", "task-1", "2025-01-01T09:00:00+00:00", "recurring", 1, true, None).await;
+    insert_log(&pool, "log-2", "task-1", "2025-01-03T09:00:00+00:00", "recurring", 2, false, Some("toast failed")).await;
+    insert_log(&pool, "log-3", "task-1", "2025-01-02T09:00:00+00:00", "due_date_based", 1, true, None).await;
+    let service = NotificationService::new(Database { pool });
+    
+    let history = service.get_notification_logs(2).await.unwrap();
+    let ids: Vec<&str> = history.iter().map(|log| log.id.as_str()).collect();
+    assert_eq!(ids, vec!["log-2", "log-3"]);
+    assert!(!history[0].success);
+    assert_eq!(history[0].error_message.as_deref(), Some("toast failed"));
+    
+    assert!(service.get_notification_logs(0).await.is_err());
+    assert!(service.get_notification_logs(1001).await.is_err());
+}
+
+/// Webhookへの送信と、失敗時の再試行のテスト
+#[tokio::test]
+async fn test_webhook_posts_notification_with_retries() {
+    let pool = create_test_pool().await;
+    insert_task(&pool, "task-1", "週次レポート").await;
+    let service = NotificationService::new(Database { pool });
+    let notification = TaskNotification {
+        task_id: "task-1".to_string(),
+        title: "週次レポート".to_string(),
+        level: 2,
+        days_until_due: Some(1),
+        notification_type: "due_date_based".to_string(),
+        notification_key: None,
+    };
+    
+    // 未設定なら何も送らない
+    assert!(service.send_webhook(&notification).await.is_ok());
+    
+    let ok = mockito::mock("POST", "/webhook-ok")
+        .match_body(mockito::Matcher::Json(serde_json::json!({
+            "id": "task-1",
+            "title": "週次レポート",
+            "level": 2,
+            "notification_type": "due_date_based",
+        })))
+        .with_status(200)
+        .expect(1)
+        .create();
+    service.set_webhook_url(Some(format!("{}/webhook-ok", mockito::server_url()))).await.unwrap();
+    service.send_webhook(&notification).await.unwrap();
+    ok.assert();
+    
+    // 5xxは最大2回再試行する
+    let failing = mockito::mock("POST", "/webhook-fail").with_status(500).expect(3).create();
+    service.set_webhook_url(Some(format!("{}/webhook-fail", mockito::server_url()))).await.unwrap();
+    assert!(service.send_webhook(&notification).await.is_err());
+    failing.assert();
+    
+    // 4xxは再試行しない
+    let rejected = mockito::mock("POST", "/webhook-rejected").with_status(404).expect(1).create();
+    service.set_webhook_url(Some(format!("{}/webhook-rejected", mockito::server_url()))).await.unwrap();
+    assert!(service.send_webhook(&notification).await.is_err());
+    rejected.assert();
+    
+    assert!(service.set_webhook_url(Some("ftp://example.com/hook".to_string())).await.is_err());
+    assert!(service.set_webhook_url(Some("not a url".to_string())).await.is_err());
+    service.set_webhook_url(None).await.unwrap();
+    assert_eq!(service.get_webhook_url().await.unwrap(), None);
+}
@@ -0,0 +1,1087 @@
+use crate::database::Database;
+use crate::models::{CreateTaskRequest, Task, TaskNotification, TaskNotificationSettings, TaskStatus};
+use crate::services::business_days::BusinessDaySettings;
+use crate::services::notification_escalation::NotificationEscalationSettings;
+use crate::services::notification_profile::NotificationProfile;
+use crate::services::notification_service::notification_text;
+use crate::services::quiet_hours::QuietHours;
+use crate::services::{NotificationService, SettingsService, TaskService};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use sqlx::SqlitePool;
+
+// テスト用のインメモリデータベースを作成
+async fn create_test_db() -> Database {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    crate::database::migrations::run_migrations(&pool).await.unwrap();
+    Database { pool }
+}
+
+async fn create_recurring_task(db: &Database, title: &str, time: &str, days_of_week: Vec<i32>) -> Task {
+    let task_service = TaskService::new(db.clone());
+    task_service.create_task(CreateTaskRequest {
+        title: title.to_string(),
+        description: None,
+        status: Some(TaskStatus::Todo),
+        parent_id: None,
+        due_date: None,
+        notification_settings: Some(TaskNotificationSettings {
+            notification_type: "recurring".to_string(),
+            days_before: None,
+            notification_time: Some(time.to_string()),
+            days_of_week: Some(days_of_week),
+            level: 2,
+            notification_until: None,
+        }),
+        browser_actions: None,
+    }).await.unwrap()
+}
+
+/// DST切り替えの前後で定期通知がローカル時刻どおりに発火するテスト
+#[tokio::test]
+async fn test_recurring_notification_across_dst_transition() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "America/New_York").await.unwrap();
+    
+    // 金曜(5)と月曜(1)の09:00
+    let task = create_recurring_task(&db, "朝会", "09:00", vec![1, 5]).await;
+    let service = NotificationService::new(db);
+    
+    // 2025-03-07(金) EST: 09:00 = 14:00Z
+    let before_dst = service.check_notifications(Utc.with_ymd_and_hms(2025, 3, 7, 14, 0, 0).unwrap()).await.unwrap();
+    assert_eq!(before_dst.len(), 1);
+    assert_eq!(before_dst[0].task_id, task.id);
+    let naive_utc_hour = service.check_notifications(Utc.with_ymd_and_hms(2025, 3, 7, 9, 0, 0).unwrap()).await.unwrap();
+    assert!(naive_utc_hour.is_empty());
+    
+    // 2025-03-10(月) EDT: 09:00 = 13:00Z
+    let after_dst = service.check_notifications(Utc.with_ymd_and_hms(2025, 3, 10, 13, 0, 0).unwrap()).await.unwrap();
+    assert_eq!(after_dst.len(), 1);
+    let stale_offset = service.check_notifications(Utc.with_ymd_and_hms(2025, 3, 10, 14, 0, 0).unwrap()).await.unwrap();
+    assert!(stale_offset.is_empty());
+    
+    // 対象外の曜日（2025-03-09は日曜）
+    let sunday = service.check_notifications(Utc.with_ymd_and_hms(2025, 3, 9, 13, 0, 0).unwrap()).await.unwrap();
+    assert!(sunday.is_empty());
+}
+
+async fn create_inbox_task(db: &Database, title: &str, created_at: &str) -> Task {
+    let task_service = TaskService::new(db.clone());
+    let task = task_service.create_task(CreateTaskRequest {
+        title: title.to_string(),
+        description: None,
+        status: Some(TaskStatus::Inbox),
+        parent_id: None,
+        due_date: None,
+        notification_settings: None,
+        browser_actions: None,
+    }).await.unwrap();
+    
+    sqlx::query("UPDATE tasks SET created_at = ?1 WHERE id = ?2")
+        .bind(created_at)
+        .bind(&task.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    task
+}
+
+/// 受信箱に放置されたタスクのリマインドテスト
+#[tokio::test]
+async fn test_inbox_aging_reminder() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    
+    let service = NotificationService::new(db.clone());
+    service.set_inbox_aging_days(3).await.unwrap();
+    assert!(service.set_inbox_aging_days(-1).await.is_err());
+    
+    let stale = create_inbox_task(&db, "放置タスク", "2025-06-01T00:00:00+00:00").await;
+    create_inbox_task(&db, "新しいタスク", "2025-06-09T00:00:00+00:00").await;
+    
+    // 2025-06-10 10:00 JST
+    let now = Utc.with_ymd_and_hms(2025, 6, 10, 1, 0, 0).unwrap();
+    let reminders = service.check_inbox_aging(now).await.unwrap();
+    assert_eq!(reminders.len(), 1);
+    assert_eq!(reminders[0].task_id, stale.id);
+    assert_eq!(reminders[0].level, 1);
+    assert_eq!(reminders[0].notification_type, "inbox_aging");
+    
+    // 通知時刻（09:00 JST）より前は出さない
+    let early = service.check_inbox_aging(Utc.with_ymd_and_hms(2025, 6, 9, 23, 0, 0).unwrap()).await.unwrap();
+    assert!(early.is_empty());
+    
+    // 同じ日に通知済みなら除外、翌日は再通知
+    sqlx::query(
+        "INSERT INTO notification_logs (id, task_id, fired_at, notification_type, level, success) VALUES ('log-1', ?1, '2025-06-10T00:30:00+00:00', 'inbox_aging', 1, 1)"
+    )
+    .bind(&stale.id)
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    assert!(service.check_inbox_aging(now).await.unwrap().is_empty());
+    let next_day = Utc.with_ymd_and_hms(2025, 6, 11, 1, 0, 0).unwrap();
+    assert_eq!(service.check_inbox_aging(next_day).await.unwrap().len(), 1);
+    
+    // 0で無効化
+    service.set_inbox_aging_days(0).await.unwrap();
+    assert!(service.check_inbox_aging(next_day).await.unwrap().is_empty());
+}
+
+/// 使用中の通知時刻の集計テスト
+#[tokio::test]
+async fn test_get_configured_times() {
+    let db = create_test_db().await;
+    
+    create_recurring_task(&db, "朝会", "09:00", vec![1, 2, 3, 4, 5]).await;
+    create_recurring_task(&db, "日報", "18:00", vec![1, 2, 3, 4, 5]).await;
+    create_recurring_task(&db, "ストレッチ", "09:00", vec![0, 6]).await;
+    let done = create_recurring_task(&db, "完了済み", "12:00", vec![1]).await;
+    sqlx::query("UPDATE tasks SET status = 'done' WHERE id = ?1")
+        .bind(&done.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    
+    let service = NotificationService::new(db);
+    let times = service.get_configured_times().await.unwrap();
+    
+    assert_eq!(times, vec![("09:00".to_string(), 2), ("18:00".to_string(), 1)]);
+}
+
+/// アプリ停止中に見逃した通知の集計テスト
+#[tokio::test]
+async fn test_missed_notifications_since_last_tick() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    
+    // 月曜(1)と水曜(3)の09:00 JST
+    let standup = create_recurring_task(&db, "朝会", "09:00", vec![1, 3]).await;
+    create_recurring_task(&db, "週末", "09:00", vec![0, 6]).await;
+    
+    let task_service = TaskService::new(db.clone());
+    let due_date_task = |title: &str, due_date: chrono::DateTime<Utc>| CreateTaskRequest {
+        title: title.to_string(),
+        description: None,
+        status: Some(TaskStatus::Todo),
+        parent_id: None,
+        due_date: Some(due_date),
+        notification_settings: Some(TaskNotificationSettings {
+            notification_type: "due_date_based".to_string(),
+            days_before: Some(1),
+            notification_time: Some("08:30".to_string()),
+            days_of_week: None,
+            level: 3,
+            notification_until: None,
+        }),
+        browser_actions: None,
+    };
+    // 期日 2025-06-12 12:00 JST → 前日 08:30 JST から毎時通知予定
+    let report = task_service.create_task(due_date_task("報告書", Utc.with_ymd_and_hms(2025, 6, 12, 3, 0, 0).unwrap())).await.unwrap();
+    task_service.create_task(due_date_task("来月の締切", Utc.with_ymd_and_hms(2025, 7, 31, 3, 0, 0).unwrap())).await.unwrap();
+    
+    let service = NotificationService::new(db.clone());
+    
+    // 最終チェック: 2025-06-09(月) 08:00 JST、起動: 2025-06-11(水) 10:00 JST
+    let last_tick = Utc.with_ymd_and_hms(2025, 6, 8, 23, 0, 0).unwrap();
+    let now = Utc.with_ymd_and_hms(2025, 6, 11, 1, 0, 0).unwrap();
+    
+    let missed = service.find_missed_notifications(last_tick, now).await.unwrap();
+    let summary: Vec<(&str, chrono::DateTime<Utc>)> = missed.iter()
+        .map(|m| (m.task_id.as_str(), m.scheduled_at))
+        .collect();
+    assert_eq!(summary, vec![
+        (standup.id.as_str(), Utc.with_ymd_and_hms(2025, 6, 9, 0, 0, 0).unwrap()),
+        (report.id.as_str(), Utc.with_ymd_and_hms(2025, 6, 10, 23, 30, 0).unwrap()),
+        (standup.id.as_str(), Utc.with_ymd_and_hms(2025, 6, 11, 0, 0, 0).unwrap()),
+        (report.id.as_str(), Utc.with_ymd_and_hms(2025, 6, 11, 0, 30, 0).unwrap()),
+    ]);
+    
+    // 起動時の検出結果はコマンド用に保持され、最終チェック時刻が更新される
+    service.record_scheduler_tick(last_tick).await.unwrap();
+    service.detect_missed_notifications_on_startup(now).await.unwrap();
+    assert_eq!(service.get_missed_notifications().len(), 4);
+    assert_eq!(service.last_scheduler_tick().await.unwrap(), Some(now));
+    
+    // 停止期間がなければ見逃しはない
+    assert!(service.find_missed_notifications(now, now).await.unwrap().is_empty());
+}
+
+/// 終了日を過ぎた繰り返し通知が止まるテスト
+#[tokio::test]
+async fn test_recurring_notification_stops_after_until_date() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    
+    let task_service = TaskService::new(db.clone());
+    let standup = |until: &str| TaskNotificationSettings {
+        notification_type: "recurring".to_string(),
+        days_before: None,
+        notification_time: Some("10:00".to_string()),
+        days_of_week: Some(vec![1, 2, 3, 4, 5]),
+        level: 1,
+        notification_until: Some(until.to_string()),
+    };
+    let task = task_service.create_task(CreateTaskRequest {
+        title: "スプリント朝会".to_string(),
+        description: None,
+        status: Some(TaskStatus::Todo),
+        parent_id: None,
+        due_date: None,
+        notification_settings: Some(standup("2025-06-13")),
+        browser_actions: None,
+    }).await.unwrap();
+    assert_eq!(task.notification_until.as_deref(), Some("2025-06-13"));
+    
+    let service = NotificationService::new(db.clone());
+    
+    // 2025-06-13(金) 10:00 JST は終了日当日なので通知する
+    let last_day = service.check_notifications(Utc.with_ymd_and_hms(2025, 6, 13, 1, 0, 0).unwrap()).await.unwrap();
+    assert_eq!(last_day.len(), 1);
+    
+    // 2025-06-16(月) 10:00 JST は終了日を過ぎているので通知しない
+    let after = service.check_notifications(Utc.with_ymd_and_hms(2025, 6, 16, 1, 0, 0).unwrap()).await.unwrap();
+    assert!(after.is_empty());
+    
+    // 不正な終了日は保存できない
+    let invalid = task_service.update_task(&task.id, crate::models::UpdateTaskRequest {
+        title: None,
+        description: None,
+        status: None,
+        parent_id: None,
+        due_date: None,
+        notification_settings: Some(standup("2025/06/30")),
+        browser_actions: None,
+        tags: None,
+    }).await;
+    assert!(invalid.is_err());
+}
+
+/// AI無効時にAI機能は明確なエラーを返し、通知は定型文で発火するテスト
+#[tokio::test]
+async fn test_ai_disabled_falls_back_for_notifications() {
+    use crate::services::agent_service::AgentError;
+    use crate::services::daily_summary::DailySummarySettings;
+    use crate::services::AgentService;
+    
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    
+    // AI無効時はモデルへのリクエストを一切送らない
+    let generate = mockito::mock("POST", "/api/generate")
+        .match_body(mockito::Matcher::Regex("資料作成|こんにちは|言い換えてください".to_string()))
+        .with_status(200)
+        .with_body(r#"{"response":"AIの文面","done":true}"#)
+        .expect(0)
+        .create();
+    let agent = AgentService::with_custom_ollama(db.pool.clone(), mockito::server_url(), "stub-model".to_string());
+    agent.set_ai_enabled(false).await.unwrap();
+    assert!(!agent.is_ai_enabled().await);
+    
+    assert!(matches!(agent.analyze_task("資料作成", true).await, Err(AgentError::AiDisabled)));
+    assert!(matches!(agent.chat("こんにちは", None).await, Err(AgentError::AiDisabled)));
+    assert!(matches!(agent.generate_motivation_boost().await, Err(AgentError::AiDisabled)));
+    assert!(matches!(agent.assemble_context("chat").await, Err(AgentError::AiDisabled)));
+    
+    // 通知は定型文のまま発火する
+    let service = NotificationService::new(db.clone());
+    service.set_daily_summary_settings(DailySummarySettings {
+        enabled: true,
+        time: "18:00".to_string(),
+        use_ai: true,
+    }).await.unwrap();
+    let summary = service
+        .check_daily_summary(Utc.with_ymd_and_hms(2025, 6, 10, 9, 30, 0).unwrap())
+        .await
+        .unwrap()
+        .expect("summary should fire after 18:00 JST");
+    let message = agent.phrase_notification(&summary, "言い換えてください").await;
+    assert_eq!(message, summary);
+    
+    generate.assert();
+}
+
+/// タグによるレベル上書きと夜間の引き下げを組み合わせた実効レベルのテスト
+#[tokio::test]
+async fn test_effective_level_with_tag_override_and_night_downgrade() {
+    use crate::models::CreateTagRequest;
+    use crate::services::notification_level::NotificationLevelRules;
+    use crate::services::TagService;
+    
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    
+    // レベル2の毎日22:30の通知に「締切」タグ（レベル3）を付ける
+    let task = create_recurring_task(&db, "請求書の送付", "22:30", vec![0, 1, 2, 3, 4, 5, 6]).await;
+    let plain = create_recurring_task(&db, "日報", "22:30", vec![0, 1, 2, 3, 4, 5, 6]).await;
+    let tag = TagService::create_tag(&db.pool, CreateTagRequest {
+        name: "締切".to_string(),
+        color: "#ff0000".to_string(),
+    }).await.unwrap();
+    TagService::add_tag_to_task(&db.pool, &task.id, &tag.id).await.unwrap();
+    
+    let service = NotificationService::new(db.clone());
+    service.set_level_rules(NotificationLevelRules {
+        tag_levels: [("締切".to_string(), 3)].into_iter().collect(),
+        night_start: Some("22:00".to_string()),
+        night_end: Some("07:00".to_string()),
+        night_downgrade: 1,
+    }).await.unwrap();
+    
+    let daytime = Utc.with_ymd_and_hms(2025, 6, 10, 3, 0, 0).unwrap(); // 12:00 JST
+    let night = Utc.with_ymd_and_hms(2025, 6, 10, 13, 30, 0).unwrap(); // 22:30 JST
+    
+    assert_eq!(service.effective_level(&task.id, daytime).await.unwrap(), 3);
+    assert_eq!(service.effective_level(&task.id, night).await.unwrap(), 2);
+    assert_eq!(service.effective_level(&plain.id, daytime).await.unwrap(), 2);
+    assert_eq!(service.effective_level(&plain.id, night).await.unwrap(), 1);
+    
+    // 実際に発火する通知も同じレベルになる
+    let fired = service.check_notifications(night).await.unwrap();
+    let level_of = |id: &str| fired.iter().find(|n| n.task_id == id).map(|n| n.level);
+    assert_eq!(level_of(&task.id), Some(2));
+    assert_eq!(level_of(&plain.id), Some(1));
+    
+    // 夜間帯の片側だけの設定は拒否
+    assert!(service.set_level_rules(NotificationLevelRules {
+        night_start: Some("22:00".to_string()),
+        ..Default::default()
+    }).await.is_err());
+}
+
+/// peekは発火予定の通知を返すが、ログや実行時刻を何も記録しない
+#[tokio::test]
+async fn test_peek_notifications_records_nothing() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    
+    // 2025-06-10(火) 09:00 JST
+    let task = create_recurring_task(&db, "朝のメール確認", "09:00", vec![2]).await;
+    let stale = create_inbox_task(&db, "放置タスク", "2025-06-01T00:00:00+00:00").await;
+    let service = NotificationService::new(db.clone());
+    service.set_inbox_aging_days(3).await.unwrap();
+    
+    let now = Utc.with_ymd_and_hms(2025, 6, 10, 0, 0, 0).unwrap();
+    for _ in 0..2 {
+        let pending = service.peek_notifications(now).await.unwrap();
+        let ids: Vec<&str> = pending.iter().map(|n| n.task_id.as_str()).collect();
+        assert_eq!(ids, vec![task.id.as_str(), stale.id.as_str()]);
+    }
+    
+    let (log_count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM notification_logs")
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(log_count, 0);
+    let (ack_count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM browser_action_acks")
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(ack_count, 0);
+    assert!(service.last_scheduler_tick().await.unwrap().is_none());
+    
+    let unchanged = TaskService::new(db).get_task_by_id(&task.id).await.unwrap();
+    assert_eq!(unchanged.status, "todo");
+    assert_eq!(unchanged.updated_at, task.updated_at);
+}
+
+/// 通知しない日は何も発火せず、翌日から再開する
+#[tokio::test]
+async fn test_no_nag_day_suppresses_notifications() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    
+    // 毎日 08:00 JST
+    let task = create_recurring_task(&db, "ストレッチ", "08:00", vec![0, 1, 2, 3, 4, 5, 6]).await;
+    let service = NotificationService::new(db);
+    
+    let day_off = NaiveDate::from_ymd_opt(2025, 6, 10).unwrap();
+    let days = service.add_no_nag_day(day_off).await.unwrap();
+    assert_eq!(days.into_iter().collect::<Vec<_>>(), vec![day_off]);
+    
+    // 2025-06-10 08:00 JST = 2025-06-09 23:00Z（UTCでは前日でもローカル日付で判定）
+    let on_day_off = Utc.with_ymd_and_hms(2025, 6, 9, 23, 0, 0).unwrap();
+    assert!(service.check_notifications(on_day_off).await.unwrap().is_empty());
+    assert!(service.is_no_nag_day(on_day_off).await.unwrap());
+    
+    // 前日と翌日は通常どおり
+    let day_before = Utc.with_ymd_and_hms(2025, 6, 8, 23, 0, 0).unwrap();
+    assert_eq!(service.check_notifications(day_before).await.unwrap().len(), 1);
+    let next_day = Utc.with_ymd_and_hms(2025, 6, 10, 23, 0, 0).unwrap();
+    let resumed = service.check_notifications(next_day).await.unwrap();
+    assert_eq!(resumed.len(), 1);
+    assert_eq!(resumed[0].task_id, task.id);
+    
+    // 停止中に過ぎた通知でも、通知しない日の分は見逃し扱いにしない
+    let missed = service.find_missed_notifications(day_before, Utc.with_ymd_and_hms(2025, 6, 11, 0, 0, 0).unwrap()).await.unwrap();
+    assert_eq!(missed.len(), 1);
+    assert_eq!(missed[0].scheduled_at, next_day);
+    
+    // 削除すると再び通知する
+    assert!(service.remove_no_nag_day(day_off).await.unwrap().is_empty());
+    assert_eq!(service.check_notifications(on_day_off).await.unwrap().len(), 1);
+    assert!(service.remove_no_nag_day(day_off).await.is_err());
+}
+
+/// 通知セルフテストが実際のDBで全ステップ成功し、後片付けされるテスト
+#[tokio::test]
+async fn test_notification_self_test_passes() {
+    let db = create_test_db().await;
+    let service = NotificationService::new(db.clone());
+    
+    let mut presented = 0;
+    let report = service
+        .run_self_test(Utc.with_ymd_and_hms(2025, 3, 10, 9, 30, 20).unwrap(), || {
+            presented += 1;
+            Ok(())
+        })
+        .await
+        .unwrap();
+    assert_eq!(presented, 1);
+    
+    let steps: Vec<&str> = report.steps.iter().map(|step| step.name.as_str()).collect();
+    assert_eq!(steps, vec!["create_task", "detect", "fire", "cleanup"]);
+    assert!(report.steps.iter().all(|step| step.success && step.detail.is_none()), "{:?}", report);
+    assert!(report.passed);
+    
+    // 一時タスクと通知ログは残らない
+    let tasks: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks").fetch_one(&db.pool).await.unwrap();
+    let logs: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM notification_logs").fetch_one(&db.pool).await.unwrap();
+    assert_eq!((tasks, logs), (0, 0));
+}
+
+/// 定期通知と期日通知の発火予定を7日分、時刻順に列挙するテスト
+#[tokio::test]
+async fn test_enumerate_schedule_merges_recurring_and_due_date() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let service = NotificationService::new(db.clone());
+    let task_service = TaskService::new(db.clone());
+    
+    // 月・水・金の08:30
+    let standup = create_recurring_task(&db, "朝会", "08:30", vec![1, 3, 5]).await;
+    let due_date_task = |title: &str, due: DateTime<Utc>| CreateTaskRequest {
+        title: title.to_string(),
+        description: None,
+        status: Some(TaskStatus::Todo),
+        parent_id: None,
+        due_date: Some(due),
+        notification_settings: Some(TaskNotificationSettings {
+            notification_type: "due_date_based".to_string(),
+            days_before: Some(1),
+            notification_time: Some("18:00".to_string()),
+            days_of_week: None,
+            level: 3,
+            notification_until: None,
+        }),
+        browser_actions: None,
+    };
+    // 期日 6/12 12:00 JST → 前日 6/11 18:00 JST から当日 18:00 JST まで毎時通知
+    let report = task_service
+        .create_task(due_date_task("報告書", Utc.with_ymd_and_hms(2025, 6, 12, 3, 0, 0).unwrap()))
+        .await
+        .unwrap();
+    // 期間外
+    task_service
+        .create_task(due_date_task("来月の申請", Utc.with_ymd_and_hms(2025, 6, 30, 3, 0, 0).unwrap()))
+        .await
+        .unwrap();
+    // 金曜は通知しない日
+    service.add_no_nag_day(NaiveDate::from_ymd_opt(2025, 6, 13).unwrap()).await.unwrap();
+    
+    // 2025-06-09(月) 00:00 JST から7日間
+    let from = Utc.with_ymd_and_hms(2025, 6, 8, 15, 0, 0).unwrap();
+    let schedule = service.enumerate_schedule_at(from, 7).await.unwrap();
+    
+    let fired: Vec<(&str, DateTime<Utc>, i32)> = schedule
+        .iter()
+        .map(|s| (s.task_id.as_str(), s.fire_at, s.level))
+        .collect();
+    let report_start = Utc.with_ymd_and_hms(2025, 6, 11, 9, 0, 0).unwrap();
+    let mut expected = vec![
+        (standup.id.as_str(), Utc.with_ymd_and_hms(2025, 6, 8, 23, 30, 0).unwrap(), 2),
+        (standup.id.as_str(), Utc.with_ymd_and_hms(2025, 6, 10, 23, 30, 0).unwrap(), 2),
+    ];
+    expected.extend((0..=24).map(|hours| (report.id.as_str(), report_start + chrono::Duration::hours(hours), 3)));
+    assert_eq!(fired, expected);
+    assert_eq!(schedule[2].title, "報告書");
+    assert_eq!(schedule[2].notification_type, "due_date_based");
+    
+    assert!(service.enumerate_schedule_at(from, 0).await.is_err());
+}
+
+/// 発火予定の一覧がクワイエットタイムを反映するテスト
+#[tokio::test]
+async fn test_enumerate_schedule_respects_quiet_hours() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let service = NotificationService::new(db.clone());
+    
+    // 月・水・金の08:30（レベル2）
+    let standup = create_recurring_task(&db, "朝会", "08:30", vec![1, 3, 5]).await;
+    // 期日 6/12 12:00 JST → 前日 6/11 18:00 JST に通知（レベル3）
+    let report = TaskService::new(db.clone())
+        .create_task(CreateTaskRequest {
+            title: "報告書".to_string(),
+            description: None,
+            status: Some(TaskStatus::Todo),
+            parent_id: None,
+            due_date: Some(Utc.with_ymd_and_hms(2025, 6, 12, 3, 0, 0).unwrap()),
+            notification_settings: Some(TaskNotificationSettings {
+                notification_type: "due_date_based".to_string(),
+                days_before: Some(1),
+                notification_time: Some("18:00".to_string()),
+                days_of_week: None,
+                level: 3,
+                notification_until: None,
+            }),
+            browser_actions: None,
+        })
+        .await
+        .unwrap();
+    
+    // 2025-06-09(月) 00:00 JST から3日間、17:00〜09:00は通知しない
+    let from = Utc.with_ymd_and_hms(2025, 6, 8, 15, 0, 0).unwrap();
+    let mut quiet_hours = QuietHours {
+        start: "17:00".to_string(),
+        end: "09:00".to_string(),
+        defer_critical: false,
+    };
+    service.set_quiet_hours(Some(quiet_hours.clone())).await.unwrap();
+    assert!(service.enumerate_schedule_at(from, 3).await.unwrap().is_empty());
+    
+    // 保留を有効にすると、夜間の毎時通知はレベル3だけ終了時刻（6/12 09:00 JST）にまとめて1回になる
+    quiet_hours.defer_critical = true;
+    service.set_quiet_hours(Some(quiet_hours)).await.unwrap();
+    let schedule = service.enumerate_schedule_at(from, 4).await.unwrap();
+    let fired: Vec<(&str, DateTime<Utc>)> = schedule.iter().map(|s| (s.task_id.as_str(), s.fire_at)).collect();
+    let deferred_end = Utc.with_ymd_and_hms(2025, 6, 12, 0, 0, 0).unwrap();
+    // 以降は6/12 16:00 JSTまで毎時（17:00・18:00 JSTの分は期間外の翌朝に保留）
+    let expected: Vec<(&str, DateTime<Utc>)> = (0..8)
+        .map(|hours| (report.id.as_str(), deferred_end + chrono::Duration::hours(hours)))
+        .collect();
+    assert_eq!(fired, expected);
+    
+    // 終了時刻が期間外なら含めない
+    assert!(service.enumerate_schedule_at(from, 3).await.unwrap().is_empty());
+    
+    service.set_quiet_hours(None).await.unwrap();
+    let schedule = service.enumerate_schedule_at(from, 3).await.unwrap();
+    assert_eq!(schedule.iter().filter(|s| s.task_id == standup.id).count(), 2);
+}
+
+/// 期日通知の日付・時刻を設定したタイムゾーンで計算するテスト
+#[tokio::test]
+async fn test_due_date_notification_uses_configured_timezone() {
+    let db = create_test_db().await;
+    let service = NotificationService::new(db.clone());
+    service.set_timezone(Some("America/New_York".to_string())).await.unwrap();
+    assert_eq!(service.get_timezone().await.unwrap().as_deref(), Some("America/New_York"));
+    
+    // 期日 6/12 12:00 EDT（16:00 UTC）、前日の18:00に通知
+    TaskService::new(db.clone())
+        .create_task(CreateTaskRequest {
+            title: "報告書".to_string(),
+            description: None,
+            status: Some(TaskStatus::Todo),
+            parent_id: None,
+            due_date: Some(Utc.with_ymd_and_hms(2025, 6, 12, 16, 0, 0).unwrap()),
+            notification_settings: Some(TaskNotificationSettings {
+                notification_type: "due_date_based".to_string(),
+                days_before: Some(1),
+                notification_time: Some("18:00".to_string()),
+                days_of_week: None,
+                level: 2,
+                notification_until: None,
+            }),
+            browser_actions: None,
+        })
+        .await
+        .unwrap();
+    
+    // 6/11 18:00 EDT = 22:00 UTC
+    let new_york = Utc.with_ymd_and_hms(2025, 6, 11, 22, 0, 0).unwrap();
+    assert_eq!(titles_of(&service.peek_notifications(new_york).await.unwrap()), vec!["報告書"]);
+    assert!(service.peek_notifications(Utc.with_ymd_and_hms(2025, 6, 11, 18, 0, 0).unwrap()).await.unwrap().is_empty());
+    
+    // 東京では期日が6/13 01:00になるので、6/12 18:00 JST（09:00 UTC）に通知
+    service.set_timezone(Some("Asia/Tokyo".to_string())).await.unwrap();
+    assert!(service.peek_notifications(new_york).await.unwrap().is_empty());
+    let tokyo = Utc.with_ymd_and_hms(2025, 6, 12, 9, 0, 0).unwrap();
+    assert_eq!(titles_of(&service.peek_notifications(tokyo).await.unwrap()), vec!["報告書"]);
+    
+    // 不正な名前は拒否し、Noneでシステムローカルに戻す
+    assert!(service.set_timezone(Some("Mars/Olympus".to_string())).await.is_err());
+    service.set_timezone(None).await.unwrap();
+    assert_eq!(service.get_timezone().await.unwrap(), None);
+}
+
+/// 金曜にスヌーズすると週末を飛ばして月曜の始業時刻まで通知が止まるテスト
+#[tokio::test]
+async fn test_snooze_until_next_business_day_skips_weekend() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let service = NotificationService::new(db.clone());
+    
+    // 毎日17:30
+    let task = create_recurring_task(&db, "日報", "17:30", vec![0, 1, 2, 3, 4, 5, 6]).await;
+    
+    // 2025-06-13(金) 17:30 JST
+    let friday = Utc.with_ymd_and_hms(2025, 6, 13, 8, 30, 0).unwrap();
+    assert_eq!(service.check_notifications(friday).await.unwrap().len(), 1);
+    
+    let until = service.snooze_until_next_business_day(&task.id, friday).await.unwrap();
+    // 2025-06-16(月) 09:00 JST
+    assert_eq!(until, Utc.with_ymd_and_hms(2025, 6, 16, 0, 0, 0).unwrap());
+    assert_eq!(service.get_snooze_until(&task.id).await.unwrap(), Some(until));
+    
+    // スヌーズ中は発火せず、終了後は通常どおり発火する
+    assert!(service.check_notifications(friday).await.unwrap().is_empty());
+    let saturday = Utc.with_ymd_and_hms(2025, 6, 14, 8, 30, 0).unwrap();
+    assert!(service.check_notifications(saturday).await.unwrap().is_empty());
+    let monday = Utc.with_ymd_and_hms(2025, 6, 16, 8, 30, 0).unwrap();
+    assert_eq!(service.check_notifications(monday).await.unwrap().len(), 1);
+}
+
+/// 休日・営業日の設定に従って翌営業日を決めるテスト
+#[tokio::test]
+async fn test_snooze_until_next_business_day_respects_settings() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let service = NotificationService::new(db.clone());
+    let task = create_recurring_task(&db, "請求書", "10:00", vec![1]).await;
+    let friday = Utc.with_ymd_and_hms(2025, 6, 13, 8, 30, 0).unwrap();
+    
+    // 月曜が祝日なら火曜、始業は08:30
+    let mut settings = BusinessDaySettings::default();
+    settings.holidays.insert(NaiveDate::from_ymd_opt(2025, 6, 16).unwrap());
+    settings.workday_start = "08:30".to_string();
+    service.set_business_day_settings(settings.clone()).await.unwrap();
+    let until = service.snooze_until_next_business_day(&task.id, friday).await.unwrap();
+    assert_eq!(until, Utc.with_ymd_and_hms(2025, 6, 16, 23, 30, 0).unwrap());
+    
+    // 土曜も営業日なら翌日の土曜
+    settings.business_days.insert(6);
+    service.set_business_day_settings(settings.clone()).await.unwrap();
+    let until = service.snooze_until_next_business_day(&task.id, friday).await.unwrap();
+    assert_eq!(until, Utc.with_ymd_and_hms(2025, 6, 13, 23, 30, 0).unwrap());
+    
+    // 不正な設定・存在しないタスク
+    settings.business_days.insert(7);
+    assert!(service.set_business_day_settings(settings).await.is_err());
+    assert!(service.snooze_until_next_business_day("missing", friday).await.is_err());
+}
+
+/// 分単位でスヌーズし、終了直後に改めて発火するテスト
+#[tokio::test]
+async fn test_snooze_notification_fires_again_after_snooze() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let service = NotificationService::new(db.clone());
+    let task = create_recurring_task(&db, "日報", "17:30", vec![0, 1, 2, 3, 4, 5, 6]).await;
+    
+    // 2025-06-13 17:30 JST
+    let fired_at = Utc.with_ymd_and_hms(2025, 6, 13, 8, 30, 0).unwrap();
+    let until = service.snooze_notification_at(&task.id, 30, fired_at).await.unwrap();
+    assert_eq!(until, Utc.with_ymd_and_hms(2025, 6, 13, 9, 0, 0).unwrap());
+    assert!(service.check_notifications(fired_at).await.unwrap().is_empty());
+    
+    // 終了直後の1分間だけ再度発火する
+    let ended = service.check_notifications(until).await.unwrap();
+    assert_eq!(ended.len(), 1);
+    assert_eq!(ended[0].task_id, task.id);
+    assert_eq!(ended[0].level, 2);
+    assert!(service.check_notifications(until + chrono::Duration::minutes(2)).await.unwrap().is_empty());
+    
+    // 解除すると本来の通知時刻に発火する
+    service.snooze_notification_at(&task.id, 60, fired_at).await.unwrap();
+    service.clear_snooze(&task.id).await.unwrap();
+    assert_eq!(service.get_snooze_until(&task.id).await.unwrap(), None);
+    assert_eq!(service.check_notifications(fired_at).await.unwrap().len(), 1);
+    
+    // 範囲外の長さ・存在しないタスク
+    assert!(service.snooze_notification_at(&task.id, 0, fired_at).await.is_err());
+    assert!(service.snooze_notification_at(&task.id, 8 * 24 * 60, fired_at).await.is_err());
+    assert!(service.snooze_notification_at("missing", 10, fired_at).await.is_err());
+}
+
+/// 同じ発火枠の通知は一度発火したら判定の窓内で再度出さず、翌日は出すテスト
+#[tokio::test]
+async fn test_fired_notification_is_not_repeated_within_same_slot() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let service = NotificationService::new(db.clone());
+    create_recurring_task(&db, "日報", "17:30", vec![0, 1, 2, 3, 4, 5, 6]).await;
+    
+    // 2025-06-13 17:30 JST
+    let slot = Utc.with_ymd_and_hms(2025, 6, 13, 8, 30, 0).unwrap();
+    let notifications = service.check_notifications(slot).await.unwrap();
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0].notification_key.as_deref(), Some("2025-06-13 17:30"));
+    
+    // 発火するまでは何度判定しても出る
+    assert_eq!(service.check_notifications(slot + chrono::Duration::seconds(30)).await.unwrap().len(), 1);
+    service.fire_notification(&notifications[0], &notifications[0].title, false, || Ok(())).await.unwrap();
+    assert!(service.check_notifications(slot + chrono::Duration::seconds(30)).await.unwrap().is_empty());
+    
+    // 日付が変われば別のキーになる
+    let next_day = service.check_notifications(slot + chrono::Duration::days(1)).await.unwrap();
+    assert_eq!(next_day[0].notification_key.as_deref(), Some("2025-06-14 17:30"));
+}
+
+/// 無視され続けた通知のレベルが段階的に上がり、完了で戻るテスト
+#[tokio::test]
+async fn test_notification_level_escalation() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let service = NotificationService::new(db.clone());
+    let task_service = TaskService::new(db.clone());
+    let task = create_recurring_task(&db, "日報", "17:30", vec![0, 1, 2, 3, 4, 5, 6]).await;
+    sqlx::query("UPDATE tasks SET notification_level = 1, created_at = '2025-01-01T00:00:00+00:00' WHERE id = ?1")
+        .bind(&task.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    
+    // 2025-06-13 17:30 JST
+    let slot = Utc.with_ymd_and_hms(2025, 6, 13, 8, 30, 0).unwrap();
+    let level_at = |day: i64| {
+        let service = &service;
+        async move { service.check_notifications(slot + chrono::Duration::days(day)).await.unwrap()[0].level }
+    };
+    let fire = |day: i64| {
+        let service = &service;
+        async move {
+            let notification = service.check_notifications(slot + chrono::Duration::days(day)).await.unwrap().remove(0);
+            service.fire_notification(&notification, &notification.title, false, || Ok(())).await.unwrap();
+        }
+    };
+    
+    // 無効のうちは何度発火しても変わらない
+    fire(0).await;
+    fire(1).await;
+    assert_eq!(level_at(2).await, 1);
+    
+    service.set_escalation_settings(NotificationEscalationSettings {
+        enabled: true,
+        fires_per_step: 2,
+        max_level: 3,
+    }).await.unwrap();
+    assert_eq!(level_at(2).await, 2);
+    fire(2).await;
+    fire(3).await;
+    assert_eq!(level_at(4).await, 3);
+    fire(4).await;
+    fire(5).await;
+    assert_eq!(level_at(6).await, 3);
+    assert_eq!(service.unresolved_fire_count(&task.id).await.unwrap(), 6);
+    
+    // 完了以外のステータス変更では数え直さない
+    task_service.move_task(&task.id, "in_progress").await.unwrap();
+    assert_eq!(service.unresolved_fire_count(&task.id).await.unwrap(), 6);
+    
+    // 完了して戻すと数え直す
+    task_service.move_task(&task.id, "done").await.unwrap();
+    task_service.move_task(&task.id, "todo").await.unwrap();
+    assert_eq!(service.unresolved_fire_count(&task.id).await.unwrap(), 0);
+    assert_eq!(level_at(6).await, 1);
+    
+    let invalid = NotificationEscalationSettings { enabled: true, fires_per_step: 0, max_level: 3 };
+    assert!(service.set_escalation_settings(invalid).await.is_err());
+}
+
+/// 期日通知が通知期間中は毎時発火し、無視され続けるとレベルが上がるテスト
+#[tokio::test]
+async fn test_due_date_notification_repeats_hourly() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let service = NotificationService::new(db.clone());
+    
+    // 期日 6/12 12:00 JST → 前日 6/11 18:00 JST から当日 18:00 JST まで毎時通知
+    let task = TaskService::new(db.clone())
+        .create_task(CreateTaskRequest {
+            title: "報告書".to_string(),
+            description: None,
+            status: Some(TaskStatus::Todo),
+            parent_id: None,
+            due_date: Some(Utc.with_ymd_and_hms(2025, 6, 12, 3, 0, 0).unwrap()),
+            notification_settings: Some(TaskNotificationSettings {
+                notification_type: "due_date_based".to_string(),
+                days_before: Some(1),
+                notification_time: Some("18:00".to_string()),
+                days_of_week: None,
+                level: 1,
+                notification_until: None,
+            }),
+            browser_actions: None,
+        })
+        .await
+        .unwrap();
+    sqlx::query("UPDATE tasks SET created_at = '2025-01-01T00:00:00+00:00' WHERE id = ?1")
+        .bind(&task.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    service.set_escalation_settings(NotificationEscalationSettings {
+        enabled: true,
+        fires_per_step: 2,
+        max_level: 3,
+    }).await.unwrap();
+    
+    // 6/11 18:00 JST
+    let start = Utc.with_ymd_and_hms(2025, 6, 11, 9, 0, 0).unwrap();
+    let hour = chrono::Duration::hours(1);
+    assert!(service.check_notifications(start - chrono::Duration::minutes(2)).await.unwrap().is_empty());
+    
+    let fire = |at: DateTime<Utc>| {
+        let service = &service;
+        async move {
+            let notification = service.check_notifications(at).await.unwrap().remove(0);
+            service.fire_notification(&notification, &notification.title, false, || Ok(())).await.unwrap();
+            notification
+        }
+    };
+    
+    let first = fire(start).await;
+    assert_eq!(first.notification_key.as_deref(), Some("2025-06-11 18:00"));
+    assert_eq!(first.level, 1);
+    // 同じ時間の枠では1回だけ、時間の途中では出ない
+    assert!(service.check_notifications(start + chrono::Duration::seconds(30)).await.unwrap().is_empty());
+    assert!(service.check_notifications(start + chrono::Duration::minutes(30)).await.unwrap().is_empty());
+    
+    let second = fire(start + hour + chrono::Duration::seconds(30)).await;
+    assert_eq!(second.notification_key.as_deref(), Some("2025-06-11 19:00"));
+    assert_eq!(second.level, 1);
+    
+    // 2回無視されたのでレベルが上がる
+    let third = service.check_notifications(start + hour * 2).await.unwrap();
+    assert_eq!(third[0].notification_key.as_deref(), Some("2025-06-11 20:00"));
+    assert_eq!(third[0].level, 2);
+    
+    // 期日当日の18:00 JSTが最後
+    let last = service.check_notifications(start + hour * 24).await.unwrap();
+    assert_eq!(last[0].notification_key.as_deref(), Some("2025-06-12 18:00"));
+    assert!(service.check_notifications(start + hour * 25).await.unwrap().is_empty());
+    
+    // 発火予定の一覧とも一致する
+    let firings = service.task_firings_between(&task, start, start + hour * 26).await.unwrap();
+    assert_eq!(firings.len(), 25);
+    assert_eq!(firings.last(), Some(&(start + hour * 24)));
+}
+
+/// 日付をまたぐクワイエットタイムの抑制と、レベル3の保留発火のテスト
+#[tokio::test]
+async fn test_quiet_hours_suppress_and_defer_critical() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let service = NotificationService::new(db.clone());
+    
+    let every_day = vec![0, 1, 2, 3, 4, 5, 6];
+    let normal = create_recurring_task(&db, "ストレッチ", "23:00", every_day.clone()).await;
+    let critical = create_recurring_task(&db, "サーバー監視", "02:00", every_day.clone()).await;
+    sqlx::query("UPDATE tasks SET notification_level = 3 WHERE id = ?1")
+        .bind(&critical.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    create_recurring_task(&db, "朝会", "07:00", every_day).await;
+    
+    // 2025-06-10 23:00 / 2025-06-11 02:00 / 07:00 JST
+    let late_night = Utc.with_ymd_and_hms(2025, 6, 10, 14, 0, 0).unwrap();
+    let small_hours = Utc.with_ymd_and_hms(2025, 6, 10, 17, 0, 0).unwrap();
+    let morning = Utc.with_ymd_and_hms(2025, 6, 10, 22, 0, 0).unwrap();
+    assert_eq!(service.check_notifications(late_night).await.unwrap()[0].task_id, normal.id);
+    
+    let mut quiet_hours = QuietHours {
+        start: "22:00".to_string(),
+        end: "07:00".to_string(),
+        defer_critical: false,
+    };
+    service.set_quiet_hours(Some(quiet_hours.clone())).await.unwrap();
+    assert_eq!(service.get_quiet_hours().await.unwrap(), Some(quiet_hours.clone()));
+    
+    // 時間帯中はレベルに関係なく抑制し、スキップした通知は終了後も出さない
+    assert!(service.check_notifications(late_night).await.unwrap().is_empty());
+    assert!(service.check_notifications(small_hours).await.unwrap().is_empty());
+    assert_eq!(titles_of(&service.check_notifications(morning).await.unwrap()), vec!["朝会"]);
+    
+    // 保留を有効にすると、終了直後にレベル3だけまとめて発火する
+    quiet_hours.defer_critical = true;
+    service.set_quiet_hours(Some(quiet_hours)).await.unwrap();
+    assert!(service.check_notifications(small_hours).await.unwrap().is_empty());
+    let resumed = service.check_notifications(morning).await.unwrap();
+    assert_eq!(titles_of(&resumed), vec!["朝会", "サーバー監視"]);
+    assert_eq!(resumed[1].level, 3);
+    assert_eq!(service.check_notifications(morning + chrono::Duration::minutes(2)).await.unwrap().len(), 0);
+    
+    // 解除・不正な設定
+    service.set_quiet_hours(None).await.unwrap();
+    assert_eq!(service.get_quiet_hours().await.unwrap(), None);
+    assert_eq!(service.check_notifications(small_hours).await.unwrap().len(), 1);
+    let invalid = QuietHours { start: "22:00".to_string(), end: "24:00".to_string(), defer_critical: false };
+    assert!(service.set_quiet_hours(Some(invalid)).await.is_err());
+}
+
+fn titles_of(notifications: &[TaskNotification]) -> Vec<&str> {
+    notifications.iter().map(|n| n.title.as_str()).collect()
+}
+
+/// 通知プロファイルを切り替えると発火する通知が変わるテスト
+#[tokio::test]
+async fn test_activate_profile_changes_fired_notifications() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    
+    // 毎日 20:00 JST（レベル2）
+    let task = create_recurring_task(&db, "日報を書く", "20:00", vec![0, 1, 2, 3, 4, 5, 6]).await;
+    let service = NotificationService::new(db);
+    
+    let profile = |name: &str, quiet_start: &str, quiet_end: &str| NotificationProfile {
+        name: name.to_string(),
+        quiet_start: Some(quiet_start.to_string()),
+        quiet_end: Some(quiet_end.to_string()),
+        default_notification_time: None,
+        min_level: 1,
+        level_rules: None,
+        focus_window_level: Some(3),
+    };
+    service.save_notification_profile(profile("work", "22:00", "07:00")).await.unwrap();
+    service.save_notification_profile(profile("home", "19:00", "08:00")).await.unwrap();
+    
+    // 2025-06-10 20:00 JST = 11:00Z
+    let at_eight_pm = Utc.with_ymd_and_hms(2025, 6, 10, 11, 0, 0).unwrap();
+    assert_eq!(service.check_notifications(at_eight_pm).await.unwrap().len(), 1);
+    
+    // 仕事用プロファイルでは通知しない時間帯の前なので発火する
+    service.activate_profile("work").await.unwrap();
+    let fired = service.check_notifications(at_eight_pm).await.unwrap();
+    assert_eq!(fired.len(), 1);
+    assert_eq!(fired[0].task_id, task.id);
+    
+    // 自宅用プロファイルは19時から通知しないので抑止される
+    service.activate_profile("home").await.unwrap();
+    assert!(service.check_notifications(at_eight_pm).await.unwrap().is_empty());
+    let schedule = service.enumerate_schedule_at(at_eight_pm - chrono::Duration::hours(1), 1).await.unwrap();
+    assert!(schedule.is_empty());
+    
+    // 最低レベルより低い通知も抑止される
+    let mut strict = profile("home", "23:00", "06:00");
+    strict.min_level = 3;
+    service.save_notification_profile(strict).await.unwrap();
+    assert!(service.check_notifications(at_eight_pm).await.unwrap().is_empty());
+    
+    // 有効なプロファイルを削除するとプロファイルなしに戻る
+    service.delete_notification_profile("home").await.unwrap();
+    assert!(service.active_profile().await.unwrap().is_none());
+    assert_eq!(service.check_notifications(at_eight_pm).await.unwrap().len(), 1);
+    assert!(service.activate_profile("home").await.is_err());
+}
+
+/// 集中セッションの開始〜終了と、セッション中に他のタスクの通知が抑止されるテスト
+#[tokio::test]
+async fn test_focus_session_suppresses_unrelated_notifications() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    
+    // 毎日 10:00 JST
+    let focused = create_recurring_task(&db, "設計書を書く", "10:00", vec![0, 1, 2, 3, 4, 5, 6]).await;
+    let other = create_recurring_task(&db, "メール確認", "10:00", vec![0, 1, 2, 3, 4, 5, 6]).await;
+    let service = NotificationService::new(db);
+    
+    // 2025-06-10 09:50 JST = 00:50Z から25分
+    let started = Utc.with_ymd_and_hms(2025, 6, 10, 0, 50, 0).unwrap();
+    assert!(service.start_focus_session(&focused.id, 0, started).await.is_err());
+    assert!(service.start_focus_session("missing", 25, started).await.is_err());
+    let session = service.start_focus_session(&focused.id, 25, started).await.unwrap();
+    assert_eq!(session.ends_at, Utc.with_ymd_and_hms(2025, 6, 10, 1, 15, 0).unwrap());
+    assert_eq!(service.get_focus_session().await.unwrap(), Some(session.clone()));
+    
+    // セッション中（10:00 JST）は対象タスクだけ通知する
+    let during = Utc.with_ymd_and_hms(2025, 6, 10, 1, 0, 0).unwrap();
+    let fired = service.check_notifications(during).await.unwrap();
+    assert_eq!(fired.len(), 1);
+    assert_eq!(fired[0].task_id, focused.id);
+    assert!(service.take_finished_focus_session(during).await.unwrap().is_none());
+    
+    // 終了時刻を過ぎたら一度だけ終了として返し、以降は通常どおり
+    let after = Utc.with_ymd_and_hms(2025, 6, 10, 1, 15, 0).unwrap();
+    assert_eq!(service.take_finished_focus_session(after).await.unwrap(), Some(session));
+    assert!(service.take_finished_focus_session(after).await.unwrap().is_none());
+    assert_eq!(service.check_notifications(during).await.unwrap().len(), 2);
+    
+    // 手動で終了した場合も通知の抑止は解除される
+    service.start_focus_session(&other.id, 30, started).await.unwrap();
+    let fired = service.check_notifications(during).await.unwrap();
+    assert_eq!(fired.len(), 1);
+    assert_eq!(fired[0].task_id, other.id);
+    assert_eq!(service.end_focus_session().await.unwrap().map(|s| s.task_id), Some(other.id));
+    assert!(service.end_focus_session().await.unwrap().is_none());
+    assert_eq!(service.check_notifications(during).await.unwrap().len(), 2);
+}
+
+/// 通知ログを（曜日, 時）ごとに集計するテスト
+#[tokio::test]
+async fn test_firing_heatmap_counts_logs_per_weekday_and_hour() {
+    let db = create_test_db().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let task = create_recurring_task(&db, "水やり", "08:00", vec![1]).await;
+    
+    let logs = [
+        // 2025-06-09(月) 08:00 JST ×2
+        ("2025-06-08T23:00:00+00:00", true),
+        ("2025-06-08T23:30:00+00:00", true),
+        // 2025-06-14(土) 21:00 JST
+        ("2025-06-14T12:00:00+00:00", true),
+        // 失敗した発火は数えない
+        ("2025-06-14T12:10:00+00:00", false),
+        // 集計期間外
+        ("2025-05-01T00:00:00+00:00", true),
+    ];
+    for (index, (fired_at, success)) in logs.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO notification_logs (id, task_id, fired_at, notification_type, level, success) VALUES (?1, ?2, ?3, 'recurring', 1, ?4)",
+        )
+        .bind(format!("log-{}", index))
+        .bind(&task.id)
+        .bind(fired_at)
+        .bind(success)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    }
+    
+    let service = NotificationService::new(db);
+    let now = Utc.with_ymd_and_hms(2025, 6, 15, 0, 0, 0).unwrap();
+    let grid = service.firing_heatmap_at(now, 30).await.unwrap();
+    
+    assert_eq!(grid[1][8], 2);
+    assert_eq!(grid[6][21], 1);
+    assert_eq!(grid.iter().flatten().sum::<u32>(), 3);
+    
+    assert!(service.firing_heatmap_at(now, 0).await.is_err());
+}
+
+/// 通知の種類と期日までの日数に応じた見出しと本文のテスト
+#[test]
+fn test_notification_text() {
+    let notification = |notification_type: &str, days_until_due: Option<i64>| TaskNotification {
+        task_id: "task-1".to_string(),
+        title: "請求書".to_string(),
+        level: 1,
+        days_until_due,
+        notification_type: notification_type.to_string(),
+        notification_key: None,
+    };
+    
+    assert_eq!(
+        notification_text(&notification("due_date_based", Some(0))),
+        ("📅 【期限当日】".to_string(), "「請求書」の期限は今日です".to_string())
+    );
+    assert_eq!(notification_text(&notification("due_date_based", Some(1))).1, "「請求書」の期限は明日です");
+    assert_eq!(
+        notification_text(&notification("due_date_based", Some(3))),
+        ("📅 【期限間近】".to_string(), "「請求書」の期限まであと3日です".to_string())
+    );
+    assert_eq!(notification_text(&notification("due_date_based", Some(-2))).1, "「請求書」の期限を2日過ぎています");
+    assert_eq!(notification_text(&notification("recurring", None)).1, "「請求書」のリマインドです");
+    assert_eq!(notification_text(&notification("inbox_aging", None)).0, "📥 未整理のタスク");
+}
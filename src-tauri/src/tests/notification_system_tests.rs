@@ -90,6 +90,7 @@ impl MockNotificationService {
                     level: task.notification_level.unwrap_or(1),
                     days_until_due: Some(days_until_due),
                     notification_type: "due_date_based".to_string(),
+                    notification_key: None,
                 });
             }
         }
@@ -135,6 +136,7 @@ impl MockNotificationService {
                     level: task.notification_level.unwrap_or(1),
                     days_until_due: None,
                     notification_type: "recurring".to_string(),
+                    notification_key: None,
                 });
             }
         }
@@ -73,6 +73,7 @@ async fn test_real_database_tag_update() {
             color: color.clone(),
             created_at: chrono::Utc::now().to_rfc3339(),
             updated_at: chrono::Utc::now().to_rfc3339(),
+            parent_id: None,
         };
         
         // タスク更新リクエストを作成
@@ -121,4 +121,111 @@ async fn test_tag_error_cases() {
     }
     
     println!("🎉 All tag error case tests passed!");
-}
\ No newline at end of file
+}
+
+/// タグ候補（前方一致優先）のテスト
+#[tokio::test]
+async fn test_suggest_tags_prefix_priority() {
+    let pool = create_test_pool().await;
+    
+    for name in ["homework", "work", "workshop", "private"] {
+        TagService::create_tag(&pool, CreateTagRequest {
+            name: name.to_string(),
+            color: "#3B82F6".to_string(),
+        }).await.unwrap();
+    }
+    
+    let suggestions = TagService::suggest_tags(&pool, "Work", 10).await.unwrap();
+    let names: Vec<&str> = suggestions.iter().map(|t| t.name.as_str()).collect();
+    
+    // 前方一致が先頭に並び、短い名前が優先される
+    assert_eq!(names[0], "work");
+    assert_eq!(names[1], "workshop");
+    assert!(!names.contains(&"private"));
+    
+    // 件数制限
+    let limited = TagService::suggest_tags(&pool, "work", 1).await.unwrap();
+    assert_eq!(limited.len(), 1);
+    assert_eq!(limited[0].name, "work");
+}
+
+/// タイプミスからのタグ候補のテスト
+#[tokio::test]
+async fn test_suggest_tags_typo_within_distance() {
+    let pool = create_test_pool().await;
+    
+    for name in ["meeting", "shopping", "urgent"] {
+        TagService::create_tag(&pool, CreateTagRequest {
+            name: name.to_string(),
+            color: "#3B82F6".to_string(),
+        }).await.unwrap();
+    }
+    
+    // 1文字違い
+    let suggestions = TagService::suggest_tags(&pool, "meetnig", 5).await.unwrap();
+    assert_eq!(suggestions.first().map(|t| t.name.as_str()), Some("meeting"));
+    
+    // 入力途中のタイプミス
+    let suggestions = TagService::suggest_tags(&pool, "urj", 5).await.unwrap();
+    assert_eq!(suggestions.first().map(|t| t.name.as_str()), Some("urgent"));
+    
+    // 距離が閾値を超える場合は候補にしない
+    let suggestions = TagService::suggest_tags(&pool, "xyzzy", 5).await.unwrap();
+    assert!(suggestions.is_empty());
+}
+
+/// タグの階層（親子関係・循環チェック・削除方法）のテスト
+#[tokio::test]
+async fn test_tag_hierarchy() {
+    let pool = create_test_pool().await;
+    
+    let mut ids = std::collections::HashMap::new();
+    for name in ["仕事", "プロジェクトA", "設計", "プロジェクトB", "個人"] {
+        let tag = TagService::create_tag(&pool, CreateTagRequest {
+            name: name.to_string(),
+            color: "#3B82F6".to_string(),
+        }).await.unwrap();
+        assert_eq!(tag.parent_id, None);
+        ids.insert(name, tag.id);
+    }
+    
+    // 仕事 > プロジェクトA > 設計、仕事 > プロジェクトB
+    for (child, parent) in [("プロジェクトA", "仕事"), ("設計", "プロジェクトA"), ("プロジェクトB", "仕事")] {
+        let tag = TagService::set_tag_parent(&pool, &ids[child], Some(&ids[parent])).await.unwrap();
+        assert_eq!(tag.parent_id.as_deref(), Some(ids[parent].as_str()));
+    }
+    
+    let children = TagService::get_child_tags(&pool, &ids["仕事"]).await.unwrap();
+    let names: Vec<&str> = children.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["プロジェクトA", "プロジェクトB"]);
+    
+    let tree = TagService::get_tag_tree(&pool).await.unwrap();
+    let roots: Vec<&str> = tree.iter().map(|node| node.tag.name.as_str()).collect();
+    assert_eq!(roots, vec!["仕事", "個人"]);
+    assert_eq!(tree[0].children.len(), 2);
+    assert_eq!(tree[0].children[0].children[0].tag.name, "設計");
+    
+    // 自分自身や子孫を親にすると循環するため拒否する
+    for parent in ["仕事", "設計"] {
+        let result = TagService::set_tag_parent(&pool, &ids["仕事"], Some(&ids[parent])).await;
+        assert!(matches!(result, Err(AppError::InvalidInput(_))));
+    }
+    let result = TagService::set_tag_parent(&pool, &ids["仕事"], Some("missing")).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+    
+    // 親を外すと最上位に戻る
+    let tag = TagService::set_tag_parent(&pool, &ids["プロジェクトB"], None).await.unwrap();
+    assert_eq!(tag.parent_id, None);
+    TagService::set_tag_parent(&pool, &ids["プロジェクトB"], Some(&ids["仕事"])).await.unwrap();
+    
+    // 通常の削除では子タグは最上位に戻る
+    TagService::delete_tag(&pool, &ids["プロジェクトA"]).await.unwrap();
+    let design = TagService::get_tag_by_id(&pool, &ids["設計"]).await.unwrap();
+    assert_eq!(design.parent_id, None);
+    
+    // 連鎖削除では子孫のタグもまとめて削除する
+    assert_eq!(TagService::delete_tag_cascade(&pool, &ids["仕事"]).await.unwrap(), 2);
+    let remaining: Vec<String> = TagService::get_all_tags(&pool).await.unwrap().into_iter().map(|t| t.name).collect();
+    assert_eq!(remaining, vec!["設計", "個人"]);
+    assert!(matches!(TagService::delete_tag_cascade(&pool, &ids["仕事"]).await, Err(AppError::NotFound(_))));
+}
@@ -15,7 +15,7 @@ async fn test_basic_task_crud_operations() {
     let create_request = CreateTaskRequest {
         title: "Test Task".to_string(),
         description: Some("Test description".to_string()),
-        status: TaskStatus::Todo,
+        status: Some(TaskStatus::Todo),
         // priority: Priority::Medium, // removed as per .kiro spec
         parent_id: None,
         due_date: None,
@@ -40,8 +40,17 @@ async fn test_basic_task_crud_operations() {
         notification_time: None,
         notification_days_of_week: None,
         notification_level: Some(1),
+        notification_until: None,
         // Browser actions
         browser_actions: None,
+        is_pinned: false,
+        status_changed_at: None,
+        is_optional: false,
+        notification_channels: None,
+        recurrence_rule: None,
+        recurrence_end: None,
+        archived_at: None,
+        deleted_at: None,
         // Tag system
         tags: None,
     };
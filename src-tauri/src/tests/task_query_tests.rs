@@ -0,0 +1,1657 @@
+use crate::database::Database;
+use crate::error::AppError;
+use crate::models::{CreateTagRequest, CreateTaskRequest, DueBucket, NotificationChannel, NotificationPreset, Task, TaskNotificationSettings, TaskStatus, UpdateTaskRequest};
+use crate::services::subtask_completion::SubtaskCompletionRules;
+use crate::services::task_limits::TaskFieldLimits;
+use crate::services::{NotificationService, SettingsService, TaskService};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use sqlx::SqlitePool;
+
+// テスト用のTaskServiceを作成
+async fn create_test_service() -> (TaskService, Database) {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    crate::database::migrations::run_migrations(&pool).await.unwrap();
+    let db = Database { pool };
+    (TaskService::new(db.clone()), db)
+}
+
+async fn create_task(service: &TaskService, title: &str, status: TaskStatus, due_date: Option<DateTime<Utc>>) -> Task {
+    service.create_task(CreateTaskRequest {
+        title: title.to_string(),
+        description: None,
+        status: Some(status),
+        parent_id: None,
+        due_date,
+        notification_settings: None,
+        browser_actions: None,
+    }).await.unwrap()
+}
+
+fn titles(tasks: &[Task]) -> Vec<&str> {
+    tasks.iter().map(|t| t.title.as_str()).collect()
+}
+
+/// 期日区分ごとのタスク分類テスト
+#[tokio::test]
+async fn test_get_tasks_by_due_bucket() {
+    let (service, db) = create_test_service().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    
+    // 基準時刻: 2025-06-10 12:00 JST
+    let now = Utc.with_ymd_and_hms(2025, 6, 10, 3, 0, 0).unwrap();
+    
+    create_task(&service, "overdue", TaskStatus::Todo, Some(Utc.with_ymd_and_hms(2025, 6, 9, 1, 0, 0).unwrap())).await;
+    create_task(&service, "today", TaskStatus::InProgress, Some(Utc.with_ymd_and_hms(2025, 6, 10, 14, 0, 0).unwrap())).await;
+    // UTCでは今日だがJSTでは翌日
+    create_task(&service, "tomorrow_jst", TaskStatus::Todo, Some(Utc.with_ymd_and_hms(2025, 6, 10, 16, 0, 0).unwrap())).await;
+    create_task(&service, "later", TaskStatus::Inbox, Some(Utc.with_ymd_and_hms(2025, 6, 20, 3, 0, 0).unwrap())).await;
+    create_task(&service, "no_date", TaskStatus::Todo, None).await;
+    create_task(&service, "done", TaskStatus::Done, Some(Utc.with_ymd_and_hms(2025, 6, 9, 1, 0, 0).unwrap())).await;
+    
+    let buckets = service.get_tasks_by_due_bucket_at(now).await.unwrap();
+    
+    assert_eq!(buckets.len(), 5);
+    assert_eq!(titles(&buckets[&DueBucket::Overdue]), vec!["overdue"]);
+    assert_eq!(titles(&buckets[&DueBucket::Today]), vec!["today"]);
+    assert_eq!(titles(&buckets[&DueBucket::ThisWeek]), vec!["tomorrow_jst"]);
+    assert_eq!(titles(&buckets[&DueBucket::Later]), vec!["later"]);
+    assert_eq!(titles(&buckets[&DueBucket::NoDate]), vec!["no_date"]);
+}
+
+/// 通知プリセットの作成と適用テスト
+#[tokio::test]
+async fn test_apply_notification_preset() {
+    let (service, _db) = create_test_service().await;
+    let task = create_task(&service, "weekly review", TaskStatus::Todo, None).await;
+    
+    let preset = NotificationPreset {
+        name: "平日の朝".to_string(),
+        settings: TaskNotificationSettings {
+            notification_type: "recurring".to_string(),
+            days_before: None,
+            notification_time: Some("08:30".to_string()),
+            days_of_week: Some(vec![1, 2, 3, 4, 5]),
+            level: 2,
+            notification_until: None,
+        },
+    };
+    service.save_notification_preset(preset).await.unwrap();
+    
+    let presets = service.get_notification_presets().await.unwrap();
+    assert_eq!(presets.len(), 1);
+    assert_eq!(presets[0].name, "平日の朝");
+    
+    let updated = service.apply_notification_preset(&task.id, "平日の朝").await.unwrap();
+    assert_eq!(updated.notification_type.as_deref(), Some("recurring"));
+    assert_eq!(updated.notification_time.as_deref(), Some("08:30"));
+    assert_eq!(updated.notification_days_of_week.as_deref(), Some("[1,2,3,4,5]"));
+    assert_eq!(updated.notification_level, Some(2));
+    
+    // 存在しないプリセット
+    let missing = service.apply_notification_preset(&task.id, "unknown").await;
+    assert!(matches!(missing, Err(AppError::NotFound(_))));
+    
+    // 不正な設定は保存できない
+    let invalid = service.save_notification_preset(NotificationPreset {
+        name: "broken".to_string(),
+        settings: TaskNotificationSettings {
+            notification_type: "recurring".to_string(),
+            days_before: None,
+            notification_time: Some("25:00".to_string()),
+            days_of_week: Some(vec![1]),
+            level: 1,
+            notification_until: None,
+        },
+    }).await;
+    assert!(matches!(invalid, Err(AppError::Validation(_))));
+    
+    service.delete_notification_preset("平日の朝").await.unwrap();
+    assert!(service.get_notification_presets().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_recurring_notification_requires_days_of_week() {
+    let (service, _db) = create_test_service().await;
+    let recurring = |days: Vec<i32>| TaskNotificationSettings {
+        notification_type: "recurring".to_string(),
+        days_before: None,
+        notification_time: Some("09:00".to_string()),
+        days_of_week: Some(days),
+        level: 1,
+        notification_until: None,
+    };
+    let update = |days: Vec<i32>| UpdateTaskRequest {
+        title: None,
+        description: None,
+        status: None,
+        parent_id: None,
+        due_date: None,
+        notification_settings: Some(recurring(days)),
+        browser_actions: None,
+        tags: None,
+    };
+    let create = |days: Vec<i32>| CreateTaskRequest {
+        title: "毎週の振り返り".to_string(),
+        description: None,
+        status: Some(TaskStatus::Todo),
+        parent_id: None,
+        due_date: None,
+        notification_settings: Some(recurring(days)),
+        browser_actions: None,
+    };
+    
+    // 曜日が空・未指定だと一度も発火しないので拒否
+    let empty = service.create_task(create(vec![])).await;
+    assert!(matches!(empty, Err(AppError::InvalidInput(_))));
+    let mut unset = create(vec![]);
+    if let Some(settings) = unset.notification_settings.as_mut() {
+        settings.days_of_week = None;
+    }
+    assert!(matches!(service.create_task(unset).await, Err(AppError::InvalidInput(_))));
+    
+    let task = service.create_task(create(vec![5])).await.unwrap();
+    assert_eq!(task.notification_days_of_week.as_deref(), Some("[5]"));
+    
+    // 更新時も同様
+    let cleared = service.update_task(&task.id, update(vec![])).await;
+    assert!(matches!(cleared, Err(AppError::InvalidInput(_))));
+    let updated = service.update_task(&task.id, update(vec![1, 3])).await.unwrap();
+    assert_eq!(updated.notification_days_of_week.as_deref(), Some("[1,3]"));
+}
+
+/// ピン留めしたタスクが同じステータス内で先頭に並ぶテスト
+#[tokio::test]
+async fn test_pinned_tasks_sort_first() {
+    let (service, _db) = create_test_service().await;
+    
+    let first = create_task(&service, "first", TaskStatus::Todo, None).await;
+    let pinned = create_task(&service, "pinned", TaskStatus::Todo, None).await;
+    create_task(&service, "latest", TaskStatus::Todo, None).await;
+    create_task(&service, "inbox", TaskStatus::Inbox, None).await;
+    
+    service.pin_task(&pinned.id).await.unwrap();
+    service.pin_task(&first.id).await.unwrap();
+    service.unpin_task(&first.id).await.unwrap();
+    
+    let tasks = service.get_tasks().await.unwrap();
+    assert_eq!(titles(&tasks), vec!["inbox", "pinned", "latest", "first"]);
+    assert!(tasks[1].is_pinned);
+    
+    let roots = service.get_root_tasks().await.unwrap();
+    assert_eq!(titles(&roots), vec!["inbox", "pinned", "latest", "first"]);
+    
+    assert!(matches!(service.pin_task("missing").await, Err(AppError::NotFound(_))));
+}
+
+/// Markdownのチェックリストを階層付きで取り込むテスト
+#[tokio::test]
+async fn test_import_markdown_checklist() {
+    let (service, _db) = create_test_service().await;
+    let project = create_task(&service, "引っ越し", TaskStatus::Todo, None).await;
+    
+    let markdown = "\
+# 引っ越し計画
+- [ ] 業者を決める
+  - [x] 見積もりを依頼
+  - [ ] 比較する
+- [x] 住所変更
+- [?] 壊れた行
+- [ ]
+  - [X] 転出届
+";
+    
+    let result = service.import_markdown(markdown, Some(project.id.clone())).await.unwrap();
+    
+    assert_eq!(titles(&result.tasks), vec!["業者を決める", "見積もりを依頼", "比較する", "住所変更", "転出届"]);
+    assert_eq!(result.warnings.len(), 2);
+    
+    let top_level = service.get_children(&project.id).await.unwrap();
+    assert_eq!(titles(&top_level), vec!["業者を決める", "住所変更"]);
+    
+    let vendor = &top_level[0];
+    let vendor_children = service.get_children(&vendor.id).await.unwrap();
+    assert_eq!(titles(&vendor_children), vec!["見積もりを依頼", "比較する"]);
+    assert_eq!(vendor.status, "todo");
+    assert_eq!(vendor_children[0].status, "done");
+    assert!(vendor_children[0].completed_at.is_some());
+    assert_eq!(vendor_children[1].status, "todo");
+    assert_eq!(top_level[1].status, "done");
+    
+    // タイトルのない行を飛ばしても、インデントから直前の項目の子になる
+    let address = service.get_children(&top_level[1].id).await.unwrap();
+    assert_eq!(titles(&address), vec!["転出届"]);
+    
+    // 存在しない親は拒否
+    assert!(matches!(
+        service.import_markdown("- [ ] a", Some("missing".to_string())).await,
+        Err(AppError::NotFound(_))
+    ));
+    
+    // 取り込み先の階層と入れ子を合わせて10階層まで（projectが1階層目）
+    let nested = |levels: usize| {
+        (0..levels).map(|i| format!("{}- [ ] level{}\n", "  ".repeat(i), i + 2)).collect::<String>()
+    };
+    let before = service.get_tasks().await.unwrap().len();
+    assert!(matches!(
+        service.import_markdown(&nested(10), Some(project.id.clone())).await,
+        Err(AppError::InvalidInput(_))
+    ));
+    assert_eq!(service.get_tasks().await.unwrap().len(), before);
+    assert_eq!(service.import_markdown(&nested(9), Some(project.id.clone())).await.unwrap().tasks.len(), 9);
+    assert!(matches!(service.import_markdown(&nested(11), None).await, Err(AppError::InvalidInput(_))));
+}
+
+/// 通知設定を複数タスクへコピーするテスト
+#[tokio::test]
+async fn test_copy_notification_settings() {
+    let (service, _db) = create_test_service().await;
+    let source = service.create_task(CreateTaskRequest {
+        title: "daily standup".to_string(),
+        description: None,
+        status: Some(TaskStatus::Todo),
+        parent_id: None,
+        due_date: None,
+        notification_settings: Some(TaskNotificationSettings {
+            notification_type: "recurring".to_string(),
+            days_before: None,
+            notification_time: Some("09:45".to_string()),
+            days_of_week: Some(vec![1, 3, 5]),
+            level: 3,
+            notification_until: Some("2025-12-31".to_string()),
+        }),
+        browser_actions: None,
+    }).await.unwrap();
+    let first = create_task(&service, "first", TaskStatus::Todo, None).await;
+    let second = create_task(&service, "second", TaskStatus::Inbox, None).await;
+    
+    let copied = service
+        .copy_notification_settings(&source.id, vec![first.id.clone(), second.id.clone()])
+        .await
+        .unwrap();
+    assert_eq!(copied.len(), 2);
+    
+    for task in [&first, &second] {
+        let updated = service.get_task_by_id(&task.id).await.unwrap();
+        assert_eq!(updated.notification_type, source.notification_type);
+        assert_eq!(updated.notification_days_before, source.notification_days_before);
+        assert_eq!(updated.notification_time, source.notification_time);
+        assert_eq!(updated.notification_days_of_week, source.notification_days_of_week);
+        assert_eq!(updated.notification_level, source.notification_level);
+        assert_eq!(updated.notification_until, source.notification_until);
+        // 通知以外のフィールドは変わらない
+        assert_eq!(updated.title, task.title);
+    }
+    
+    // コピー先に存在しないタスクが含まれていれば何も更新しない
+    let third = create_task(&service, "third", TaskStatus::Todo, None).await;
+    let result = service
+        .copy_notification_settings(&source.id, vec![third.id.clone(), "missing".to_string()])
+        .await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+    let untouched = service.get_task_by_id(&third.id).await.unwrap();
+    assert_eq!(untouched.notification_type.as_deref(), Some("none"));
+}
+
+/// タイトル・説明の文字数上限のテスト
+#[tokio::test]
+async fn test_task_field_length_limits() {
+    let (service, _db) = create_test_service().await;
+    service.set_field_limits(TaskFieldLimits {
+        max_title_length: 10,
+        max_description_length: 20,
+    }).await.unwrap();
+    
+    let request = |title: String, description: Option<String>| CreateTaskRequest {
+        title,
+        description,
+        status: Some(TaskStatus::Todo),
+        parent_id: None,
+        due_date: None,
+        notification_settings: None,
+        browser_actions: None,
+    };
+    let update = |title: Option<String>, description: Option<String>| UpdateTaskRequest {
+        title,
+        description,
+        status: None,
+        parent_id: None,
+        due_date: None,
+        notification_settings: None,
+        browser_actions: None,
+        tags: None,
+    };
+    
+    // 上限ちょうどは作成できる（マルチバイト文字も1文字として数える）
+    let task = service
+        .create_task(request("あ".repeat(10), Some("x".repeat(20))))
+        .await
+        .unwrap();
+    assert_eq!(task.title.chars().count(), 10);
+    
+    // 上限を超えると作成できない
+    let too_long_title = service.create_task(request("あ".repeat(11), None)).await;
+    match too_long_title {
+        Err(AppError::InvalidInput(message)) => assert!(message.contains("limit is 10")),
+        other => panic!("expected InvalidInput, got {:?}", other),
+    }
+    let too_long_description = service.create_task(request("ok".to_string(), Some("x".repeat(21)))).await;
+    match too_long_description {
+        Err(AppError::InvalidInput(message)) => assert!(message.contains("limit is 20")),
+        other => panic!("expected InvalidInput, got {:?}", other),
+    }
+    
+    // 更新時も同じ上限を適用する
+    assert!(service.update_task(&task.id, update(Some("b".repeat(10)), Some("y".repeat(20)))).await.is_ok());
+    assert!(matches!(
+        service.update_task(&task.id, update(Some("b".repeat(11)), None)).await,
+        Err(AppError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        service.update_task(&task.id, update(None, Some("y".repeat(21)))).await,
+        Err(AppError::InvalidInput(_))
+    ));
+    let unchanged = service.get_task_by_id(&task.id).await.unwrap();
+    assert_eq!(unchanged.title, "b".repeat(10));
+    
+    // Markdownの取り込みも同じ上限を適用し、1件でも超えれば何も作らない
+    let markdown = format!("- [ ] {}\n- [ ] {}\n", "c".repeat(10), "c".repeat(11));
+    assert!(matches!(service.import_markdown(&markdown, None).await, Err(AppError::InvalidInput(_))));
+    assert_eq!(service.get_tasks().await.unwrap().len(), 1);
+    
+    // 上限0は設定できない
+    assert!(matches!(
+        service.set_field_limits(TaskFieldLimits { max_title_length: 0, max_description_length: 20 }).await,
+        Err(AppError::InvalidInput(_))
+    ));
+}
+
+/// ステータス省略時に設定の既定ステータスを使うテスト
+#[tokio::test]
+async fn test_default_task_status() {
+    let (service, _db) = create_test_service().await;
+    let request = |title: &str, status: Option<TaskStatus>| CreateTaskRequest {
+        title: title.to_string(),
+        description: None,
+        status,
+        parent_id: None,
+        due_date: None,
+        notification_settings: None,
+        browser_actions: None,
+    };
+    
+    // 未設定ならTODO
+    assert_eq!(service.get_default_status().await.unwrap().map(|s| s.to_string()), None);
+    let task = service.create_task(request("unset", None)).await.unwrap();
+    assert_eq!(task.status, "todo");
+    
+    service.set_default_status(Some(TaskStatus::Inbox)).await.unwrap();
+    let task = service.create_task(request("omitted", None)).await.unwrap();
+    assert_eq!(task.status, "inbox");
+    
+    // 明示したステータスが優先される
+    let task = service.create_task(request("explicit", Some(TaskStatus::InProgress))).await.unwrap();
+    assert_eq!(task.status, "in_progress");
+    
+    // JSONでstatusを省略した場合も既定値を使う
+    let from_json: CreateTaskRequest = serde_json::from_str(r#"{"title":"from json"}"#).unwrap();
+    let task = service.create_task(from_json).await.unwrap();
+    assert_eq!(task.status, "inbox");
+    
+    // 既定値を解除するとTODOに戻る
+    service.set_default_status(None).await.unwrap();
+    let task = service.create_task(request("cleared", None)).await.unwrap();
+    assert_eq!(task.status, "todo");
+}
+
+/// 着手中になってからの経過時間順の取得テスト
+#[tokio::test]
+async fn test_get_longest_in_progress() {
+    let (service, db) = create_test_service().await;
+    let to_in_progress = UpdateTaskRequest {
+        title: None,
+        description: None,
+        status: Some(TaskStatus::InProgress),
+        parent_id: None,
+        due_date: None,
+        notification_settings: None,
+        browser_actions: None,
+        tags: None,
+    };
+    
+    let recent = create_task(&service, "recent", TaskStatus::Todo, None).await;
+    let oldest = create_task(&service, "oldest", TaskStatus::Todo, None).await;
+    let middle = create_task(&service, "middle", TaskStatus::Todo, None).await;
+    create_task(&service, "still todo", TaskStatus::Todo, None).await;
+    
+    for task in [&recent, &oldest, &middle] {
+        let updated = service.update_task(&task.id, to_in_progress.clone()).await.unwrap();
+        assert!(updated.status_changed_at.is_some());
+    }
+    
+    // 着手した時刻を固定する
+    for (id, changed_at) in [
+        (&recent.id, "2025-06-10T09:00:00+00:00"),
+        (&oldest.id, "2025-06-01T09:00:00+00:00"),
+        (&middle.id, "2025-06-05T09:00:00+09:00"),
+    ] {
+        sqlx::query("UPDATE tasks SET status_changed_at = ?2 WHERE id = ?1")
+            .bind(id)
+            .bind(changed_at)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+    
+    let longest = service.get_longest_in_progress(10).await.unwrap();
+    assert_eq!(titles(&longest), vec!["oldest", "middle", "recent"]);
+    assert_eq!(titles(&service.get_longest_in_progress(1).await.unwrap()), vec!["oldest"]);
+    
+    // ステータスが変わらない更新では着手時刻を変えない
+    let unchanged = service.update_task(&oldest.id, to_in_progress.clone()).await.unwrap();
+    assert_eq!(unchanged.status_changed_at.as_deref(), Some("2025-06-01T09:00:00+00:00"));
+    
+    // 別のステータスに移ると対象外になる
+    service.move_task(&oldest.id, "done").await.unwrap();
+    assert_eq!(titles(&service.get_longest_in_progress(10).await.unwrap()), vec!["middle", "recent"]);
+}
+
+async fn create_child(service: &TaskService, parent: &Task, title: &str, status: TaskStatus) -> Task {
+    service.create_task(CreateTaskRequest {
+        title: title.to_string(),
+        description: None,
+        status: Some(status),
+        parent_id: Some(parent.id.clone()),
+        due_date: None,
+        notification_settings: None,
+        browser_actions: None,
+    }).await.unwrap()
+}
+
+/// 任意の子タスクは親の完了を妨げず、進捗率にも数えないテスト
+#[tokio::test]
+async fn test_optional_children_do_not_block_parent_completion() {
+    let (service, _db) = create_test_service().await;
+    service.set_subtask_completion_rules(SubtaskCompletionRules {
+        require_children_complete: true,
+        ignore_optional_children: true,
+    }).await.unwrap();
+    
+    let parent = create_task(&service, "リリース準備", TaskStatus::InProgress, None).await;
+    let required = create_child(&service, &parent, "テスト", TaskStatus::Done).await;
+    let optional = create_child(&service, &parent, "ブログ記事", TaskStatus::Todo).await;
+    assert!(!optional.is_optional);
+    
+    // 必須扱いの未完了の子があると完了にできない
+    let blocked = service.move_task(&parent.id, "done").await;
+    assert!(matches!(blocked, Err(AppError::InvalidInput(ref msg)) if msg.contains("ブログ記事")));
+    assert_eq!(service.get_task_with_children(&parent.id).await.unwrap().progress, Some(50));
+    
+    // 任意にすると完了を妨げず、進捗率は必須の子だけで計算する
+    let optional = service.set_task_optional(&optional.id, true).await.unwrap();
+    assert!(optional.is_optional);
+    assert_eq!(service.get_task_by_id(&parent.id).await.unwrap().progress, Some(100));
+    assert_eq!(service.get_task_with_children(&parent.id).await.unwrap().progress, Some(100));
+    
+    let done = service.move_task(&parent.id, "done").await.unwrap();
+    assert_eq!(done.status, "done");
+    assert_eq!(service.get_task_by_id(&required.id).await.unwrap().status, "done");
+}
+
+/// 必須の未完了の子タスクがあれば、進捗率100%でも親は完了しないテスト
+#[tokio::test]
+async fn test_required_child_blocks_parent_completion() {
+    let (service, _db) = create_test_service().await;
+    
+    // 既定では子タスクの状態に関係なく完了にできる
+    assert_eq!(service.get_subtask_completion_rules().await.unwrap(), SubtaskCompletionRules::default());
+    let free_parent = create_task(&service, "自由な親", TaskStatus::Todo, None).await;
+    create_child(&service, &free_parent, "未完了の子", TaskStatus::Todo).await;
+    assert_eq!(service.move_task(&free_parent.id, "done").await.unwrap().status, "done");
+    
+    let mut rules = SubtaskCompletionRules {
+        require_children_complete: true,
+        ignore_optional_children: true,
+    };
+    service.set_subtask_completion_rules(rules).await.unwrap();
+    
+    let parent = create_task(&service, "引っ越し", TaskStatus::InProgress, None).await;
+    let required = create_child(&service, &parent, "荷造り", TaskStatus::InProgress).await;
+    let optional = create_child(&service, &parent, "挨拶回り", TaskStatus::Done).await;
+    service.set_task_optional(&optional.id, true).await.unwrap();
+    
+    assert!(matches!(service.move_task(&parent.id, "done").await, Err(AppError::InvalidInput(_))));
+    assert!(matches!(service.update_progress(&parent.id, 100).await, Err(AppError::InvalidInput(_))));
+    assert_eq!(service.get_task_by_id(&parent.id).await.unwrap().status, "in_progress");
+    
+    // 任意の子も数える設定なら、任意の子の未完了も親の完了を妨げる
+    rules.ignore_optional_children = false;
+    service.set_subtask_completion_rules(rules).await.unwrap();
+    service.move_task(&required.id, "done").await.unwrap();
+    service.move_task(&optional.id, "todo").await.unwrap();
+    assert!(matches!(service.move_task(&parent.id, "done").await, Err(AppError::InvalidInput(ref msg)) if msg.contains("挨拶回り")));
+    
+    service.move_task(&optional.id, "done").await.unwrap();
+    assert_eq!(service.move_task(&parent.id, "done").await.unwrap().status, "done");
+}
+
+async fn set_completed_at(db: &Database, id: &str, completed_at: DateTime<Utc>) {
+    sqlx::query("UPDATE tasks SET status = 'done', completed_at = ?2 WHERE id = ?1")
+        .bind(id)
+        .bind(completed_at.to_rfc3339())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+}
+
+/// 子タスクの最近の完了ペースから完了予定日を見積もるテスト
+#[tokio::test]
+async fn test_estimate_completion_eta() {
+    let (service, db) = create_test_service().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let now = Utc.with_ymd_and_hms(2025, 6, 10, 3, 0, 0).unwrap();
+    
+    let parent = create_task(&service, "本の執筆", TaskStatus::InProgress, None).await;
+    // 直近14日間に7章完了（1日0.5章）、1章はそれより前に完了
+    for day in 0..7 {
+        let child = create_child(&service, &parent, &format!("第{}章", day + 1), TaskStatus::Todo).await;
+        set_completed_at(&db, &child.id, now - chrono::Duration::days(day * 2 + 1)).await;
+    }
+    let early = create_child(&service, &parent, "構成案", TaskStatus::Todo).await;
+    set_completed_at(&db, &early.id, now - chrono::Duration::days(30)).await;
+    for title in ["第8章", "第9章", "第10章"] {
+        create_child(&service, &parent, title, TaskStatus::Todo).await;
+    }
+    
+    // 残り3章 ÷ 0.5章/日 = 6日後
+    let eta = service.estimate_completion_eta_at(&parent.id, now).await.unwrap();
+    assert_eq!(eta, Some(NaiveDate::from_ymd_opt(2025, 6, 16).unwrap()));
+}
+
+/// 最近の完了がなければ見積もれない（None）テスト
+#[tokio::test]
+async fn test_estimate_completion_eta_zero_velocity() {
+    let (service, db) = create_test_service().await;
+    let now = Utc.with_ymd_and_hms(2025, 6, 10, 3, 0, 0).unwrap();
+    
+    let parent = create_task(&service, "大掃除", TaskStatus::InProgress, None).await;
+    let old = create_child(&service, &parent, "窓拭き", TaskStatus::Todo).await;
+    set_completed_at(&db, &old.id, now - chrono::Duration::days(20)).await;
+    create_child(&service, &parent, "換気扇", TaskStatus::Todo).await;
+    
+    assert_eq!(service.estimate_completion_eta_at(&parent.id, now).await.unwrap(), None);
+    
+    // 子タスクのない親、存在しない親
+    let lonely = create_task(&service, "子なし", TaskStatus::Todo, None).await;
+    assert_eq!(
+        service.estimate_completion_eta_at(&lonely.id, now).await.unwrap(),
+        Some(NaiveDate::from_ymd_opt(2025, 6, 10).unwrap())
+    );
+    assert!(matches!(service.estimate_completion_eta_at("missing", now).await, Err(AppError::NotFound(_))));
+}
+
+/// 親子関係を入れ子にしたJSON出力のテスト
+#[tokio::test]
+async fn test_export_tree_json() {
+    let (service, _db) = create_test_service().await;
+    let parent = create_task(&service, "旅行の準備", TaskStatus::Todo, None).await;
+    create_child(&service, &parent, "宿の予約", TaskStatus::Done).await;
+    let tickets = create_child(&service, &parent, "切符の購入", TaskStatus::Todo).await;
+    create_child(&service, &tickets, "座席の指定", TaskStatus::Todo).await;
+    create_task(&service, "単独タスク", TaskStatus::Inbox, None).await;
+    
+    let tag = service.create_tag(crate::models::CreateTagRequest {
+        name: "旅行".to_string(),
+        color: "#3b82f6".to_string(),
+    }).await.unwrap();
+    service.add_tag_to_task(&parent.id, &tag.id).await.unwrap();
+    
+    let json: serde_json::Value = serde_json::from_str(&service.export_tree_json().await.unwrap()).unwrap();
+    let roots = json.as_array().unwrap();
+    assert_eq!(roots.len(), 2);
+    
+    let parent_node = roots.iter().find(|node| node["id"] == parent.id.as_str()).unwrap();
+    let children = parent_node["children"].as_array().unwrap();
+    assert_eq!(children.len(), 2);
+    assert_eq!(children[0]["title"], "宿の予約");
+    assert_eq!(children[1]["children"][0]["title"], "座席の指定");
+    assert_eq!(parent_node["tags"][0]["name"], "旅行");
+    assert_eq!(parent_node["notificationType"], "none");
+}
+
+/// 過去の期日でも作成はするが、警告を返すテスト
+#[tokio::test]
+async fn test_create_task_with_past_due_date_warns() {
+    let (service, _db) = create_test_service().await;
+    let request = |title: &str, due_date: DateTime<Utc>| CreateTaskRequest {
+        title: title.to_string(),
+        description: None,
+        status: Some(TaskStatus::Todo),
+        parent_id: None,
+        due_date: Some(due_date),
+        notification_settings: None,
+        browser_actions: None,
+    };
+    
+    let past = service
+        .create_task_with_warnings(request("過去の期日", Utc::now() - chrono::Duration::days(1)))
+        .await
+        .unwrap();
+    assert_eq!(past.warnings.len(), 1);
+    assert!(past.warnings[0].contains("in the past"));
+    let saved = service.get_task_by_id(&past.task.id).await.unwrap();
+    assert_eq!(saved.title, "過去の期日");
+    
+    // JSONではタスクのフィールドと並んで警告が入る
+    let json = serde_json::to_value(&past).unwrap();
+    assert_eq!(json["title"], "過去の期日");
+    assert_eq!(json["warnings"].as_array().unwrap().len(), 1);
+    
+    let future = service
+        .create_task_with_warnings(request("未来の期日", Utc::now() + chrono::Duration::days(1)))
+        .await
+        .unwrap();
+    assert!(future.warnings.is_empty());
+}
+
+/// 今後30日間に一度も発火しない通知設定を検出するテスト
+#[tokio::test]
+async fn test_find_inert_notifications() {
+    let (service, db) = create_test_service().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let notifications = NotificationService::new(db.clone());
+    let now = Utc.with_ymd_and_hms(2025, 6, 10, 0, 0, 0).unwrap();
+    
+    // 検証を通らない設定も含めて直接書き込む
+    let configure = |task_id: String, notification_type: &'static str, days_of_week: Option<&'static str>, due_date: Option<&'static str>, until: Option<&'static str>| {
+        let pool = db.pool.clone();
+        async move {
+            sqlx::query(
+                r#"
+                UPDATE tasks
+                SET notification_type = ?2, notification_time = '09:00', notification_days_of_week = ?3,
+                    notification_days_before = 1, due_date = ?4, notification_until = ?5
+                WHERE id = ?1
+                "#,
+            )
+            .bind(task_id)
+            .bind(notification_type)
+            .bind(days_of_week)
+            .bind(due_date)
+            .bind(until)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+    };
+    
+    let weekly = create_task(&service, "weekly", TaskStatus::Todo, None).await;
+    configure(weekly.id.clone(), "recurring", Some("[1,3,5]"), None, None).await;
+    let upcoming = create_task(&service, "upcoming", TaskStatus::Todo, None).await;
+    configure(upcoming.id.clone(), "due_date_based", None, Some("2025-06-20T00:00:00+00:00"), None).await;
+    
+    let no_days = create_task(&service, "no days", TaskStatus::Todo, None).await;
+    configure(no_days.id.clone(), "recurring", Some("[]"), None, None).await;
+    let expired = create_task(&service, "expired", TaskStatus::Todo, None).await;
+    configure(expired.id.clone(), "recurring", Some("[1,3,5]"), None, Some("2025-06-01")).await;
+    let past_due = create_task(&service, "past due", TaskStatus::Todo, None).await;
+    configure(past_due.id.clone(), "due_date_based", None, Some("2025-06-01T00:00:00+00:00"), None).await;
+    let no_due_date = create_task(&service, "no due date", TaskStatus::Todo, None).await;
+    configure(no_due_date.id.clone(), "due_date_based", None, None, None).await;
+    
+    // 完了済み・通知なしのタスクは対象外
+    let done = create_task(&service, "done", TaskStatus::Done, None).await;
+    configure(done.id.clone(), "recurring", Some("[]"), None, None).await;
+    create_task(&service, "silent", TaskStatus::Todo, None).await;
+    
+    let inert = service.find_inert_notifications_at(&notifications, now).await.unwrap();
+    assert_eq!(titles(&inert), vec!["no days", "expired", "past due", "no due date"]);
+}
+
+/// タスクごとの通知経路の保存テスト
+#[tokio::test]
+async fn test_set_notification_channels() {
+    let (service, _db) = create_test_service().await;
+    let task = create_task(&service, "tabs", TaskStatus::Todo, None).await;
+    // 未設定なら従来の動作
+    assert_eq!(task.notification_channels(), None);
+    
+    let channels = vec![NotificationChannel::Tray, NotificationChannel::Browser];
+    let updated = service.set_notification_channels(&task.id, Some(channels.clone())).await.unwrap();
+    assert_eq!(updated.notification_channels(), Some(channels));
+    
+    // 空の指定は一度も知らせないので拒否
+    let empty = service.set_notification_channels(&task.id, Some(Vec::new())).await;
+    assert!(matches!(empty, Err(AppError::InvalidInput(_))));
+    
+    let reset = service.set_notification_channels(&task.id, None).await.unwrap();
+    assert_eq!(reset.notification_channels(), None);
+    
+    let missing = service.set_notification_channels("missing", None).await;
+    assert!(matches!(missing, Err(AppError::NotFound(_))));
+}
+
+/// 期日を今日からの日数・営業日数でまとめて設定するテスト
+#[tokio::test]
+async fn test_set_due_date_relative() {
+    let (service, db) = create_test_service().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    // 2025-06-12(木) 10:00 JST
+    let now = Utc.with_ymd_and_hms(2025, 6, 12, 1, 0, 0).unwrap();
+    let timed = create_task(&service, "timed", TaskStatus::Todo, Some(Utc.with_ymd_and_hms(2025, 6, 1, 9, 30, 0).unwrap())).await;
+    let undated = create_task(&service, "undated", TaskStatus::Todo, None).await;
+    let untouched = create_task(&service, "untouched", TaskStatus::Todo, None).await;
+    let ids = vec![timed.id.clone(), undated.id.clone()];
+    
+    // 暦日: 3日後は日曜。既存の時刻は保ち、期日のないタスクはその日の0時
+    let updated = service.set_due_date_relative_at(ids.clone(), 3, false, now).await.unwrap();
+    assert_eq!(updated[0].due_date.as_deref(), Some("2025-06-15T09:30:00+00:00"));
+    assert_eq!(updated[1].due_date.as_deref(), Some("2025-06-14T15:00:00+00:00"));
+    
+    // 営業日: 木曜から3営業日後は土日を飛ばして翌週火曜
+    let updated = service.set_due_date_relative_at(ids.clone(), 3, true, now).await.unwrap();
+    assert_eq!(updated[0].due_date.as_deref(), Some("2025-06-17T09:30:00+00:00"));
+    assert_eq!(updated[1].due_date.as_deref(), Some("2025-06-16T15:00:00+00:00"));
+    assert!(service.get_task_by_id(&untouched.id).await.unwrap().due_date.is_none());
+    
+    // 存在しないタスクが含まれていれば何も更新しない
+    let result = service
+        .set_due_date_relative_at(vec![untouched.id.clone(), "missing".to_string()], 1, false, now)
+        .await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+    assert!(service.get_task_by_id(&untouched.id).await.unwrap().due_date.is_none());
+    
+    // 負の営業日数は受け付けない
+    let negative = service.set_due_date_relative_at(ids, -1, true, now).await;
+    assert!(matches!(negative, Err(AppError::InvalidInput(_))));
+}
+
+/// 完了日の範囲でタスクを取得するテスト（境界はアプリのタイムゾーンで判定）
+#[tokio::test]
+async fn test_get_tasks_completed_between() {
+    let (service, db) = create_test_service().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    
+    let completions = [
+        // 2025-05-31 23:59 JST（範囲外）
+        ("before", "2025-05-31T14:59:00+00:00"),
+        // 2025-06-01 00:00 JST（開始の境界）
+        ("first", "2025-05-31T15:00:00+00:00"),
+        ("middle", "2025-06-15T03:00:00+00:00"),
+        // 2025-06-30 23:59 JST（終了の境界）
+        ("last", "2025-06-30T14:59:00+00:00"),
+        // 2025-07-01 00:00 JST（範囲外）
+        ("after", "2025-06-30T15:00:00+00:00"),
+    ];
+    // 作成順と完了順を逆にして、完了日時順に並ぶことを確かめる
+    for (title, completed_at) in completions.iter().rev() {
+        let task = create_task(&service, title, TaskStatus::Done, None).await;
+        sqlx::query("UPDATE tasks SET completed_at = ?2 WHERE id = ?1")
+            .bind(&task.id)
+            .bind(completed_at)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+    create_task(&service, "open", TaskStatus::Todo, None).await;
+    
+    let june = |day| NaiveDate::from_ymd_opt(2025, 6, day).unwrap();
+    let tasks = service.get_tasks_completed_between(june(1), june(30)).await.unwrap();
+    assert_eq!(titles(&tasks), vec!["first", "middle", "last"]);
+    assert!(tasks.iter().all(|task| task.tags.is_some()));
+    
+    let single_day = service.get_tasks_completed_between(june(15), june(15)).await.unwrap();
+    assert_eq!(titles(&single_day), vec!["middle"]);
+    
+    let reversed = service.get_tasks_completed_between(june(30), june(1)).await;
+    assert!(matches!(reversed, Err(AppError::InvalidInput(_))));
+}
+
+/// 期日のオフセットとアプリのタイムゾーンの照合テスト
+#[tokio::test]
+async fn test_audit_due_date_timezones() {
+    let (service, db) = create_test_service().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    
+    for (title, due_date) in [
+        ("utc", "2025-06-20T00:00:00+00:00"),
+        ("local", "2025-06-20T09:00:00+09:00"),
+        ("date only", "2025-06-20"),
+    ] {
+        let task = create_task(&service, title, TaskStatus::Todo, None).await;
+        sqlx::query("UPDATE tasks SET due_date = ?2 WHERE id = ?1")
+            .bind(&task.id)
+            .bind(due_date)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+    create_task(&service, "no due date", TaskStatus::Todo, None).await;
+    
+    let audits = service.audit_due_date_timezones().await.unwrap();
+    assert_eq!(audits.len(), 3);
+    let audit = |title: &str| audits.iter().find(|a| a.title == title).unwrap();
+    
+    // UTCで保存された期日はアプリのタイムゾーン（+09:00）と一致しない
+    let utc = audit("utc");
+    assert_eq!(utc.offset_seconds, Some(0));
+    assert_eq!(utc.app_offset_seconds, Some(9 * 3600));
+    assert!(!utc.matches_app_timezone);
+    
+    let local = audit("local");
+    assert_eq!(local.offset_seconds, Some(9 * 3600));
+    assert!(local.matches_app_timezone);
+    
+    // 解析できない期日も要確認として扱う
+    let date_only = audit("date only");
+    assert_eq!(date_only.offset_seconds, None);
+    assert!(!date_only.matches_app_timezone);
+}
+
+/// 繰り返しタスクの次回の期日のテスト
+#[tokio::test]
+async fn test_next_recurrence() {
+    let (service, db) = create_test_service().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let now = Utc.with_ymd_and_hms(2025, 6, 12, 1, 0, 0).unwrap();
+    // 期日は 2025-01-31(金) 18:00 JST
+    let due = Utc.with_ymd_and_hms(2025, 1, 31, 9, 0, 0).unwrap();
+    let task = create_task(&service, "月次レポート", TaskStatus::Todo, Some(due)).await;
+    
+    // 規則がなければ次回はない
+    assert_eq!(service.next_recurrence_at(&task.id, now).await.unwrap(), None);
+    
+    // 毎月末日: 2月は28日に丸め、期日の時刻を保つ
+    service.set_recurrence_rule(&task.id, Some("monthly:31".to_string()), None).await.unwrap();
+    assert_eq!(
+        service.next_recurrence_at(&task.id, now).await.unwrap(),
+        Some(Utc.with_ymd_and_hms(2025, 2, 28, 9, 0, 0).unwrap())
+    );
+    
+    // 毎週月・水: 金曜の次は翌月の月曜
+    service.set_recurrence_rule(&task.id, Some("weekly:1,3".to_string()), None).await.unwrap();
+    assert_eq!(
+        service.next_recurrence_at(&task.id, now).await.unwrap(),
+        Some(Utc.with_ymd_and_hms(2025, 2, 3, 9, 0, 0).unwrap())
+    );
+    
+    // 第2火曜日
+    service.set_recurrence_rule(&task.id, Some("monthly:2:2".to_string()), None).await.unwrap();
+    assert_eq!(
+        service.next_recurrence_at(&task.id, now).await.unwrap(),
+        Some(Utc.with_ymd_and_hms(2025, 2, 11, 9, 0, 0).unwrap())
+    );
+    
+    // 期日がなければ今日の次から数える（0時）
+    let undated = create_task(&service, "掃除", TaskStatus::Todo, None).await;
+    service.set_recurrence_rule(&undated.id, Some("daily".to_string()), None).await.unwrap();
+    assert_eq!(
+        service.next_recurrence_at(&undated.id, now).await.unwrap(),
+        Some(Utc.with_ymd_and_hms(2025, 6, 12, 15, 0, 0).unwrap())
+    );
+    
+    let invalid = service.set_recurrence_rule(&task.id, Some("monthly:32".to_string()), None).await;
+    assert!(matches!(invalid, Err(AppError::InvalidInput(_))));
+    
+    // 保存済みの規則が解釈できない場合も入力エラーとして返す
+    sqlx::query("UPDATE tasks SET recurrence_rule = 'fortnightly' WHERE id = ?1")
+        .bind(&task.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let invalid = service.next_recurrence_at(&task.id, now).await;
+    assert!(matches!(invalid, Err(AppError::InvalidInput(_))));
+}
+
+/// タイトル・説明文の全文検索のテスト
+#[tokio::test]
+async fn test_search_tasks() {
+    let (service, _db) = create_test_service().await;
+    let with_description = |title: &str, description: &str, status: TaskStatus| CreateTaskRequest {
+        title: title.to_string(),
+        description: Some(description.to_string()),
+        status: Some(status),
+        parent_id: None,
+        due_date: None,
+        notification_settings: None,
+        browser_actions: None,
+    };
+    service.create_task(with_description("Rust勉強会の準備", "資料をまとめる", TaskStatus::Todo)).await.unwrap();
+    service.create_task(with_description("請求書の送付", "rustの案件分", TaskStatus::Inbox)).await.unwrap();
+    service.create_task(with_description("進捗100%の報告", "週次", TaskStatus::Done)).await.unwrap();
+    create_task(&service, "買い物", TaskStatus::Todo, None).await;
+    
+    // 大文字小文字を区別せず、タイトル・説明文の両方を対象に、ステータス順で返す
+    let results = service.search_tasks("RUST").await.unwrap();
+    assert_eq!(titles(&results), vec!["請求書の送付", "Rust勉強会の準備"]);
+    assert!(results.iter().all(|task| task.tags.is_some()));
+    
+    // 複数キーワードはAND検索
+    let results = service.search_tasks("rust  資料").await.unwrap();
+    assert_eq!(titles(&results), vec!["Rust勉強会の準備"]);
+    
+    // LIKEのワイルドカードは文字として扱う
+    let results = service.search_tasks("100%").await.unwrap();
+    assert_eq!(titles(&results), vec!["進捗100%の報告"]);
+    assert_eq!(service.search_tasks("%").await.unwrap().len(), 1);
+    
+    assert!(service.search_tasks("   ").await.unwrap().is_empty());
+}
+
+/// ページ単位のタスク取得のテスト
+#[tokio::test]
+async fn test_get_tasks_paginated() {
+    let (service, _db) = create_test_service().await;
+    for title in ["task1", "task2", "task3", "task4", "task5"] {
+        create_task(&service, title, TaskStatus::Todo, None).await;
+    }
+    let inbox = create_task(&service, "inbox", TaskStatus::Inbox, None).await;
+    let tag = service.create_tag(CreateTagRequest { name: "仕事".to_string(), color: "#ff0000".to_string() }).await.unwrap();
+    service.add_tag_to_task(&inbox.id, &tag.id).await.unwrap();
+    
+    // 並び順はget_tasksと同じで、ページを順にたどると全件がそろう
+    let all = service.get_tasks().await.unwrap();
+    let mut paged = Vec::new();
+    for offset in [0, 4] {
+        let (tasks, total) = service.get_tasks_paginated(4, offset).await.unwrap();
+        assert_eq!(total, 6);
+        paged.extend(tasks);
+    }
+    assert_eq!(titles(&paged), titles(&all));
+    
+    // タグはまとめて取得し、タグのないタスクは空
+    assert_eq!(paged[0].title, "inbox");
+    assert_eq!(paged[0].tags.as_ref().unwrap().iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), vec!["仕事"]);
+    assert!(paged[1..].iter().all(|task| task.tags.as_ref().is_some_and(|tags| tags.is_empty())));
+    
+    // 範囲外のページは空で、全件数は返す
+    let (tasks, total) = service.get_tasks_paginated(10, 6).await.unwrap();
+    assert!(tasks.is_empty());
+    assert_eq!(total, 6);
+    
+    assert!(matches!(service.get_tasks_paginated(0, 0).await, Err(AppError::InvalidInput(_))));
+    assert!(matches!(service.get_tasks_paginated(10, -1).await, Err(AppError::InvalidInput(_))));
+}
+
+/// 親子関係の循環・階層数のバリデーションのテスト
+#[tokio::test]
+async fn test_parent_validation_rejects_cycles_and_deep_hierarchies() {
+    let (service, _db) = create_test_service().await;
+    let create_child = |title: &str, parent_id: Option<String>| CreateTaskRequest {
+        title: title.to_string(),
+        description: None,
+        status: Some(TaskStatus::Todo),
+        parent_id,
+        due_date: None,
+        notification_settings: None,
+        browser_actions: None,
+    };
+    let set_parent = |parent_id: &str| UpdateTaskRequest {
+        title: None,
+        description: None,
+        status: None,
+        parent_id: Some(parent_id.to_string()),
+        due_date: None,
+        notification_settings: None,
+        browser_actions: None,
+        tags: None,
+    };
+    
+    // a -> b -> c
+    let a = service.create_task(create_child("a", None)).await.unwrap();
+    let b = service.create_task(create_child("b", Some(a.id.clone()))).await.unwrap();
+    let c = service.create_task(create_child("c", Some(b.id.clone()))).await.unwrap();
+    
+    // 自分自身・子孫を親にはできない
+    for parent in [&a, &b, &c] {
+        assert!(matches!(service.update_task(&a.id, set_parent(&parent.id)).await, Err(AppError::InvalidInput(_))));
+    }
+    assert_eq!(service.get_task_by_id(&a.id).await.unwrap().parent_id, None);
+    
+    // 循環しない付け替えはできる
+    let other = service.create_task(create_child("other", None)).await.unwrap();
+    let moved = service.update_task(&b.id, set_parent(&other.id)).await.unwrap();
+    assert_eq!(moved.parent_id, Some(other.id.clone()));
+    
+    // 存在しない親・ゴミ箱の親
+    assert!(matches!(service.update_task(&c.id, set_parent("missing")).await, Err(AppError::NotFound(_))));
+    let trashed = service.create_task(create_child("trashed", None)).await.unwrap();
+    service.delete_task(&trashed.id).await.unwrap();
+    assert!(matches!(service.update_task(&c.id, set_parent(&trashed.id)).await, Err(AppError::NotFound(_))));
+    assert_eq!(service.get_task_by_id(&c.id).await.unwrap().parent_id, Some(b.id.clone()));
+    
+    // 10階層までは作れる（other -> b -> c で3階層）
+    let mut parent_id = c.id.clone();
+    for level in 4..=10 {
+        parent_id = service.create_task(create_child(&format!("level{}", level), Some(parent_id))).await.unwrap().id;
+    }
+    let too_deep = service.create_task(create_child("level11", Some(parent_id.clone()))).await;
+    assert!(matches!(too_deep, Err(AppError::InvalidInput(_))));
+    
+    // 子孫を含めて10階層を超える付け替えも拒否（bの下は9階層）
+    let root = service.create_task(create_child("root", None)).await.unwrap();
+    let nested = service.create_task(create_child("nested", Some(root.id.clone()))).await.unwrap();
+    assert!(matches!(service.update_task(&b.id, set_parent(&nested.id)).await, Err(AppError::InvalidInput(_))));
+    assert!(service.update_task(&b.id, set_parent(&root.id)).await.is_ok());
+}
+
+/// 子孫を含めたタスク削除のテスト
+#[tokio::test]
+async fn test_delete_task_cascade() {
+    let (service, db) = create_test_service().await;
+    let create_child = |title: &str, parent_id: Option<String>| CreateTaskRequest {
+        title: title.to_string(),
+        description: None,
+        status: Some(TaskStatus::Todo),
+        parent_id,
+        due_date: None,
+        notification_settings: None,
+        browser_actions: None,
+    };
+    
+    // root -> child -> grandchild、root -> child2、別のタスク
+    let root = service.create_task(create_child("root", None)).await.unwrap();
+    let child = service.create_task(create_child("child", Some(root.id.clone()))).await.unwrap();
+    let grandchild = service.create_task(create_child("grandchild", Some(child.id.clone()))).await.unwrap();
+    service.create_task(create_child("child2", Some(root.id.clone()))).await.unwrap();
+    let other = create_task(&service, "other", TaskStatus::Todo, None).await;
+    
+    let tag = service.create_tag(CreateTagRequest { name: "仕事".to_string(), color: "#ff0000".to_string() }).await.unwrap();
+    for task_id in [&grandchild.id, &other.id] {
+        service.add_tag_to_task(task_id, &tag.id).await.unwrap();
+    }
+    
+    // 子孫の途中から削除すると、その配下だけが消える
+    assert_eq!(service.delete_task_cascade(&child.id).await.unwrap(), 2);
+    assert_eq!(titles(&service.get_tasks().await.unwrap()), vec!["other", "child2", "root"]);
+    
+    assert_eq!(service.delete_task_cascade(&root.id).await.unwrap(), 2);
+    assert_eq!(titles(&service.get_tasks().await.unwrap()), vec!["other"]);
+    
+    // 削除したタスクのタグの関連付けも消え、他のタスクの分は残る
+    let remaining: Vec<String> = sqlx::query_scalar("SELECT task_id FROM task_tags")
+        .fetch_all(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, vec![other.id.clone()]);
+    
+    assert!(matches!(service.delete_task_cascade(&root.id).await, Err(AppError::NotFound(_))));
+}
+
+/// 繰り返しタスクの完了で次回分を作成するテスト
+#[tokio::test]
+async fn test_complete_recurring_task_creates_next_instance() {
+    let (service, db) = create_test_service().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let now = Utc.with_ymd_and_hms(2025, 6, 12, 1, 0, 0).unwrap();
+    
+    let parent = create_task(&service, "定例", TaskStatus::Todo, None).await;
+    // 期日は 2025-06-13(金) 18:00 JST、毎週月・金
+    let task = service.create_task(CreateTaskRequest {
+        title: "週報".to_string(),
+        description: Some("今週の進捗".to_string()),
+        status: Some(TaskStatus::Todo),
+        parent_id: Some(parent.id.clone()),
+        due_date: Some(Utc.with_ymd_and_hms(2025, 6, 13, 9, 0, 0).unwrap()),
+        notification_settings: Some(TaskNotificationSettings {
+            notification_type: "due_date_based".to_string(),
+            days_before: Some(1),
+            notification_time: Some("17:00".to_string()),
+            days_of_week: None,
+            level: 2,
+            notification_until: None,
+        }),
+        browser_actions: None,
+    }).await.unwrap();
+    sqlx::query("UPDATE tasks SET browser_actions = '{\"enabled\":true,\"actions\":[]}' WHERE id = ?1")
+        .bind(&task.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let tag = service.create_tag(CreateTagRequest { name: "仕事".to_string(), color: "#ff0000".to_string() }).await.unwrap();
+    service.add_tag_to_task(&task.id, &tag.id).await.unwrap();
+    service
+        .set_recurrence_rule(&task.id, Some("weekly:1,5".to_string()), NaiveDate::from_ymd_opt(2025, 6, 16))
+        .await
+        .unwrap();
+    
+    // 完了すると次の月曜の同じ時刻で次回分ができ、設定を引き継ぐ
+    let result = service.complete_task_at(&task.id, now).await.unwrap();
+    assert_eq!(result.task.status, "done");
+    let next = result.next_task.unwrap();
+    assert_ne!(next.id, task.id);
+    assert_eq!(next.status, "todo");
+    assert_eq!(next.title, "週報");
+    assert_eq!(next.parent_id, Some(parent.id.clone()));
+    assert_eq!(next.due_date, Some(Utc.with_ymd_and_hms(2025, 6, 16, 9, 0, 0).unwrap().to_rfc3339()));
+    assert_eq!(next.notification_type.as_deref(), Some("due_date_based"));
+    assert_eq!(next.notification_time.as_deref(), Some("17:00"));
+    assert_eq!(next.notification_level, Some(2));
+    assert_eq!(next.browser_actions.as_deref(), Some("{\"enabled\":true,\"actions\":[]}"));
+    assert_eq!(next.recurrence_rule.as_deref(), Some("weekly:1,5"));
+    assert_eq!(next.recurrence_end.as_deref(), Some("2025-06-16"));
+    let stored = service.get_task_by_id(&next.id).await.unwrap();
+    assert_eq!(stored.tags.unwrap().iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), vec!["仕事"]);
+    
+    // 完了済みのタスクからは作らない
+    assert!(service.complete_task_at(&task.id, now).await.unwrap().next_task.is_none());
+    
+    // 次の該当日(6/20)が最終日を過ぎるので作らない
+    let result = service.complete_task_at(&next.id, now).await.unwrap();
+    assert_eq!(result.task.status, "done");
+    assert!(result.next_task.is_none());
+    
+    // 繰り返さないタスクは完了するだけ
+    let plain = create_task(&service, "単発", TaskStatus::Todo, None).await;
+    assert!(service.complete_task_at(&plain.id, now).await.unwrap().next_task.is_none());
+    assert_eq!(service.get_tasks_by_status("todo").await.unwrap().len(), 1);
+}
+
+/// カンバンの移動・一括移動で完了にしても繰り返しタスクの次回分ができるテスト
+#[tokio::test]
+async fn test_moving_recurring_task_to_done_creates_next_instance() {
+    let (service, db) = create_test_service().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    
+    // 期日は 2025-06-13(金) 18:00 JST、毎日
+    let due = Utc.with_ymd_and_hms(2025, 6, 13, 9, 0, 0).unwrap();
+    let daily = create_task(&service, "日報", TaskStatus::Todo, Some(due)).await;
+    service.set_recurrence_rule(&daily.id, Some("daily".to_string()), None).await.unwrap();
+    let weekly = create_task(&service, "週報", TaskStatus::InProgress, Some(due)).await;
+    service.set_recurrence_rule(&weekly.id, Some("weekly:1".to_string()), None).await.unwrap();
+    
+    service.move_task(&daily.id, "done").await.unwrap();
+    service.bulk_move_tasks(std::slice::from_ref(&weekly.id), "done").await.unwrap();
+    
+    let todo = service.get_tasks_by_status("todo").await.unwrap();
+    let mut next: Vec<(&str, Option<&str>)> = todo.iter().map(|t| (t.title.as_str(), t.due_date.as_deref())).collect();
+    next.sort();
+    let saturday = Utc.with_ymd_and_hms(2025, 6, 14, 9, 0, 0).unwrap().to_rfc3339();
+    let monday = Utc.with_ymd_and_hms(2025, 6, 16, 9, 0, 0).unwrap().to_rfc3339();
+    assert_eq!(next, vec![("日報", Some(saturday.as_str())), ("週報", Some(monday.as_str()))]);
+    
+    // 完了済みのタスクを再び完了にしても増えない
+    service.move_task(&daily.id, "done").await.unwrap();
+    service.bulk_move_tasks(std::slice::from_ref(&weekly.id), "done").await.unwrap();
+    assert_eq!(service.get_tasks_by_status("todo").await.unwrap().len(), 2);
+}
+
+/// タスクのアーカイブのテスト
+#[tokio::test]
+async fn test_archive_task() {
+    let (service, db) = create_test_service().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    let notifications = NotificationService::new(db.clone());
+    // 2025-06-10 09:00 JST
+    let now = Utc.with_ymd_and_hms(2025, 6, 10, 0, 0, 0).unwrap();
+    
+    let daily = service.create_task(CreateTaskRequest {
+        title: "日報".to_string(),
+        description: None,
+        status: Some(TaskStatus::Todo),
+        parent_id: None,
+        due_date: None,
+        notification_settings: Some(TaskNotificationSettings {
+            notification_type: "recurring".to_string(),
+            days_before: None,
+            notification_time: Some("09:00".to_string()),
+            days_of_week: Some(vec![0, 1, 2, 3, 4, 5, 6]),
+            level: 1,
+            notification_until: None,
+        }),
+        browser_actions: None,
+    }).await.unwrap();
+    let done = create_task(&service, "完了済み", TaskStatus::Done, None).await;
+    create_task(&service, "作業中", TaskStatus::InProgress, None).await;
+    assert_eq!(notifications.peek_notifications(now).await.unwrap().len(), 1);
+    
+    // アーカイブ済みは一覧・通知から外れ、明示的に取得できる
+    let archived = service.archive_task(&done.id).await.unwrap();
+    assert!(archived.archived_at.is_some());
+    service.archive_task(&daily.id).await.unwrap();
+    assert_eq!(titles(&service.get_tasks().await.unwrap()), vec!["作業中"]);
+    assert_eq!(service.get_tasks_paginated(10, 0).await.unwrap().1, 1);
+    assert_eq!(titles(&service.get_archived_tasks().await.unwrap()).len(), 2);
+    assert!(notifications.peek_notifications(now).await.unwrap().is_empty());
+    
+    // 再アーカイブしても日時は変わらない
+    assert_eq!(service.archive_task(&done.id).await.unwrap().archived_at, archived.archived_at);
+    
+    // 戻すと通常の一覧・通知に戻る
+    let restored = service.unarchive_task(&daily.id).await.unwrap();
+    assert_eq!(restored.archived_at, None);
+    assert_eq!(titles(&service.get_archived_tasks().await.unwrap()), vec!["完了済み"]);
+    assert_eq!(notifications.peek_notifications(now).await.unwrap().len(), 1);
+    
+    assert!(matches!(service.archive_task("missing").await, Err(AppError::NotFound(_))));
+}
+
+/// タスク間の依存関係の保存・循環検出・完了前チェックのテスト
+#[tokio::test]
+async fn test_task_dependencies() {
+    let (service, _db) = create_test_service().await;
+    let design = create_task(&service, "設計", TaskStatus::Todo, None).await;
+    let implement = create_task(&service, "実装", TaskStatus::Todo, None).await;
+    let test = create_task(&service, "テスト", TaskStatus::Todo, None).await;
+    let docs = create_task(&service, "ドキュメント", TaskStatus::Todo, None).await;
+    
+    // 設計 → 実装 → テスト
+    service.add_dependency(&design.id, &implement.id, "blocks").await.unwrap();
+    service.add_dependency(&test.id, &implement.id, "requires").await.unwrap();
+    service.add_dependency(&docs.id, &test.id, "relates_to").await.unwrap();
+    assert_eq!(service.get_dependencies(&implement.id).await.unwrap().len(), 2);
+    
+    // 循環する依存は拒否（relates_toは順序に影響しない）
+    for (from, to, dependency_type) in [(&test, &design, "blocks"), (&design, &test, "requires")] {
+        let result = service.add_dependency(&from.id, &to.id, dependency_type).await;
+        assert!(matches!(result, Err(AppError::InvalidInput(_))), "{} {} {}", from.title, dependency_type, to.title);
+    }
+    service.add_dependency(&test.id, &docs.id, "relates_to").await.unwrap();
+    assert!(matches!(service.add_dependency(&design.id, &design.id, "blocks").await, Err(AppError::InvalidInput(_))));
+    assert!(matches!(service.add_dependency(&design.id, &docs.id, "follows").await, Err(AppError::InvalidInput(_))));
+    assert!(matches!(service.add_dependency(&design.id, "missing", "blocks").await, Err(AppError::NotFound(_))));
+    
+    // 同じ組み合わせは種別を置き換える（置き換え後の循環も検出する）
+    let replaced = service.add_dependency(&docs.id, &test.id, "requires").await.unwrap();
+    assert_eq!(replaced.dependency_type, "requires");
+    assert!(service.add_dependency(&test.id, &docs.id, "requires").await.is_err());
+    
+    // 先に行うタスクが未完了なら警告
+    let check = service.can_complete(&test.id).await.unwrap();
+    assert!(!check.can_complete);
+    assert_eq!(check.warnings.len(), 1);
+    assert!(check.warnings[0].contains("実装"));
+    assert!(service.can_complete(&design.id).await.unwrap().can_complete);
+    
+    service.move_task(&design.id, "done").await.unwrap();
+    assert!(service.can_complete(&implement.id).await.unwrap().can_complete);
+    
+    service.remove_dependency(&test.id, &implement.id).await.unwrap();
+    assert!(service.can_complete(&test.id).await.unwrap().can_complete);
+    assert!(matches!(service.remove_dependency(&test.id, &implement.id).await, Err(AppError::NotFound(_))));
+}
+
+/// 依存関係を満たす着手順の取得と、循環した依存のエラーのテスト
+#[tokio::test]
+async fn test_get_topological_order() {
+    let (service, db) = create_test_service().await;
+    let release = create_task(&service, "リリース", TaskStatus::Todo, None).await;
+    let test = create_task(&service, "テスト", TaskStatus::Todo, None).await;
+    let implement = create_task(&service, "実装", TaskStatus::Todo, None).await;
+    let design = create_task(&service, "設計", TaskStatus::Todo, None).await;
+    
+    // 設計 → 実装 → テスト → リリース
+    service.add_dependency(&design.id, &implement.id, "blocks").await.unwrap();
+    service.add_dependency(&test.id, &implement.id, "requires").await.unwrap();
+    service.add_dependency(&release.id, &test.id, "requires").await.unwrap();
+    let order = service.get_topological_order(None).await.unwrap();
+    assert_eq!(titles(&order), vec!["設計", "実装", "テスト", "リリース"]);
+    
+    // 指定したタスクの子孫だけを並べる（外部のタスクへの依存は無視する）
+    let mut request = CreateTaskRequest {
+        title: "結合テスト".to_string(),
+        description: None,
+        status: Some(TaskStatus::Todo),
+        parent_id: Some(test.id.clone()),
+        due_date: None,
+        notification_settings: None,
+        browser_actions: None,
+    };
+    let integration = service.create_task(request.clone()).await.unwrap();
+    request.title = "単体テスト".to_string();
+    let unit = service.create_task(request).await.unwrap();
+    service.add_dependency(&unit.id, &integration.id, "blocks").await.unwrap();
+    service.add_dependency(&design.id, &unit.id, "blocks").await.unwrap();
+    let subtree = service.get_topological_order(Some(test.id.clone())).await.unwrap();
+    assert_eq!(titles(&subtree), vec!["テスト", "単体テスト", "結合テスト"]);
+    assert!(matches!(service.get_topological_order(Some("missing".to_string())).await, Err(AppError::NotFound(_))));
+    
+    // 循環した依存（追加時の検査を経ずに保存されたもの）は、関わるタスクを挙げてエラーにする
+    sqlx::query("INSERT INTO task_dependencies (from_task_id, to_task_id, dependency_type, created_at) VALUES (?1, ?2, 'blocks', ?3)")
+        .bind(&release.id)
+        .bind(&design.id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    match service.get_topological_order(None).await {
+        Err(AppError::Validation(message)) => {
+            for title in ["設計", "実装", "テスト", "リリース"] {
+                assert!(message.contains(title), "{}", message);
+            }
+        }
+        other => panic!("expected a cycle error, got {:?}", other.map(|tasks| tasks.len())),
+    }
+}
+
+/// タスクテンプレートの保存・展開のテスト
+#[tokio::test]
+async fn test_task_templates() {
+    let (service, db) = create_test_service().await;
+    SettingsService::set(&db.pool, "timezone", "Asia/Tokyo").await.unwrap();
+    // 保存日は 2025-06-10(火) JST
+    let saved_at = Utc.with_ymd_and_hms(2025, 6, 10, 1, 0, 0).unwrap();
+    
+    // 週次レビュー（2日後の18:00 JST、通知あり）→ 準備（翌日）・振り返り
+    let review = service.create_task(CreateTaskRequest {
+        title: "週次レビュー".to_string(),
+        description: Some("今週の振り返り".to_string()),
+        status: Some(TaskStatus::InProgress),
+        parent_id: None,
+        due_date: Some(Utc.with_ymd_and_hms(2025, 6, 12, 9, 0, 0).unwrap()),
+        notification_settings: Some(TaskNotificationSettings {
+            notification_type: "due_date_based".to_string(),
+            days_before: Some(1),
+            notification_time: Some("10:00".to_string()),
+            days_of_week: None,
+            level: 2,
+            notification_until: None,
+        }),
+        browser_actions: None,
+    }).await.unwrap();
+    let child = |title: &str, due_date: Option<DateTime<Utc>>| CreateTaskRequest {
+        title: title.to_string(),
+        description: None,
+        status: Some(TaskStatus::Done),
+        parent_id: Some(review.id.clone()),
+        due_date,
+        notification_settings: None,
+        browser_actions: None,
+    };
+    service.create_task(child("準備", Some(Utc.with_ymd_and_hms(2025, 6, 11, 0, 0, 0).unwrap()))).await.unwrap();
+    service.create_task(child("振り返り", None)).await.unwrap();
+    
+    let template = service.save_as_template_at(&review.id, " 週次 ", saved_at).await.unwrap();
+    assert_eq!(template.name, "週次");
+    assert_eq!(template.root.due_offset_days, Some(2));
+    assert_eq!(template.root.due_time.as_deref(), Some("18:00:00"));
+    assert_eq!(template.root.children.iter().map(|c| c.title.as_str()).collect::<Vec<_>>(), vec!["準備", "振り返り"]);
+    assert_eq!(service.get_task_templates().await.unwrap(), vec![template.clone()]);
+    
+    // 展開日を基準に期日を計算し、通知設定を引き継いで未着手で作る
+    let created_at = Utc.with_ymd_and_hms(2025, 6, 17, 1, 0, 0).unwrap();
+    let root = service.create_from_template_at(&template.id, created_at).await.unwrap();
+    assert_ne!(root.id, review.id);
+    assert_eq!(root.title, "週次レビュー");
+    assert_eq!(root.description.as_deref(), Some("今週の振り返り"));
+    assert_eq!(root.status, "todo");
+    assert_eq!(root.due_date, Some(Utc.with_ymd_and_hms(2025, 6, 19, 9, 0, 0).unwrap().to_rfc3339()));
+    assert_eq!(root.notification_type.as_deref(), Some("due_date_based"));
+    assert_eq!(root.notification_days_before, Some(1));
+    assert_eq!(root.notification_time.as_deref(), Some("10:00"));
+    assert_eq!(root.notification_level, Some(2));
+    
+    let children = service.get_children(&root.id).await.unwrap();
+    let mut children: Vec<(String, Option<String>, String)> = children
+        .into_iter()
+        .map(|c| (c.title, c.due_date, c.status))
+        .collect();
+    children.sort();
+    assert_eq!(children, vec![
+        ("振り返り".to_string(), None, "todo".to_string()),
+        ("準備".to_string(), Some(Utc.with_ymd_and_hms(2025, 6, 18, 0, 0, 0).unwrap().to_rfc3339()), "todo".to_string()),
+    ]);
+    
+    assert!(matches!(service.save_as_template_at(&review.id, "  ", saved_at).await, Err(AppError::InvalidInput(_))));
+    assert!(matches!(service.save_as_template_at("missing", "x", saved_at).await, Err(AppError::NotFound(_))));
+    
+    service.delete_task_template(&template.id).await.unwrap();
+    assert!(service.get_task_templates().await.unwrap().is_empty());
+    assert!(matches!(service.create_from_template(&template.id).await, Err(AppError::NotFound(_))));
+}
+
+/// 複数タスクの一括ステータス変更のテスト
+#[tokio::test]
+async fn test_bulk_move_tasks() {
+    let (service, _db) = create_test_service().await;
+    service.set_subtask_completion_rules(SubtaskCompletionRules {
+        require_children_complete: true,
+        ignore_optional_children: true,
+    }).await.unwrap();
+    let parent = create_task(&service, "親", TaskStatus::Todo, None).await;
+    let child = |title: &str| CreateTaskRequest {
+        title: title.to_string(),
+        description: None,
+        status: Some(TaskStatus::Todo),
+        parent_id: Some(parent.id.clone()),
+        due_date: None,
+        notification_settings: None,
+        browser_actions: None,
+    };
+    let first = service.create_task(child("子1")).await.unwrap();
+    let second = service.create_task(child("子2")).await.unwrap();
+    let other = create_task(&service, "別", TaskStatus::Inbox, None).await;
+    
+    // 存在しないIDがあれば何も変更せず、すべて報告する
+    let result = service
+        .bulk_move_tasks(&[first.id.clone(), "missing1".to_string(), "missing2".to_string()], "done")
+        .await;
+    match result {
+        Err(AppError::NotFound(message)) => assert!(message.contains("missing1") && message.contains("missing2")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(service.get_task_by_id(&first.id).await.unwrap().status, "todo");
+    
+    // 必須の子タスクが残る親は完了にできない
+    let result = service.bulk_move_tasks(&[parent.id.clone(), first.id.clone()], "done").await;
+    assert!(matches!(result, Err(AppError::InvalidInput(_))));
+    assert_eq!(service.get_task_by_id(&first.id).await.unwrap().status, "todo");
+    
+    // 完了日時を設定し、親の進捗率を再計算する
+    let moved = service.bulk_move_tasks(&[first.id.clone(), other.id.clone()], "done").await.unwrap();
+    assert_eq!(titles(&moved), vec!["子1", "別"]);
+    assert!(moved.iter().all(|task| task.status == "done" && task.completed_at.is_some()));
+    assert_eq!(service.get_task_by_id(&parent.id).await.unwrap().progress, Some(50));
+    
+    // 子タスクと同時なら親も完了にできる
+    let moved = service.bulk_move_tasks(&[parent.id.clone(), second.id.clone()], "done").await.unwrap();
+    assert!(moved.iter().all(|task| task.status == "done"));
+    assert_eq!(service.get_task_by_id(&parent.id).await.unwrap().progress, Some(100));
+    
+    // 戻すと完了日時は消える
+    let moved = service.bulk_move_tasks(std::slice::from_ref(&first.id), "in_progress").await.unwrap();
+    assert_eq!(moved[0].completed_at, None);
+    assert_eq!(service.get_task_by_id(&parent.id).await.unwrap().progress, Some(50));
+    
+    assert!(matches!(service.bulk_move_tasks(std::slice::from_ref(&first.id), "later").await, Err(AppError::InvalidInput(_))));
+    assert!(service.bulk_move_tasks(&[], "done").await.unwrap().is_empty());
+}
+
+/// タスク複製のテスト
+#[tokio::test]
+async fn test_duplicate_task() {
+    let (service, db) = create_test_service().await;
+    let project = create_task(&service, "プロジェクト", TaskStatus::Todo, None).await;
+    let child = |title: &str, parent_id: &str, status: TaskStatus| CreateTaskRequest {
+        title: title.to_string(),
+        description: Some(format!("{}の説明", title)),
+        status: Some(status),
+        parent_id: Some(parent_id.to_string()),
+        due_date: None,
+        notification_settings: None,
+        browser_actions: None,
+    };
+    let release = service.create_task(child("リリース", &project.id, TaskStatus::InProgress)).await.unwrap();
+    let build = service.create_task(child("ビルド", &release.id, TaskStatus::Done)).await.unwrap();
+    service.create_task(child("告知", &release.id, TaskStatus::Todo)).await.unwrap();
+    service.update_progress(&release.id, 40).await.unwrap();
+    sqlx::query("UPDATE tasks SET browser_actions = '{\"enabled\":true,\"actions\":[]}' WHERE id = ?1")
+        .bind(&release.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let tag = service.create_tag(CreateTagRequest { name: "仕事".to_string(), color: "#ff0000".to_string() }).await.unwrap();
+    for task_id in [&release.id, &build.id] {
+        service.add_tag_to_task(task_id, &tag.id).await.unwrap();
+    }
+    
+    // 単体の複製: 同じ親の下に作り、進捗はリセット
+    let copy = service.duplicate_task(&release.id, false).await.unwrap();
+    assert_ne!(copy.id, release.id);
+    assert_eq!(copy.title, "リリース (コピー)");
+    assert_eq!(copy.description.as_deref(), Some("リリースの説明"));
+    assert_eq!(copy.status, "in_progress");
+    assert_eq!(copy.parent_id, Some(project.id.clone()));
+    assert_eq!(copy.progress, Some(0));
+    assert_eq!(copy.browser_actions.as_deref(), Some("{\"enabled\":true,\"actions\":[]}"));
+    assert_eq!(copy.tags.unwrap().len(), 1);
+    assert!(service.get_children(&copy.id).await.unwrap().is_empty());
+    
+    // 子孫ごとの複製: 親子関係を複製側に張り替え、完了済みは未着手に戻す
+    let tree = service.duplicate_task(&release.id, true).await.unwrap();
+    let children = service.get_children(&tree.id).await.unwrap();
+    assert_eq!(titles(&children), vec!["ビルド", "告知"]);
+    assert!(children.iter().all(|c| c.status == "todo" && c.completed_at.is_none()));
+    assert!(children.iter().all(|c| c.id != build.id));
+    let build_copy = service.get_task_by_id(&children[0].id).await.unwrap();
+    assert_eq!(build_copy.tags.unwrap().len(), 1);
+    
+    // 元のタスクはそのまま
+    assert_eq!(titles(&service.get_children(&release.id).await.unwrap()), vec!["ビルド", "告知"]);
+    assert_eq!(service.get_task_by_id(&build.id).await.unwrap().status, "done");
+    
+    assert!(matches!(service.duplicate_task("missing", true).await, Err(AppError::NotFound(_))));
+}
+
+/// ゴミ箱（ソフトデリート）と復元・完全削除のテスト
+#[tokio::test]
+async fn test_task_trash() {
+    let (service, _db) = create_test_service().await;
+    
+    let project = create_task(&service, "引っ越し", TaskStatus::Todo, None).await;
+    let child = create_child(&service, &project, "荷造り", TaskStatus::Todo).await;
+    let memo = create_task(&service, "メモ", TaskStatus::Todo, None).await;
+    
+    // 削除したタスクは通常の取得から外れ、ゴミ箱に入る
+    service.delete_task(&memo.id).await.unwrap();
+    assert!(matches!(service.get_task_by_id(&memo.id).await, Err(AppError::NotFound(_))));
+    assert_eq!(service.get_tasks().await.unwrap().len(), 2);
+    let trash = service.get_deleted_tasks().await.unwrap();
+    assert_eq!(titles(&trash), vec!["メモ"]);
+    assert!(trash[0].deleted_at.is_some());
+    assert!(matches!(service.delete_task(&memo.id).await, Err(AppError::NotFound(_))));
+    
+    // 復元すると元に戻る
+    let restored = service.restore_task(&memo.id).await.unwrap();
+    assert_eq!(restored.deleted_at, None);
+    assert!(service.get_deleted_tasks().await.unwrap().is_empty());
+    assert!(matches!(service.restore_task(&memo.id).await, Err(AppError::NotFound(_))));
+    
+    // 親を削除すると子孫もゴミ箱に入り、復元すると一緒に戻る
+    let grandchild = create_child(&service, &child, "段ボール", TaskStatus::Todo).await;
+    service.delete_task(&grandchild.id).await.unwrap();
+    service.delete_task(&project.id).await.unwrap();
+    assert!(matches!(service.get_task_by_id(&child.id).await, Err(AppError::NotFound(_))));
+    assert_eq!(service.get_deleted_tasks().await.unwrap().len(), 3);
+    assert!(matches!(service.add_tag_to_task(&child.id, "any").await, Err(AppError::NotFound(_))));
+    service.restore_task(&project.id).await.unwrap();
+    assert_eq!(service.get_task_by_id(&child.id).await.unwrap().parent_id, Some(project.id.clone()));
+    // 先に個別に削除していた孫はゴミ箱に残る
+    assert_eq!(titles(&service.get_deleted_tasks().await.unwrap()), vec!["段ボール"]);
+    
+    // ゴミ箱の親の下には移せない
+    service.delete_task(&memo.id).await.unwrap();
+    let moved = service.update_task(&child.id, UpdateTaskRequest {
+        title: None,
+        description: None,
+        status: None,
+        parent_id: Some(memo.id.clone()),
+        due_date: None,
+        notification_settings: None,
+        browser_actions: None,
+        tags: None,
+    }).await;
+    assert!(matches!(moved, Err(AppError::NotFound(_))));
+    service.restore_task(&memo.id).await.unwrap();
+    
+    // ゴミ箱を空にすると子孫も完全に削除される
+    service.delete_task(&project.id).await.unwrap();
+    assert_eq!(service.empty_trash().await.unwrap(), 3);
+    assert!(service.get_deleted_tasks().await.unwrap().is_empty());
+    
+    // 保存期間を過ぎたものだけが自動で削除される
+    service.delete_task(&memo.id).await.unwrap();
+    let now = Utc::now();
+    assert_eq!(service.purge_expired_trash_at(now + Duration::days(29)).await.unwrap(), 0);
+    assert_eq!(service.purge_expired_trash_at(now + Duration::days(31)).await.unwrap(), 1);
+    assert!(service.get_deleted_tasks().await.unwrap().is_empty());
+    assert!(service.get_tasks().await.unwrap().is_empty());
+}
+
+/// 期限切れタスクの取得テスト（オフセットの異なる期日を時刻で比較する）
+#[tokio::test]
+async fn test_get_overdue_tasks() {
+    let (service, db) = create_test_service().await;
+    // 2025-06-10 09:00 JST
+    let now = Utc.with_ymd_and_hms(2025, 6, 10, 0, 0, 0).unwrap();
+    
+    let mut ids = Vec::new();
+    for (title, status, due) in [
+        ("昨日の締切", TaskStatus::Todo, "2025-06-09T10:00:00+09:00"),
+        ("先週の締切", TaskStatus::InProgress, "2025-06-03T00:00:00Z"),
+        // 文字列では現在（UTC 00:00）より後に見えるが、UTCでは前日23:30なので期限切れ
+        ("今朝の締切", TaskStatus::Todo, "2025-06-10T08:30:00+09:00"),
+        // 文字列では過去に見えるが、UTCでは09:00なのでまだ期限前
+        ("夕方の締切", TaskStatus::Todo, "2025-06-09T23:00:00-10:00"),
+        ("完了済み", TaskStatus::Done, "2025-06-01T00:00:00Z"),
+    ] {
+        let task = create_task(&service, title, status, None).await;
+        sqlx::query("UPDATE tasks SET due_date = ?2 WHERE id = ?1")
+            .bind(&task.id)
+            .bind(due)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        ids.push(task.id);
+    }
+    create_task(&service, "期日なし", TaskStatus::Todo, None).await;
+    
+    let overdue = service.get_overdue_tasks_at(now).await.unwrap();
+    assert_eq!(titles(&overdue), vec!["先週の締切", "昨日の締切", "今朝の締切"]);
+    assert_eq!(service.get_overdue_count_at(now).await.unwrap(), 3);
+    
+    // ゴミ箱・アーカイブ済みは数えない
+    service.delete_task(&ids[0]).await.unwrap();
+    service.archive_task(&ids[1]).await.unwrap();
+    assert_eq!(titles(&service.get_overdue_tasks_at(now).await.unwrap()), vec!["今朝の締切"]);
+    assert_eq!(service.get_overdue_count_at(now).await.unwrap(), 1);
+}
+
+/// 埋め込みのコサイン類似度による類似タスク検索テスト
+#[tokio::test]
+async fn test_find_similar_tasks() {
+    let (service, _db) = create_test_service().await;
+    let base = create_task(&service, "牛乳を買う", TaskStatus::Todo, None).await;
+    let close = create_task(&service, "卵を買う", TaskStatus::Todo, None).await;
+    let far = create_task(&service, "レポートを書く", TaskStatus::Todo, None).await;
+    let opposite = create_task(&service, "買い物をやめる", TaskStatus::Todo, None).await;
+    let other_model = create_task(&service, "パンを買う", TaskStatus::Todo, None).await;
+    create_task(&service, "埋め込みなし", TaskStatus::Todo, None).await;
+    
+    let model = "nomic-embed-text";
+    service.save_task_embedding(&base.id, model, &[1.0, 0.0, 0.0]).await.unwrap();
+    service.save_task_embedding(&close.id, model, &[0.9, 0.1, 0.0]).await.unwrap();
+    service.save_task_embedding(&far.id, model, &[0.0, 1.0, 0.0]).await.unwrap();
+    service.save_task_embedding(&opposite.id, model, &[-1.0, 0.0, 0.0]).await.unwrap();
+    // 別モデルの埋め込みは比較しない
+    service.save_task_embedding(&other_model.id, "mxbai-embed-large", &[1.0, 0.0, 0.0]).await.unwrap();
+    
+    let similar = service.find_similar_tasks(&base.id, 10).await.unwrap();
+    let similar_titles: Vec<&str> = similar.iter().map(|s| s.task.title.as_str()).collect();
+    assert_eq!(similar_titles, vec!["卵を買う", "レポートを書く", "買い物をやめる"]);
+    assert!(similar[0].similarity > 0.99);
+    assert_eq!(similar[2].similarity, -1.0);
+    
+    let top = service.find_similar_tasks(&base.id, 1).await.unwrap();
+    assert_eq!(top.len(), 1);
+    assert_eq!(top[0].task.id, close.id);
+    
+    // 保存し直すと置き換わり、ゴミ箱のタスクは対象外
+    service.save_task_embedding(&far.id, model, &[1.0, 0.0, 0.0]).await.unwrap();
+    assert_eq!(service.find_similar_tasks(&base.id, 1).await.unwrap()[0].task.id, far.id);
+    service.delete_task(&far.id).await.unwrap();
+    assert_eq!(service.find_similar_tasks(&base.id, 1).await.unwrap()[0].task.id, close.id);
+    
+    // 埋め込みのないタスクはNotFound
+    let missing = service.find_similar_tasks("no-such-task", 5).await;
+    assert!(matches!(missing, Err(AppError::NotFound(_))));
+}
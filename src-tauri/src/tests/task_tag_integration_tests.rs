@@ -31,7 +31,7 @@ async fn test_task_update_with_tags() {
     let create_request = CreateTaskRequest {
         title: "タグテスト用タスク".to_string(),
         description: Some("タグ機能のテスト".to_string()),
-        status: TaskStatus::Todo,
+        status: Some(TaskStatus::Todo),
         parent_id: None,
         due_date: None,
         notification_settings: None,
@@ -134,7 +134,7 @@ async fn test_create_tag_and_add_to_task() {
     let create_request = CreateTaskRequest {
         title: "新規タグテスト用タスク".to_string(),
         description: Some("新規タグを即座に追加".to_string()),
-        status: TaskStatus::Todo,
+        status: Some(TaskStatus::Todo),
         parent_id: None,
         due_date: None,
         notification_settings: None,
@@ -187,4 +187,109 @@ async fn test_create_tag_and_add_to_task() {
     TagService::delete_tag(&pool, &new_tag.id).await.unwrap();
     
     println!("🎉 Create tag and add to task test passed!");
-}
\ No newline at end of file
+}
+
+/// タグによるタスクの絞り込みテスト（AND/OR）
+#[tokio::test]
+async fn test_get_tasks_by_tags() {
+    let pool = create_test_pool().await;
+    let task_service = TaskService::new(Database { pool: pool.clone() });
+    
+    let work = TagService::create_tag(&pool, CreateTagRequest { name: "仕事".to_string(), color: "#3b82f6".to_string() }).await.unwrap();
+    let urgent = TagService::create_tag(&pool, CreateTagRequest { name: "至急".to_string(), color: "#ef4444".to_string() }).await.unwrap();
+    let home = TagService::create_tag(&pool, CreateTagRequest { name: "家".to_string(), color: "#22c55e".to_string() }).await.unwrap();
+    
+    let mut task_ids = Vec::new();
+    for (title, tag_ids) in [
+        ("見積もり送付", vec![&work.id, &urgent.id]),
+        ("議事録", vec![&work.id]),
+        ("ゴミ出し", vec![&home.id]),
+        ("タグなし", vec![]),
+    ] {
+        let task = task_service.create_task(CreateTaskRequest {
+            title: title.to_string(),
+            description: None,
+            status: Some(TaskStatus::Todo),
+            parent_id: None,
+            due_date: None,
+            notification_settings: None,
+            browser_actions: None,
+        }).await.unwrap();
+        for tag_id in tag_ids {
+            task_service.add_tag_to_task(&task.id, tag_id).await.unwrap();
+        }
+        task_ids.push(task.id);
+    }
+    let sorted_titles = |tasks: Vec<crate::models::Task>| {
+        let mut titles: Vec<String> = tasks.into_iter().map(|task| task.title).collect();
+        titles.sort();
+        titles
+    };
+    
+    let tasks = task_service.get_tasks_by_tag(&work.id).await.unwrap();
+    assert!(tasks.iter().all(|task| task.tags.as_ref().is_some_and(|tags| tags.iter().any(|tag| tag.id == work.id))));
+    assert_eq!(sorted_titles(tasks), vec!["見積もり送付", "議事録"]);
+    
+    // AND: すべてのタグを持つタスクだけ
+    let both = task_service.get_tasks_by_tags(&[work.id.clone(), urgent.id.clone()], true).await.unwrap();
+    assert_eq!(sorted_titles(both), vec!["見積もり送付"]);
+    
+    // OR: いずれかのタグを持つタスク（重複なし）
+    let any = task_service.get_tasks_by_tags(&[urgent.id.clone(), home.id.clone(), work.id.clone()], false).await.unwrap();
+    assert_eq!(sorted_titles(any), vec!["ゴミ出し", "見積もり送付", "議事録"]);
+    
+    // 同じタグの重複指定はANDの件数に数えない
+    let duplicated = task_service.get_tasks_by_tags(&[work.id.clone(), work.id.clone()], true).await.unwrap();
+    assert_eq!(duplicated.len(), 2);
+    
+    assert!(task_service.get_tasks_by_tags(&[], false).await.unwrap().is_empty());
+}
+
+/// 親タグでの絞り込みに子孫のタグ付きタスクも含めるテスト
+#[tokio::test]
+async fn test_get_tasks_by_tag_recursive() {
+    let pool = create_test_pool().await;
+    let task_service = TaskService::new(Database { pool: pool.clone() });
+    
+    let work = TagService::create_tag(&pool, CreateTagRequest { name: "仕事".to_string(), color: "#3b82f6".to_string() }).await.unwrap();
+    let project = TagService::create_tag(&pool, CreateTagRequest { name: "プロジェクトA".to_string(), color: "#3b82f6".to_string() }).await.unwrap();
+    let design = TagService::create_tag(&pool, CreateTagRequest { name: "設計".to_string(), color: "#3b82f6".to_string() }).await.unwrap();
+    let home = TagService::create_tag(&pool, CreateTagRequest { name: "家".to_string(), color: "#22c55e".to_string() }).await.unwrap();
+    task_service.set_tag_parent(&project.id, Some(&work.id)).await.unwrap();
+    task_service.set_tag_parent(&design.id, Some(&project.id)).await.unwrap();
+    
+    for (title, tag_ids) in [
+        ("週報", vec![&work.id]),
+        ("画面設計", vec![&design.id, &project.id]),
+        ("ゴミ出し", vec![&home.id]),
+    ] {
+        let task = task_service.create_task(CreateTaskRequest {
+            title: title.to_string(),
+            description: None,
+            status: Some(TaskStatus::Todo),
+            parent_id: None,
+            due_date: None,
+            notification_settings: None,
+            browser_actions: None,
+        }).await.unwrap();
+        for tag_id in tag_ids {
+            task_service.add_tag_to_task(&task.id, tag_id).await.unwrap();
+        }
+    }
+    let sorted_titles = |tasks: Vec<crate::models::Task>| {
+        let mut titles: Vec<String> = tasks.into_iter().map(|task| task.title).collect();
+        titles.sort();
+        titles
+    };
+    
+    // 親タグ自身のタスクと、子孫のタグを持つタスク（重複なし）
+    let tasks = task_service.get_tasks_by_tag_recursive(&work.id).await.unwrap();
+    assert_eq!(sorted_titles(tasks), vec!["画面設計", "週報"]);
+    let tasks = task_service.get_tasks_by_tag_recursive(&design.id).await.unwrap();
+    assert_eq!(sorted_titles(tasks), vec!["画面設計"]);
+    // 直接付いたタグだけの絞り込みは変わらない
+    let tasks = task_service.get_tasks_by_tag(&work.id).await.unwrap();
+    assert_eq!(sorted_titles(tasks), vec!["週報"]);
+    
+    assert!(task_service.get_tasks_by_tag_recursive("missing").await.is_err());
+}
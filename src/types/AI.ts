@@ -61,10 +61,20 @@ export interface AgentConfig {
   default_model: string;
   base_url: string;
   timeout_seconds: number;
+  retry_policy?: RetryPolicy;
+  backend_type?: LlmBackendType;
+  embedding_model?: string;
   available_models: string[];
   model_preferences: Record<string, ModelPreference>;
 }
 
+export type LlmBackendType = 'ollama' | 'open_ai_compat';
+
+export interface RetryPolicy {
+  max_retries: number;
+  initial_delay_ms: number;
+}
+
 export interface ModelPreference {
   display_name: string;
   description: string;
@@ -76,4 +86,23 @@ export enum ModelPerformanceTier {
   Fast = 'Fast',
   Balanced = 'Balanced',
   Quality = 'Quality',
-}
\ No newline at end of file
+}
+
+export interface ConversationMessage {
+  role: 'user' | 'assistant';
+  content: string;
+  timestamp: string;
+}
+
+export interface AgentConversation {
+  id: string;
+  messages: ConversationMessage[];
+  created_at: string;
+  updated_at: string;
+}
+
+export interface ConversationSummary {
+  id: string;
+  preview: string;
+  updatedAt: string;
+}
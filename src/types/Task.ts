@@ -11,6 +11,12 @@ export interface Tag {
   color: string;
   createdAt: Date;
   updatedAt: Date;
+  parentId?: string | null;
+}
+
+// 子タグを入れ子にしたタグ
+export interface TagTreeNode extends Tag {
+  children: TagTreeNode[];
 }
 
 export interface CreateTagRequest {